@@ -0,0 +1,1048 @@
+use std::{collections::BTreeMap, ffi::OsStr, path::PathBuf};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Duration, Utc};
+use ffxiv_timers_core::{
+    check::Severity,
+    clock,
+    de::datetime_or_default,
+    fallback, gamedata,
+    i18n::tr,
+    json::{self, CropReport, CropStatus, Placement, PlotReport, Tool},
+    options::Options,
+    paths, profiles,
+    scan::{self, Scan},
+    source::{self, Failure, Support},
+    theme::{self, Role},
+    time::{format_duration, format_local, Past, Style},
+    urgency::{self, Thresholds, Urgency},
+    watch::{self, Refresh},
+    worlds,
+};
+use serde::{Deserialize, Serialize};
+use termcolor::WriteColor;
+
+/// Accountant's per-plot crop files, inside the plugin config directory.
+const CROPDATA_FOLDER: [&str; 2] = ["Accountant", "crops_plot"];
+
+/// How the folders beside it holding indoor crops start, e.g.
+/// `crops_private`.
+const CROP_FOLDER_PREFIX: &str = "crops_";
+
+/// How often `--watch` redraws the countdowns.
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How many seconds `--watch` goes between reading the crop files again
+/// when nothing says they've changed, unless `--watch SECONDS` is given.
+const DEFAULT_WATCH_INTERVAL: u64 = 30;
+
+/// How many minutes ahead of the system clock a crop's timestamps can be
+/// before the clock and Accountant are taken to disagree, leaving room for
+/// a game server's clock being a little off.
+const SKEW_TOLERANCE_MINUTES: i64 = 5;
+
+/// How long produce on the Island Sanctuary's cropland takes to grow, and
+/// how long it can go unwatered before wilting. Its seeds aren't in the
+/// crop table, so every island crop is timed alike.
+const ISLAND_GROW_HOURS: i64 = 120;
+const ISLAND_WILT_HOURS: i64 = 24;
+
+/// The fields `--format` can use, one row per kind of crop.
+pub const FORMAT_FIELDS: &[&str] = &["crop", "crop_id", "plots", "status", "remaining", "at"];
+
+/// The directory Accountant writes garden crop files to.
+pub fn data_path() -> anyhow::Result<PathBuf> {
+    paths::plugin_path(CROPDATA_FOLDER)
+}
+
+/// Every directory Accountant writes crop files to: the garden's, then
+/// those for flowerpots indoors that are there.
+pub fn data_paths() -> anyhow::Result<Vec<PathBuf>> {
+    let plot_folder = data_path()?;
+    let mut indoors = plot_folder
+        .parent()
+        .and_then(|dir| dir.read_dir().ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            *path != plot_folder
+                && path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| name.starts_with(CROP_FOLDER_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    indoors.sort();
+    Ok(std::iter::once(plot_folder).chain(indoors).collect())
+}
+
+fn crop(id: u32) -> Option<&'static gamedata::Crop> {
+    gamedata::crops().rows().iter().find(|crop| crop.id == id)
+}
+
+/// The crop's name, or its seed's id when the crop table doesn't know it.
+fn crop_name(id: u32) -> String {
+    crop(id).map_or(format!("Crop#{id}"), |crop| crop.name.clone())
+}
+
+/// Whether the crop's timings are known. Seeds missing from the crop table
+/// are never judged wilting, withered or done, as there's nothing to time
+/// them by.
+fn is_timed(crop_info: &CropInfo) -> bool {
+    crop_info.island || crop(crop_info.plant_id).is_some()
+}
+
+fn crop_grow_time(crop_info: &CropInfo) -> Duration {
+    if crop_info.island {
+        return Duration::hours(ISLAND_GROW_HOURS);
+    }
+    crop(crop_info.plant_id).map_or(Duration::zero(), gamedata::Crop::grow_time)
+}
+
+fn crop_wilt_time(crop_info: &CropInfo) -> Duration {
+    if crop_info.island {
+        return Duration::hours(ISLAND_WILT_HOURS);
+    }
+    crop(crop_info.plant_id).map_or(Duration::zero(), gamedata::Crop::wilt_time)
+}
+
+fn crop_wither_time(crop_info: &CropInfo) -> Duration {
+    crop_wilt_time(crop_info) + Duration::days(1)
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct AccountantCropData {
+    #[serde(rename = "Item1")]
+    house_info: HouseInfo,
+    #[serde(rename = "Item2")]
+    crops: Vec<CropInfo>,
+}
+
+/// Apartments have no plot, so any field can be missing.
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct HouseInfo {
+    zone: u32,
+    server_id: u32,
+    ward: u32,
+    plot: u32,
+}
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CropInfo {
+    #[serde(deserialize_with = "datetime_or_default")]
+    plant_time: DateTime<Utc>,
+    #[serde(deserialize_with = "datetime_or_default")]
+    last_tending: DateTime<Utc>,
+    plant_id: u32,
+    accurate_plant_time: bool,
+    /// Whether it's on the Island Sanctuary, from the file's zone
+    #[serde(skip)]
+    island: bool,
+}
+
+/// How pressing a crop or plot is, as of `now`: overdue once it's wilting
+/// or withered, due when ready to harvest, and otherwise by how near its
+/// next stage is.
+pub fn urgency(
+    status: CropStatus,
+    next_stage: Option<DateTime<Utc>>,
+    thresholds: &Thresholds,
+    now: DateTime<Utc>,
+) -> Urgency {
+    match status {
+        CropStatus::Wilt | CropStatus::Dead => Urgency::Overdue,
+        CropStatus::Done => Urgency::Due,
+        CropStatus::Good | CropStatus::Okay => {
+            next_stage.map_or(Urgency::Ok, |time| thresholds.level(time, now))
+        }
+    }
+}
+
+fn crop_status(crop: &CropInfo) -> CropStatus {
+    if !is_timed(crop) {
+        return CropStatus::Good;
+    }
+    let now = clock::now();
+    let wilt_time = crop.last_tending + crop_wilt_time(crop);
+    let wither_time = crop.last_tending + crop_wither_time(crop);
+    let finish_time = crop.plant_time + crop_grow_time(crop);
+    if wither_time < finish_time && wither_time < now {
+        CropStatus::Dead
+    } else if finish_time < now && crop.accurate_plant_time {
+        CropStatus::Done
+    } else if finish_time < wither_time {
+        // A guessed plant time can have it ready hours later than it seems,
+        // so it's only taken as a lower bound on the harvest
+        CropStatus::Good
+    } else if wilt_time < now {
+        CropStatus::Wilt
+    } else {
+        CropStatus::Okay
+    }
+}
+
+/// How far a crop's timestamps are ahead of the clock, when that's more
+/// than [`SKEW_TOLERANCE_MINUTES`].
+fn clock_skew(crop: &CropInfo) -> Option<Duration> {
+    let ahead = crop.plant_time.max(crop.last_tending) - clock::now();
+    (ahead > Duration::minutes(SKEW_TOLERANCE_MINUTES)).then_some(ahead)
+}
+
+/// The furthest any plot growing the crop is ahead of the clock.
+fn report_skew(report: &CropReport) -> Option<Duration> {
+    report
+        .patches
+        .iter()
+        .filter_map(|patch| patch.clock_skew)
+        .max()
+        .map(Duration::seconds)
+}
+
+/// A crop in one plot, with the names that depend on the profile it's
+/// from.
+struct Patch {
+    house: HouseInfo,
+    placement: Placement,
+    crop: CropInfo,
+    world: String,
+    profile: Option<String>,
+}
+
+/// Every kind of crop growing in a housing plot, by its most urgent plot.
+/// Those on the Island Sanctuary are left out.
+pub fn reports() -> anyhow::Result<Vec<CropReport>> {
+    reports_where(false)
+}
+
+/// Every kind of crop growing on the Island Sanctuary, for
+/// `--include-island`.
+fn island_reports() -> anyhow::Result<Vec<CropReport>> {
+    reports_where(true)
+}
+
+fn reports_where(island: bool) -> anyhow::Result<Vec<CropReport>> {
+    worlds::load_overrides(None)?;
+    let mut entries_by_crop: BTreeMap<u32, Vec<Patch>> = BTreeMap::new();
+    for patch in profiles::each(read)? {
+        if patch.crop.island != island {
+            continue;
+        }
+        if !is_timed(&patch.crop) && !entries_by_crop.contains_key(&patch.crop.plant_id) {
+            tracing::warn!(
+                "No timings for crop id {}; add it to gamedata/crops.json to time it",
+                patch.crop.plant_id
+            );
+        }
+        entries_by_crop
+            .entry(patch.crop.plant_id)
+            .or_default()
+            .push(patch);
+    }
+
+    Ok(entries_by_crop
+        .into_iter()
+        .map(|(crop_id, patches)| crop_report(crop_id, &patches))
+        .collect())
+}
+
+/// The plots in one profile's files.
+fn read(profile: Option<&str>) -> anyhow::Result<Vec<Patch>> {
+    load(profile).map(|(patches, _)| patches)
+}
+
+/// The crop files' layout, as the loader reads it.
+pub fn support() -> Support {
+    let sample = AccountantCropData {
+        crops: vec![CropInfo::default()],
+        ..AccountantCropData::default()
+    };
+    Support {
+        files: format!("{}/{CROP_FOLDER_PREFIX}*/*.json", CROPDATA_FOLDER[0]),
+        layouts: vec![source::shape(&sample)],
+    }
+}
+
+/// What the crop files in the profile being read hold, for `doctor`.
+pub fn scan() -> anyhow::Result<Scan> {
+    load(None).map(|(_, scan)| scan)
+}
+
+fn load(profile: Option<&str>) -> anyhow::Result<(Vec<Patch>, Scan)> {
+    let mut scan = Scan::default();
+    let mut patches = vec![];
+    let mut loaded = vec![];
+    for (i, folder) in data_paths()?.into_iter().enumerate() {
+        // Only the garden's folder has to be there
+        let outdoor = i == 0;
+        let files = scan::json_files::<AccountantCropData>(&folder, &mut scan);
+        let files = match files {
+            Ok(files) => files,
+            Err(e) if outdoor => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "Failed to read Accountant's crop data in {folder:?}; install the \
+                         Accountant plugin and visit your garden plots in game, or pass \
+                         --data-root"
+                    )
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read Accountant's crop data in {folder:?}: {e}");
+                continue;
+            }
+        };
+        let prefix = match outdoor {
+            true => String::new(),
+            false => format!(
+                "{}/",
+                folder.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        };
+        read_files(files, outdoor, &prefix, profile, &mut patches, &mut loaded);
+    }
+    scan.rows = patches.len();
+    for line in file_table(&loaded, &scan.unusable) {
+        tracing::debug!("{line}");
+    }
+    Ok((patches, scan))
+}
+
+/// Adds the crops in one folder's files to `patches`, and what each file
+/// held to `loaded`, its name after `prefix`.
+fn read_files(
+    files: Vec<scan::JsonFile<AccountantCropData>>,
+    outdoor: bool,
+    prefix: &str,
+    profile: Option<&str>,
+    patches: &mut Vec<Patch>,
+    loaded: &mut Vec<FileStats>,
+) {
+    for scan::JsonFile { name, data, .. } in files {
+        let placement = match (outdoor, data.house_info.plot) {
+            (true, _) => Placement::Outdoor,
+            (false, 0) => Placement::Apartment,
+            (false, _) => Placement::Indoor,
+        };
+        let (planted, empty): (Vec<CropInfo>, Vec<CropInfo>) =
+            data.crops.into_iter().partition(|crop| crop.plant_id != 0);
+        loaded.push(FileStats {
+            name: format!("{prefix}{name}"),
+            house: house_address(&data.house_info, placement),
+            crops: planted.len(),
+            skipped: empty.len(),
+            newest: planted
+                .iter()
+                .chain(&empty)
+                .flat_map(|crop| [crop.plant_time, crop.last_tending])
+                .filter(|&time| time != DateTime::<Utc>::default())
+                .max(),
+        });
+        for mut crop in planted {
+            crop.island = gamedata::is_island(data.house_info.zone);
+            patches.push(Patch {
+                house: data.house_info,
+                placement,
+                crop,
+                world: worlds::name(data.house_info.server_id),
+                profile: profile.map(str::to_string),
+            });
+        }
+    }
+}
+
+/// What one of Accountant's crop files held, for `--verbose`.
+struct FileStats {
+    name: String,
+    house: String,
+    /// Crops growing in it
+    crops: usize,
+    /// Empty beds, which Accountant writes with no plant
+    skipped: usize,
+    /// The latest time anything in it was planted or tended
+    newest: Option<DateTime<Utc>>,
+}
+
+fn house_address(house: &HouseInfo, placement: Placement) -> String {
+    address(
+        &worlds::name(house.server_id),
+        &district_name(house.zone),
+        house.ward,
+        house.plot,
+        placement,
+    )
+}
+
+/// Where a crop grows, e.g. "Cactuar Mist ward 1 plot 1", or "Cactuar
+/// Mist ward 1 apartment". The Island Sanctuary has no wards.
+fn address(world: &str, district: &str, ward: u32, plot: u32, placement: Placement) -> String {
+    let mut address = format!("{world} {district}");
+    match placement {
+        _ if ward == 0 => {}
+        Placement::Outdoor => address += &format!(" ward {ward} plot {plot}"),
+        Placement::Indoor => address += &format!(" ward {ward} plot {plot} (indoors)"),
+        Placement::Apartment => address += &format!(" ward {ward} apartment"),
+    }
+    address
+}
+
+/// A table of every crop file read, by name, with those that couldn't be
+/// used listed by why in place of a house.
+fn file_table(loaded: &[FileStats], unusable: &[(String, Failure)]) -> Vec<String> {
+    let mut rows = loaded
+        .iter()
+        .map(|file| {
+            [
+                file.name.clone(),
+                file.house.clone(),
+                file.crops.to_string(),
+                file.skipped.to_string(),
+                file.newest.map_or("-".to_string(), |time| {
+                    time.format("%Y-%m-%d %H:%M UTC").to_string()
+                }),
+            ]
+        })
+        .chain(unusable.iter().map(|(name, failure)| {
+            [
+                name.clone(),
+                format!("({})", failure.label()),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]
+        }))
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| a[0].cmp(&b[0]));
+    let header = ["file", "house", "crops", "skipped", "newest"].map(str::to_string);
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let [name, house, crops, skipped, newest] = row;
+            let [name_w, house_w, crops_w, skipped_w, _] = widths;
+            format!("{name:<name_w$}  {house:<house_w$}  {crops:>crops_w$}  {skipped:>skipped_w$}  {newest}")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// How urgent the crops are: critical once any is wilting or withered, and
+/// a warning while any is ready to harvest.
+pub fn severity(reports: &[CropReport]) -> Severity {
+    let (thresholds, now) = (urgency::thresholds(Tool::Crops), clock::now());
+    reports
+        .iter()
+        .map(|report| urgency(report.status, report.stage_time, &thresholds, now).severity())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Prints when each kind of crop next needs tending or harvesting, and
+/// returns how urgent that is.
+pub fn run(options: &Options) -> anyhow::Result<Severity> {
+    if options.if_tended {
+        if options.watch || options.json || options.format.is_some() {
+            bail!("--if-tended can't be combined with --watch, --json or --format");
+        }
+        worlds::load_overrides(None)?;
+        let crops = profiles::each(read)?
+            .into_iter()
+            .map(|patch| patch.crop)
+            .filter(|crop| !crop.island)
+            .collect::<Vec<_>>();
+        print_if_tended(&mut options.stdout(), &if_tended(&crops, clock::now()))?;
+        return Ok(Severity::Ok);
+    }
+    if options.include_island && (options.json || options.format.is_some()) {
+        bail!("--include-island only applies to the text report, not --json or --format");
+    }
+    if options.detail && (options.json || options.format.is_some()) {
+        bail!("--detail only applies to the text report; --json already lists every plot");
+    }
+    if options.watch {
+        watch(options)?;
+        return Ok(Severity::Ok);
+    }
+    let format = options.row_format(FORMAT_FIELDS)?;
+    let (reports, cached_at) = fallback::load(Tool::Crops, options.fallback_cache, reports)?;
+    let severity = severity(&reports);
+    if !options.trust_data && (options.json || options.format.is_some()) {
+        if let Some(skew) = reports.iter().filter_map(report_skew).max() {
+            tracing::warn!(
+                "Accountant's crop data is {} ahead of the system clock, so the statuses \
+                 may be wrong; check the clock, or pass --trust-data",
+                format_duration(skew, Style::Compact, Past::Ago)
+            );
+        }
+    }
+    if options.json {
+        json::emit_cached(Tool::Crops, reports, cached_at)?;
+        return Ok(severity);
+    }
+    if let Some(format) = format {
+        let now = clock::now();
+        format.write(
+            std::io::stdout().lock(),
+            &reports,
+            |report, field| match field {
+                "crop" => report.name.clone(),
+                "crop_id" => report.plant_id.to_string(),
+                "plots" => report.patch_count.to_string(),
+                "status" => report.status.name().to_string(),
+                "remaining" => report
+                    .stage_time
+                    .map(|time| format_duration(time - now, Style::Compact, Past::Ago))
+                    .unwrap_or_default(),
+                "at" => report.stage_time.map(format_local).unwrap_or_default(),
+                _ => unreachable!("checked by Template::parse"),
+            },
+        )?;
+        return Ok(severity);
+    }
+    let islands = match options.include_island {
+        true => island_reports()?,
+        false => vec![],
+    };
+    print(
+        &mut options.stdout(),
+        &reports,
+        &islands,
+        cached_at,
+        options,
+    )?;
+    Ok(severity)
+}
+
+/// The text report: each kind of crop, colored by its status, with how
+/// long until it next needs tending, and then those on the Island Sanctuary
+/// under their own header. Unless `--trust-data`, crops whose timestamps
+/// are ahead of the clock are flagged instead of timed, under a warning
+/// that the two disagree. With `--detail`, each crop's plots follow it.
+fn print(
+    stdout: &mut dyn WriteColor,
+    reports: &[CropReport],
+    islands: &[CropReport],
+    cached_at: Option<DateTime<Utc>>,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let max_name_len = reports
+        .iter()
+        .chain(islands)
+        .map(|report| report.name.len() + 5)
+        .max()
+        .unwrap_or(0);
+    for (title, reports) in [
+        (fallback::mark(tr("crops.title"), cached_at), reports),
+        (tr("crops.island").to_string(), islands),
+    ] {
+        if reports.is_empty() {
+            continue;
+        }
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "{title}")?;
+        print_crops(stdout, reports, max_name_len, options)?;
+    }
+    Ok(())
+}
+
+/// One section of the text report, its crops' names padded to
+/// `max_name_len`.
+fn print_crops(
+    stdout: &mut dyn WriteColor,
+    reports: &[CropReport],
+    max_name_len: usize,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let trust_data = options.trust_data;
+    let skew = |report| report_skew(report).filter(|_| !trust_data);
+    if let Some(largest) = reports.iter().filter_map(skew).max() {
+        stdout.set_color(&theme::error())?;
+        writeln!(
+            stdout,
+            "    The system clock and Accountant's data disagree: plots are up to {} \
+             ahead of the clock. Check the clock, or pass --trust-data.",
+            format_duration(largest, Style::Compact, Past::Ago)
+        )?;
+    }
+    let thresholds = urgency::thresholds(Tool::Crops);
+    for report in reports {
+        let now = clock::now();
+        let (time_display, role) = timing(
+            report.status,
+            report.stage_time,
+            report.estimated,
+            skew(report),
+            &thresholds,
+            now,
+        );
+        stdout.set_color(&theme::spec(role))?;
+        writeln!(
+            stdout,
+            "    {crop_display:<max_name_len$} {time_display}",
+            crop_display = format!("{} ({})", report.name, report.patch_count)
+        )?;
+        if options.detail {
+            print_plots(stdout, &report.patches, &thresholds, trust_data, now)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// How long until a crop or plot's next stage, and the color to show it
+/// in, or a question mark when its timestamps are `skew` ahead of the
+/// clock. An `estimated` harvest is marked with a `~`, and once it's
+/// passed is only said to be soon. Any other stage that's passed, as when
+/// the clock has moved on since the files were read, is overdue.
+fn timing(
+    status: CropStatus,
+    next_stage: Option<DateTime<Utc>>,
+    estimated: bool,
+    skew: Option<Duration>,
+    thresholds: &Thresholds,
+    now: DateTime<Utc>,
+) -> (String, Role) {
+    if let Some(ahead) = skew {
+        let display = format!(
+            "- ? (data {} ahead of the clock)",
+            format_duration(ahead, Style::Compact, Past::Ago)
+        );
+        return (display, Role::Warn);
+    }
+    let display = next_stage
+        .map(|time| match estimated {
+            true if time <= now => "- ready soon (estimated)".to_string(),
+            true => format!(
+                "- ~{} (~{}, estimated)",
+                format_duration(time - now, Style::Compact, Past::Ago),
+                format_local(time)
+            ),
+            false => format!(
+                "- {} ({})",
+                format_duration(time - now, Style::Compact, Past::Overdue),
+                format_local(time)
+            ),
+        })
+        .unwrap_or_default();
+    let role = match next_stage {
+        Some(time) if time < now && !estimated => Role::Error,
+        _ => urgency(status, next_stage, thresholds, now).role(),
+    };
+    (display, role)
+}
+
+/// `--detail`: the plots growing one crop, under a line for each house so
+/// a garden's beds are listed together.
+fn print_plots(
+    stdout: &mut dyn WriteColor,
+    patches: &[PlotReport],
+    thresholds: &Thresholds,
+    trust_data: bool,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let mut houses: Vec<(String, Vec<&PlotReport>)> = vec![];
+    for patch in patches {
+        let house = plot_address(patch);
+        match houses.iter_mut().find(|(address, _)| *address == house) {
+            Some((_, beds)) => beds.push(patch),
+            None => houses.push((house, vec![patch])),
+        }
+    }
+    let status_len = patches
+        .iter()
+        .map(|patch| patch.status.name().len())
+        .max()
+        .unwrap_or(0);
+    for (house, beds) in houses {
+        stdout.set_color(&theme::dim())?;
+        writeln!(stdout, "        {house}")?;
+        for bed in beds {
+            let skew = bed
+                .clock_skew
+                .map(Duration::seconds)
+                .filter(|_| !trust_data);
+            let (time_display, role) = timing(
+                bed.status,
+                bed.stage_time,
+                bed.estimated,
+                skew,
+                thresholds,
+                now,
+            );
+            stdout.set_color(&theme::spec(role))?;
+            writeln!(
+                stdout,
+                "            {:<status_len$} {time_display}",
+                bed.status.name()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Where a plot is, with the profile it's from when several are read.
+fn plot_address(patch: &PlotReport) -> String {
+    let mut address = address(
+        &patch.world,
+        &patch.district,
+        patch.ward,
+        patch.plot,
+        patch.placement,
+    );
+    if let Some(profile) = &patch.profile {
+        address += &format!(" [{profile}]");
+    }
+    address
+}
+
+/// `--watch`: the report redrawn every second, and read again whenever
+/// Accountant rewrites a plot's file, a crop moves on to its next stage or
+/// the interval passes. The interval catches the crop folder being removed
+/// and written again, which the folder's watch doesn't survive.
+fn watch(options: &Options) -> anyhow::Result<()> {
+    let mut stdout = options.watch_stdout()?;
+    let folders = profiles::each(|_| data_paths())?;
+    let interval = options.watch_interval.unwrap_or(DEFAULT_WATCH_INTERVAL);
+    let reload_every = Some(std::time::Duration::from_secs(interval.max(1)));
+    let (mut crops, mut islands, mut cached_at) = (vec![], vec![], None);
+    watch::run(
+        &mut stdout,
+        &folders,
+        Some(TICK),
+        reload_every,
+        |stdout, refresh| {
+            let now = clock::now();
+            let staged = crops
+                .iter()
+                .chain(&islands)
+                .filter(|crop: &&CropReport| !crop.estimated)
+                .any(|crop| crop.stage_time.is_some_and(|time| time <= now));
+            if refresh == Refresh::Reload || staged {
+                (crops, cached_at) = fallback::load(Tool::Crops, options.fallback_cache, reports)?;
+                if options.include_island {
+                    islands = island_reports()?;
+                }
+            }
+            print(stdout, &crops, &islands, cached_at, options)
+        },
+    )
+}
+
+/// What tending every crop right now would leave.
+#[derive(Debug, PartialEq)]
+struct Tended {
+    /// When the first crop still growing would need tending again
+    next_visit: Option<DateTime<Utc>>,
+    /// The crops that would be ready to harvest before then, by id, with
+    /// how many plots and when the first is ready
+    ready: BTreeMap<u32, (usize, DateTime<Utc>)>,
+}
+
+/// When crops tended at `now` would next need a visit. Those that finish
+/// growing before they'd wilt never need tending again, so they only set
+/// when to come back if nothing else does.
+fn if_tended(crops: &[CropInfo], now: DateTime<Utc>) -> Tended {
+    let growing = crops.iter().filter(|crop| {
+        is_timed(crop)
+            && matches!(
+                crop_status(crop),
+                CropStatus::Good | CropStatus::Okay | CropStatus::Wilt
+            )
+    });
+    let (finishing, tending): (Vec<&CropInfo>, Vec<_>) = growing
+        .partition(|crop| crop.plant_time + crop_grow_time(crop) <= now + crop_wilt_time(crop));
+    let next_visit = tending.iter().map(|crop| now + crop_wilt_time(crop)).min();
+
+    let mut ready: BTreeMap<u32, (usize, DateTime<Utc>)> = BTreeMap::new();
+    for crop in finishing {
+        let finish = crop.plant_time + crop_grow_time(crop);
+        if next_visit.is_some_and(|visit| finish > visit) {
+            continue;
+        }
+        let (plots, first) = ready.entry(crop.plant_id).or_insert((0, finish));
+        *plots += 1;
+        *first = finish.min(*first);
+    }
+    Tended { next_visit, ready }
+}
+
+/// `--if-tended`: the next visit a garden lap now would leave, and what to
+/// bring seeds to replant.
+fn print_if_tended(stdout: &mut dyn WriteColor, tended: &Tended) -> anyhow::Result<()> {
+    let now = clock::now();
+    let when = |time: DateTime<Utc>| {
+        format!(
+            "{} ({})",
+            format_local(time),
+            format_duration(time - now, Style::Humanized, Past::Ago)
+        )
+    };
+    stdout.set_color(&theme::header())?;
+    match tended.next_visit {
+        Some(visit) => writeln!(
+            stdout,
+            "If you tend everything now, next required visit: {}",
+            when(visit)
+        )?,
+        None if tended.ready.is_empty() => writeln!(stdout, "Nothing is growing")?,
+        None => writeln!(
+            stdout,
+            "If you tend everything now, nothing needs tending again before it's ready"
+        )?,
+    }
+    if tended.ready.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        stdout,
+        "Ready to harvest before then, so bring seeds to replant:"
+    )?;
+    stdout.set_color(&theme::ready())?;
+    for (&crop_id, &(plots, first)) in &tended.ready {
+        writeln!(
+            stdout,
+            "    {} ({plots}) - {}",
+            crop_name(crop_id),
+            when(first)
+        )?;
+    }
+    Ok(())
+}
+
+/// Sums up every plot growing one kind of crop by its most urgent status.
+fn crop_report(crop_id: u32, patches: &[Patch]) -> CropReport {
+    let overall_status = patches
+        .iter()
+        .map(|patch| crop_status(&patch.crop))
+        .max()
+        .unwrap_or(CropStatus::Okay);
+
+    // Only plots in the overall status say when it changes; a plot doing
+    // better may be past the stage the others are waiting on. A harvest
+    // time worked out from an accurate plant time is taken over any
+    // guessed one.
+    let stage = |accurate: bool| {
+        patches
+            .iter()
+            .filter(|patch| crop_status(&patch.crop) == overall_status)
+            .filter(|patch| patch.crop.accurate_plant_time == accurate)
+            .filter_map(|patch| stage_change(&patch.crop, overall_status))
+            .min()
+    };
+    let (next_stage, estimated) = match (stage(true), stage(false)) {
+        (Some(time), _) if overall_status == CropStatus::Good => (Some(time), false),
+        (None, Some(time)) if overall_status == CropStatus::Good => (Some(time), true),
+        (accurate, guessed) => (accurate.into_iter().chain(guessed).min(), false),
+    };
+
+    CropReport {
+        plant_id: crop_id,
+        name: crop_name(crop_id),
+        patch_count: patches.len(),
+        status: overall_status,
+        stage_time: next_stage,
+        estimated,
+        patches: patches
+            .iter()
+            .map(|patch| {
+                let status = crop_status(&patch.crop);
+                let next_stage = stage_change(&patch.crop, status);
+                PlotReport {
+                    world: patch.world.clone(),
+                    district: district_name(patch.house.zone),
+                    ward: patch.house.ward,
+                    plot: patch.house.plot,
+                    placement: patch.placement,
+                    status,
+                    stage_time: next_stage,
+                    estimated: next_stage.is_some()
+                        && status == CropStatus::Good
+                        && !patch.crop.accurate_plant_time,
+                    profile: patch.profile.clone(),
+                    clock_skew: clock_skew(&patch.crop).map(|skew| skew.num_seconds()),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// When a crop in the given status moves on to the next one.
+fn stage_change(crop: &CropInfo, status: CropStatus) -> Option<DateTime<Utc>> {
+    if !is_timed(crop) {
+        return None;
+    }
+    match status {
+        CropStatus::Dead | CropStatus::Done => None,
+        CropStatus::Okay => Some(crop.last_tending + crop_wilt_time(crop)),
+        CropStatus::Wilt => Some(crop.last_tending + crop_wither_time(crop)),
+        CropStatus::Good => Some(crop.plant_time + crop_grow_time(crop)),
+    }
+}
+
+/// The housing district, or the Island Sanctuary, for a territory id.
+fn district_name(zone: u32) -> String {
+    gamedata::zone(zone).map_or(format!("Zone#{zone}"), |zone| zone.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_or_empty_crop_data_does_not_panic() {
+        // Keeps what is read out of the real state directory
+        ffxiv_timers_core::read_cache::disable();
+        let dir = std::env::temp_dir().join(format!("crop-timers-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        paths::set_plugin_configs_dir(dir.clone());
+        let options = Options {
+            json: true,
+            ..Options::default()
+        };
+
+        assert!(run(&options).is_err());
+        std::fs::create_dir_all(data_path().unwrap()).unwrap();
+        run(&options).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_timestamps_well_ahead_of_the_clock_are_skew() {
+        let crop = |ahead: Duration| CropInfo {
+            plant_time: clock::now() - Duration::hours(1),
+            last_tending: clock::now() + ahead,
+            plant_id: 0,
+            accurate_plant_time: true,
+            island: false,
+        };
+        assert_eq!(clock_skew(&crop(Duration::minutes(-10))), None);
+        assert_eq!(clock_skew(&crop(Duration::minutes(4))), None);
+        let skew = clock_skew(&crop(Duration::hours(3))).unwrap();
+        assert!((skew - Duration::hours(3)).num_seconds().abs() <= 1);
+    }
+
+    #[test]
+    fn unknown_seeds_are_never_judged() {
+        let crop = CropInfo {
+            plant_time: clock::now() - Duration::days(30),
+            last_tending: clock::now() - Duration::days(30),
+            plant_id: 99999,
+            accurate_plant_time: true,
+            island: false,
+        };
+        assert_eq!(crop_name(99999), "Crop#99999");
+        assert_eq!(crop_status(&crop), CropStatus::Good);
+        assert_eq!(stage_change(&crop, CropStatus::Good), None);
+        assert_eq!(if_tended(&[crop], clock::now()).ready.len(), 0);
+    }
+
+    #[test]
+    fn stages_come_from_plots_in_the_overall_status() {
+        let now = clock::now();
+        let patch = |planted_hours_ago, tended_hours_ago| Patch {
+            house: HouseInfo::default(),
+            placement: Placement::Outdoor,
+            crop: CropInfo {
+                plant_time: now - Duration::hours(planted_hours_ago),
+                last_tending: now - Duration::hours(tended_hours_ago),
+                plant_id: 4842,
+                accurate_plant_time: true,
+                island: false,
+            },
+            world: "Cactuar".to_string(),
+            profile: None,
+        };
+        // Almond grows in 120 hours and wilts after 48: the first finishes
+        // before withering, though it wilted 2 hours ago, while the second
+        // needs tending in 38 hours
+        let report = crop_report(4842, &[patch(110, 50), patch(10, 10)]);
+        assert_eq!(report.status, CropStatus::Okay);
+        assert_eq!(report.stage_time, Some(now + Duration::hours(38)));
+    }
+
+    #[test]
+    fn passed_stages_are_overdue() {
+        let (now, thresholds) = (clock::now(), urgency::thresholds(Tool::Crops));
+        let at = now - Duration::seconds(3912);
+        let (display, role) = timing(CropStatus::Okay, Some(at), false, None, &thresholds, now);
+        assert!(display.starts_with("- overdue 01:05:12 ("), "{display}");
+        assert_eq!(role, Role::Error);
+    }
+
+    #[test]
+    fn guessed_plant_times_only_bound_the_harvest() {
+        let now = clock::now();
+        let patch = |planted_hours_ago, accurate_plant_time| Patch {
+            house: HouseInfo::default(),
+            placement: Placement::Outdoor,
+            crop: CropInfo {
+                plant_time: now - Duration::hours(planted_hours_ago),
+                last_tending: now - Duration::hours(1),
+                plant_id: 4842,
+                accurate_plant_time,
+                island: false,
+            },
+            world: "Cactuar".to_string(),
+            profile: None,
+        };
+        // Almond grows in 120 hours and wilts after 48
+        assert_eq!(crop_status(&patch(121, true).crop), CropStatus::Done);
+        assert_eq!(crop_status(&patch(121, false).crop), CropStatus::Good);
+
+        let report = crop_report(4842, &[patch(100, false), patch(90, true)]);
+        assert_eq!(report.stage_time, Some(now + Duration::hours(30)));
+        assert!(!report.estimated);
+        assert!(report.patches[0].estimated);
+        assert!(!report.patches[1].estimated);
+
+        let report = crop_report(4842, &[patch(100, false)]);
+        assert_eq!(report.stage_time, Some(now + Duration::hours(20)));
+        assert!(report.estimated);
+    }
+
+    #[test]
+    fn crops_finishing_before_they_wilt_need_no_visit_to_tend() {
+        let now = clock::now();
+        let crop = |plant_id, planted_hours_ago| CropInfo {
+            plant_time: now - Duration::hours(planted_hours_ago),
+            last_tending: now - Duration::hours(1),
+            plant_id,
+            accurate_plant_time: true,
+            island: false,
+        };
+        // Almond grows in 120 hours and wilts after 48, Krakka Root in 72
+        // and 24, Sylkis Bud in 120 and 48 and Old World Fig in 120 and 48
+        let almond = crop(4842, 100);
+        let crops = [
+            almond,
+            crop(4842, 10),
+            crop(8165, 10),
+            // Done before it wilts, but after the Krakka Root needs tending
+            crop(7895, 80),
+            // Already done
+            crop(12896, 200),
+        ];
+        let tended = if_tended(&crops, now);
+        assert_eq!(tended.next_visit, Some(now + Duration::hours(24)));
+        assert_eq!(
+            tended.ready,
+            BTreeMap::from([(4842, (1, now + Duration::hours(20)))])
+        );
+
+        // Nothing left to tend: coming back is only for the harvest
+        let tended = if_tended(&[almond], now);
+        assert_eq!(tended.next_visit, None);
+        assert_eq!(tended.ready.len(), 1);
+    }
+}
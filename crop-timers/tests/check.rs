@@ -0,0 +1,31 @@
+//! Runs crop-timers in a fake home, checking what `--check` exits with and
+//! that nothing but the report is printed to stdout.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_crop-timers");
+
+#[test]
+fn check_exits_with_the_most_urgent_crop() {
+    let home = FakeHome::warned("crop-timers-check-urgency");
+    home.crop(1, Duration::zero(), Duration::zero());
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 0);
+    // Grown, and tended recently enough not to wither
+    home.crop(1, Duration::days(6), Duration::hours(1));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 1);
+    assert_eq!(home.run(BIN, &["--check", "--json"]).warned_status(), 1);
+    // Still growing, but untended for over two days
+    home.crop(1, Duration::hours(24), Duration::hours(50));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 2);
+    assert_eq!(home.run(BIN, &[]).warned_status(), 0);
+}
+
+#[test]
+fn errors_are_unknown_under_check() {
+    let home = FakeHome::warned("crop-timers-check-errors");
+    let run = home.run(BIN, &["--check"]);
+    assert_eq!(run.warned_status(), 3);
+    assert!(run.stdout.is_empty());
+    assert_eq!(home.run(BIN, &[]).warned_status(), 1);
+}
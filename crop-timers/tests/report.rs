@@ -0,0 +1,309 @@
+//! Runs crop-timers in a fake home, checking the plain-text report.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_crop-timers");
+
+#[test]
+fn reports_when_each_crop_next_changes() {
+    let home = FakeHome::new("crop-timers-report-growing");
+    home.crop(1, Duration::hours(24), Duration::hours(1));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Crop Timers");
+    assert!(
+        lines[1].starts_with("    Almond (1)  - 1d 23:00:00 ("),
+        "{}",
+        lines[1]
+    );
+    assert_eq!(lines.len(), 2);
+    assert!(!run.stdout.contains('\x1b'));
+}
+
+#[test]
+fn plots_of_a_crop_are_counted_together() {
+    let home = FakeHome::new("crop-timers-report-plots");
+    home.crop(1, Duration::hours(24), Duration::hours(1))
+        .crop(2, Duration::days(6), Duration::hours(1))
+        .crop(3, Duration::hours(24), Duration::hours(50));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    // A withered crop has nothing left to wait for
+    assert_eq!(run.stdout, "Crop Timers\n    Almond (3)  \n");
+}
+
+#[test]
+fn nothing_planted_prints_nothing() {
+    let home = FakeHome::new("crop-timers-report-empty");
+    home.file("Accountant/crops_plot/.keep", "");
+    let run = home.run(BIN, &[]);
+    assert_eq!(
+        (run.status, &*run.stdout),
+        (0, ""),
+        "stderr: {}",
+        run.stderr
+    );
+}
+
+#[test]
+fn verbose_lists_what_each_file_held() {
+    let home = FakeHome::new("crop-timers-report-verbose");
+    home.crop(1, Duration::hours(24), Duration::hours(1))
+        .file(
+            "Accountant/crops_plot/2.json",
+            r#"{"Item1": {"Zone": 341, "ServerId": 79, "Ward": 12, "Plot": 30},
+                "Item2": [{"PlantTime": "0001-01-01T00:00:00",
+                           "LastTending": "0001-01-01T00:00:00", "PlantId": 0,
+                           "AccuratePlantTime": false}]}"#,
+        )
+        .file("Accountant/crops_plot/3.json", "{")
+        .file(
+            "Accountant/crops_plot/4.json",
+            r#"{"Item1": 7, "Item2": []}"#,
+        );
+    let run = home.run(BIN, &["--verbose"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let tended = (home.now() - Duration::hours(1)).format("%Y-%m-%d %H:%M UTC");
+    let table = run
+        .stderr
+        .lines()
+        .filter_map(|line| line.split_once("DEBUG "))
+        .map(|(_, line)| line.trim_start())
+        .skip_while(|line| !line.starts_with("file "))
+        .take(5)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        table,
+        [
+            "file    house                               crops  skipped  newest".to_string(),
+            format!("1.json  Cactuar Mist ward 1 plot 1              1        0  {tended}"),
+            "2.json  Cactuar The Goblet ward 12 plot 30      0        1  -".to_string(),
+            "3.json  (unreadable)".to_string(),
+            "4.json  (incompatible)".to_string(),
+        ],
+        "stderr: {}",
+        run.stderr
+    );
+}
+
+#[test]
+fn unchanged_files_are_read_from_the_cache() {
+    let home = FakeHome::new("crop-timers-report-cache");
+    home.crop(1, Duration::hours(24), Duration::hours(1));
+    let cache = |run: &ffxiv_timers_testing::Run| {
+        run.stderr
+            .lines()
+            .filter(|line| line.contains("Read cache"))
+            .map(|line| line.contains("Read cache hit"))
+            .collect::<Vec<_>>()
+    };
+
+    let first = home.run(BIN, &["--verbose"]);
+    assert_eq!(cache(&first), [false], "stderr: {}", first.stderr);
+    let second = home.run(BIN, &["--verbose"]);
+    assert_eq!(cache(&second), [true], "stderr: {}", second.stderr);
+    assert_eq!(second.stdout, first.stdout);
+
+    home.crop(1, Duration::days(6), Duration::hours(1));
+    let replanted = home.run(BIN, &["--verbose"]);
+    assert_eq!(cache(&replanted), [false], "stderr: {}", replanted.stderr);
+    assert_ne!(replanted.stdout, first.stdout);
+
+    let uncached = home.run(BIN, &["--verbose", "--no-cache"]);
+    assert_eq!(cache(&uncached), [false; 0], "stderr: {}", uncached.stderr);
+    assert_eq!(uncached.stdout, replanted.stdout);
+}
+
+#[test]
+fn island_crops_are_only_listed_when_asked_for() {
+    let home = FakeHome::new("crop-timers-report-island");
+    home.crop(1, Duration::hours(24), Duration::hours(1))
+        .island_crop(Duration::hours(119), Duration::hours(1));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{}", run.stdout);
+    assert!(lines[1].starts_with("    Almond (1)  "), "{}", lines[1]);
+
+    let run = home.run(BIN, &["--include-island"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Crop Timers");
+    assert_eq!(lines[2], "Island");
+    // Grown in five days, though its seed isn't in the crop table
+    assert!(
+        lines[3].starts_with("    Crop#37000 (1)  - 01:00:00 ("),
+        "{}",
+        lines[3]
+    );
+
+    let run = home.run(BIN, &["--include-island", "--json"]);
+    assert_eq!(run.status, 1);
+    assert!(run.stderr.contains("--include-island"), "{}", run.stderr);
+}
+
+#[test]
+fn detail_lists_beds_under_their_house() {
+    let home = FakeHome::new("crop-timers-report-detail");
+    let ago = |hours| (home.now() - Duration::hours(hours)).to_rfc3339();
+    home.crop(1, Duration::hours(24), Duration::hours(1)).file(
+        "Accountant/crops_plot/2.json",
+        format!(
+            r#"{{"Item1": {{"Zone": 341, "ServerId": 79, "Ward": 12, "Plot": 30}},
+                "Item2": [{{"PlantTime": "{}", "LastTending": "{}", "PlantId": 4842,
+                            "AccuratePlantTime": true}},
+                          {{"PlantTime": "{}", "LastTending": "{}", "PlantId": 4842,
+                            "AccuratePlantTime": true}}]}}"#,
+            ago(24),
+            ago(1),
+            ago(130),
+            ago(1),
+        ),
+    );
+    let run = home.run(BIN, &["--detail"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(lines[1].starts_with("    Almond (3)  "), "{}", run.stdout);
+    assert_eq!(lines[2], "        Cactuar Mist ward 1 plot 1");
+    assert!(
+        lines[3].starts_with("            okay - 1d 23:00:00 ("),
+        "{}",
+        run.stdout
+    );
+    assert_eq!(lines[4], "        Cactuar The Goblet ward 12 plot 30");
+    assert!(
+        lines[5].starts_with("            okay - "),
+        "{}",
+        run.stdout
+    );
+    assert_eq!(lines[6], "            done ");
+    assert_eq!(lines.len(), 7, "{}", run.stdout);
+
+    let run = home.run(BIN, &["--detail", "--json"]);
+    assert_eq!(run.status, 1);
+    assert!(run.stderr.contains("--detail"), "{}", run.stderr);
+}
+
+#[test]
+fn json_lists_each_crop_with_its_plots() {
+    let home = FakeHome::new("crop-timers-report-json");
+    home.crop(1, Duration::hours(24), Duration::hours(1)).crop(
+        2,
+        Duration::hours(130),
+        Duration::hours(1),
+    );
+    let run = home.run(BIN, &["--json", "--color", "always"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert!(!run.stdout.contains('\x1b'));
+    let json: serde_json::Value = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(json["tool"], "crops");
+    let crop = &json["data"][0];
+    assert_eq!(crop["plant_id"], 4842);
+    assert_eq!(crop["name"], "Almond");
+    assert_eq!(crop["patch_count"], 2);
+    // The most urgent plot's status, with nothing left to wait for
+    assert_eq!(crop["status"], "done");
+    assert!(crop["stage_time"].is_null());
+
+    let plot = &crop["patches"][0];
+    assert_eq!(plot["district"], "Mist");
+    assert_eq!(
+        (plot["ward"].as_u64(), plot["plot"].as_u64()),
+        (Some(1), Some(1))
+    );
+    assert_eq!(plot["status"], "okay");
+    let stage_time = plot["stage_time"].as_str().unwrap();
+    let stage_time = chrono::DateTime::parse_from_rfc3339(stage_time).unwrap();
+    assert_eq!(stage_time, home.now() + Duration::hours(47));
+}
+
+#[test]
+fn guessed_plant_times_are_marked() {
+    let home = FakeHome::new("crop-timers-report-estimated");
+    let ago = |hours| (home.now() - Duration::hours(hours)).to_rfc3339();
+    let guessed = |file: &str, planted| {
+        home.file(
+            format!("Accountant/crops_plot/{file}.json"),
+            format!(
+                r#"{{"Item1": {{"Zone": 339, "ServerId": 79, "Ward": 1, "Plot": 1}},
+                    "Item2": [{{"PlantTime": "{}", "LastTending": "{}", "PlantId": 4842,
+                                "AccuratePlantTime": false}}]}}"#,
+                ago(planted),
+                ago(1),
+            ),
+        );
+    };
+    guessed("1", 100);
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(
+        lines[1].starts_with("    Almond (1)  - ~20:00:00 (~"),
+        "{}",
+        lines[1]
+    );
+    assert!(lines[1].ends_with(", estimated)"), "{}", lines[1]);
+
+    // Past the guessed harvest, it isn't called done
+    guessed("1", 121);
+    let run = home.run(BIN, &["--detail"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines[1], "    Almond (1)  - ready soon (estimated)");
+    assert_eq!(lines[3], "            good - ready soon (estimated)");
+}
+
+#[test]
+fn indoor_and_apartment_pots_are_read_too() {
+    let home = FakeHome::new("crop-timers-report-indoors");
+    let ago = |hours| (home.now() - Duration::hours(hours)).to_rfc3339();
+    let almond = format!(
+        r#"{{"PlantTime": "{}", "LastTending": "{}", "PlantId": 4842,
+             "AccuratePlantTime": true}}"#,
+        ago(24),
+        ago(1)
+    );
+    home.crop(1, Duration::hours(24), Duration::hours(1))
+        .file(
+            "Accountant/crops_private/1.json",
+            format!(
+                r#"{{"Item1": {{"Zone": 339, "ServerId": 79, "Ward": 1, "Plot": 1}},
+                    "Item2": [{almond}]}}"#
+            ),
+        )
+        // Apartments have no plot
+        .file(
+            "Accountant/crops_apartment/1.json",
+            format!(
+                r#"{{"Item1": {{"Zone": 341, "ServerId": 79, "Ward": 4}}, "Item2": [{almond}]}}"#
+            ),
+        );
+    let run = home.run(BIN, &["--detail"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(lines[1].starts_with("    Almond (3)  - "), "{}", run.stdout);
+    let houses = lines
+        .iter()
+        .filter(|line| line.starts_with("        C"))
+        .map(|line| line.trim())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        houses,
+        [
+            "Cactuar Mist ward 1 plot 1",
+            "Cactuar The Goblet ward 4 apartment",
+            "Cactuar Mist ward 1 plot 1 (indoors)",
+        ],
+        "{}",
+        run.stdout
+    );
+
+    let run = home.run(BIN, &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(&run.stdout).unwrap();
+    let patches = &json["data"][0]["patches"];
+    assert!(patches[0].get("placement").is_none());
+    assert_eq!(patches[1]["placement"], "apartment");
+    assert_eq!(patches[2]["placement"], "indoor");
+}
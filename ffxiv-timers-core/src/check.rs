@@ -0,0 +1,84 @@
+//! What `--check` exits with, the same for every tool so a script or
+//! monitoring system can run any of them alike. Each tool only says how
+//! urgent its report is. Either way, the report is the only thing printed to
+//! stdout, and everything said about it is logged to stderr.
+
+use std::process::ExitCode;
+
+use tracing::error;
+
+/// How much a report wants attention, from least to most.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Nothing to do yet
+    #[default]
+    Ok,
+    /// Something is ready to be seen to, like a crop to harvest or a
+    /// submarine back from its voyage
+    Warning,
+    /// Something will be lost without attention, like a wilting crop
+    Critical,
+}
+
+impl Severity {
+    /// The exit status for `--check`: 0, 1 or 2, as monitoring plugins use.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Severity::Ok => 0,
+            Severity::Warning => 1,
+            Severity::Critical => 2,
+        }
+    }
+}
+
+/// The exit status for `--check` when there's no report to judge, because
+/// something failed.
+pub const EXIT_UNKNOWN: u8 = 3;
+
+/// How a tool's run ends: with `check`, the exit status for the severity it
+/// found, or [`EXIT_UNKNOWN`] after logging the error it failed with.
+/// Otherwise severities don't change anything, and an error is returned for
+/// `main` to print.
+pub fn finish(check: bool, result: anyhow::Result<Severity>) -> anyhow::Result<ExitCode> {
+    match (check, result) {
+        (false, result) => result.map(|_| ExitCode::SUCCESS),
+        (true, Ok(severity)) => Ok(ExitCode::from(severity.exit_code())),
+        (true, Err(err)) => {
+            error!("{err:#}");
+            Ok(ExitCode::from(EXIT_UNKNOWN))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn only_check_changes_the_exit_status() {
+        let ok = |severity| -> anyhow::Result<Severity> { Ok(severity) };
+        assert_eq!(
+            finish(false, ok(Severity::Critical)).unwrap(),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            finish(true, ok(Severity::Warning)).unwrap(),
+            ExitCode::from(1)
+        );
+        assert_eq!(
+            finish(true, ok(Severity::Critical)).unwrap(),
+            ExitCode::from(2)
+        );
+        assert!(finish(false, Err(anyhow!("unreadable"))).is_err());
+        assert_eq!(
+            finish(true, Err(anyhow!("unreadable"))).unwrap(),
+            ExitCode::from(EXIT_UNKNOWN)
+        );
+        assert_eq!(
+            [Severity::Warning, Severity::Ok].into_iter().max(),
+            Some(Severity::Warning)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Where every countdown gets the time from. Each binary sets the clock once
+//! at startup with [`init`], and everything that compares a timer to now
+//! asks [`now`], so a timing bug can be reproduced by stopping the clock
+//! at the moment it happened with [`FAKE_NOW_ENV`].
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+/// An RFC 3339 time to stop the clock at, instead of reading the real time.
+pub const FAKE_NOW_ENV: &str = "FFXIV_FAKE_NOW";
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's clock.
+#[derive(Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at one time, as [`FAKE_NOW_ENV`] sets and tests use.
+#[derive(Debug)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// The clock the environment asks for: stopped at [`FAKE_NOW_ENV`] when
+/// it's set, or the system's.
+pub fn from_env() -> anyhow::Result<Box<dyn Clock>> {
+    let Some(fake) = std::env::var_os(FAKE_NOW_ENV) else {
+        return Ok(Box::new(SystemClock));
+    };
+    let fake = fake.to_string_lossy();
+    let now = DateTime::parse_from_rfc3339(&fake)
+        .with_context(|| format!("{FAKE_NOW_ENV} isn't an RFC 3339 time: {fake:?}"))?;
+    debug!("Stopping the clock at {now}, from {FAKE_NOW_ENV}");
+    Ok(Box::new(FixedClock(now.with_timezone(&Utc))))
+}
+
+static CLOCK: OnceLock<Box<dyn Clock>> = OnceLock::new();
+
+/// Sets the clock from the environment for the rest of the run.
+pub fn init() -> anyhow::Result<()> {
+    set(from_env()?);
+    Ok(())
+}
+
+/// Uses `clock` for the rest of the run. Only the first call has any effect.
+pub fn set(clock: Box<dyn Clock>) {
+    let _ = CLOCK.set(clock);
+}
+
+/// The current time, from the clock set at startup, or the system's if
+/// none was.
+pub fn now() -> DateTime<Utc> {
+    CLOCK.get().map_or_else(Utc::now, |clock| clock.now())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn a_fake_now_stops_the_clock() {
+        std::env::set_var(FAKE_NOW_ENV, "2024-03-01T12:00:00+01:00");
+        let clock = from_env().unwrap();
+        assert_eq!(clock.now().to_rfc3339(), "2024-03-01T11:00:00+00:00");
+        std::env::set_var(FAKE_NOW_ENV, "yesterday");
+        assert!(from_env().is_err());
+        std::env::remove_var(FAKE_NOW_ENV);
+        let elapsed = Utc::now() - from_env().unwrap().now();
+        assert!(elapsed < Duration::seconds(5));
+    }
+}
@@ -0,0 +1,495 @@
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf, sync::OnceLock};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::{
+    i18n::Lang,
+    options::ColorWhen,
+    paths, profiles,
+    theme::{Preset, ThemeColor},
+};
+
+/// Top-level keys `config.toml` understands. Others are warned about rather
+/// than rejected, so a config written for a newer version still loads.
+const KNOWN_KEYS: &[&str] = &[
+    "data_root",
+    "probe_paths",
+    "color",
+    "pager",
+    "lang",
+    "worlds",
+    "watchlist",
+    "aliases",
+    "map_tiers",
+    "fc_order",
+    "sub_labels",
+    "mqtt",
+    "notify",
+    "urgency",
+    "theme",
+    "fallback_max_age",
+    "updated_max_age",
+    "profiles",
+];
+
+/// Persistent defaults shared by every tool, read from `config.toml` in the
+/// config directory. Command line flags take precedence over environment
+/// variables, which take precedence over these.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// The plugin config directory, like `--data-root`
+    pub data_root: Option<PathBuf>,
+    /// Where to look for the plugin config directory when it isn't given,
+    /// in order, in place of the launchers' usual places. Relative paths
+    /// are taken from the home directory.
+    pub probe_paths: Vec<PathBuf>,
+    /// When to color reports, like `--color`
+    pub color: Option<ColorWhen>,
+    /// Whether long reports are paged, unless `--no-pager` is given
+    /// [default: true]
+    pub pager: Option<bool>,
+    /// The language reports are printed in, like `--lang`
+    pub lang: Option<Lang>,
+    /// World names by id, for worlds added or renamed since the built-in
+    /// table was written; `worlds.csv` takes precedence
+    pub worlds: HashMap<String, String>,
+    /// Items a plain inventory query reports when none are given
+    pub watchlist: Vec<u32>,
+    /// Names to show in place of character names
+    pub aliases: HashMap<String, String>,
+    /// Labels for the treasure maps each character gathers, shown beside
+    /// them in the map report, by character name or alias
+    pub map_tiers: HashMap<String, String>,
+    /// Free company tags in the order their submarines are listed; others
+    /// follow in the usual order
+    pub fc_order: Vec<String>,
+    /// What each submarine is for, shown after its name, by `TAG/Name`,
+    /// e.g. `"MOGS/Nautilus" = "exp route"`
+    pub sub_labels: HashMap<String, String>,
+    /// The broker `publish-mqtt` sends Home Assistant sensors to
+    pub mqtt: Option<MqttConfig>,
+    /// What `notify-daemon` watches for and how it tells you
+    pub notify: NotifyConfig,
+    /// When timers count as soon and overdue
+    pub urgency: UrgencyConfig,
+    /// Colors for each part of a report
+    pub theme: ThemeConfig,
+    /// How old, in seconds, reports kept by `--fallback-cache` can be and
+    /// still be shown [default: an hour]
+    pub fallback_max_age: Option<u64>,
+    /// How old, in seconds, a character's task file can be before the map
+    /// report says when it was last updated [default: three days]
+    pub updated_max_age: Option<u64>,
+    /// Separate game installs to read, each with its own plugin configs
+    pub profiles: Vec<ProfileConfig>,
+}
+
+/// The `[mqtt]` table.
+#[derive(Deserialize, Debug)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "MqttConfig::default_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Also the Home Assistant device id
+    #[serde(default = "MqttConfig::default_client_id")]
+    pub client_id: String,
+    /// Where state and availability are published
+    #[serde(default = "MqttConfig::default_client_id")]
+    pub topic_prefix: String,
+    /// Where Home Assistant looks for sensors
+    #[serde(default = "MqttConfig::default_discovery_prefix")]
+    pub discovery_prefix: String,
+}
+
+impl MqttConfig {
+    fn default_port() -> u16 {
+        1883
+    }
+
+    fn default_client_id() -> String {
+        "ffxiv-timers".to_string()
+    }
+
+    fn default_discovery_prefix() -> String {
+        "homeassistant".to_string()
+    }
+}
+
+/// The `[notify]` table.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Seconds between checks
+    pub interval: u64,
+    /// Show desktop notifications, which is the default when no other
+    /// channel is set
+    pub desktop: Option<bool>,
+    /// Where each event is POSTed: a URL, given the event as JSON, or a
+    /// `[notify.webhook]` table
+    pub webhook: Option<WebhookConfig>,
+    /// A program and its arguments, run for each event with the event in
+    /// `FFXIV_TIMERS_EVENT*` environment variables
+    pub command: Vec<String>,
+    /// Sent once a crop that will wilt is soon to, by `[urgency]`
+    pub crop_wilting: EventConfig,
+    pub map_ready: EventConfig,
+    pub sub_returned: EventConfig,
+    /// Warns when fewer than `threshold` of a watchlist item are held
+    /// across every character; off without a threshold
+    pub item_low: EventConfig,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> NotifyConfig {
+        NotifyConfig {
+            interval: 300,
+            desktop: None,
+            webhook: None,
+            command: vec![],
+            crop_wilting: EventConfig::default(),
+            map_ready: EventConfig::default(),
+            sub_returned: EventConfig::default(),
+            item_low: EventConfig::default(),
+        }
+    }
+}
+
+/// `webhook` in `[notify]`, as a URL or a table with headers and a payload
+/// template.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(from = "WebhookSetting")]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Sent with every request, e.g. `Authorization` for Gotify
+    pub headers: HashMap<String, String>,
+    /// The JSON to send, with the event's fields filled in like `--format`
+    /// lines [default: the event itself]
+    pub payload: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WebhookSetting {
+    Url(String),
+    Table {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        payload: Option<String>,
+    },
+}
+
+impl From<WebhookSetting> for WebhookConfig {
+    fn from(setting: WebhookSetting) -> WebhookConfig {
+        match setting {
+            WebhookSetting::Url(url) => WebhookConfig {
+                url,
+                ..WebhookConfig::default()
+            },
+            WebhookSetting::Table {
+                url,
+                headers,
+                payload,
+            } => WebhookConfig {
+                url,
+                headers,
+                payload,
+            },
+        }
+    }
+}
+
+/// One kind of `notify-daemon` event.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct EventConfig {
+    pub enabled: bool,
+    pub threshold: Option<u32>,
+}
+
+impl Default for EventConfig {
+    fn default() -> EventConfig {
+        EventConfig {
+            enabled: true,
+            threshold: None,
+        }
+    }
+}
+
+/// The `[urgency]` table: minutes before a deadline it counts as soon, and
+/// after it as overdue, with `[urgency.crops]`, `[urgency.maps]` and
+/// `[urgency.subs]` taking precedence for their own report.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct UrgencyConfig {
+    pub soon: Option<u32>,
+    pub overdue: Option<u32>,
+    pub crops: ThresholdConfig,
+    pub maps: ThresholdConfig,
+    pub subs: ThresholdConfig,
+}
+
+/// One report's table in `[urgency]`.
+#[derive(Copy, Clone, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ThresholdConfig {
+    pub soon: Option<u32>,
+    pub overdue: Option<u32>,
+}
+
+/// The `[theme]` table: a preset like `--theme`, and colors for any roles
+/// it should be overridden for.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub preset: Option<Preset>,
+    pub header: Option<ThemeColor>,
+    pub good: Option<ThemeColor>,
+    pub warn: Option<ThemeColor>,
+    pub urgent: Option<ThemeColor>,
+    pub ready: Option<ThemeColor>,
+    pub error: Option<ThemeColor>,
+    pub dim: Option<ThemeColor>,
+}
+
+/// A `[[profiles]]` entry: one game install's plugin configs, and the
+/// world names and aliases that only apply to it.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// What `--profile` picks it by, and its rows are tagged with
+    pub label: Option<String>,
+    pub data_root: Option<PathBuf>,
+    /// Taking precedence over the top-level `[worlds]` and `worlds.csv`
+    pub worlds: HashMap<String, String>,
+    /// Taking precedence over the top-level `[aliases]`
+    pub aliases: HashMap<String, String>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Where the config file is read from.
+pub fn path() -> Option<PathBuf> {
+    paths::config_file()
+}
+
+/// Reads the config file, if there is one, for [`get`] to return for the
+/// rest of the run. Later calls keep the first result.
+pub fn load() -> anyhow::Result<&'static Config> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = match path() {
+        Some(path) if path.exists() => {
+            debug!("Reading settings from {}", path.display());
+            let data = read_to_string(&path).with_context(|| format!("Failed to open {path:?}"))?;
+            for key in unknown_keys(&data)? {
+                warn!("Ignoring unknown setting `{key}` in {}", path.display());
+            }
+            parse(&data).with_context(|| format!("Failed to parse {path:?}"))?
+        }
+        _ => Config::default(),
+    };
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded config, or the defaults if it hasn't been loaded.
+pub fn get() -> &'static Config {
+    static DEFAULT: OnceLock<Config> = OnceLock::new();
+    CONFIG
+        .get()
+        .unwrap_or_else(|| DEFAULT.get_or_init(Config::default))
+}
+
+fn parse(data: &str) -> anyhow::Result<Config> {
+    Ok(toml::from_str(data)?)
+}
+
+fn unknown_keys(data: &str) -> anyhow::Result<Vec<String>> {
+    let table: toml::Table = toml::from_str(data)?;
+    Ok(table
+        .keys()
+        .filter(|key| !KNOWN_KEYS.contains(&key.as_str()))
+        .cloned()
+        .collect())
+}
+
+impl Config {
+    /// The name to show for a character, after aliases, including those of
+    /// the profile being read.
+    pub fn alias<'a>(&'a self, name: &'a str) -> &'a str {
+        profiles::current()
+            .and_then(|profile| profile.aliases.get(name))
+            .or_else(|| self.aliases.get(name))
+            .map_or(name, String::as_str)
+    }
+
+    /// The map tier label for a character, by their own name or else the
+    /// name they're shown as.
+    pub fn map_tier(&self, name: &str) -> Option<&str> {
+        self.map_tiers
+            .get(name)
+            .or_else(|| self.map_tiers.get(self.alias(name)))
+            .map(String::as_str)
+    }
+
+    /// Where a free company sorts, by its position in `fc_order`.
+    pub fn fc_rank(&self, tag: &str) -> usize {
+        self.fc_order
+            .iter()
+            .position(|other| other == tag)
+            .unwrap_or(self.fc_order.len())
+    }
+
+    /// The label for submarine `name` of the free company `tag`, from
+    /// `[sub_labels]`.
+    pub fn sub_label(&self, tag: &str, name: &str) -> Option<&str> {
+        self.sub_labels
+            .get(&format!("{tag}/{name}"))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+data_root = "/games/pluginConfigs"
+probe_paths = [".xlcore/pluginConfigs", "/mnt/deck/pluginConfigs"]
+color = "never"
+pager = false
+lang = "de"
+fallback_max_age = 600
+updated_max_age = 86400
+watchlist = [21072, 5111]
+fc_order = ["MOGS"]
+lodestone = true
+
+[aliases]
+"Alice Doe" = "Main"
+
+[worlds]
+404 = "Marilith"
+
+[map_tiers]
+"Main" = "ophiotauroskin"
+
+[sub_labels]
+"MOGS/Nautilus" = "exp route"
+
+[mqtt]
+host = "broker.lan"
+username = "timers"
+
+[notify]
+webhook = "https://example.com/hook"
+
+[notify.item_low]
+threshold = 100
+
+[urgency]
+soon = 45
+
+[urgency.subs]
+overdue = 240
+
+[[profiles]]
+label = "alt"
+data_root = "/games/alt/pluginConfigs"
+
+[profiles.aliases]
+"Alice Doe" = "Alt"
+
+[[profiles]]
+data_root = "/games/third/pluginConfigs"
+
+[theme]
+preset = "light"
+header = "32, 32, 32"
+"#;
+
+    #[test]
+    fn reads_every_setting() {
+        let config = parse(CONFIG).unwrap();
+        assert_eq!(
+            config.data_root,
+            Some(PathBuf::from("/games/pluginConfigs"))
+        );
+        assert_eq!(
+            config.probe_paths[1],
+            PathBuf::from("/mnt/deck/pluginConfigs")
+        );
+        assert_eq!(config.color, Some(ColorWhen::Never));
+        assert_eq!(config.pager, Some(false));
+        assert_eq!(config.lang, Some(Lang::De));
+        assert_eq!(config.fallback_max_age, Some(600));
+        assert_eq!(config.updated_max_age, Some(86400));
+        assert_eq!(config.watchlist, [21072, 5111]);
+        assert_eq!(config.alias("Alice Doe"), "Main");
+        assert_eq!(config.alias("Bob"), "Bob");
+        assert_eq!(config.worlds["404"], "Marilith");
+        assert_eq!(config.map_tier("Alice Doe"), Some("ophiotauroskin"));
+        assert_eq!(config.map_tier("Bob"), None);
+        assert_eq!(config.fc_rank("MOGS"), 0);
+        assert_eq!(config.fc_rank("KUPO"), 1);
+        assert_eq!(config.sub_label("MOGS", "Nautilus"), Some("exp route"));
+        assert_eq!(config.sub_label("KUPO", "Nautilus"), None);
+        let mqtt = config.mqtt.unwrap();
+        assert_eq!((&*mqtt.host, mqtt.port), ("broker.lan", 1883));
+        assert_eq!(mqtt.username.as_deref(), Some("timers"));
+        assert_eq!(mqtt.topic_prefix, "ffxiv-timers");
+        assert_eq!(config.notify.interval, 300);
+        assert_eq!(config.notify.item_low.threshold, Some(100));
+        assert!(config.notify.item_low.enabled && config.notify.map_ready.enabled);
+        assert_eq!(config.urgency.soon, Some(45));
+        assert_eq!(config.urgency.subs.overdue, Some(240));
+        assert_eq!(config.urgency.crops.soon, None);
+        let webhook = config.notify.webhook.unwrap();
+        assert_eq!(webhook.url, "https://example.com/hook");
+        assert_eq!(webhook.payload, None);
+        assert_eq!(config.theme.preset, Some(Preset::Light));
+        assert_eq!(
+            config.theme.header,
+            Some(ThemeColor(termcolor::Color::Rgb(32, 32, 32)))
+        );
+        assert_eq!(config.theme.good, None);
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles[0].label.as_deref(), Some("alt"));
+        assert_eq!(config.profiles[0].aliases["Alice Doe"], "Alt");
+        assert_eq!(config.profiles[1].label, None);
+        assert_eq!(
+            config.profiles[1].data_root,
+            Some(PathBuf::from("/games/third/pluginConfigs"))
+        );
+    }
+
+    #[test]
+    fn a_webhook_table_has_headers_and_a_payload() {
+        let config = parse(
+            r#"
+[notify.webhook]
+url = "https://gotify.lan/message"
+payload = '{{"title": "{title}", "message": "{body}"}}'
+
+[notify.webhook.headers]
+X-Gotify-Key = "secret"
+"#,
+        )
+        .unwrap();
+        let webhook = config.notify.webhook.unwrap();
+        assert_eq!(webhook.url, "https://gotify.lan/message");
+        assert_eq!(webhook.headers["X-Gotify-Key"], "secret");
+        assert!(webhook.payload.unwrap().contains("{body}"));
+    }
+
+    #[test]
+    fn unknown_keys_are_only_reported() {
+        assert_eq!(unknown_keys(CONFIG).unwrap(), ["lodestone"]);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{de::Visitor, Deserializer};
+
+/// Deserializes a timestamp string, falling back to the Unix epoch for
+/// values that don't parse. The Accountant plugin writes placeholder dates
+/// for timers that were never set.
+pub fn datetime_or_default<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DateTimeOrDefault;
+
+    impl<'de> Visitor<'de> for DateTimeOrDefault {
+        type Value = DateTime<Utc>;
+
+        fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("string")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<DateTime<Utc>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(DateTime::from_str(value).unwrap_or_default())
+        }
+    }
+
+    deserializer.deserialize_str(DateTimeOrDefault)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    struct Timer {
+        #[serde(deserialize_with = "datetime_or_default")]
+        at: DateTime<Utc>,
+    }
+
+    fn parse(json: &str) -> serde_json::Result<DateTime<Utc>> {
+        serde_json::from_str::<Timer>(json).map(|timer| timer.at)
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        let at = parse(r#"{"at": "2023-10-01T12:30:00Z"}"#).unwrap();
+        assert_eq!(at.to_rfc3339(), "2023-10-01T12:30:00+00:00");
+    }
+
+    #[test]
+    fn bad_timestamps_become_the_default() {
+        assert_eq!(
+            parse(r#"{"at": "0001-01-01T00:00:00"}"#).unwrap(),
+            DateTime::<Utc>::default()
+        );
+        assert_eq!(parse(r#"{"at": ""}"#).unwrap(), DateTime::<Utc>::default());
+    }
+
+    #[test]
+    fn non_strings_are_errors() {
+        assert!(parse(r#"{"at": 5}"#).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! The last reports each tool read, kept by `--fallback-cache` so a file the
+//! game is part way through writing shows slightly old timers rather than an
+//! error.
+
+use std::path::Path;
+
+use chrono::{DateTime, Local, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{clock, config, i18n::trf, json::Tool, state};
+
+const SCHEMA: u32 = 1;
+
+/// How old, in seconds, cached reports can be and still be shown, unless
+/// `config.toml` sets `fallback_max_age`.
+pub const DEFAULT_MAX_AGE: u64 = 3600;
+
+#[derive(Serialize, Deserialize)]
+struct Cached<T> {
+    read_at: DateTime<Utc>,
+    reports: T,
+}
+
+/// Reads a tool's reports with `read`. When `enabled`, they're also kept
+/// for next time, and if `read` fails the last ones kept are returned
+/// instead, as long as they aren't too old, along with when they were read.
+pub fn load<T: Serialize + DeserializeOwned>(
+    tool: Tool,
+    enabled: bool,
+    read: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<(T, Option<DateTime<Utc>>)> {
+    if !enabled {
+        return Ok((read()?, None));
+    }
+    let path = state::path(&format!("{}-last.json", tool.name()))?;
+    let max_age = config::get().fallback_max_age.unwrap_or(DEFAULT_MAX_AGE);
+    load_from(&path, tool.name(), max_age, read)
+}
+
+fn load_from<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    name: &str,
+    max_age: u64,
+    read: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<(T, Option<DateTime<Utc>>)> {
+    let err = match read() {
+        Ok(reports) => {
+            let cached = Cached {
+                read_at: clock::now(),
+                reports,
+            };
+            if let Err(err) = state::save(path, SCHEMA, &cached) {
+                warn!("Failed to keep {name} reports: {err:#}");
+            }
+            return Ok((cached.reports, None));
+        }
+        Err(err) => err,
+    };
+    let cached = match state::load::<Option<Cached<T>>>(path, SCHEMA) {
+        Ok(Some(cached)) => cached,
+        Ok(None) => {
+            debug!("No {name} reports kept to fall back on");
+            return Err(err);
+        }
+        Err(cache_err) => {
+            warn!("Failed to read the {name} reports kept: {cache_err:#}");
+            return Err(err);
+        }
+    };
+    let age = (clock::now() - cached.read_at).num_seconds();
+    if age > i64::try_from(max_age).unwrap_or(i64::MAX) {
+        debug!("The {name} reports kept are {age}s old, past fallback_max_age");
+        return Err(err);
+    }
+    warn!("Showing {name} from {}: {err:#}", cached.read_at);
+    Ok((cached.reports, Some(cached.read_at)))
+}
+
+/// `text` with a note like `(stale, from 09:14)` after it if the reports
+/// it's about were read at `cached_at` rather than now.
+pub fn mark(text: &str, cached_at: Option<DateTime<Utc>>) -> String {
+    let Some(read_at) = cached_at else {
+        return text.to_string();
+    };
+    let time = read_at.with_timezone(&Local).format("%H:%M").to_string();
+    format!("{text} {}", trf("status.stale", &[("time", &time)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::anyhow;
+    use chrono::Duration;
+
+    use super::*;
+
+    fn scratch(test: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("fallback-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn failures_fall_back_to_the_last_reports() {
+        let dir = scratch("last");
+        let path = dir.join("crops-last.json");
+        let failed = || Err(anyhow!("mid-write"));
+        assert!(load_from::<Vec<u32>>(&path, "crops", 60, failed).is_err());
+
+        let (reports, read_at) = load_from(&path, "crops", 60, || Ok(vec![1, 2])).unwrap();
+        assert_eq!((reports, read_at), (vec![1, 2], None));
+        let (reports, read_at) = load_from::<Vec<u32>>(&path, "crops", 60, failed).unwrap();
+        assert_eq!(reports, [1, 2]);
+        assert!(Utc::now() - read_at.unwrap() < Duration::seconds(5));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn old_reports_are_not_shown() {
+        let dir = scratch("old");
+        let path = dir.join("maps-last.json");
+        let cached = Cached {
+            read_at: Utc::now() - Duration::hours(2),
+            reports: vec![1],
+        };
+        state::save(&path, SCHEMA, &cached).unwrap();
+        let failed = || Err(anyhow!("missing"));
+        let err = load_from::<Vec<u32>>(&path, "maps", 3600, failed).unwrap_err();
+        assert_eq!(err.to_string(), "missing");
+        assert!(load_from::<Vec<u32>>(&path, "maps", 3 * 3600, failed).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,320 @@
+//! Game constants that change with patches: crop timings, worlds, zones,
+//! item names and container sizes. A snapshot of each table is built in, as of
+//! [`SNAPSHOT_PATCH`]. Worlds, zones, items and containers are taken from the
+//! game's sheets. The sheets don't carry grow or wilt times, so the crops
+//! table is compiled from community gardening guides instead, and is the one
+//! most likely to be off. Any table can be replaced by a file of the same
+//! name in the `gamedata` directory beside `config.toml`, e.g.
+//! `gamedata/crops.json`, so a new patch's crops or worlds don't have to wait
+//! for a release. Each file says which patch it's from, for `doctor` to show.
+//! Tables are loaded on first use, for the rest of the run.
+
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use chrono::Duration;
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{debug, warn};
+
+use crate::paths;
+
+/// The patch the built-in tables were taken from.
+pub const SNAPSHOT_PATCH: &str = "7.1";
+
+/// How many submarines a free company can register once it has unlocked
+/// every slot.
+pub const SUBMARINE_SLOTS: usize = 4;
+
+/// The directory beside `config.toml` whose tables replace the built-in ones.
+const OVERRIDE_DIR: &str = "gamedata";
+
+const CROPS: &str = include_str!("../gamedata/crops.json");
+const WORLDS: &str = include_str!("../gamedata/worlds.json");
+const ZONES: &str = include_str!("../gamedata/zones.json");
+const ITEMS: &str = include_str!("../gamedata/items.json");
+const CONTAINERS: &str = include_str!("../gamedata/containers.json");
+
+/// One table, as written in its file.
+#[derive(Deserialize, Debug)]
+pub struct Table<T> {
+    /// The game patch the rows are from, e.g. `7.1`
+    pub patch: String,
+    pub rows: Vec<T>,
+}
+
+/// A crop that can be grown in a garden plot, by its seed's item id.
+#[derive(Deserialize, Debug)]
+pub struct Crop {
+    pub id: u32,
+    pub name: String,
+    /// From planting until it can be harvested
+    pub grow_hours: i64,
+    /// How long it can go untended before wilting
+    pub wilt_hours: i64,
+}
+
+impl Crop {
+    /// From planting until it can be harvested.
+    pub fn grow_time(&self) -> Duration {
+        Duration::hours(self.grow_hours)
+    }
+
+    /// From tending until it wilts.
+    pub fn wilt_time(&self) -> Duration {
+        Duration::hours(self.wilt_hours)
+    }
+
+    /// From tending until it withers, a day after wilting.
+    pub fn wither_time(&self) -> Duration {
+        self.wilt_time() + Duration::days(1)
+    }
+}
+
+/// A public world, by its row in the game's World sheet.
+#[derive(Deserialize, Debug)]
+pub struct World {
+    pub id: u32,
+    pub name: String,
+    pub data_center: String,
+}
+
+/// A zone crops can be grown in, by its row in the game's TerritoryType
+/// sheet.
+#[derive(Deserialize, Debug)]
+pub struct Zone {
+    pub id: u32,
+    pub name: String,
+    pub kind: ZoneKind,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ZoneKind {
+    /// A housing district, whose plots have gardens
+    Housing,
+    /// The Island Sanctuary, whose cropland grows on its own schedule
+    Island,
+}
+
+/// An item whose name is known even without an item table.
+#[derive(Deserialize, Debug)]
+pub struct Item {
+    pub id: u32,
+    pub name: String,
+}
+
+/// One page of an inventory, by the `InventoryType` InventoryTools writes.
+#[derive(Deserialize, Debug)]
+pub struct Container {
+    pub id: u32,
+    /// Which inventory the page belongs to, e.g. `saddlebag`; pages of the
+    /// same group are counted together
+    pub group: String,
+    /// Slots on the page, or 0 for those without a limit, like currency
+    pub capacity: u32,
+    /// Names a page that holds one kind of item, like the armoury chest's
+    #[serde(default)]
+    pub section: Option<String>,
+}
+
+/// Where a table was read from.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// The built-in snapshot
+    Snapshot,
+    /// A file in the override directory
+    Override(PathBuf),
+}
+
+/// A table in use, and where it came from.
+#[derive(Debug)]
+pub struct Loaded<T> {
+    pub table: Table<T>,
+    pub origin: Origin,
+    /// Why an override file was passed over for the snapshot, if it was
+    pub ignored: Option<String>,
+}
+
+impl<T> Loaded<T> {
+    pub fn rows(&self) -> &[T] {
+        &self.table.rows
+    }
+}
+
+/// The table `name` from `dir` when there's a file for it there, or else
+/// from the `snapshot`. An override that can't be read is passed over with a
+/// warning rather than failing every report that looks something up.
+fn load<T: DeserializeOwned>(name: &str, snapshot: &str, dir: Option<&Path>) -> Loaded<T> {
+    let mut ignored = None;
+    let path = dir.map(|dir| dir.join(format!("{name}.json")));
+    if let Some(path) = path.filter(|path| path.exists()) {
+        debug!("Reading the {name} table from {}", path.display());
+        let table = read_to_string(&path)
+            .with_context(|| format!("Failed to open {path:?}"))
+            .and_then(|data| {
+                serde_json::from_str(&data).with_context(|| format!("Failed to parse {path:?}"))
+            });
+        match table {
+            Ok(table) => {
+                return Loaded {
+                    table,
+                    origin: Origin::Override(path),
+                    ignored: None,
+                }
+            }
+            Err(err) => {
+                warn!("Using the built-in {name} table: {err:#}");
+                ignored = Some(format!("{err:#}"));
+            }
+        }
+    }
+    Loaded {
+        table: serde_json::from_str(snapshot).expect("the built-in tables are valid"),
+        origin: Origin::Snapshot,
+        ignored,
+    }
+}
+
+/// Where tables replacing the built-in ones are read from.
+pub fn override_dir() -> Option<PathBuf> {
+    Some(paths::config_dir()?.join(OVERRIDE_DIR))
+}
+
+/// Crops' names and timings.
+pub fn crops() -> &'static Loaded<Crop> {
+    static TABLE: OnceLock<Loaded<Crop>> = OnceLock::new();
+    TABLE.get_or_init(|| load("crops", CROPS, override_dir().as_deref()))
+}
+
+/// Worlds' names and data centers.
+pub fn worlds() -> &'static Loaded<World> {
+    static TABLE: OnceLock<Loaded<World>> = OnceLock::new();
+    TABLE.get_or_init(|| load("worlds", WORLDS, override_dir().as_deref()))
+}
+
+/// Names and kinds of the zones gardens are in.
+pub fn zones() -> &'static Loaded<Zone> {
+    static TABLE: OnceLock<Loaded<Zone>> = OnceLock::new();
+    TABLE.get_or_init(|| load("zones", ZONES, override_dir().as_deref()))
+}
+
+/// The zone with territory id `id`, if the table knows it.
+pub fn zone(id: u32) -> Option<&'static Zone> {
+    zones().rows().iter().find(|zone| zone.id == id)
+}
+
+/// Whether territory id `id` is the Island Sanctuary rather than a housing
+/// district.
+pub fn is_island(id: u32) -> bool {
+    zone(id).is_some_and(|zone| zone.kind == ZoneKind::Island)
+}
+
+/// Names of currencies and other items most reports mention.
+pub fn items() -> &'static Loaded<Item> {
+    static TABLE: OnceLock<Loaded<Item>> = OnceLock::new();
+    TABLE.get_or_init(|| load("items", ITEMS, override_dir().as_deref()))
+}
+
+/// Inventory pages' groups and sizes.
+pub fn containers() -> &'static Loaded<Container> {
+    static TABLE: OnceLock<Loaded<Container>> = OnceLock::new();
+    TABLE.get_or_init(|| load("containers", CONTAINERS, override_dir().as_deref()))
+}
+
+/// What `doctor` says about a table.
+#[derive(Debug)]
+pub struct TableInfo {
+    pub name: &'static str,
+    pub patch: &'static str,
+    pub origin: &'static Origin,
+    /// Why an override was passed over, if one was
+    pub ignored: Option<&'static str>,
+}
+
+/// Every table in use.
+pub fn summary() -> Vec<TableInfo> {
+    fn info<T>(name: &'static str, loaded: &'static Loaded<T>) -> TableInfo {
+        TableInfo {
+            name,
+            patch: &loaded.table.patch,
+            origin: &loaded.origin,
+            ignored: loaded.ignored.as_deref(),
+        }
+    }
+    vec![
+        info("crops", crops()),
+        info("worlds", worlds()),
+        info("zones", zones()),
+        info("items", items()),
+        info("containers", containers()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn the_snapshot_is_valid_and_from_one_patch() {
+        let snapshot = |loaded: Loaded<serde_json::Value>| {
+            assert_eq!(loaded.origin, Origin::Snapshot);
+            assert_eq!(loaded.table.patch, SNAPSHOT_PATCH);
+            loaded.table.rows.len()
+        };
+        for (name, data) in [
+            ("crops", CROPS),
+            ("worlds", WORLDS),
+            ("zones", ZONES),
+            ("items", ITEMS),
+            ("containers", CONTAINERS),
+        ] {
+            assert!(snapshot(load(name, data, None)) > 0, "{name} is empty");
+        }
+        let crops: Loaded<Crop> = load("crops", CROPS, None);
+        let almond = crops.rows().iter().find(|crop| crop.id == 4842).unwrap();
+        assert_eq!((almond.grow_hours, almond.wilt_hours), (120, 48));
+        for seed in ["Thavnairian Onion", "Voidrake"] {
+            assert!(crops.rows().iter().any(|crop| crop.name == seed), "{seed}");
+        }
+        let zones: Loaded<Zone> = load("zones", ZONES, None);
+        let islands = zones
+            .rows()
+            .iter()
+            .filter(|zone| zone.kind == ZoneKind::Island);
+        assert_eq!(islands.map(|zone| zone.id).collect::<Vec<_>>(), [1055]);
+    }
+
+    #[test]
+    fn an_override_replaces_only_its_own_table() {
+        let dir = std::env::temp_dir().join(format!("gamedata-override-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("crops.json"),
+            r#"{"patch": "7.2", "rows": [{"id": 1, "name": "Moonseed", "grow_hours": 1, "wilt_hours": 2}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("items.json"), "{not json").unwrap();
+
+        let crops: Loaded<Crop> = load("crops", CROPS, Some(&dir));
+        assert_eq!(crops.origin, Origin::Override(dir.join("crops.json")));
+        assert_eq!(crops.table.patch, "7.2");
+        assert_eq!(crops.rows().len(), 1);
+        assert_eq!(crops.rows()[0].name, "Moonseed");
+        // Tables without a file of their own keep the snapshot
+        let worlds: Loaded<World> = load("worlds", WORLDS, Some(&dir));
+        assert_eq!(worlds.origin, Origin::Snapshot);
+        assert!(worlds.ignored.is_none());
+        // As do those whose file can't be read, saying why
+        let items: Loaded<Item> = load("items", ITEMS, Some(&dir));
+        assert_eq!(items.origin, Origin::Snapshot);
+        assert!(items.ignored.unwrap().contains("items.json"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+//! Report labels in each supported language. Every language is a table of
+//! message keys, so adding one only means adding its table here; keys it
+//! leaves out are shown in English.
+
+use std::{str::FromStr, sync::OnceLock};
+
+use anyhow::bail;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::config;
+
+/// A language reports can be printed in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+    Fr,
+    Ja,
+}
+
+impl FromStr for Lang {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Lang> {
+        match value {
+            "en" => Ok(Lang::En),
+            "de" => Ok(Lang::De),
+            "fr" => Ok(Lang::Fr),
+            "ja" => Ok(Lang::Ja),
+            _ => bail!("invalid value `{value}` for --lang; expected en, de, fr or ja"),
+        }
+    }
+}
+
+impl Lang {
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::De => DE,
+            Lang::Fr => FR,
+            Lang::Ja => JA,
+        }
+    }
+}
+
+/// Message keys and their English text, which every other language falls
+/// back to. `{n}` and `{time}` are filled in by [`trf`].
+const EN: &[(&str, &str)] = &[
+    ("crops.title", "Crop Timers"),
+    ("crops.island", "Island"),
+    ("maps.title", "Map Allowances"),
+    ("maps.updated", "updated {time}"),
+    ("subs.title", "Submarines"),
+    ("status.ready", "ready"),
+    ("status.unassigned", "unassigned"),
+    ("status.stale", "(stale, from {time})"),
+    ("subs.unassigned", "Unassigned"),
+    ("subs.complete", "Voyage complete"),
+    ("subs.slot_unused", "({n} slot unused)"),
+    ("subs.slots_unused", "({n} slots unused)"),
+    ("time.in", "in {time}"),
+    ("time.ago", "{time} ago"),
+    ("time.overdue", "overdue {time}"),
+    ("time.days", "{n}d"),
+    ("time.under_a_minute", "less than a minute"),
+    ("time.a_minute", "about a minute"),
+    ("time.minutes", "about {n} minutes"),
+    ("time.hours", "about {n} hours"),
+    ("time.days_long", "about {n} days"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("crops.title", "Pflanzen"),
+    ("crops.island", "Insel"),
+    ("maps.title", "Schatzkarten"),
+    ("maps.updated", "aktualisiert {time}"),
+    ("subs.title", "Tauchboote"),
+    ("status.ready", "bereit"),
+    ("status.unassigned", "nicht zugewiesen"),
+    ("status.stale", "(veraltet, Stand {time})"),
+    ("subs.unassigned", "Nicht zugewiesen"),
+    ("subs.complete", "Erkundung abgeschlossen"),
+    ("subs.slot_unused", "({n} Platz ungenutzt)"),
+    ("subs.slots_unused", "({n} Plätze ungenutzt)"),
+    ("time.in", "in {time}"),
+    ("time.ago", "vor {time}"),
+    ("time.overdue", "{time} überfällig"),
+    ("time.days", "{n}T"),
+    ("time.under_a_minute", "weniger als einer Minute"),
+    ("time.a_minute", "etwa einer Minute"),
+    ("time.minutes", "etwa {n} Minuten"),
+    ("time.hours", "etwa {n} Stunden"),
+    ("time.days_long", "etwa {n} Tagen"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("crops.title", "Cultures"),
+    ("crops.island", "Île"),
+    ("maps.title", "Cartes au trésor"),
+    ("maps.updated", "mis à jour {time}"),
+    ("subs.title", "Sous-marins"),
+    ("status.ready", "prêt"),
+    ("status.unassigned", "non assigné"),
+    ("status.stale", "(périmé, données de {time})"),
+    ("subs.unassigned", "Non assigné"),
+    ("subs.complete", "Exploration terminée"),
+    ("subs.slot_unused", "({n} emplacement inutilisé)"),
+    ("subs.slots_unused", "({n} emplacements inutilisés)"),
+    ("time.in", "dans {time}"),
+    ("time.ago", "il y a {time}"),
+    ("time.overdue", "en retard de {time}"),
+    ("time.days", "{n}j"),
+    ("time.under_a_minute", "moins d'une minute"),
+    ("time.a_minute", "environ une minute"),
+    ("time.minutes", "environ {n} minutes"),
+    ("time.hours", "environ {n} heures"),
+    ("time.days_long", "environ {n} jours"),
+];
+
+const JA: &[(&str, &str)] = &[
+    ("crops.title", "栽培タイマー"),
+    ("crops.island", "無人島"),
+    ("maps.title", "地図の採集権"),
+    ("maps.updated", "{time}に更新"),
+    ("subs.title", "潜水艦"),
+    ("status.ready", "完了"),
+    ("status.unassigned", "未登録"),
+    ("status.stale", "(古いデータ、{time}時点)"),
+    ("subs.unassigned", "未登録"),
+    ("subs.complete", "探索完了"),
+    ("subs.slot_unused", "(空き枠{n})"),
+    ("subs.slots_unused", "(空き枠{n})"),
+    ("time.in", "{time}後"),
+    ("time.ago", "{time}前"),
+    ("time.overdue", "{time}超過"),
+    ("time.days", "{n}日"),
+    ("time.under_a_minute", "1分未満"),
+    ("time.a_minute", "約1分"),
+    ("time.minutes", "約{n}分"),
+    ("time.hours", "約{n}時間"),
+    ("time.days_long", "約{n}日"),
+];
+
+/// Set from the command line, taking precedence over `config.toml`.
+static LANG_OVERRIDE: OnceLock<Lang> = OnceLock::new();
+
+/// Prints reports in `lang` for the rest of the run, whatever `config.toml`
+/// says. Only the first call has any effect.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG_OVERRIDE.set(lang);
+}
+
+/// The language reports are printed in.
+pub fn lang() -> Lang {
+    LANG_OVERRIDE
+        .get()
+        .copied()
+        .or(config::get().lang)
+        .unwrap_or_default()
+}
+
+/// The text for a message key in the current language.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(lang(), key)
+}
+
+/// Like [`tr`], filling in each `{name}` with its value.
+pub fn trf(key: &'static str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(tr(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+fn lookup(lang: Lang, key: &'static str) -> &'static str {
+    let find = |lang: Lang| {
+        lang.catalog()
+            .iter()
+            .find(|(other, _)| *other == key)
+            .map(|(_, text)| *text)
+    };
+    if let Some(text) = find(lang) {
+        return text;
+    }
+    match find(Lang::En) {
+        Some(text) => {
+            debug!("No {lang:?} text for `{key}`, using English");
+            text
+        }
+        None => {
+            debug!("No text for `{key}` in any language");
+            key
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_has_english_text() {
+        for lang in [Lang::De, Lang::Fr, Lang::Ja] {
+            for (key, _) in lang.catalog() {
+                assert!(
+                    EN.iter().any(|(english, _)| english == key),
+                    "{lang:?} has `{key}`, which English doesn't"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn missing_text_falls_back_to_english() {
+        assert_eq!(lookup(Lang::De, "subs.title"), "Tauchboote");
+        assert_eq!(lookup(Lang::En, "subs.title"), "Submarines");
+        assert_eq!(lookup(Lang::Fr, "not.a.key"), "not.a.key");
+    }
+}
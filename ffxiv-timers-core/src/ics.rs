@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+
+/// Longest a content line may be, in octets, before it's folded.
+const LINE_LIMIT: usize = 75;
+
+/// One timer as a calendar event.
+pub struct Event {
+    /// Stays the same for the thing being timed, so a calendar that imports
+    /// a newer export moves the event instead of adding another
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    /// Put in front of the summary and in `CATEGORIES`, e.g. `Crops`
+    pub category: &'static str,
+    pub summary: String,
+    pub description: String,
+}
+
+/// A whole `VCALENDAR` holding `events`, with CRLF line endings.
+pub fn calendar(events: &[Event], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//ffxiv-timers//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", escape(&event.uid)),
+            format!("DTSTAMP:{}", timestamp(now)),
+            format!("DTSTART:{}", timestamp(event.start)),
+            format!(
+                "SUMMARY:{}",
+                escape(&format!("[{}] {}", event.category, event.summary))
+            ),
+            format!("DESCRIPTION:{}", escape(&event.description)),
+            format!("CATEGORIES:{}", escape(event.category)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// Times are always written in UTC, which every calendar shows in the
+/// viewer's own zone.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a text value's backslashes, separators and line breaks.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            ';' => escaped.push_str(r"\;"),
+            ',' => escaped.push_str(r"\,"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a content line longer than 75 octets into continuation lines
+/// starting with a space, never in the middle of a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / LINE_LIMIT * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape("a;b,c\\d\r\ne"), r"a\;b\,c\\d\ne");
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        assert_eq!(fold(&"x".repeat(75)), "x".repeat(75));
+        let folded = fold(&"x".repeat(200));
+        let lines = folded.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(
+            lines.iter().map(|line| line.len()).collect::<Vec<_>>(),
+            [75, 75, 52]
+        );
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), "x".repeat(200));
+    }
+
+    #[test]
+    fn folding_keeps_characters_whole() {
+        // « is 2 octets (C2 AB) and € 3 (E2 82 AC); after 74 and 73 octets
+        // of ASCII, the limit falls inside each of them
+        for (before, c) in [(74, '«'), (73, '€')] {
+            let line = format!("{}{c}x", "x".repeat(before));
+            assert!(!line.is_char_boundary(LINE_LIMIT), "{c}");
+            let folded = fold(&line);
+            assert!(folded.split("\r\n").all(|line| line.len() <= LINE_LIMIT));
+            assert_eq!(folded, format!("{}\r\n {c}x", "x".repeat(before)));
+        }
+    }
+
+    #[test]
+    fn writes_utc_events() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let event = Event {
+            uid: "map-alice@ffxiv-timers".to_string(),
+            start,
+            category: "Maps",
+            summary: "Alice, ready".to_string(),
+            description: String::new(),
+        };
+        let calendar = calendar(&[event], start);
+        assert!(calendar.contains("\r\nDTSTART:20240102T030405Z\r\n"));
+        assert!(calendar.contains("\r\nSUMMARY:[Maps] Alice\\, ready\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+}
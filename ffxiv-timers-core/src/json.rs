@@ -0,0 +1,415 @@
+use std::{cell::RefCell, io::Write};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+
+/// Which tool a `--json` report came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tool {
+    Crops,
+    Maps,
+    Subs,
+    Inventory,
+}
+
+impl Tool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Crops => "crops",
+            Tool::Maps => "maps",
+            Tool::Subs => "subs",
+            Tool::Inventory => "inventory",
+        }
+    }
+
+    /// Bumped whenever the tool's `data` changes in a way older readers
+    /// can't handle. Adding fields doesn't count.
+    pub fn schema(self) -> u32 {
+        match self {
+            Tool::Crops | Tool::Maps | Tool::Subs => 1,
+            // 1 was the bare report from before the envelope
+            Tool::Inventory => 2,
+        }
+    }
+}
+
+/// What every `--json` report is wrapped in, so readers can tell the tools
+/// apart.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Envelope<T> {
+    pub tool: String,
+    pub schema: u32,
+    pub generated_at: DateTime<Utc>,
+    /// When `data` was read, if it's the last report `--fallback-cache`
+    /// kept rather than one read now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<DateTime<Utc>>,
+    pub data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(tool: Tool, data: T) -> Envelope<T> {
+        Envelope {
+            tool: tool.name().to_string(),
+            schema: tool.schema(),
+            generated_at: clock::now(),
+            cached_at: None,
+            data,
+        }
+    }
+}
+
+/// One kind of crop across every plot growing it.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CropReport {
+    /// The seed's item id
+    pub plant_id: u32,
+    pub name: String,
+    /// How many plots are growing it
+    pub patch_count: usize,
+    /// The most urgent plot's status
+    pub status: CropStatus,
+    /// When the status next changes, if it still can
+    pub stage_time: Option<DateTime<Utc>>,
+    /// Whether `stage_time` is a harvest worked out from a plant time
+    /// Accountant only guessed, so it may be hours early
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    /// Each plot growing the crop
+    #[serde(default)]
+    pub patches: Vec<PlotReport>,
+}
+
+/// One garden plot.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PlotReport {
+    pub world: String,
+    /// The housing district, e.g. "Mist"
+    pub district: String,
+    pub ward: u32,
+    /// The plot number, or 0 in an apartment
+    pub plot: u32,
+    /// Whether it's in the garden, a pot indoors or an apartment
+    #[serde(default, skip_serializing_if = "Placement::is_outdoor")]
+    pub placement: Placement,
+    pub status: CropStatus,
+    pub stage_time: Option<DateTime<Utc>>,
+    /// Whether `stage_time` is worked out from a guessed plant time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    /// The profile it's from, when several are read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// How many seconds its timestamps are ahead of the system clock, when
+    /// that's too far to be rounding and one of the two is probably wrong
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_skew: Option<i64>,
+}
+
+/// Where a plot's crop is grown, from the Accountant folder it was read
+/// from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// A bed in a house's garden
+    #[default]
+    Outdoor,
+    /// A flowerpot inside a private house or free company room
+    Indoor,
+    /// A flowerpot in an apartment, which has no plot
+    Apartment,
+}
+
+impl Placement {
+    fn is_outdoor(&self) -> bool {
+        *self == Placement::Outdoor
+    }
+}
+
+/// Where a crop is at, from least to most urgent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CropStatus {
+    /// Will finish growing before it needs tending
+    Good,
+    /// Growing, but needs tending before it finishes
+    Okay,
+    /// Needs tending now or it will wither
+    Wilt,
+    /// Ready to harvest
+    Done,
+    /// Withered
+    Dead,
+}
+
+impl CropStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            CropStatus::Good => "good",
+            CropStatus::Okay => "okay",
+            CropStatus::Wilt => "wilt",
+            CropStatus::Done => "done",
+            CropStatus::Dead => "dead",
+        }
+    }
+}
+
+/// One character's treasure map allowance.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MapReport {
+    pub character: String,
+    pub world: String,
+    pub next_map: DateTime<Utc>,
+    /// The map tier the character gathers, from `[map_tiers]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+    /// When Accountant last wrote the character's file, which it does on
+    /// logout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<DateTime<Utc>>,
+    /// The profile it's from, when several are read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+/// One free company's submarines.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct FreeCompanyReport {
+    pub character: String,
+    pub tag: String,
+    pub world: String,
+    pub submarines: Vec<SubmarineReport>,
+    /// The profile it's from, when several are read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SubmarineReport {
+    pub name: String,
+    /// Missing for submarines that aren't on a voyage
+    pub return_time: Option<DateTime<Utc>>,
+    /// What it's for, from `[sub_labels]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// The items behind an inventory report.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct InventoryReport {
+    /// The report mode, e.g. "query" or "currency"
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub items: Vec<HeldItem>,
+}
+
+/// How much of an item one character or retainer holds at one quality.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct HeldItem {
+    pub item_id: u32,
+    pub item_name: String,
+    pub character: String,
+    pub world: String,
+    pub is_retainer: bool,
+    pub quantity: u32,
+    pub hq: bool,
+    /// The profile it's from, when several are read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+thread_local! {
+    /// Envelopes held back by [`collect`] instead of being printed.
+    static COLLECTED: RefCell<Option<Vec<serde_json::Value>>> = const { RefCell::new(None) };
+}
+
+/// Prints a tool's report in its envelope.
+pub fn emit<T: Serialize>(tool: Tool, data: T) -> anyhow::Result<()> {
+    emit_cached(tool, data, None)
+}
+
+/// Like [`emit`], noting when `data` was read if it came from the
+/// `--fallback-cache`.
+pub fn emit_cached<T: Serialize>(
+    tool: Tool,
+    data: T,
+    cached_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    let envelope = Envelope {
+        cached_at,
+        ..Envelope::new(tool, data)
+    };
+    let collecting = COLLECTED.with_borrow(Option::is_some);
+    if collecting {
+        let value = serde_json::to_value(&envelope)?;
+        COLLECTED.with_borrow_mut(|collected| collected.get_or_insert_default().push(value));
+        return Ok(());
+    }
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &envelope)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// Runs `report`, keeping the envelopes it emits rather than printing them.
+pub fn collect<R>(report: impl FnOnce() -> R) -> (Vec<serde_json::Value>, R) {
+    COLLECTED.set(Some(vec![]));
+    let result = report();
+    (COLLECTED.take().unwrap_or_default(), result)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip<T>(envelope: &Envelope<T>, expected: serde_json::Value)
+    where
+        T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let value = serde_json::to_value(envelope).unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(
+            &serde_json::from_value::<Envelope<T>>(value).unwrap(),
+            envelope
+        );
+    }
+
+    fn at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+    }
+
+    fn envelope<T>(tool: Tool, data: T) -> Envelope<T> {
+        Envelope {
+            generated_at: at(),
+            ..Envelope::new(tool, data)
+        }
+    }
+
+    #[test]
+    fn crops_and_maps_keep_their_field_names() {
+        let crops = envelope(
+            Tool::Crops,
+            vec![CropReport {
+                plant_id: 4842,
+                name: "Almond".to_string(),
+                patch_count: 2,
+                status: CropStatus::Good,
+                stage_time: None,
+                estimated: false,
+                patches: vec![PlotReport {
+                    world: "Tonberry".to_string(),
+                    district: "Mist".to_string(),
+                    ward: 3,
+                    plot: 12,
+                    placement: Placement::Outdoor,
+                    status: CropStatus::Good,
+                    stage_time: None,
+                    estimated: false,
+                    profile: None,
+                    clock_skew: None,
+                }],
+            }],
+        );
+        round_trip(
+            &crops,
+            json!({
+                "tool": "crops", "schema": 1, "generated_at": "2024-01-02T03:04:05Z",
+                "data": [{"plant_id": 4842, "name": "Almond", "patch_count": 2,
+                          "status": "good", "stage_time": null,
+                          "patches": [{"world": "Tonberry", "district": "Mist", "ward": 3,
+                                       "plot": 12, "status": "good", "stage_time": null}]}],
+            }),
+        );
+
+        let maps = envelope(
+            Tool::Maps,
+            vec![MapReport {
+                character: "Alice Doe".to_string(),
+                world: "Tonberry".to_string(),
+                next_map: at(),
+                tier: Some("ophiotauroskin".to_string()),
+                updated: None,
+                profile: Some("alt".to_string()),
+            }],
+        );
+        round_trip(
+            &maps,
+            json!({
+                "tool": "maps", "schema": 1, "generated_at": "2024-01-02T03:04:05Z",
+                "data": [{"character": "Alice Doe", "world": "Tonberry",
+                          "next_map": "2024-01-02T03:04:05Z", "tier": "ophiotauroskin",
+                          "profile": "alt"}],
+            }),
+        );
+    }
+
+    #[test]
+    fn subs_and_inventory_keep_their_field_names() {
+        let subs = envelope(
+            Tool::Subs,
+            vec![FreeCompanyReport {
+                character: "Alice Doe".to_string(),
+                tag: "MOGS".to_string(),
+                world: "Tonberry".to_string(),
+                submarines: vec![SubmarineReport {
+                    name: "Nautilus".to_string(),
+                    return_time: Some(at()),
+                    label: None,
+                }],
+                profile: None,
+            }],
+        );
+        round_trip(
+            &subs,
+            json!({
+                "tool": "subs", "schema": 1, "generated_at": "2024-01-02T03:04:05Z",
+                "data": [{"character": "Alice Doe", "tag": "MOGS", "world": "Tonberry",
+                          "submarines": [{"name": "Nautilus",
+                                          "return_time": "2024-01-02T03:04:05Z"}]}],
+            }),
+        );
+
+        let inventory = envelope(
+            Tool::Inventory,
+            InventoryReport {
+                kind: "query".to_string(),
+                items: vec![HeldItem {
+                    item_id: 21072,
+                    item_name: "Venture".to_string(),
+                    character: "Alice Doe".to_string(),
+                    world: "Tonberry".to_string(),
+                    is_retainer: false,
+                    quantity: 12,
+                    hq: false,
+                    profile: None,
+                }],
+            },
+        );
+        round_trip(
+            &inventory,
+            json!({
+                "tool": "inventory", "schema": 2, "generated_at": "2024-01-02T03:04:05Z",
+                "data": {"type": "query", "items": [{
+                    "item_id": 21072, "item_name": "Venture", "character": "Alice Doe",
+                    "world": "Tonberry", "is_retainer": false, "quantity": 12, "hq": false,
+                }]},
+            }),
+        );
+    }
+
+    #[test]
+    fn collected_envelopes_are_not_printed() {
+        let (collected, result) = collect(|| {
+            emit(Tool::Maps, Vec::<MapReport>::new())?;
+            emit(Tool::Subs, Vec::<FreeCompanyReport>::new())
+        });
+        result.unwrap();
+        assert_eq!(collected.len(), 2);
+        assert_eq!(collected[1]["tool"], "subs");
+    }
+}
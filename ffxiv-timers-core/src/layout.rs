@@ -0,0 +1,225 @@
+//! Fitting report columns into the terminal. Each column says how it gives
+//! way when there isn't room: names are truncated with an ellipsis first,
+//! and only then are columns like timestamps dropped altogether.
+
+use std::io::IsTerminal;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How a column gives up space when the terminal is too narrow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shrink {
+    /// Always shown in full
+    Never,
+    /// Cut short with an ellipsis, but to no fewer than `min` columns
+    Truncate { min: usize },
+    /// Left out, once truncating the others isn't enough. Later columns are
+    /// dropped before earlier ones.
+    Drop,
+}
+
+/// How text is placed in a column wider than it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+}
+
+/// One column of a report, as wide as the widest text it holds.
+#[derive(Clone, Debug)]
+pub struct Column {
+    /// How wide the separator before it is
+    gap: usize,
+    width: usize,
+    shrink: Shrink,
+}
+
+impl Column {
+    pub fn new(gap: usize, shrink: Shrink) -> Column {
+        Column {
+            gap,
+            width: 0,
+            shrink,
+        }
+    }
+
+    /// Widens the column to fit each of `texts`.
+    pub fn measure(mut self, texts: impl IntoIterator<Item = impl AsRef<str>>) -> Column {
+        self.width = texts
+            .into_iter()
+            .map(|text| width(text.as_ref()))
+            .fold(self.width, usize::max);
+        self
+    }
+}
+
+/// How many columns standard output has, or None when it isn't a terminal
+/// and nothing needs fitting.
+pub fn available() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| usize::from(width))
+}
+
+/// The width each column gets after an `indent`, or None for those
+/// dropped. Everything is shown in full when `available` is None.
+pub fn allocate(indent: usize, columns: &[Column], available: Option<usize>) -> Vec<Option<usize>> {
+    let mut widths = columns
+        .iter()
+        .map(|column| Some(column.width))
+        .collect::<Vec<_>>();
+    let Some(available) = available else {
+        return widths;
+    };
+    let total = |widths: &[Option<usize>], truncated: bool| {
+        let shown = columns.iter().zip(widths).filter_map(|(column, width)| {
+            let width = match column.shrink {
+                Shrink::Truncate { min } if truncated => min.min((*width)?),
+                _ => (*width)?,
+            };
+            Some(column.gap + width)
+        });
+        indent + shown.sum::<usize>()
+    };
+
+    // Drop what won't fit even with everything truncated as far as it goes
+    for index in (0..columns.len()).rev() {
+        if total(&widths, true) <= available {
+            break;
+        }
+        if columns[index].shrink == Shrink::Drop {
+            widths[index] = None;
+        }
+    }
+    // Then take from the widest truncatable column until the rest fits
+    while total(&widths, false) > available {
+        let widest = columns
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .filter_map(|(index, (column, width))| match (column.shrink, width) {
+                (Shrink::Truncate { min }, Some(width)) if *width > min => Some((index, *width)),
+                _ => None,
+            })
+            .max_by_key(|&(_, width)| width);
+        let Some((index, width)) = widest else {
+            break;
+        };
+        widths[index] = Some(width - 1);
+    }
+    widths
+}
+
+/// How many columns `text` takes, counting each grapheme cluster as one.
+pub fn width(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// `text` cut short with an ellipsis if it's wider than `width`, never
+/// splitting a grapheme cluster.
+pub fn truncate(text: &str, width: usize) -> String {
+    if self::width(text) <= width {
+        return text.to_string();
+    }
+    let Some(kept) = width.checked_sub(1) else {
+        return String::new();
+    };
+    let mut truncated = text.graphemes(true).take(kept).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+/// `text` truncated or padded with spaces to exactly `width` columns.
+pub fn fit(text: &str, width: usize, align: Align) -> String {
+    let text = truncate(text, width);
+    let padding = width - self::width(&text);
+    match align {
+        Align::Left => format!("{text}{}", " ".repeat(padding)),
+        Align::Center => {
+            let before = padding / 2;
+            format!(
+                "{}{text}{}",
+                " ".repeat(before),
+                " ".repeat(padding - before)
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A map allowance row: a name, when it's ready, and at what time.
+    fn render(available: Option<usize>) -> Vec<String> {
+        let rows = [
+            (
+                "Ysolde Aubépine-Delacroix (Phoenix)",
+                "3h 12m",
+                "(Tue 14:05)",
+            ),
+            ("Alice Doe (Tonberry)", "ready", "(Mon 09:30)"),
+        ];
+        let columns = [
+            Column::new(0, Shrink::Truncate { min: 12 }).measure(rows.iter().map(|row| row.0)),
+            Column::new(3, Shrink::Never).measure(rows.iter().map(|row| row.1)),
+            Column::new(1, Shrink::Drop).measure(rows.iter().map(|row| row.2)),
+        ];
+        let widths = allocate(4, &columns, available);
+        rows.iter()
+            .map(|(name, time, at)| {
+                let name = fit(name, widths[0].unwrap(), Align::Left);
+                match widths[2] {
+                    Some(_) => {
+                        let time = fit(time, widths[1].unwrap(), Align::Left);
+                        format!("    {name} - {time} {at}")
+                    }
+                    None => format!("    {name} - {time}"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rows_fit_each_width() {
+        assert_eq!(
+            render(None),
+            [
+                "    Ysolde Aubépine-Delacroix (Phoenix) - 3h 12m (Tue 14:05)",
+                "    Alice Doe (Tonberry)                - ready  (Mon 09:30)",
+            ]
+        );
+        assert_eq!(render(Some(80)), render(None));
+        assert_eq!(
+            render(Some(50)),
+            [
+                "    Ysolde Aubépine-Delacroi… - 3h 12m (Tue 14:05)",
+                "    Alice Doe (Tonberry)      - ready  (Mon 09:30)",
+            ]
+        );
+        // Names go down to their minimum before the times are dropped
+        assert_eq!(
+            render(Some(34)),
+            [
+                "    Ysolde Aubépine-Dela… - 3h 12m",
+                "    Alice Doe (Tonberry)  - ready",
+            ]
+        );
+        assert_eq!(
+            render(Some(10)),
+            ["    Ysolde Aubé… - 3h 12m", "    Alice Doe (… - ready"]
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_grapheme_clusters_whole() {
+        // An "e" with a combining acute accent is one column, not two
+        let name = "Rene\u{301}e Marchand";
+        assert_eq!(width(name), 14);
+        assert_eq!(truncate(name, 5), "Rene\u{301}…");
+        assert_eq!(truncate(name, 14), name);
+        assert_eq!(truncate(name, 0), "");
+        assert_eq!(fit("Alice", 9, Align::Center), "  Alice  ");
+    }
+}
@@ -0,0 +1,32 @@
+//! Pieces shared by the ffxiv-timers binaries: finding the Dalamud plugins'
+//! files, the game's constants, formatting timers and fitting them to the
+//! terminal, naming worlds, coloring, translating and paging output, the
+//! clock, watching files for changes, and logging.
+
+pub mod check;
+pub mod clock;
+pub mod config;
+pub mod de;
+pub mod fallback;
+pub mod gamedata;
+pub mod i18n;
+pub mod ics;
+pub mod json;
+pub mod layout;
+pub mod logging;
+pub mod options;
+pub mod pager;
+pub mod paths;
+pub mod profiles;
+pub mod read_cache;
+pub mod scan;
+pub mod shutdown;
+pub mod source;
+pub mod state;
+pub mod template;
+pub mod theme;
+pub mod time;
+pub mod urgency;
+pub mod watch;
+pub mod webhook;
+pub mod worlds;
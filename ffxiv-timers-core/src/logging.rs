@@ -0,0 +1,20 @@
+use tracing_subscriber::EnvFilter;
+
+/// Sends warnings and notes to stderr, where they can't mix with a report
+/// printed for another program. `--verbose` adds debug events such as each
+/// file looked at, `--quiet` leaves only errors, and `RUST_LOG` overrides
+/// both. Only the first call has any effect.
+pub fn init(verbose: bool, quiet: bool) {
+    let level = match (verbose, quiet) {
+        (true, _) => "debug",
+        (false, true) => "error",
+        (false, false) => "info",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time()
+        .try_init();
+}
@@ -0,0 +1,325 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use serde::Deserialize;
+use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use crate::{
+    config, i18n,
+    pager::{self, Pager},
+    paths, profiles, read_cache,
+    template::RowFormat,
+    theme, urgency,
+};
+
+/// Settings every tool takes, whether it's run on its own or as one of the
+/// `ffxiv-timers` subcommands.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// From `--color`, before the environment and config are considered
+    pub color: Option<ColorWhen>,
+    /// Print JSON for other programs instead of the colored report
+    pub json: bool,
+    /// Log debug events, like where files were looked for
+    pub verbose: bool,
+    /// Log only errors
+    pub quiet: bool,
+    /// Exit with the report's [`Severity`](crate::check::Severity), from
+    /// `--check`
+    pub check: bool,
+    /// Keep each report read, and show the last one kept when the plugin's
+    /// files can't be read, from `--fallback-cache`
+    pub fallback_cache: bool,
+    /// A line to print per row in place of the report, from `--format`
+    pub format: Option<String>,
+    /// Printed once before the rows, from `--format-header`
+    pub format_header: Option<String>,
+    /// Printed once after the rows, from `--format-footer`
+    pub format_footer: Option<String>,
+    /// Keep redrawing the report as its files change, from `--watch`
+    pub watch: bool,
+    /// Seconds between reading the files again under `--watch` even when
+    /// nothing seems to have changed, from `--watch SECONDS`, for the
+    /// tools that take it
+    pub watch_interval: Option<u64>,
+    /// Take the plugin's timestamps at their word even when they're ahead
+    /// of the system clock, from `--trust-data`
+    pub trust_data: bool,
+    /// Count a free company's unregistered submarine slots towards
+    /// `--check`, from `--strict-slots`
+    pub strict_slots: bool,
+    /// Show when crops would next need tending if all were tended now,
+    /// from `--if-tended`
+    pub if_tended: bool,
+    /// List crops on the Island Sanctuary under their own header, from
+    /// `--include-island`
+    pub include_island: bool,
+    /// List each crop's plots under it, house by house, from `--detail`
+    pub detail: bool,
+    /// How many of each character's coming map allowances to list, from
+    /// `--project N`
+    pub project: Option<usize>,
+    /// Keep showing a character's files from a world they moved from, from
+    /// `--no-merge`
+    pub no_merge: bool,
+}
+
+impl Options {
+    /// Reads the flags the small tools take: `--color WHEN`, `--theme
+    /// PRESET`, `--lang LANG`, `--json`, `--verbose`, `--quiet`, `--config
+    /// PATH`, `--profile LABEL`, `--soon MINUTES`, `--fallback-cache`,
+    /// `--check`, `--no-pager`, `--no-cache`, `--watch [SECONDS]`, `--trust-data`,
+    /// `--strict-slots`, `--if-tended`, `--include-island`, `--detail`,
+    /// `--project N`, `--no-merge` and the `--format` templates.
+    pub fn parse_from(
+        name: &str,
+        args: impl IntoIterator<Item = String>,
+    ) -> anyhow::Result<Options> {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let (flag, mut inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .take()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow!("{flag} requires a value"))
+            };
+            match &*flag {
+                "--color" => options.color = Some(value()?.parse()?),
+                "--theme" => theme::set_preset(value()?.parse()?),
+                "--lang" => i18n::set_lang(value()?.parse()?),
+                "--config" => paths::set_config_file(value()?.into()),
+                "--profile" => profiles::select(value()?),
+                "--soon" => {
+                    let minutes = value()?;
+                    urgency::set_soon(minutes.parse().map_err(|_| {
+                        anyhow!("invalid value `{minutes}` for --soon; expected minutes")
+                    })?)
+                }
+                "--json" => options.json = true,
+                "-v" | "--verbose" => options.verbose = true,
+                "-q" | "--quiet" => options.quiet = true,
+                "--fallback-cache" => options.fallback_cache = true,
+                "--check" => options.check = true,
+                "--no-pager" => pager::disable(),
+                "--no-cache" => read_cache::disable(),
+                "--watch" => {
+                    options.watch = true;
+                    // The interval is optional, so only a number is taken as one
+                    let seconds = inline_value
+                        .take()
+                        .or_else(|| args.next_if(|arg| arg.parse::<u64>().is_ok()));
+                    if let Some(seconds) = seconds {
+                        options.watch_interval = Some(seconds.parse().map_err(|_| {
+                            anyhow!("invalid value `{seconds}` for --watch; expected seconds")
+                        })?);
+                    }
+                }
+                "--trust-data" => options.trust_data = true,
+                "--strict-slots" => options.strict_slots = true,
+                "--if-tended" => options.if_tended = true,
+                "--include-island" => options.include_island = true,
+                "--detail" => options.detail = true,
+                "--project" => {
+                    let count = value()?;
+                    options.project = Some(count.parse().map_err(|_| {
+                        anyhow!("invalid value `{count}` for --project; expected a count")
+                    })?)
+                }
+                "--no-merge" => options.no_merge = true,
+                "--format" => options.format = Some(value()?),
+                "--format-header" => options.format_header = Some(value()?),
+                "--format-footer" => options.format_footer = Some(value()?),
+                "-h" | "--help" => {
+                    println!(
+                        "Usage: {name} [--color auto|always|never] [--theme dark|light] \
+                         [--lang en|de|fr|ja] [--json] [--verbose | --quiet] [--config PATH] \
+                         [--profile LABEL] [--soon MINUTES] [--fallback-cache] [--check] \
+                         [--no-pager] [--no-cache] [--watch [SECONDS]] [--trust-data] [--strict-slots] [--if-tended] [--include-island] [--detail] [--project N] [--no-merge] [--format TEMPLATE [--format-header TEMPLATE] \
+                         [--format-footer TEMPLATE]]"
+                    );
+                    std::process::exit(0);
+                }
+                _ => bail!("unrecognized argument `{flag}`; see `{name} --help`"),
+            }
+        }
+        if options.verbose && options.quiet {
+            bail!("--verbose can't be combined with --quiet");
+        }
+        Ok(options)
+    }
+
+    /// The `--format` templates, checked against the fields a tool offers,
+    /// or `None` for the usual report.
+    pub fn row_format(&self, fields: &[&str]) -> anyhow::Result<Option<RowFormat>> {
+        let Some(row) = &self.format else {
+            if self.format_header.is_some() || self.format_footer.is_some() {
+                bail!("--format-header and --format-footer need --format");
+            }
+            return Ok(None);
+        };
+        if self.json {
+            bail!("--format can't be combined with --json");
+        }
+        RowFormat::parse(
+            row,
+            self.format_header.as_deref(),
+            self.format_footer.as_deref(),
+            fields,
+        )
+        .map(Some)
+    }
+
+    /// Standard output for a report, never colored when it's carrying JSON
+    /// and otherwise paged when it's too long.
+    pub fn stdout(&self) -> Stdout {
+        match self.json {
+            true => Stdout::new(ColorChoice::Never),
+            false => Stdout::paged(color_choice(self.color)),
+        }
+    }
+
+    /// Standard output for `--watch`, which redraws the report in place, so
+    /// it's never paged and can't be JSON or `--format` rows.
+    pub fn watch_stdout(&self) -> anyhow::Result<Stdout> {
+        if self.json || self.format.is_some() {
+            bail!("--watch can't be combined with --json or --format");
+        }
+        Ok(Stdout::new(color_choice(self.color)))
+    }
+}
+
+/// When to color output, as given to `--color`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorWhen {
+    /// Only when printing to a terminal, unless the environment says otherwise
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorWhen {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<ColorWhen> {
+        match value {
+            "auto" => Ok(ColorWhen::Auto),
+            "always" => Ok(ColorWhen::Always),
+            "never" => Ok(ColorWhen::Never),
+            _ => bail!("invalid value `{value}` for --color; expected auto, always or never"),
+        }
+    }
+}
+
+/// How to color standard output, given `--color` if it was passed. The flag
+/// wins over `NO_COLOR` and `CLICOLOR_FORCE`, which win over `config.toml`;
+/// otherwise only a terminal is colored.
+pub fn color_choice(flag: Option<ColorWhen>) -> ColorChoice {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let forced = std::env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0");
+    let env = match (no_color, forced) {
+        (true, _) => Some(ColorWhen::Never),
+        (false, true) => Some(ColorWhen::Always),
+        (false, false) => None,
+    };
+    resolve(
+        flag.or(env).or(config::get().color),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+fn resolve(when: Option<ColorWhen>, terminal: bool) -> ColorChoice {
+    match when.unwrap_or(ColorWhen::Auto) {
+        ColorWhen::Always => ColorChoice::Always,
+        ColorWhen::Never => ColorChoice::Never,
+        ColorWhen::Auto if terminal => ColorChoice::Auto,
+        ColorWhen::Auto => ColorChoice::Never,
+    }
+}
+
+/// Standard output that resets its color when dropped, so a report that
+/// fails partway doesn't leave the terminal tinted.
+pub struct Stdout(Box<dyn WriteColor>);
+
+impl Stdout {
+    pub fn new(color: ColorChoice) -> Stdout {
+        Stdout(Box::new(StandardStream::stdout(color)))
+    }
+
+    /// Like [`Stdout::new`], but held back to go through the pager if the
+    /// finished report is too long, when [`pager::enabled`].
+    pub fn paged(color: ColorChoice) -> Stdout {
+        match pager::enabled() {
+            true => Stdout(Box::new(Pager::new(color))),
+            false => Stdout::new(color),
+        }
+    }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl WriteColor for Stdout {
+    fn supports_color(&self) -> bool {
+        self.0.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.0.set_color(spec)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.0.reset()
+    }
+}
+
+impl Drop for Stdout {
+    fn drop(&mut self) {
+        let _ = self.0.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_colors_only_terminals() {
+        assert_eq!(resolve(None, true), ColorChoice::Auto);
+        assert_eq!(resolve(None, false), ColorChoice::Never);
+        assert_eq!(resolve(Some(ColorWhen::Auto), false), ColorChoice::Never);
+        assert_eq!(resolve(Some(ColorWhen::Always), false), ColorChoice::Always);
+        assert_eq!(resolve(Some(ColorWhen::Never), true), ColorChoice::Never);
+    }
+
+    #[test]
+    fn watch_takes_an_optional_interval() {
+        let parse = |args: &[&str]| {
+            Options::parse_from("crop-timers", args.iter().map(|arg| arg.to_string()))
+        };
+        let options = parse(&["--watch"]).unwrap();
+        assert!(options.watch);
+        assert_eq!(options.watch_interval, None);
+        let options = parse(&["--watch", "10", "--json"]).unwrap();
+        assert_eq!((options.watch_interval, options.json), (Some(10), true));
+        assert_eq!(parse(&["--watch=45"]).unwrap().watch_interval, Some(45));
+        assert!(parse(&["--watch=soon"]).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Long reports shown through a pager, as git does. A report is held back
+//! until it's finished, then paged only if it's longer than the terminal is
+//! tall. Nothing is paged when standard output isn't a terminal, nor for
+//! JSON, CSV or `--format` output, which is meant for other programs.
+
+use std::{
+    cell::RefCell,
+    ffi::OsString,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+use termcolor::{Buffer, ColorChoice, ColorSpec, WriteColor};
+use tracing::{debug, warn};
+
+use crate::config;
+
+/// Used when `$PAGER` isn't set: `-R` keeps the colors, `-F` quits straight
+/// away when everything fits after all, and `-X` leaves the report on screen.
+const DEFAULT_PAGER: &str = "less -RFX";
+
+/// Set from the command line.
+static DISABLED: OnceLock<()> = OnceLock::new();
+
+thread_local! {
+    /// Reports held back by [`together`] to be paged as one.
+    static HELD: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Never pages for the rest of the run, from `--no-pager`.
+pub fn disable() {
+    let _ = DISABLED.set(());
+}
+
+/// Whether a report can be paged: standard output is a terminal, and
+/// neither `--no-pager` nor `pager = false` in `config.toml` turned it off.
+pub fn enabled() -> bool {
+    DISABLED.get().is_none() && config::get().pager != Some(false) && io::stdout().is_terminal()
+}
+
+/// A report being written, to be shown once it's dropped.
+pub struct Pager {
+    buffer: Buffer,
+}
+
+impl Pager {
+    pub fn new(color: ColorChoice) -> Pager {
+        let colored = match color {
+            ColorChoice::Always | ColorChoice::AlwaysAnsi => true,
+            ColorChoice::Auto => std::env::var_os("TERM").is_some_and(|term| term != "dumb"),
+            ColorChoice::Never => false,
+        };
+        Pager {
+            buffer: match colored {
+                true => Buffer::ansi(),
+                false => Buffer::no_color(),
+            },
+        }
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteColor for Pager {
+    fn supports_color(&self) -> bool {
+        self.buffer.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.buffer.set_color(spec)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.buffer.reset()
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let report = self.buffer.as_slice();
+        let held = HELD.with_borrow_mut(|held| match held {
+            Some(held) => {
+                held.extend_from_slice(report);
+                true
+            }
+            None => false,
+        });
+        if !held {
+            show(report);
+        }
+    }
+}
+
+/// Runs `report`, paging everything it writes as one report, as for several
+/// reports printed one after another.
+pub fn together<T>(report: impl FnOnce() -> T) -> T {
+    HELD.set(Some(vec![]));
+    let result = report();
+    show(&HELD.take().unwrap_or_default());
+    result
+}
+
+/// Writes `report` to standard output, through the pager if it doesn't fit.
+fn show(report: &[u8]) {
+    let lines = report.iter().filter(|&&byte| byte == b'\n').count();
+    let height = terminal_size::terminal_size().map(|(_, terminal_size::Height(height))| height);
+    if !too_long(lines, height.map(usize::from)) {
+        let _ = io::stdout().write_all(report);
+        return;
+    }
+    let command = command(std::env::var_os("PAGER"));
+    if let Err(err) = page(&command, report) {
+        warn!("Couldn't run the pager `{command}`: {err}");
+        let _ = io::stdout().write_all(report);
+    }
+}
+
+/// Whether a report of `lines` needs paging, leaving a line for the prompt.
+fn too_long(lines: usize, height: Option<usize>) -> bool {
+    height.is_some_and(|height| lines >= height)
+}
+
+/// The pager to run, from `$PAGER` when it's set.
+fn command(env: Option<OsString>) -> String {
+    env.and_then(|pager| pager.into_string().ok())
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+fn page(command: &str, report: &[u8]) -> io::Result<()> {
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or(DEFAULT_PAGER);
+    debug!("Paging the report with `{command}`");
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("the pager's stdin is piped");
+    match stdin.write_all(report) {
+        // Quitting the pager before the end is fine
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+        _ => {}
+    }
+    drop(stdin);
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_reports_taller_than_the_terminal_are_paged() {
+        assert!(!too_long(23, Some(24)));
+        assert!(too_long(24, Some(24)));
+        assert!(!too_long(500, None));
+        assert_eq!(command(None), DEFAULT_PAGER);
+        assert_eq!(command(Some(" ".into())), DEFAULT_PAGER);
+        assert_eq!(command(Some("more".into())), "more");
+    }
+}
@@ -0,0 +1,506 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    sync::{Once, OnceLock},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{config, profiles};
+
+/// Where the launchers keep Dalamud plugin configs, relative to the home
+/// directory, in the order they're probed. The first is used when none exist.
+/// On Linux, the `$XDG_CONFIG_HOME` variant is probed after these.
+#[cfg(target_os = "windows")]
+const PLUGIN_CONFIGS: &[&str] = &[r#"AppData\Roaming\XIVLauncher\pluginConfigs"#];
+#[cfg(target_os = "linux")]
+const PLUGIN_CONFIGS: &[&str] = &[
+    ".xlcore/pluginConfigs",
+    // XIVLauncher.Core installed from Flathub, as on the Steam Deck
+    ".var/app/dev.goats.xivlauncher/config/XIVLauncher/pluginConfigs",
+    // and where older Flathub builds kept it
+    ".var/app/dev.goats.xivlauncher/data/xlcore/pluginConfigs",
+];
+#[cfg(target_os = "macos")]
+const PLUGIN_CONFIGS: &[&str] = &[
+    "Library/Application Support/XIV on Mac/pluginConfigs",
+    // XIVLauncher.Core built from source
+    ".xlcore/pluginConfigs",
+];
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+const PLUGIN_CONFIGS: &[&str] = &[".xlcore/pluginConfigs"];
+
+/// XIVLauncher's own settings, kept next to its default plugin config
+/// directory even when its data has been moved somewhere else.
+const LAUNCHER_CONFIG: &str = "launcherConfigV3.json";
+
+/// The parts of the launcher's settings that say where its data lives.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LauncherConfig {
+    /// The plugin config directory itself
+    plugin_configs_path: Option<PathBuf>,
+    /// The launcher's data directory, holding `pluginConfigs`
+    roaming_path: Option<PathBuf>,
+}
+
+/// Environment variable pointing at a plugin config directory somewhere
+/// else, e.g. for a non-default XIVLauncher install.
+pub const PLUGIN_CONFIGS_ENV: &str = "FFXIV_TIMERS_PLUGIN_CONFIGS";
+
+/// Environment variable standing in for the home directory, which the
+/// launcher's plugin configs and this tool's own config, cache and state are
+/// all found in, laid out as on Linux. Lets tests run against a fake home.
+pub const HOME_ENV: &str = "FFXIV_TIMERS_HOME";
+
+/// The home given with [`HOME_ENV`], if any.
+pub fn home_env() -> Option<PathBuf> {
+    std::env::var_os(HOME_ENV)
+        .map(PathBuf::from)
+        .filter(|dir| !dir.as_os_str().is_empty())
+}
+
+/// This tool's directory in `base` of the [`HOME_ENV`] home, if it's set.
+fn home_env_dir(base: &str) -> Option<PathBuf> {
+    Some(home_env()?.join(base).join("ffxiv-timers"))
+}
+
+/// Set from the command line, taking precedence over the environment.
+static PLUGIN_CONFIGS_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Uses `dir` as the plugin config directory for the rest of the run. Only
+/// the first call has any effect.
+pub fn set_plugin_configs_dir(dir: PathBuf) {
+    let _ = PLUGIN_CONFIGS_OVERRIDE.set(dir);
+}
+
+/// Whether the plugin config directory was given on the command line or in
+/// the environment, which takes precedence over any profiles.
+pub fn plugin_configs_given() -> bool {
+    PLUGIN_CONFIGS_OVERRIDE.get().is_some() || plugin_configs_env().is_some()
+}
+
+fn plugin_configs_env() -> Option<PathBuf> {
+    std::env::var_os(PLUGIN_CONFIGS_ENV)
+        .map(PathBuf::from)
+        .filter(|dir| !dir.as_os_str().is_empty())
+}
+
+/// The directory holding every plugin's config files: the one given on the
+/// command line, in the environment, for the profile being read or in
+/// `config.toml`, then the one XIVLauncher's settings point at, otherwise
+/// the first launcher directory that exists.
+pub fn plugin_configs_dir() -> anyhow::Result<PathBuf> {
+    plugin_configs_origin().map(|(dir, _)| dir)
+}
+
+/// Like [`plugin_configs_dir`], along with where it came from, e.g.
+/// `--data-root` or `the launcher's settings`.
+pub fn plugin_configs_origin() -> anyhow::Result<(PathBuf, &'static str)> {
+    let (home, xdg_config) = match home_env() {
+        Some(home) => (Some(home.clone()), Some(home.join(".config"))),
+        None => (
+            directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf()),
+            directories::BaseDirs::new().map(|dirs| dirs.config_dir().to_path_buf()),
+        ),
+    };
+    let candidates = home
+        .as_deref()
+        .map(|home| candidates(home, &config::get().probe_paths, xdg_config.as_deref()))
+        .unwrap_or_default();
+    let launcher = launcher_plugin_configs(&candidates);
+    let configured = profiles::current()
+        .and_then(|profile| profile.data_root.clone())
+        .or_else(|| config::get().data_root.clone());
+    resolve(
+        PLUGIN_CONFIGS_OVERRIDE.get().cloned(),
+        plugin_configs_env(),
+        configured,
+        launcher,
+        &candidates,
+        |dir| dir.is_dir(),
+    )
+    .with_context(|| {
+        format!(
+            "Couldn't find a home directory to look for the launcher's plugin configs in; \
+             pass --data-root or set {PLUGIN_CONFIGS_ENV} to where they are"
+        )
+    })
+}
+
+/// Where the launcher keeps plugin configs in `home` when nothing has moved
+/// them, as a fake home for tests should lay them out.
+pub fn default_plugin_configs(home: &Path) -> PathBuf {
+    home.join(PLUGIN_CONFIGS[0])
+}
+
+/// The directories to probe for plugin configs, in order: `probe_paths` from
+/// `config.toml` when set, with relative ones taken from the home directory,
+/// otherwise [`PLUGIN_CONFIGS`] and, on Linux, the one in `xdg_config`.
+fn candidates(home: &Path, configured: &[PathBuf], xdg_config: Option<&Path>) -> Vec<PathBuf> {
+    if !configured.is_empty() {
+        return configured.iter().map(|dir| home.join(dir)).collect();
+    }
+    let mut candidates = PLUGIN_CONFIGS
+        .iter()
+        .map(|dir| home.join(dir))
+        .collect::<Vec<_>>();
+    if cfg!(target_os = "linux") {
+        // XIVLauncher.Core packaged to follow the XDG base directories
+        let xdg = xdg_config.map(|dir| dir.join("XIVLauncher/pluginConfigs"));
+        candidates.extend(xdg.filter(|dir| !candidates.contains(dir)));
+    }
+    candidates
+}
+
+/// The plugin config directory set in the first launcher config found next
+/// to one of the `candidates`.
+fn launcher_plugin_configs(candidates: &[PathBuf]) -> Option<PathBuf> {
+    static NOTED: Once = Once::new();
+    for dir in candidates {
+        let path = dir.with_file_name(LAUNCHER_CONFIG);
+        let Ok(contents) = read_to_string(&path) else {
+            continue;
+        };
+        match parse_launcher_config(&contents) {
+            Ok(Some(dir)) => return Some(dir),
+            Ok(None) => {}
+            Err(err) => NOTED
+                .call_once(|| debug!("Ignoring {}, which couldn't be read: {err}", path.display())),
+        }
+    }
+    None
+}
+
+fn parse_launcher_config(contents: &str) -> serde_json::Result<Option<PathBuf>> {
+    let config: LauncherConfig = serde_json::from_str(contents)?;
+    let set = |dir: &PathBuf| !dir.as_os_str().is_empty();
+    Ok(config.plugin_configs_path.filter(set).or_else(|| {
+        config
+            .roaming_path
+            .filter(set)
+            .map(|dir| dir.join("pluginConfigs"))
+    }))
+}
+
+fn resolve(
+    explicit: Option<PathBuf>,
+    env: Option<PathBuf>,
+    configured: Option<PathBuf>,
+    launcher: Option<PathBuf>,
+    candidates: &[PathBuf],
+    exists: impl Fn(&Path) -> bool,
+) -> Option<(PathBuf, &'static str)> {
+    let given = [
+        ("--data-root", explicit),
+        (
+            PLUGIN_CONFIGS_ENV,
+            env.filter(|dir| !dir.as_os_str().is_empty()),
+        ),
+        ("config.toml", configured),
+        ("the launcher's settings", launcher),
+    ];
+    if let Some((source, dir)) = given
+        .into_iter()
+        .find_map(|(source, dir)| Some((source, dir?)))
+    {
+        debug!("Using plugin configs in {}, from {source}", dir.display());
+        return Some((dir, source));
+    }
+    let found = candidates.iter().find(|dir| {
+        debug!("Looking for plugin configs in {}", dir.display());
+        exists(dir)
+    });
+    match found {
+        Some(dir) => {
+            debug!("Using plugin configs in {}, the first found", dir.display());
+            Some((dir.clone(), "the launcher's usual places"))
+        }
+        None => {
+            let dir = candidates.first()?;
+            debug!(
+                "No plugin configs found; expecting them in {}",
+                dir.display()
+            );
+            Some((dir.clone(), "the launcher's default place"))
+        }
+    }
+}
+
+/// A file or directory inside the plugin config directory, given as path
+/// components, e.g. `["Accountant", "tasks"]`.
+pub fn plugin_path<P: AsRef<Path>>(parts: impl IntoIterator<Item = P>) -> anyhow::Result<PathBuf> {
+    let mut path = plugin_configs_dir()?;
+    path.extend(parts);
+    debug!("Reading plugin data from {}", path.display());
+    Ok(path)
+}
+
+/// The shared config file, inside the config directory.
+const CONFIG_FILE: &str = "config.toml";
+
+/// Environment variable pointing at a config file somewhere else.
+pub const CONFIG_FILE_ENV: &str = "FFXIV_TIMERS_CONFIG";
+
+/// Set from the command line, taking precedence over the environment.
+static CONFIG_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Uses `path` as the config file for the rest of the run, with the other
+/// settings and tables read from beside it. Only the first call has any
+/// effect.
+pub fn set_config_file(path: PathBuf) {
+    let _ = CONFIG_FILE_OVERRIDE.set(path);
+}
+
+fn config_file_override() -> Option<PathBuf> {
+    CONFIG_FILE_OVERRIDE.get().cloned().or_else(|| {
+        std::env::var_os(CONFIG_FILE_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Where the shared `config.toml` is read from.
+pub fn config_file() -> Option<PathBuf> {
+    match config_file_override() {
+        Some(path) => Some(path),
+        None => Some(config_dir()?.join(CONFIG_FILE)),
+    }
+}
+
+fn project_dirs() -> Option<directories::ProjectDirs> {
+    directories::ProjectDirs::from("", "", "ffxiv-timers")
+}
+
+/// Where user settings and tables like `items.csv` live: beside the config
+/// file if one was given, otherwise the platform config directory.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(path) = config_file_override() {
+        return Some(path.parent().unwrap_or(Path::new("")).to_path_buf());
+    }
+    if let Some(dir) = home_env_dir(".config") {
+        return Some(dir);
+    }
+    Some(project_dirs()?.config_dir().to_path_buf())
+}
+
+/// Where data that can be refetched, like market prices, is kept.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = home_env_dir(".cache") {
+        return Some(dir);
+    }
+    Some(project_dirs()?.cache_dir().to_path_buf())
+}
+
+/// Where data built up over time was kept before [`state_dir`].
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = home_env_dir(".local/share") {
+        return Some(dir);
+    }
+    Some(project_dirs()?.data_dir().to_path_buf())
+}
+
+/// Where data built up over time, like item history and sent notifications,
+/// is kept: `$XDG_STATE_HOME` on Linux, and the local data directory on
+/// Windows and macOS, which have no separate place for state.
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(dir) = home_env_dir(".local/state") {
+        return Some(dir);
+    }
+    let dirs = project_dirs()?;
+    Some(
+        dirs.state_dir()
+            .unwrap_or(dirs.data_local_dir())
+            .to_path_buf(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(
+        explicit: Option<PathBuf>,
+        env: Option<PathBuf>,
+        configured: Option<PathBuf>,
+        launcher: Option<PathBuf>,
+        candidates: &[PathBuf],
+        exists: impl Fn(&Path) -> bool,
+    ) -> Option<PathBuf> {
+        let resolved = super::resolve(explicit, env, configured, launcher, candidates, exists);
+        resolved.map(|(dir, _)| dir)
+    }
+
+    fn usual(home: &Path) -> Vec<PathBuf> {
+        candidates(home, &[], Some(&home.join(".config")))
+    }
+
+    #[test]
+    fn defaults_to_the_first_launcher_directory() {
+        let home = PathBuf::from("home");
+        assert_eq!(
+            resolve(None, None, None, None, &usual(&home), |_| false),
+            Some(home.join(PLUGIN_CONFIGS[0]))
+        );
+        assert_eq!(
+            resolve(
+                None,
+                Some(PathBuf::new()),
+                None,
+                None,
+                &usual(&home),
+                |_| false
+            ),
+            Some(home.join(PLUGIN_CONFIGS[0]))
+        );
+    }
+
+    #[test]
+    fn needs_a_home_without_overrides() {
+        assert_eq!(resolve(None, None, None, None, &[], |_| true), None);
+        assert_eq!(
+            resolve(
+                None,
+                None,
+                None,
+                Some(PathBuf::from("launcher")),
+                &[],
+                |_| true
+            ),
+            Some(PathBuf::from("launcher"))
+        );
+    }
+
+    #[test]
+    fn picks_the_first_launcher_directory_that_exists() {
+        let home = PathBuf::from("home");
+        let candidates = usual(&home);
+        let last = candidates.last().unwrap().clone();
+        assert_eq!(
+            resolve(None, None, None, None, &candidates, |dir| dir == last),
+            Some(last)
+        );
+        assert_eq!(
+            resolve(None, None, None, None, &candidates, |_| true),
+            Some(home.join(PLUGIN_CONFIGS[0]))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn probes_flatpak_after_xlcore() {
+        let home = PathBuf::from("home");
+        let candidates = usual(&home);
+        let flatpak = home.join(".var/app/dev.goats.xivlauncher/config/XIVLauncher/pluginConfigs");
+        let xdg = home.join(".config/XIVLauncher/pluginConfigs");
+        assert_eq!(candidates[0], home.join(".xlcore/pluginConfigs"));
+        assert_eq!(candidates[1], flatpak);
+        assert_eq!(candidates.last(), Some(&xdg));
+        // With both the flatpak and XDG directories, the flatpak one wins
+        assert_eq!(
+            resolve(None, None, None, None, &candidates, |dir| dir == flatpak
+                || dir == xdg),
+            Some(flatpak.clone())
+        );
+        assert_eq!(
+            resolve(None, None, None, None, &candidates, |dir| dir == xdg),
+            Some(xdg.clone())
+        );
+        // An XDG_CONFIG_HOME already among the usual places isn't probed twice
+        let sandboxed = home.join(".var/app/dev.goats.xivlauncher/config");
+        assert_eq!(
+            super::candidates(&home, &[], Some(&sandboxed)).len(),
+            PLUGIN_CONFIGS.len()
+        );
+    }
+
+    #[test]
+    fn configured_probe_order_replaces_the_usual() {
+        let home = PathBuf::from("home");
+        let configured = [
+            PathBuf::from("/games/pluginConfigs"),
+            PathBuf::from(".xlcore/pluginConfigs"),
+        ];
+        let candidates = candidates(&home, &configured, Some(&home.join(".config")));
+        assert_eq!(
+            candidates,
+            [
+                PathBuf::from("/games/pluginConfigs"),
+                home.join(".xlcore/pluginConfigs")
+            ]
+        );
+        assert_eq!(
+            resolve(None, None, None, None, &candidates, |dir| dir.is_relative()),
+            Some(home.join(".xlcore/pluginConfigs"))
+        );
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let home = usual(Path::new("home"));
+        let env = Some(PathBuf::from("env"));
+        let explicit = Some(PathBuf::from("explicit"));
+        let configured = Some(PathBuf::from("configured"));
+        let launcher = Some(PathBuf::from("launcher"));
+        assert_eq!(
+            resolve(
+                None,
+                env.clone(),
+                configured.clone(),
+                launcher.clone(),
+                &home,
+                |_| true
+            ),
+            Some(PathBuf::from("env"))
+        );
+        assert_eq!(
+            resolve(
+                None,
+                None,
+                configured.clone(),
+                launcher.clone(),
+                &home,
+                |_| true
+            ),
+            Some(PathBuf::from("configured"))
+        );
+        assert_eq!(
+            resolve(None, None, None, launcher.clone(), &home, |_| true),
+            Some(PathBuf::from("launcher"))
+        );
+        assert_eq!(
+            resolve(explicit, env.clone(), configured, launcher, &home, |_| true),
+            Some(PathBuf::from("explicit"))
+        );
+        let origin = |env| super::resolve(None, env, None, None, &home, |_| false);
+        assert_eq!(origin(env).unwrap().1, PLUGIN_CONFIGS_ENV);
+        assert_eq!(origin(None).unwrap().1, "the launcher's default place");
+    }
+
+    #[test]
+    fn reads_relocated_launcher_data() {
+        let relocated = r#"{"GamePath": "D:\\FFXIV", "RoamingPath": "D:\\XIVLauncher"}"#;
+        assert_eq!(
+            parse_launcher_config(relocated).unwrap(),
+            Some(PathBuf::from("D:\\XIVLauncher").join("pluginConfigs"))
+        );
+        let direct = r#"{"PluginConfigsPath": "/data/plugins", "RoamingPath": "/data"}"#;
+        assert_eq!(
+            parse_launcher_config(direct).unwrap(),
+            Some(PathBuf::from("/data/plugins"))
+        );
+    }
+
+    #[test]
+    fn default_launcher_data_falls_through() {
+        let default = r#"{"GamePath": "C:\\FFXIV", "RoamingPath": ""}"#;
+        assert_eq!(parse_launcher_config(default).unwrap(), None);
+        assert_eq!(
+            parse_launcher_config(r#"{"GamePath": null}"#).unwrap(),
+            None
+        );
+        assert!(parse_launcher_config("{\"GamePath\": ").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Separate game installs, each with its own plugin config directory, from
+//! the `[[profiles]]` in `config.toml`. Reports read every profile in turn
+//! and merge what they find, unless `--profile` picks one.
+
+use std::{cell::Cell, sync::OnceLock};
+
+use anyhow::bail;
+use tracing::{debug, warn};
+
+use crate::{
+    config::{self, ProfileConfig},
+    paths,
+};
+
+/// Set from the command line.
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+thread_local! {
+    /// The profile [`each`] is reading, as an index into the config's.
+    static CURRENT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Reads only the profile labelled `label` for the rest of the run. Only the
+/// first call has any effect.
+pub fn select(label: String) {
+    let _ = SELECTED.set(label);
+}
+
+/// The profile whose files are being read: the one [`each`] is on, or the
+/// one `--profile` picked.
+pub fn current() -> Option<&'static ProfileConfig> {
+    let profiles = &config::get().profiles;
+    let index = CURRENT.get().or_else(|| {
+        let label = SELECTED.get()?;
+        position(profiles, label)
+    })?;
+    profiles.get(index)
+}
+
+fn position(profiles: &[ProfileConfig], label: &str) -> Option<usize> {
+    profiles
+        .iter()
+        .position(|profile| profile.label.as_deref() == Some(label))
+}
+
+/// What a profile is called in output: its label, or its place in the
+/// config for one without.
+fn name(profiles: &[ProfileConfig], index: usize) -> String {
+    match &profiles[index].label {
+        Some(label) => label.clone(),
+        None => format!("profile {}", index + 1),
+    }
+}
+
+/// The profiles to read, as indexes into the config's. None means the usual
+/// single plugin config directory, as when there are no profiles or one was
+/// given with `--data-root` or the environment.
+fn active() -> anyhow::Result<Vec<usize>> {
+    let profiles = &config::get().profiles;
+    let selected = SELECTED.get();
+    if profiles.is_empty() {
+        if let Some(label) = selected {
+            bail!("No profile `{label}`; config.toml has no [[profiles]]");
+        }
+        return Ok(vec![]);
+    }
+    if paths::plugin_configs_given() {
+        debug!("Ignoring the configured profiles for the plugin configs given");
+        return Ok(vec![]);
+    }
+    let Some(label) = selected else {
+        return Ok((0..profiles.len()).collect());
+    };
+    match position(profiles, label) {
+        Some(index) => Ok(vec![index]),
+        None => {
+            let labels = profiles
+                .iter()
+                .filter_map(|profile| profile.label.as_deref())
+                .collect::<Vec<_>>();
+            bail!(
+                "No profile `{label}`; expected one of {}",
+                labels.join(", ")
+            )
+        }
+    }
+}
+
+/// Makes a profile current until dropped.
+struct Reading {
+    previous: Option<usize>,
+}
+
+impl Reading {
+    fn start(index: Option<usize>) -> Reading {
+        Reading {
+            previous: CURRENT.replace(index),
+        }
+    }
+}
+
+impl Drop for Reading {
+    fn drop(&mut self) {
+        CURRENT.set(self.previous);
+    }
+}
+
+/// Runs `read` with each profile's files in turn, merging what it returns.
+/// When more than one profile is read, `read` gets the profile's name to tag
+/// its rows with. A profile that fails is skipped with a warning, unless
+/// they all do.
+pub fn each<T>(
+    mut read: impl FnMut(Option<&str>) -> anyhow::Result<Vec<T>>,
+) -> anyhow::Result<Vec<T>> {
+    let active = active()?;
+    if active.len() <= 1 {
+        let _reading = Reading::start(active.first().copied());
+        return read(None);
+    }
+    let profiles = &config::get().profiles;
+    let mut merged = vec![];
+    let mut failed = vec![];
+    for &index in &active {
+        let _reading = Reading::start(Some(index));
+        let name = name(profiles, index);
+        match read(Some(&name)) {
+            Ok(rows) => merged.extend(rows),
+            Err(err) => failed.push((name, err)),
+        }
+    }
+    if failed.len() == active.len() {
+        let (name, err) = failed.swap_remove(0);
+        return Err(err.context(format!("Failed to read profile {name}")));
+    }
+    for (name, err) in failed {
+        warn!("Skipping profile {name}: {err:#}");
+    }
+    Ok(merged)
+}
+
+/// `text` followed by `[profile]` when it's from one of several profiles and
+/// could be confused with a row from another.
+pub fn tag(text: &str, profile: Option<&str>, ambiguous: bool) -> String {
+    match profile {
+        Some(profile) if ambiguous => format!("{text} [{profile}]"),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlabelled_profiles_are_named_by_position() {
+        let profiles = [
+            ProfileConfig {
+                label: Some("main".to_string()),
+                ..ProfileConfig::default()
+            },
+            ProfileConfig::default(),
+        ];
+        assert_eq!(name(&profiles, 0), "main");
+        assert_eq!(name(&profiles, 1), "profile 2");
+        assert_eq!(position(&profiles, "main"), Some(0));
+        assert_eq!(position(&profiles, "alt"), None);
+    }
+
+    #[test]
+    fn only_ambiguous_rows_are_tagged() {
+        assert_eq!(tag("Alice", Some("alt"), true), "Alice [alt]");
+        assert_eq!(tag("Alice", Some("alt"), false), "Alice");
+        assert_eq!(tag("Alice", None, true), "Alice");
+    }
+}
@@ -0,0 +1,265 @@
+//! What a loader last made of a plugin's files, kept with a fingerprint of
+//! them so an unchanged source isn't parsed again. Status bars run the
+//! status line every few seconds, and most of the time nothing has changed
+//! since the last run. Between runs the cache is kept in the state
+//! directory; `serve` and `notify-daemon`, which read every source over and
+//! over in one process, keep it in memory instead.
+//!
+//! A file's fingerprint is its size, when it was last modified and a hash of
+//! its first and last few KB, so a file rewritten within the same second
+//! with the same length is still noticed unless only its middle changed.
+//! Hashes kept on disk are FNV-1a, which unlike std's `DefaultHasher` is
+//! the same from one Rust release to the next.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::UNIX_EPOCH,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::state;
+
+// 1 hashed with DefaultHasher, which can change between Rust releases
+const SCHEMA: u32 = 2;
+
+/// How much of each end of a file goes into its fingerprint.
+const EDGE_BYTES: u64 = 4096;
+
+static DISABLED: OnceLock<()> = OnceLock::new();
+static MEMORY: OnceLock<Mutex<HashMap<String, Entry>>> = OnceLock::new();
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static MISSES: AtomicUsize = AtomicUsize::new(0);
+
+/// Always reads the sources for the rest of the run, from `--no-cache`.
+pub fn disable() {
+    let _ = DISABLED.set(());
+}
+
+/// Keeps the cache in memory for the rest of the run instead of in the
+/// state directory, for modes that read the same sources many times.
+pub fn keep_in_memory() {
+    let _ = MEMORY.set(Mutex::default());
+}
+
+/// One source file as last seen. A file that doesn't exist is seen too, so
+/// one appearing is a change.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Fingerprint {
+    path: PathBuf,
+    size: Option<u64>,
+    modified_nanos: Option<u128>,
+    edges: Option<u64>,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Fingerprint {
+        let meta = path.metadata().ok();
+        Fingerprint {
+            path: path.to_path_buf(),
+            size: meta.as_ref().map(|meta| meta.len()),
+            modified_nanos: meta
+                .and_then(|meta| meta.modified().ok())
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_nanos()),
+            edges: edges(path).ok(),
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// `hash` carried on over `bytes` with 64-bit FNV-1a. Start from
+/// [`FNV_OFFSET`].
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// A hash of the first and last [`EDGE_BYTES`] of the file at `path`.
+fn edges(path: &Path) -> std::io::Result<u64> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut buf = vec![0; EDGE_BYTES.min(len) as usize];
+    file.read_exact(&mut buf)?;
+    let mut hash = fnv1a(FNV_OFFSET, &buf);
+    if len > EDGE_BYTES {
+        let start = (len - EDGE_BYTES).max(EDGE_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        buf.clear();
+        file.take(EDGE_BYTES).read_to_end(&mut buf)?;
+        hash = fnv1a(hash, &buf);
+    }
+    Ok(hash)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Entry {
+    sources: Vec<Fingerprint>,
+    data: Value,
+}
+
+/// What `read` makes of the files at `sources`, from the cache when none
+/// of them has changed since it was kept. It's kept by `name` and `at`, the
+/// file or directory the sources are found from, so each profile's are kept
+/// apart. Errors aren't kept, so a source that failed is read again next
+/// time.
+pub fn read_through<T: Serialize + DeserializeOwned>(
+    name: &str,
+    at: &Path,
+    sources: &[PathBuf],
+    read: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if DISABLED.get().is_some() {
+        return read();
+    }
+    let fingerprints = sources
+        .iter()
+        .map(|path| Fingerprint::of(path))
+        .collect::<Vec<_>>();
+    let key = key(name, at);
+    if let Some(data) = lookup(&key, &fingerprints) {
+        match serde_json::from_value(data) {
+            Ok(data) => {
+                let hits = HITS.fetch_add(1, Ordering::Relaxed) + 1;
+                debug!(
+                    "Read cache hit for {name} ({hits} hits, {} misses)",
+                    MISSES.load(Ordering::Relaxed)
+                );
+                return Ok(data);
+            }
+            Err(err) => debug!("Ignoring the cached {name}: {err}"),
+        }
+    }
+    let misses = MISSES.fetch_add(1, Ordering::Relaxed) + 1;
+    debug!(
+        "Read cache miss for {name} ({} hits, {misses} misses)",
+        HITS.load(Ordering::Relaxed)
+    );
+
+    let data = read()?;
+    let entry = Entry {
+        sources: fingerprints,
+        data: serde_json::to_value(&data)?,
+    };
+    store(&key, entry);
+    Ok(data)
+}
+
+fn key(name: &str, at: &Path) -> String {
+    let hash = fnv1a(FNV_OFFSET, at.as_os_str().as_encoded_bytes());
+    format!("{name}-{hash:016x}")
+}
+
+fn lookup(key: &str, fingerprints: &[Fingerprint]) -> Option<Value> {
+    if let Some(memory) = MEMORY.get() {
+        let memory = memory.lock().ok()?;
+        let entry = memory.get(key)?;
+        return (entry.sources == fingerprints).then(|| entry.data.clone());
+    }
+    let path = state::path(&format!("read-cache/{key}.json")).ok()?;
+    let entry = state::load::<Entry>(&path, SCHEMA).ok()?;
+    (entry.sources == fingerprints).then_some(entry.data)
+}
+
+fn store(key: &str, entry: Entry) {
+    if let Some(memory) = MEMORY.get() {
+        if let Ok(mut memory) = memory.lock() {
+            memory.insert(key.to_string(), entry);
+        }
+        return;
+    }
+    let saved = state::path(&format!("read-cache/{key}.json"))
+        .and_then(|path| state::save(&path, SCHEMA, &entry));
+    if let Err(err) = saved {
+        debug!("Couldn't keep the read cache: {err:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn a_rewrite_keeping_size_and_time_is_still_noticed() {
+        let dir = std::env::temp_dir().join(format!("read-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("crops.json");
+        fs::write(&path, "first").unwrap();
+        let before = Fingerprint::of(&path);
+        assert_eq!(Fingerprint::of(&path), before);
+
+        let modified = path.metadata().unwrap().modified().unwrap();
+        fs::write(&path, "again").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let after = Fingerprint::of(&path);
+        assert_eq!(
+            (after.size, after.modified_nanos),
+            (before.size, before.modified_nanos)
+        );
+        assert_ne!(after, before);
+
+        // Past the first few KB, only the end is looked at
+        let long = |end: &str| format!("{}{end}", "x".repeat(3 * EDGE_BYTES as usize));
+        fs::write(&path, long("one")).unwrap();
+        let before = Fingerprint::of(&path);
+        fs::write(&path, long("two")).unwrap();
+        assert_ne!(edges(&path).unwrap(), before.edges.unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hashes_kept_on_disk_never_change() {
+        // The published FNV-1a test vectors
+        assert_eq!(fnv1a(FNV_OFFSET, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET, b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(
+            fnv1a(fnv1a(FNV_OFFSET, b"foo"), b"bar"),
+            0x8594_4171_f739_67e8
+        );
+        assert_eq!(key("crops", Path::new("a")), "crops-af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn unchanged_sources_are_read_once() {
+        keep_in_memory();
+        let dir = std::env::temp_dir().join(format!("read-cache-once-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("subs.db");
+        fs::write(&path, "1").unwrap();
+        let sources = [path.clone()];
+        let reads = std::cell::Cell::new(0);
+        let read = || {
+            read_through("test", &path, &sources, || {
+                reads.set(reads.get() + 1);
+                Ok(fs::read_to_string(&path)?)
+            })
+            .unwrap()
+        };
+        assert_eq!(read(), "1");
+        assert_eq!(read(), "1");
+        fs::write(&path, "22").unwrap();
+        assert_eq!(read(), "22");
+        assert_eq!(reads.get(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+//! What a tool's loader made of a plugin's files, so `doctor` can judge them
+//! from the very same reads the reports make.
+
+use std::{
+    ffi::OsStr,
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{read_cache, source::Failure};
+
+/// What was found in one profile's files for one report.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Scan {
+    /// Files that were read and parsed
+    pub files: usize,
+    /// Rows found in them, like plots or characters
+    pub rows: usize,
+    /// Why each file or row that couldn't be used was skipped
+    pub failed: Vec<String>,
+    /// Each file that couldn't be read or parsed, by name, with why
+    pub unusable: Vec<(String, Failure)>,
+    /// When the newest of the files was last written
+    pub modified: Option<SystemTime>,
+}
+
+impl Scan {
+    /// Notes that `path` was read, keeping its time if it's the newest.
+    pub fn read(&mut self, path: &Path) {
+        self.files += 1;
+        let modified = path.metadata().and_then(|meta| meta.modified()).ok();
+        self.modified = self.modified.max(modified);
+    }
+
+    /// Adds what was found in `other` to this scan.
+    fn merge(&mut self, other: Scan) {
+        self.files += other.files;
+        self.rows += other.rows;
+        self.failed.extend(other.failed);
+        self.unusable.extend(other.unusable);
+        self.modified = self.modified.max(other.modified);
+    }
+}
+
+/// One file parsed by [`json_files`].
+#[derive(Serialize, Deserialize)]
+pub struct JsonFile<T> {
+    pub name: String,
+    /// When the file was last written
+    pub modified: Option<SystemTime>,
+    pub data: T,
+}
+
+/// Every `.json` file in `dir` parsed as a `T`. Those that can't be read or
+/// parsed are skipped with a warning, and noted in `scan`.
+pub fn json_dir<T: Serialize + DeserializeOwned>(
+    dir: &Path,
+    scan: &mut Scan,
+) -> io::Result<Vec<T>> {
+    Ok(json_files(dir, scan)?
+        .into_iter()
+        .map(|file| file.data)
+        .collect())
+}
+
+/// Like [`json_dir`], with the name of the file each `T` came from and when
+/// it was written. Goes through the [`read_cache`], so files unchanged
+/// since the last run aren't parsed again.
+pub fn json_files<T: Serialize + DeserializeOwned>(
+    dir: &Path,
+    scan: &mut Scan,
+) -> io::Result<Vec<JsonFile<T>>> {
+    let mut paths = vec![];
+    for entry in dir.read_dir()? {
+        let Ok(entry) = entry else { continue };
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        if !kind.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if path.extension() != Some(OsStr::new("json")) {
+            debug!("Skipping {}, which isn't JSON", path.display());
+            continue;
+        }
+        paths.push(path);
+    }
+    paths.sort();
+
+    let name = dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy();
+    let (parsed, found) = read_cache::read_through(&name, dir, &paths, || {
+        let mut found = Scan::default();
+        let parsed = parse_all(&paths, &mut found);
+        Ok((parsed, found))
+    })
+    .map_err(io::Error::other)?;
+    scan.merge(found);
+    Ok(parsed)
+}
+
+fn parse_all<T: DeserializeOwned>(paths: &[PathBuf], scan: &mut Scan) -> Vec<JsonFile<T>> {
+    let mut parsed = vec![];
+    for path in paths {
+        debug!("Reading {}", path.display());
+        let Ok(contents) = read_to_string(path) else {
+            warn!("Failed to open {:?}", path);
+            scan.failed
+                .push(format!("{} couldn't be opened", file_name(path)));
+            scan.unusable.push((file_name(path), Failure::Corrupt));
+            continue;
+        };
+        match serde_json::from_str(&contents) {
+            Ok(data) => {
+                scan.read(path);
+                parsed.push(JsonFile {
+                    name: file_name(path),
+                    modified: path.metadata().and_then(|meta| meta.modified()).ok(),
+                    data,
+                });
+            }
+            Err(err) => {
+                warn!("Failed to deserialize {:?}: {err}", path);
+                scan.failed.push(format!("{}: {err}", file_name(path)));
+                scan.unusable
+                    .push((file_name(path), Failure::of_json(&err)));
+            }
+        }
+    }
+    parsed
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path.to_path_buf())
+        .display()
+        .to_string()
+}
@@ -0,0 +1,47 @@
+//! Stopping long-running modes, like `notify-daemon` and `--watch`, between
+//! one pass and the next instead of part way through one, so they can tidy
+//! up after themselves on Ctrl-C or `kill`.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Catches SIGINT and SIGTERM from now on, for [`requested`] and [`wait`]
+/// to report instead of the process being killed.
+#[cfg(unix)]
+pub fn listen() {
+    extern "C" fn stop(_: libc::c_int) {
+        STOP.store(true, Ordering::Relaxed);
+    }
+    let handler = stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only stores to an atomic, which is
+    // async-signal-safe
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn listen() {}
+
+/// Whether a signal has asked to stop.
+pub fn requested() -> bool {
+    STOP.load(Ordering::Relaxed)
+}
+
+/// Sleeps for `duration`, reporting whether a signal asked to stop.
+pub fn wait(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while !requested() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return false;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(250)));
+    }
+    true
+}
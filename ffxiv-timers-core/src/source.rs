@@ -0,0 +1,256 @@
+//! What the modes reading every report at once, like `all`, `serve` and
+//! `notify-daemon`, make of a report they couldn't read. The error is sorted
+//! into why it failed, so the rest can go on and a broken source can be
+//! shown as one line and judged by how bad its failure is.
+
+use std::{fmt, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::check::Severity;
+
+/// Why a report's files couldn't be read, from least to most worrying.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Failure {
+    /// The plugin hasn't written them, as when it isn't installed
+    Missing,
+    /// They're there but couldn't be read or parsed, as when the plugin
+    /// was part way through writing them
+    Corrupt,
+    /// They're laid out in a way this version doesn't know, as after the
+    /// plugin changed them
+    Incompatible,
+}
+
+impl Failure {
+    pub fn label(self) -> &'static str {
+        match self {
+            Failure::Missing => "missing",
+            Failure::Corrupt => "unreadable",
+            Failure::Incompatible => "incompatible",
+        }
+    }
+
+    /// How much the failure wants attention: a missing plugin is only not
+    /// in use, but files this version can't read need it updating.
+    pub fn severity(self) -> Severity {
+        match self {
+            Failure::Missing => Severity::Ok,
+            Failure::Corrupt => Severity::Warning,
+            Failure::Incompatible => Severity::Critical,
+        }
+    }
+
+    /// Why `err` happened, from the errors in its chain. Anything not known
+    /// to be otherwise is taken as corrupt.
+    pub fn of(err: &anyhow::Error) -> Failure {
+        for cause in err.chain() {
+            if cause.is::<Incompatible>() {
+                return Failure::Incompatible;
+            }
+            if cause.is::<MissingInput>() {
+                return Failure::Missing;
+            }
+            if let Some(err) = cause.downcast_ref::<serde_json::Error>() {
+                return Failure::of_json(err);
+            }
+            if let Some(err) = cause.downcast_ref::<io::Error>() {
+                if err.kind() == io::ErrorKind::NotFound {
+                    return Failure::Missing;
+                }
+            }
+        }
+        Failure::Corrupt
+    }
+
+    /// Why a file couldn't be parsed: fields of the wrong shape mean the
+    /// plugin changed them, while anything else is a damaged file.
+    pub fn of_json(err: &serde_json::Error) -> Failure {
+        match err.classify() {
+            serde_json::error::Category::Data => Failure::Incompatible,
+            _ => Failure::Corrupt,
+        }
+    }
+}
+
+/// A layout the loaders don't know, like a database column or CSV width a
+/// plugin update changed, so [`Failure::of`] can tell it from a damaged file.
+#[derive(Debug)]
+pub struct Incompatible(pub String);
+
+impl fmt::Display for Incompatible {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Incompatible {}
+
+/// Files a report is read from that aren't there, as when the plugin writing
+/// them isn't installed, so a tool can exit with a status of its own for it.
+#[derive(Debug)]
+pub struct MissingInput(pub String);
+
+impl fmt::Display for MissingInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MissingInput {}
+
+/// A report that couldn't be read, and why.
+#[derive(Debug)]
+pub struct SourceError {
+    pub failure: Failure,
+    pub error: anyhow::Error,
+}
+
+impl SourceError {
+    /// The report's files aren't there; `paths` are the ones looked for.
+    pub fn missing(paths: &[PathBuf]) -> SourceError {
+        let paths = paths.iter().map(|path| path.display().to_string());
+        SourceError {
+            failure: Failure::Missing,
+            error: anyhow::anyhow!("{} doesn't exist", paths.collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+impl From<anyhow::Error> for SourceError {
+    fn from(error: anyhow::Error) -> SourceError {
+        SourceError {
+            failure: Failure::of(&error),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({:#})", self.failure.label(), self.error)
+    }
+}
+
+/// One report from a mode reading them all: its data, or why there isn't any.
+pub type SourceResult<T> = Result<T, SourceError>;
+
+/// The worst severity among the reports' `results`, counting each failure
+/// by its kind.
+pub fn severity<'a>(results: impl IntoIterator<Item = &'a SourceResult<Severity>>) -> Severity {
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(severity) => *severity,
+            Err(err) => err.failure.severity(),
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// The layouts of a plugin's files a loader can read, for `ffxiv-timers
+/// version`. Each loader builds its own from what it reads, so they stay
+/// true as the loaders change.
+#[derive(Debug, Serialize)]
+pub struct Support {
+    /// The plugin's files, relative to its config directory
+    pub files: String,
+    /// Each layout that can be read
+    pub layouts: Vec<String>,
+}
+
+/// The fields of `sample` once serialized, nested as they're written but
+/// without their values, like `{Item1: {Name}, Item2: [{Map}]}`.
+pub fn shape(sample: &impl Serialize) -> String {
+    fn describe(value: &serde_json::Value) -> Option<String> {
+        match value {
+            serde_json::Value::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, value)| match describe(value) {
+                        Some(inner) => format!("{name}: {inner}"),
+                        None => name.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                Some(format!("{{{}}}", fields.join(", ")))
+            }
+            serde_json::Value::Array(items) => Some(format!(
+                "[{}]",
+                items.first().and_then(describe).unwrap_or_default()
+            )),
+            _ => None,
+        }
+    }
+    serde_json::to_value(sample)
+        .ok()
+        .and_then(|value| describe(&value))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    fn parse(json: &str) -> anyhow::Result<Vec<u32>> {
+        serde_json::from_str(json).context("Failed to parse tasks.json")
+    }
+
+    #[test]
+    fn failures_are_told_apart_by_their_cause() {
+        let missing = std::fs::read("/nonexistent/tasks.json").context("Failed to open");
+        assert_eq!(Failure::of(&missing.unwrap_err()), Failure::Missing);
+        assert_eq!(Failure::of(&parse("[1, 2").unwrap_err()), Failure::Corrupt);
+        assert_eq!(
+            Failure::of(&parse(r#"{"Map": 1}"#).unwrap_err()),
+            Failure::Incompatible
+        );
+        let layout = anyhow::Error::new(Incompatible("27 columns".into())).context("Failed");
+        assert_eq!(Failure::of(&layout), Failure::Incompatible);
+        let absent = anyhow::Error::new(MissingInput("inventories.csv is missing".into()));
+        assert_eq!(Failure::of(&absent), Failure::Missing);
+        assert_eq!(
+            Failure::of(&anyhow::anyhow!("impossible return time")),
+            Failure::Corrupt
+        );
+    }
+
+    #[test]
+    fn shapes_leave_out_values() {
+        let sample = serde_json::json!({"Item1": {"Name": "Alice", "ServerId": 79},
+                                        "Item2": [{"Map": "2024-01-01"}], "Tag": null});
+        assert_eq!(
+            shape(&sample),
+            "{Item1: {Name, ServerId}, Item2: [{Map}], Tag}"
+        );
+    }
+
+    #[test]
+    fn the_worst_source_decides_the_severity() {
+        let broken = |failure| -> SourceResult<Severity> {
+            Err(SourceError {
+                failure,
+                error: anyhow::anyhow!("broken"),
+            })
+        };
+        let error = SourceError::missing(&["subs.db".into()]);
+        assert_eq!(error.to_string(), "missing (subs.db doesn't exist)");
+        let missing = Err(error);
+        assert_eq!(severity([&Ok(Severity::Ok), &missing]), Severity::Ok);
+        assert_eq!(
+            severity([&Ok(Severity::Ok), &missing, &broken(Failure::Corrupt)]),
+            Severity::Warning
+        );
+        assert_eq!(
+            severity([&Ok(Severity::Warning), &broken(Failure::Incompatible)]),
+            Severity::Critical
+        );
+        assert_eq!(
+            severity([&broken(Failure::Corrupt), &Ok(Severity::Critical)]),
+            Severity::Critical
+        );
+        assert_eq!(severity([]), Severity::Ok);
+    }
+}
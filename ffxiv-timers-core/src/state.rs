@@ -0,0 +1,285 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::paths;
+
+/// Where the state file `name` is kept, moving it over from the data
+/// directory earlier versions used if it's still there.
+pub fn path(name: &str) -> anyhow::Result<PathBuf> {
+    let Some(dir) = paths::state_dir() else {
+        bail!("Couldn't find a state directory to keep {name} in");
+    };
+    let path = dir.join(name);
+    let old = paths::data_dir().map(|dir| dir.join(name));
+    if let Some(old) = old.filter(|old| *old != path && old.exists() && !path.exists()) {
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {dir:?}"))?;
+        fs::rename(&old, &path).with_context(|| format!("Failed to move {old:?} to {path:?}"))?;
+        debug!("Moved {} to {}", old.display(), path.display());
+    }
+    Ok(path)
+}
+
+/// Reads a file written by [`save`] with the same `schema`, or the default
+/// when there isn't one yet. A file that can't be read as one, or is from an
+/// older schema, is moved aside with a warning and the default returned, so
+/// a damaged file costs its contents rather than stopping the tool. Files
+/// from a newer schema are an error, to leave them for the version that
+/// wrote them.
+pub fn load<T: DeserializeOwned + Default>(path: &Path, schema: u32) -> anyhow::Result<T> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            debug!("No state in {} yet", path.display());
+            return Ok(T::default());
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+    };
+    match decode(&data, schema) {
+        Ok(value) => Ok(value),
+        Err(Unreadable::Newer(found)) => bail!(
+            "{path:?} was written by a newer version of ffxiv-timers (schema {found}, not \
+             {schema}); upgrade, or remove the file to start over"
+        ),
+        Err(Unreadable::Damaged(reason)) => {
+            let backup = backup_path(path);
+            fs::rename(path, &backup).with_context(|| format!("Failed to move {path:?} aside"))?;
+            warn!(
+                "Starting {} over because {reason}; the old file is kept as {}",
+                path.display(),
+                backup.display()
+            );
+            Ok(T::default())
+        }
+    }
+}
+
+/// What [`save`] writes: the value under `data`, beside its schema.
+#[derive(Serialize)]
+struct Stamped<'a, T> {
+    schema: u32,
+    data: &'a T,
+}
+
+/// Writes `value` stamped with its `schema` through
+/// [`write_atomically_with`], serialized straight to the file.
+pub fn save<T: Serialize>(path: &Path, schema: u32, value: &T) -> anyhow::Result<()> {
+    write_atomically_with(path, |file| {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(
+            &mut writer,
+            &Stamped {
+                schema,
+                data: value,
+            },
+        )?;
+        Ok(writer.flush()?)
+    })
+}
+
+/// Writes to a temporary file beside `path` which then replaces it, so a
+/// crash part way through leaves the previous contents intact. The
+/// temporary name adds `.tmp` to the whole file name, so it never matches
+/// the pattern of whatever reads the real file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    write_atomically_with(path, |file| Ok(file.write_all(contents)?))
+}
+
+/// Like [`write_atomically`], with `write` writing the contents to the
+/// temporary file itself, so they needn't be built in memory first. If it
+/// fails, the temporary file is removed and `path` left as it was.
+pub fn write_atomically_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {dir:?}"))?;
+    }
+    let temp = with_suffix(path, ".tmp");
+    let mut file = File::create(&temp).with_context(|| format!("Failed to create {temp:?}"))?;
+    let written = write(&mut file).and_then(|()| Ok(file.sync_all()?));
+    if let Err(err) = written {
+        drop(file);
+        let _ = fs::remove_file(&temp);
+        return Err(err.context(format!("Failed to write {temp:?}")));
+    }
+    fs::rename(&temp, path).with_context(|| format!("Failed to replace {path:?}"))?;
+    Ok(())
+}
+
+/// Why a state file couldn't be used.
+enum Unreadable {
+    /// Written by a newer version, with this schema
+    Newer(u64),
+    /// Not a state file this version understands, and why
+    Damaged(String),
+}
+
+fn decode<T: DeserializeOwned>(data: &[u8], schema: u32) -> Result<T, Unreadable> {
+    let damaged =
+        |err: serde_json::Error| Unreadable::Damaged(format!("it couldn't be read: {err}"));
+    let mut value: Value = serde_json::from_slice(data).map_err(damaged)?;
+    match value.get("schema").and_then(Value::as_u64) {
+        // Written before files were stamped
+        None => serde_json::from_value(value).map_err(damaged),
+        Some(found) if found > u64::from(schema) => Err(Unreadable::Newer(found)),
+        Some(found) if found < u64::from(schema) => Err(Unreadable::Damaged(format!(
+            "it's from an older version (schema {found}, not {schema})"
+        ))),
+        Some(_) => serde_json::from_value(value["data"].take()).map_err(damaged),
+    }
+}
+
+/// A name beside `path` for a damaged copy, unique to the second.
+fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(
+        path,
+        &format!(".corrupt-{}", Utc::now().format("%Y%m%dT%H%M%S")),
+    )
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    struct Sent {
+        fired: BTreeSet<String>,
+    }
+
+    fn sent(keys: &[&str]) -> Sent {
+        Sent {
+            fired: keys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    /// An empty directory of its own for each test.
+    fn scratch(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("state-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".corrupt-"))
+            .collect()
+    }
+
+    #[test]
+    fn saved_state_loads_back() {
+        let dir = scratch("round-trip");
+        let path = dir.join("nested").join("sent.json");
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), Sent::default());
+        save(&path, 1, &sent(&["map_ready:Alice"])).unwrap();
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["map_ready:Alice"]));
+        let stamped: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(stamped["schema"], 1);
+        assert!(!with_suffix(&path, ".tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_crash_while_saving_keeps_the_last_state() {
+        let dir = scratch("partial-temp");
+        let path = dir.join("sent.json");
+        save(&path, 1, &sent(&["a"])).unwrap();
+        // The process died after writing half of the next save's temporary
+        // file, before it could replace the real one
+        let full =
+            serde_json::to_vec(&serde_json::json!({ "schema": 1, "data": sent(&["a", "b"]) }))
+                .unwrap();
+        fs::write(with_suffix(&path, ".tmp"), &full[..full.len() / 2]).unwrap();
+
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["a"]));
+        save(&path, 1, &sent(&["a", "b"])).unwrap();
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["a", "b"]));
+        assert!(!with_suffix(&path, ".tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_half_written_file_is_backed_up_and_started_over() {
+        let dir = scratch("partial-file");
+        let path = dir.join("sent.json");
+        // As left by a writer that didn't go through a temporary file
+        let truncated = br#"{"schema": 1, "data": {"fired": ["a", "#;
+        fs::write(&path, truncated).unwrap();
+
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), Sent::default());
+        assert!(!path.exists());
+        let backups = backups(&dir);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read(&backups[0]).unwrap(), truncated);
+        save(&path, 1, &sent(&["b"])).unwrap();
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["b"]));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn mismatched_contents_are_backed_up() {
+        let dir = scratch("mismatched");
+        let path = dir.join("sent.json");
+        fs::write(&path, r#"{"schema": 1, "data": {"fired": 7}}"#).unwrap();
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), Sent::default());
+        assert_eq!(backups(&dir).len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn schemas_are_checked() {
+        let dir = scratch("schemas");
+        let path = dir.join("sent.json");
+        save(&path, 3, &sent(&["a"])).unwrap();
+        assert!(load::<Sent>(&path, 2).is_err());
+        assert!(path.exists() && backups(&dir).is_empty());
+
+        assert_eq!(load::<Sent>(&path, 4).unwrap(), Sent::default());
+        assert_eq!(backups(&dir).len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn values_that_fail_to_serialize_are_an_error() {
+        let dir = scratch("unserializable");
+        let path = dir.join("sent.json");
+        save(&path, 1, &sent(&["a"])).unwrap();
+        // JSON object keys have to be strings
+        let bad = BTreeMap::from([((1, 2), "a")]);
+        assert!(save(&path, 1, &bad).is_err());
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["a"]));
+        assert!(!with_suffix(&path, ".tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unstamped_files_still_load() {
+        let dir = scratch("unstamped");
+        let path = dir.join("sent.json");
+        fs::write(&path, r#"{"fired": ["a"]}"#).unwrap();
+        assert_eq!(load::<Sent>(&path, 1).unwrap(), sent(&["a"]));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
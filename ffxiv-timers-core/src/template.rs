@@ -0,0 +1,181 @@
+use std::io::Write;
+
+use anyhow::bail;
+
+/// A line of text with `{field}` placeholders, checked against the fields a
+/// tool documents before anything is read. `{{` and `}}` are literal braces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    /// Splits `text` into literal text and placeholders, which must each be
+    /// one of `fields`.
+    pub fn parse(text: &str, fields: &[&str]) -> anyhow::Result<Template> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => bail!(
+                                "unclosed `{{` in format `{text}`; write `{{{{` for a literal brace"
+                            ),
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    if !fields.contains(&name.as_str()) {
+                        bail!(
+                            "unknown field `{{{name}}}` in format `{text}`; expected one of {}",
+                            fields
+                                .iter()
+                                .map(|field| format!("{{{field}}}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        );
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(name));
+                }
+                '}' => bail!("unmatched `}}` in format `{text}`; write `}}}}` for a literal brace"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Text(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Fills in each placeholder with `value(field)`.
+    pub fn render(&self, value: impl Fn(&str) -> String) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(field) => value(field),
+            })
+            .collect()
+    }
+}
+
+/// The fields `--format-header` and `--format-footer` can use.
+pub const SUMMARY_FIELDS: &[&str] = &["count"];
+
+/// `--format` with its optional header and footer: one line per row, with
+/// the header and footer printed once around them.
+#[derive(Clone, Debug)]
+pub struct RowFormat {
+    pub row: Template,
+    pub header: Option<Template>,
+    pub footer: Option<Template>,
+}
+
+impl RowFormat {
+    /// Parses the templates, checking the row against a tool's `fields`.
+    pub fn parse(
+        row: &str,
+        header: Option<&str>,
+        footer: Option<&str>,
+        fields: &[&str],
+    ) -> anyhow::Result<RowFormat> {
+        let summary = |text| Template::parse(text, SUMMARY_FIELDS);
+        Ok(RowFormat {
+            row: Template::parse(row, fields)?,
+            header: header.map(summary).transpose()?,
+            footer: footer.map(summary).transpose()?,
+        })
+    }
+
+    /// Writes the header, a line for each row and the footer.
+    pub fn write<T>(
+        &self,
+        mut out: impl Write,
+        rows: &[T],
+        value: impl Fn(&T, &str) -> String,
+    ) -> std::io::Result<()> {
+        let count = |_: &str| rows.len().to_string();
+        if let Some(header) = &self.header {
+            writeln!(out, "{}", header.render(count))?;
+        }
+        for row in rows {
+            writeln!(out, "{}", self.row.render(|field| value(row, field)))?;
+        }
+        if let Some(footer) = &self.footer {
+            writeln!(out, "{}", footer.render(count))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[&str] = &["name", "remaining"];
+
+    fn render(text: &str) -> String {
+        Template::parse(text, FIELDS)
+            .unwrap()
+            .render(|field| field.to_uppercase())
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        assert_eq!(render("{name}: {remaining}"), "NAME: REMAINING");
+        assert_eq!(render("{name}{name}"), "NAMENAME");
+        assert_eq!(render("no fields"), "no fields");
+        assert_eq!(render(""), "");
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        assert_eq!(render("{{{name}}}"), "{NAME}");
+        assert_eq!(render("{{name}}"), "{name}");
+        assert_eq!(render("}}{{"), "}{");
+    }
+
+    #[test]
+    fn rejects_unknown_fields_listing_the_valid_ones() {
+        let err = Template::parse("{name} {world}", FIELDS).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown field `{world}` in format `{name} {world}`; expected one of {name}, \
+             {remaining}"
+        );
+    }
+
+    #[test]
+    fn rejects_unbalanced_braces() {
+        assert!(Template::parse("{name", FIELDS).is_err());
+        assert!(Template::parse("{na{me}", FIELDS).is_err());
+        assert!(Template::parse("name}", FIELDS).is_err());
+    }
+
+    #[test]
+    fn writes_header_rows_and_footer() {
+        let format =
+            RowFormat::parse("- {name}", Some("{count} rows"), Some("end"), FIELDS).unwrap();
+        let mut out = vec![];
+        format
+            .write(&mut out, &["a", "b"], |row, _| row.to_string())
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "2 rows\n- a\n- b\nend\n");
+        assert!(RowFormat::parse("{name}", Some("{name}"), None, FIELDS).is_err());
+    }
+}
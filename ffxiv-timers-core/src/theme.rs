@@ -0,0 +1,243 @@
+//! The colors every report uses, so they stay consistent between tools.
+//! Each is picked by the role it plays, from the `--theme` preset or the one
+//! in `config.toml`, with any colors the `[theme]` table sets on top.
+
+use std::{str::FromStr, sync::OnceLock};
+
+use anyhow::bail;
+use serde::{Deserialize, Deserializer};
+use termcolor::{Color, ColorSpec};
+
+use crate::config;
+
+/// What a colored piece of a report is for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Report titles and group headings
+    Header,
+    /// Ordinary lines, and timers still running
+    Good,
+    /// Things that need attention soon
+    Warn,
+    /// States outside the usual progression that need attention now, like
+    /// an unassigned submarine
+    Urgent,
+    /// Timers that are done and things that are ready
+    Ready,
+    /// Errors and things that are lost
+    Error,
+    /// Hints and details that matter less than what's around them
+    Dim,
+}
+
+/// A built-in set of colors, for terminals with a dark or light background.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl FromStr for Preset {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Preset> {
+        match value {
+            "dark" => Ok(Preset::Dark),
+            "light" => Ok(Preset::Light),
+            _ => bail!("invalid value `{value}` for --theme; expected dark or light"),
+        }
+    }
+}
+
+impl Preset {
+    fn color(self, role: Role) -> Color {
+        match (self, role) {
+            (Preset::Dark, Role::Header) => Color::Rgb(255, 255, 255),
+            (Preset::Light, Role::Header) => Color::Black,
+            (Preset::Dark, Role::Good) => Color::Cyan,
+            (Preset::Light, Role::Good) => Color::Blue,
+            (Preset::Dark, Role::Warn) => Color::Yellow,
+            (Preset::Light, Role::Warn) => Color::Ansi256(130),
+            (_, Role::Urgent) => Color::Magenta,
+            (Preset::Dark, Role::Ready) => Color::Green,
+            (Preset::Light, Role::Ready) => Color::Ansi256(28),
+            (_, Role::Error) => Color::Red,
+            (Preset::Dark, Role::Dim) => Color::Ansi256(8),
+            (Preset::Light, Role::Dim) => Color::Ansi256(244),
+        }
+    }
+}
+
+/// A color from `config.toml`: a name, an ANSI color number or RGB.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ThemeColor(pub Color);
+
+impl FromStr for ThemeColor {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<ThemeColor> {
+        let named = match value {
+            "black" => Some(Color::Black),
+            "blue" => Some(Color::Blue),
+            "green" => Some(Color::Green),
+            "red" => Some(Color::Red),
+            "cyan" => Some(Color::Cyan),
+            "magenta" => Some(Color::Magenta),
+            "yellow" => Some(Color::Yellow),
+            "white" => Some(Color::White),
+            _ => None,
+        };
+        let hex = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        let rgb = match value.strip_prefix('#') {
+            Some(hex_digits) if hex_digits.len() == 6 && hex_digits.is_ascii() => {
+                match (
+                    hex(&hex_digits[..2]),
+                    hex(&hex_digits[2..4]),
+                    hex(&hex_digits[4..]),
+                ) {
+                    (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                    _ => None,
+                }
+            }
+            Some(_) => None,
+            None => match value
+                .split(',')
+                .map(|part| part.trim().parse())
+                .collect::<Result<Vec<u8>, _>>()
+            {
+                Ok(parts) => match parts[..] {
+                    [n] => Some(Color::Ansi256(n)),
+                    [r, g, b] => Some(Color::Rgb(r, g, b)),
+                    _ => None,
+                },
+                Err(_) => None,
+            },
+        };
+        match named.or(rgb) {
+            Some(color) => Ok(ThemeColor(color)),
+            None => bail!(
+                "invalid color `{value}`; expected black, blue, green, red, cyan, magenta, \
+                 yellow or white, an ANSI color number from 0 to 255, or RGB as `#rrggbb` or \
+                 `r,g,b`"
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ThemeColor, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Set from the command line, taking precedence over `config.toml`.
+static PRESET_OVERRIDE: OnceLock<Preset> = OnceLock::new();
+
+/// Uses `preset` for the rest of the run, whatever `config.toml` says.
+/// Only the first call has any effect.
+pub fn set_preset(preset: Preset) {
+    let _ = PRESET_OVERRIDE.set(preset);
+}
+
+/// The color for a role in the current theme.
+pub fn color(role: Role) -> Color {
+    let theme = &config::get().theme;
+    let preset = PRESET_OVERRIDE
+        .get()
+        .copied()
+        .or(theme.preset)
+        .unwrap_or_default();
+    let configured = match role {
+        Role::Header => theme.header,
+        Role::Good => theme.good,
+        Role::Warn => theme.warn,
+        Role::Urgent => theme.urgent,
+        Role::Ready => theme.ready,
+        Role::Error => theme.error,
+        Role::Dim => theme.dim,
+    };
+    configured.map_or_else(|| preset.color(role), |ThemeColor(color)| color)
+}
+
+/// The color spec for a role in the current theme.
+pub fn spec(role: Role) -> ColorSpec {
+    ColorSpec::new().set_fg(Some(color(role))).clone()
+}
+
+/// Report titles and group headings.
+pub fn header() -> ColorSpec {
+    spec(Role::Header)
+}
+
+/// Ordinary lines, and timers still running.
+pub fn good() -> ColorSpec {
+    spec(Role::Good)
+}
+
+/// Timers that are done and things that are ready.
+pub fn ready() -> ColorSpec {
+    spec(Role::Ready)
+}
+
+/// Things that need attention soon.
+pub fn warn() -> ColorSpec {
+    spec(Role::Warn)
+}
+
+/// Errors and things that are lost.
+pub fn error() -> ColorSpec {
+    spec(Role::Error)
+}
+
+/// States outside the usual progression that need attention now, like an
+/// unassigned submarine.
+pub fn urgent() -> ColorSpec {
+    spec(Role::Urgent)
+}
+
+/// Hints and details that matter less than what's around them.
+pub fn dim() -> ColorSpec {
+    spec(Role::Dim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLES: [Role; 7] = [
+        Role::Header,
+        Role::Good,
+        Role::Warn,
+        Role::Urgent,
+        Role::Ready,
+        Role::Error,
+        Role::Dim,
+    ];
+
+    #[test]
+    fn preset_colors_are_distinct() {
+        for preset in [Preset::Dark, Preset::Light] {
+            let colors = ROLES.map(|role| preset.color(role));
+            for (index, color) in colors.iter().enumerate() {
+                assert!(colors[index + 1..].iter().all(|other| other != color));
+            }
+        }
+    }
+
+    #[test]
+    fn parses_each_color_format() {
+        let parse = |value: &str| value.parse::<ThemeColor>().map(|ThemeColor(color)| color);
+        assert_eq!(parse("cyan").unwrap(), Color::Cyan);
+        assert_eq!(parse("#ff8000").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse("255, 128, 0").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse("244").unwrap(), Color::Ansi256(244));
+        for bad in ["teal", "#ff80", "#gg8000", "256", "1,2", ""] {
+            let err = parse(bad).unwrap_err().to_string();
+            assert!(err.contains("expected black"), "{bad}: {err}");
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Duration, Local, SubsecRound, Utc};
+
+use crate::i18n::{tr, trf};
+
+/// How [`format_duration`] lays out a countdown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// `HH:MM:SS`, with a day count in front once it's a day or more, e.g.
+    /// `1d 03:12:44`
+    Compact,
+    /// A rough phrase like `in about 27 hours`
+    Humanized,
+    /// `HH:MM:SS` with the hours running past 24, e.g. `27:12:44`
+    Clock,
+}
+
+/// What [`format_duration`] shows once the time has passed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Past {
+    /// The zero duration, like a timer that's run out
+    Zero,
+    /// How long ago it was, e.g. `00:05:00 ago` or `about 5 minutes ago`
+    Ago,
+    /// How late it is, for a deadline that's been missed, e.g. `overdue
+    /// 00:05:00`
+    Overdue,
+    /// A fixed word, like `ready`
+    Label(&'static str),
+}
+
+/// Formats the time until something happens. Negative durations are shown
+/// the way `past` says.
+pub fn format_duration(duration: Duration, style: Style, past: Past) -> String {
+    let seconds = duration.num_seconds();
+    if seconds >= 0 {
+        return match style {
+            Style::Humanized => trf("time.in", &[("time", &humanize(seconds))]),
+            _ => clock(seconds, style),
+        };
+    }
+    match past {
+        Past::Zero => format_duration(Duration::zero(), style, past),
+        Past::Label(label) => label.to_string(),
+        Past::Ago => match style {
+            Style::Humanized => trf("time.ago", &[("time", &humanize(-seconds))]),
+            _ => trf("time.ago", &[("time", &clock(-seconds, style))]),
+        },
+        Past::Overdue => match style {
+            Style::Humanized => trf("time.overdue", &[("time", &humanize(-seconds))]),
+            _ => trf("time.overdue", &[("time", &clock(-seconds, style))]),
+        },
+    }
+}
+
+/// `HH:MM:SS` for a positive number of seconds, splitting off whole days
+/// unless it's the clock style.
+fn clock(total: i64, style: Style) -> String {
+    let (minutes, seconds) = (total / 60 % 60, total % 60);
+    if style == Style::Clock {
+        return format!("{:02}:{minutes:02}:{seconds:02}", total / 3600);
+    }
+    let clock = format!("{:02}:{minutes:02}:{seconds:02}", total / 3600 % 24);
+    match total / 86400 {
+        0 => clock,
+        days => format!("{} {clock}", trf("time.days", &[("n", &days.to_string())])),
+    }
+}
+
+/// A rough phrase for a positive number of seconds, e.g. `about 27 hours`.
+fn humanize(seconds: i64) -> String {
+    let round = |key, unit: i64| trf(key, &[("n", &((seconds + unit / 2) / unit).to_string())]);
+    match seconds {
+        0..=59 => tr("time.under_a_minute").to_string(),
+        60..=89 => tr("time.a_minute").to_string(),
+        90..=5399 => round("time.minutes", 60),
+        5400..=172_799 => round("time.hours", 3600),
+        _ => round("time.days_long", 86400),
+    }
+}
+
+/// Formats a point in time in the local time zone, to the second.
+pub fn format_local(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .round_subsecs(0)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compact(duration: Duration) -> String {
+        format_duration(duration, Style::Compact, Past::Zero)
+    }
+
+    #[test]
+    fn short_durations_are_clock_times() {
+        assert_eq!(compact(Duration::zero()), "00:00:00");
+        assert_eq!(compact(Duration::seconds(59)), "00:00:59");
+        assert_eq!(
+            compact(Duration::hours(23) + Duration::minutes(5)),
+            "23:05:00"
+        );
+    }
+
+    #[test]
+    fn long_durations_count_days() {
+        assert_eq!(compact(Duration::days(1)), "1d 00:00:00");
+        assert_eq!(
+            compact(Duration::days(1) - Duration::seconds(1)),
+            "23:59:59"
+        );
+        assert_eq!(
+            compact(Duration::days(5) + Duration::seconds(3723)),
+            "5d 01:02:03"
+        );
+    }
+
+    #[test]
+    fn clock_style_keeps_days_in_the_hours() {
+        let duration = Duration::hours(27) + Duration::seconds(764);
+        assert_eq!(
+            format_duration(duration, Style::Clock, Past::Zero),
+            "27:12:44"
+        );
+        assert_eq!(
+            format_duration(Duration::days(1), Style::Clock, Past::Zero),
+            "24:00:00"
+        );
+    }
+
+    #[test]
+    fn humanized_durations_round_to_one_unit() {
+        let humanized = |duration| format_duration(duration, Style::Humanized, Past::Ago);
+        assert_eq!(humanized(Duration::zero()), "in less than a minute");
+        assert_eq!(humanized(Duration::seconds(59)), "in less than a minute");
+        assert_eq!(humanized(Duration::seconds(61)), "in about a minute");
+        assert_eq!(humanized(Duration::minutes(44)), "in about 44 minutes");
+        assert_eq!(humanized(Duration::days(1)), "in about 24 hours");
+        assert_eq!(
+            humanized(Duration::hours(27) + Duration::minutes(12)),
+            "in about 27 hours"
+        );
+        assert_eq!(humanized(Duration::days(3)), "in about 3 days");
+        assert_eq!(humanized(Duration::minutes(-5)), "about 5 minutes ago");
+    }
+
+    #[test]
+    fn past_times_follow_the_caller() {
+        let past = Duration::seconds(-90);
+        assert_eq!(compact(past), "00:00:00");
+        assert_eq!(
+            format_duration(past, Style::Compact, Past::Ago),
+            "00:01:30 ago"
+        );
+        assert_eq!(
+            format_duration(past, Style::Humanized, Past::Label("ready")),
+            "ready"
+        );
+        assert_eq!(
+            format_duration(
+                Duration::milliseconds(-500),
+                Style::Compact,
+                Past::Label("ready")
+            ),
+            "00:00:00"
+        );
+    }
+
+    #[test]
+    fn missed_deadlines_say_how_late_they_are() {
+        let overdue = |duration| format_duration(duration, Style::Compact, Past::Overdue);
+        assert_eq!(overdue(Duration::zero()), "00:00:00");
+        assert_eq!(overdue(Duration::milliseconds(-999)), "00:00:00");
+        assert_eq!(overdue(Duration::seconds(-1)), "overdue 00:00:01");
+        assert_eq!(
+            overdue(-(Duration::hours(1) + Duration::seconds(312))),
+            "overdue 01:05:12"
+        );
+        assert_eq!(
+            overdue(-(Duration::days(1) + Duration::seconds(3912))),
+            "overdue 1d 01:05:12"
+        );
+        assert_eq!(
+            format_duration(Duration::hours(-30), Style::Clock, Past::Overdue),
+            "overdue 30:00:00"
+        );
+        assert_eq!(
+            format_duration(Duration::minutes(-5), Style::Humanized, Past::Overdue),
+            "overdue about 5 minutes"
+        );
+    }
+}
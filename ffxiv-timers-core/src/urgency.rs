@@ -0,0 +1,192 @@
+//! How close each timer is to wanting attention, judged the same way by
+//! every report. A tool turns its deadlines into an [`Urgency`] with the
+//! [`Thresholds`] for it, and the rest follows from that level: the color a
+//! row is shown in, what `--check` exits with, the status bar's class and
+//! whether `notify-daemon` says anything.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    check::Severity,
+    config::{self, UrgencyConfig},
+    json::Tool,
+    theme::Role,
+};
+
+/// How close a deadline is, from least to most pressing.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    /// Nothing to do for a while
+    #[default]
+    Ok,
+    /// Within the `soon` threshold of the deadline
+    Soon,
+    /// Past the deadline: ready to be seen to
+    Due,
+    /// Past the `overdue` threshold, or something will be lost without
+    /// attention, like a wilting crop
+    Overdue,
+}
+
+impl Urgency {
+    /// What `--check` counts the level as.
+    pub fn severity(self) -> Severity {
+        match self {
+            Urgency::Ok | Urgency::Soon => Severity::Ok,
+            Urgency::Due => Severity::Warning,
+            Urgency::Overdue => Severity::Critical,
+        }
+    }
+
+    /// The color the level is shown in.
+    pub fn role(self) -> Role {
+        match self {
+            Urgency::Ok => Role::Good,
+            Urgency::Soon => Role::Warn,
+            Urgency::Due => Role::Ready,
+            Urgency::Overdue => Role::Error,
+        }
+    }
+
+    /// The status bar's class for the level, named as before the levels
+    /// were shared so existing bar styles keep working.
+    pub fn class(self) -> &'static str {
+        match self {
+            Urgency::Ok => "idle",
+            Urgency::Soon => "notice",
+            Urgency::Due => "warning",
+            Urgency::Overdue => "critical",
+        }
+    }
+}
+
+/// When one report's deadlines turn soon and overdue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Thresholds {
+    /// How long before a deadline it's soon
+    pub soon: Duration,
+    /// How long after a deadline it's overdue, or never without one
+    pub overdue: Option<Duration>,
+}
+
+impl Thresholds {
+    /// The level of a deadline `at`, as of `now`.
+    pub fn level(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> Urgency {
+        let left = at - now;
+        if left > self.soon {
+            Urgency::Ok
+        } else if left > Duration::zero() {
+            Urgency::Soon
+        } else if self.overdue.is_some_and(|overdue| -left >= overdue) {
+            Urgency::Overdue
+        } else {
+            Urgency::Due
+        }
+    }
+}
+
+/// Minutes before a deadline it's soon, when nothing sets it.
+fn default_soon(tool: Tool) -> u32 {
+    match tool {
+        // Enough warning to get home before a crop wilts
+        Tool::Crops => 60,
+        Tool::Maps | Tool::Subs | Tool::Inventory => 30,
+    }
+}
+
+static SOON: OnceLock<u32> = OnceLock::new();
+
+/// Uses `minutes` as every report's `soon` threshold for the rest of the
+/// run, as `--soon` does. Only the first call has any effect.
+pub fn set_soon(minutes: u32) {
+    let _ = SOON.set(minutes);
+}
+
+/// A report's thresholds: each from `--soon`, the report's own table in
+/// `[urgency]`, the table itself, or the default, in that order.
+pub fn thresholds(tool: Tool) -> Thresholds {
+    resolve(tool, SOON.get().copied(), &config::get().urgency)
+}
+
+fn resolve(tool: Tool, flag: Option<u32>, config: &UrgencyConfig) -> Thresholds {
+    let own = match tool {
+        Tool::Crops => config.crops,
+        Tool::Maps => config.maps,
+        Tool::Subs => config.subs,
+        Tool::Inventory => Default::default(),
+    };
+    let soon = flag
+        .or(own.soon)
+        .or(config.soon)
+        .unwrap_or_else(|| default_soon(tool));
+    let overdue = own.overdue.or(config.overdue);
+    Thresholds {
+        soon: Duration::minutes(soon.into()),
+        overdue: overdue.map(|minutes| Duration::minutes(minutes.into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ThresholdConfig;
+
+    use super::*;
+
+    #[test]
+    fn the_flag_beats_the_reports_table_beats_the_whole_table() {
+        let minutes = |thresholds: Thresholds| thresholds.soon.num_minutes();
+        let mut config = UrgencyConfig::default();
+        assert_eq!(minutes(resolve(Tool::Crops, None, &config)), 60);
+        assert_eq!(minutes(resolve(Tool::Subs, None, &config)), 30);
+        config.soon = Some(45);
+        assert_eq!(minutes(resolve(Tool::Crops, None, &config)), 45);
+        config.crops = ThresholdConfig {
+            soon: Some(120),
+            overdue: Some(10),
+        };
+        assert_eq!(minutes(resolve(Tool::Crops, None, &config)), 120);
+        // Other reports keep the shared value
+        assert_eq!(minutes(resolve(Tool::Subs, None, &config)), 45);
+        assert_eq!(minutes(resolve(Tool::Crops, Some(5), &config)), 5);
+        assert_eq!(minutes(resolve(Tool::Subs, Some(5), &config)), 5);
+
+        // There's no flag for overdue, and no default
+        assert_eq!(resolve(Tool::Subs, None, &config).overdue, None);
+        config.overdue = Some(240);
+        assert_eq!(
+            resolve(Tool::Subs, None, &config).overdue,
+            Some(Duration::hours(4))
+        );
+        assert_eq!(
+            resolve(Tool::Crops, Some(5), &config).overdue,
+            Some(Duration::minutes(10))
+        );
+    }
+
+    #[test]
+    fn deadlines_move_through_each_level() {
+        let now = Utc::now();
+        let thresholds = Thresholds {
+            soon: Duration::minutes(30),
+            overdue: Some(Duration::hours(2)),
+        };
+        let level = |minutes| thresholds.level(now + Duration::minutes(minutes), now);
+        assert_eq!(level(31), Urgency::Ok);
+        assert_eq!(level(30), Urgency::Soon);
+        assert_eq!(level(1), Urgency::Soon);
+        assert_eq!(level(0), Urgency::Due);
+        assert_eq!(level(-119), Urgency::Due);
+        assert_eq!(level(-120), Urgency::Overdue);
+        let never = Thresholds {
+            overdue: None,
+            ..thresholds
+        };
+        assert_eq!(never.level(now - Duration::days(30), now), Urgency::Due);
+        assert_eq!(Urgency::Soon.severity(), Severity::Ok);
+        assert_eq!(Urgency::Overdue.severity(), Severity::Critical);
+    }
+}
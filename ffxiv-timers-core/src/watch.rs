@@ -0,0 +1,369 @@
+//! `--watch`: showing a report full screen and redrawing it as the plugins
+//! rewrite its files, until Ctrl-C. Changes are noticed through filesystem
+//! notifications where there are any, or else by polling modification
+//! times. Between reloads a report with countdowns can also be redrawn on a
+//! tick from the data it already has, so the timers keep running without
+//! re-reading anything, and a report can be read again on an interval
+//! whatever the notifications say. A frame that hasn't changed isn't
+//! drawn again, so the screen doesn't flicker.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use termcolor::{Buffer, WriteColor};
+
+use crate::{clock, shutdown, theme};
+
+/// The plugins rewrite their files in bursts, so a reload waits until
+/// nothing has changed for this long.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+/// How often modification times are checked without filesystem notifications.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// The longest a wait goes without looking for Ctrl-C.
+const STOP_CHECK: Duration = Duration::from_millis(250);
+
+/// Why the report is being drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Refresh {
+    /// The files changed, or it's the first draw: read them again
+    Reload,
+    /// Only time has passed: redraw what was last read
+    Tick,
+}
+
+/// What the loop does next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    Reload,
+    Tick,
+    /// Nothing until a file changes or this long has passed, if ever
+    Wait(Option<Duration>),
+}
+
+/// When to reload and when to only redraw, given when files changed and
+/// when the report was last drawn. It keeps no clock of its own, so it can
+/// be driven with made-up instants.
+#[derive(Debug)]
+pub struct Schedule {
+    tick: Option<Duration>,
+    reload_every: Option<Duration>,
+    debounce: Duration,
+    /// The last change not yet reloaded
+    changed_at: Option<Instant>,
+    /// `None` until the first draw
+    rendered_at: Option<Instant>,
+    reloaded_at: Option<Instant>,
+}
+
+impl Schedule {
+    /// Redraws every `tick`, if given, and reloads once changes have
+    /// stopped for `debounce`, and at least every `reload_every`.
+    pub fn new(
+        tick: Option<Duration>,
+        reload_every: Option<Duration>,
+        debounce: Duration,
+    ) -> Schedule {
+        Schedule {
+            tick,
+            reload_every,
+            debounce,
+            changed_at: None,
+            rendered_at: None,
+            reloaded_at: None,
+        }
+    }
+
+    /// Notes that a watched file changed at `at`, putting off the reload
+    /// until the burst of changes it's part of is over.
+    pub fn changed(&mut self, at: Instant) {
+        self.changed_at = Some(at);
+    }
+
+    /// What to do at `now`. A reload due at the same time as a tick wins,
+    /// since it redraws anyway.
+    pub fn next(&self, now: Instant) -> Step {
+        let Some(rendered_at) = self.rendered_at else {
+            return Step::Reload;
+        };
+        let mut wait: Option<Duration> = None;
+        let mut until = |due: Instant| {
+            wait = Some(wait.map_or(due - now, |wait| wait.min(due - now)));
+        };
+        if let Some(changed_at) = self.changed_at {
+            let settled = changed_at + self.debounce;
+            if now >= settled {
+                return Step::Reload;
+            }
+            until(settled);
+        }
+        if let (Some(every), Some(reloaded_at)) = (self.reload_every, self.reloaded_at) {
+            let due = reloaded_at + every;
+            if now >= due {
+                return Step::Reload;
+            }
+            until(due);
+        }
+        if let Some(tick) = self.tick {
+            let due = rendered_at + tick;
+            if now >= due {
+                return Step::Tick;
+            }
+            until(due);
+        }
+        Step::Wait(wait)
+    }
+
+    /// Notes that the report was drawn at `at`, which a reload brings up
+    /// to date with every change before it.
+    pub fn rendered(&mut self, at: Instant, refresh: Refresh) {
+        self.rendered_at = Some(at);
+        if refresh == Refresh::Reload {
+            self.changed_at = None;
+            self.reloaded_at = Some(at);
+        }
+    }
+}
+
+/// Draws the report with `render`, then again whenever one of `paths`
+/// changes and, with a `tick`, that often in between, until Ctrl-C. With
+/// `reload_every`, it's also read again that often, which catches changes
+/// the notifications miss, like a directory removed and made again. Each
+/// path is a file, or a directory whose entries are all watched. Errors
+/// from one reload, such as reading a half-written file, are shown in place
+/// of the report until the next one instead of ending the watch.
+pub fn run(
+    stdout: &mut dyn WriteColor,
+    paths: &[PathBuf],
+    tick: Option<Duration>,
+    reload_every: Option<Duration>,
+    render: impl FnMut(&mut dyn WriteColor, Refresh) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    shutdown::listen();
+    // Hide the cursor, which would otherwise sit blinking under the report
+    write!(stdout, "\x1b[?25l")?;
+    let result = draw_until_stopped(stdout, paths, tick, reload_every, render);
+    stdout.reset()?;
+    writeln!(stdout, "\x1b[?25h")?;
+    stdout.flush()?;
+    result
+}
+
+fn draw_until_stopped(
+    stdout: &mut dyn WriteColor,
+    paths: &[PathBuf],
+    tick: Option<Duration>,
+    reload_every: Option<Duration>,
+    mut render: impl FnMut(&mut dyn WriteColor, Refresh) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let (changed, changes) = mpsc::channel();
+    let _watcher = match notify_on_change(paths, changed.clone()) {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            tracing::warn!("Filesystem notifications are unavailable ({err}), polling for changes");
+            poll(paths.to_vec(), changed);
+            None
+        }
+    };
+
+    let mut schedule = Schedule::new(tick, reload_every, DEBOUNCE);
+    let mut reloaded_at = clock::now();
+    let mut failed = false;
+    let mut last_frame = None;
+    while !shutdown::requested() {
+        let now = Instant::now();
+        let refresh = match schedule.next(now) {
+            Step::Reload => Refresh::Reload,
+            Step::Tick => Refresh::Tick,
+            Step::Wait(wait) => {
+                let wait = wait.map_or(STOP_CHECK, |wait| wait.min(STOP_CHECK));
+                match changes.recv_timeout(wait) {
+                    Ok(()) => schedule.changed(Instant::now()),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => thread::sleep(wait),
+                }
+                continue;
+            }
+        };
+        schedule.rendered(now, refresh);
+        // A failed reload stays on screen until the next one
+        if refresh == Refresh::Tick && failed {
+            continue;
+        }
+        if refresh == Refresh::Reload {
+            reloaded_at = clock::now();
+        }
+
+        // Drawn off screen first, so the terminal never shows half a report
+        let mut frame = match stdout.supports_color() {
+            true => Buffer::ansi(),
+            false => Buffer::no_color(),
+        };
+        frame.set_color(&theme::header())?;
+        writeln!(
+            frame,
+            "Refreshed at {}\n",
+            reloaded_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+        )?;
+        frame.reset()?;
+        failed = match render(&mut frame, refresh) {
+            Ok(()) => false,
+            Err(err) => {
+                frame.set_color(&theme::error())?;
+                writeln!(frame, "Error: {err:#}")?;
+                true
+            }
+        };
+        frame.reset()?;
+        if last_frame.as_deref() == Some(frame.as_slice()) {
+            continue;
+        }
+
+        // Move the cursor home and clear the screen
+        write!(stdout, "\x1b[H\x1b[2J")?;
+        stdout.write_all(frame.as_slice())?;
+        stdout.flush()?;
+        last_frame = Some(frame.into_inner());
+    }
+    Ok(())
+}
+
+/// Watches directories themselves, and files through the directories
+/// holding them, since a file replaced by a rename would otherwise stop
+/// being watched.
+fn notify_on_change(paths: &[PathBuf], changed: Sender<()>) -> notify::Result<RecommendedWatcher> {
+    let targets = paths.to_vec();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        let watched = |path: &PathBuf| {
+            targets.contains(path)
+                || path
+                    .parent()
+                    .is_some_and(|dir| targets.iter().any(|target| target == dir))
+        };
+        if !event.kind.is_access() && event.paths.iter().any(watched) {
+            let _ = changed.send(());
+        }
+    })?;
+
+    let mut dirs = paths
+        .iter()
+        .filter_map(|path| match path.is_dir() {
+            true => Some(path.as_path()),
+            false => path.parent(),
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    Ok(watcher)
+}
+
+/// Falls back to comparing modification times on a background thread.
+fn poll(paths: Vec<PathBuf>, changed: Sender<()>) {
+    let stamps = move || paths.iter().map(|path| stamp(path)).collect::<Vec<_>>();
+    thread::spawn(move || {
+        let mut last = stamps();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = stamps();
+            if current != last {
+                last = current;
+                if changed.send(()).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Modification times of a file, or of a directory and everything directly
+/// in it, since plugins rewrite their files in place.
+pub fn stamp(path: &Path) -> Vec<Option<SystemTime>> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let mut stamp = vec![modified(path)];
+    if let Ok(entries) = fs::read_dir(path) {
+        let mut entries = entries
+            .flatten()
+            .map(|entry| (entry.file_name(), modified(&entry.path())))
+            .collect::<Vec<_>>();
+        entries.sort();
+        stamp.extend(entries.into_iter().map(|(_, modified)| modified));
+    }
+    stamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn reloads_wait_for_changes_to_settle_while_ticks_keep_going() {
+        let start = Instant::now();
+        let at = |secs: u64| start + SECOND * secs as u32;
+        let mut schedule = Schedule::new(Some(SECOND * 5), None, SECOND * 2);
+        // The first draw reads the files
+        assert_eq!(schedule.next(at(0)), Step::Reload);
+        schedule.rendered(at(0), Refresh::Reload);
+        assert_eq!(schedule.next(at(1)), Step::Wait(Some(SECOND * 4)));
+        assert_eq!(schedule.next(at(5)), Step::Tick);
+        schedule.rendered(at(5), Refresh::Tick);
+
+        // A burst of changes puts the reload off until it's over
+        schedule.changed(at(8));
+        assert_eq!(schedule.next(at(9)), Step::Wait(Some(SECOND)));
+        schedule.changed(at(9));
+        // A tick falling due meanwhile only redraws
+        assert_eq!(schedule.next(at(10)), Step::Tick);
+        schedule.rendered(at(10), Refresh::Tick);
+        assert_eq!(schedule.next(at(10)), Step::Wait(Some(SECOND)));
+        assert_eq!(schedule.next(at(11)), Step::Reload);
+        schedule.rendered(at(11), Refresh::Reload);
+        assert_eq!(schedule.next(at(12)), Step::Wait(Some(SECOND * 4)));
+    }
+
+    #[test]
+    fn without_a_tick_only_changes_redraw() {
+        let start = Instant::now();
+        let mut schedule = Schedule::new(None, None, SECOND * 2);
+        schedule.rendered(start, Refresh::Reload);
+        assert_eq!(schedule.next(start + SECOND * 3600), Step::Wait(None));
+        schedule.changed(start + SECOND);
+        assert_eq!(schedule.next(start + SECOND * 2), Step::Wait(Some(SECOND)));
+        assert_eq!(schedule.next(start + SECOND * 3), Step::Reload);
+    }
+
+    #[test]
+    fn reloads_come_at_least_every_interval() {
+        let start = Instant::now();
+        let at = |secs: u64| start + SECOND * secs as u32;
+        let mut schedule = Schedule::new(Some(SECOND), Some(SECOND * 30), SECOND * 2);
+        schedule.rendered(at(0), Refresh::Reload);
+        assert_eq!(schedule.next(at(1)), Step::Tick);
+        schedule.rendered(at(29), Refresh::Tick);
+        assert_eq!(schedule.next(at(29)), Step::Wait(Some(SECOND)));
+        assert_eq!(schedule.next(at(30)), Step::Reload);
+
+        schedule.rendered(at(30), Refresh::Reload);
+
+        // A reload for a change puts the next one off
+        schedule.changed(at(40));
+        assert_eq!(schedule.next(at(42)), Step::Reload);
+        schedule.rendered(at(42), Refresh::Reload);
+        schedule.rendered(at(71), Refresh::Tick);
+        assert_eq!(schedule.next(at(71)), Step::Wait(Some(SECOND)));
+        assert_eq!(schedule.next(at(72)), Step::Reload);
+    }
+}
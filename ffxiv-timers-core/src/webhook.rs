@@ -0,0 +1,182 @@
+//! Sending `notify-daemon`'s events to a webhook: Discord, Slack, ntfy.sh,
+//! Gotify or anything else that takes a POST. The body is the event as JSON,
+//! or `[notify.webhook]`'s payload template filled in the way `--format`
+//! lines are, with each field escaped to sit inside a JSON string.
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::{config::WebhookConfig, template::Template};
+
+/// The fields a payload template can use.
+pub const FIELDS: &[&str] = &["kind", "key", "title", "body"];
+
+/// How long a webhook gets to answer.
+#[cfg(feature = "webhook")]
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Something worth telling the player about.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Event {
+    /// Which `[notify]` table the event belongs to
+    pub kind: &'static str,
+    /// The same for as long as the occurrence lasts, e.g. until the map is
+    /// gathered
+    pub key: String,
+    pub title: String,
+    pub body: String,
+}
+
+impl Event {
+    /// What `--test-webhook` sends.
+    pub fn sample() -> Event {
+        Event {
+            kind: "test",
+            key: "test".to_string(),
+            title: "ffxiv-timers".to_string(),
+            body: "A test event: the webhook is set up".to_string(),
+        }
+    }
+
+    fn field(&self, field: &str) -> &str {
+        match field {
+            "kind" => self.kind,
+            "key" => &self.key,
+            "title" => &self.title,
+            "body" => &self.body,
+            _ => unreachable!("checked by Template::parse"),
+        }
+    }
+}
+
+/// A configured webhook, its payload template checked.
+#[derive(Debug)]
+pub struct Webhook {
+    pub url: String,
+    /// Sorted by name
+    pub headers: Vec<(String, String)>,
+    payload: Option<Template>,
+}
+
+impl Webhook {
+    /// Checks the payload template, which must make JSON once filled in,
+    /// so a mistake shows at startup rather than at the first event.
+    pub fn new(config: &WebhookConfig) -> anyhow::Result<Webhook> {
+        let payload = config
+            .payload
+            .as_deref()
+            .map(|payload| Template::parse(payload, FIELDS))
+            .transpose()
+            .context("Failed to parse the webhook payload in [notify.webhook]")?;
+        let mut headers = config
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        headers.sort();
+        let webhook = Webhook {
+            url: config.url.clone(),
+            headers,
+            payload,
+        };
+        serde_json::from_str::<serde_json::Value>(&webhook.payload(&Event::sample()))
+            .context("The webhook payload in [notify.webhook] isn't JSON once filled in")?;
+        Ok(webhook)
+    }
+
+    /// The body sent for `event`.
+    pub fn payload(&self, event: &Event) -> String {
+        match &self.payload {
+            Some(template) => template.render(|field| json_string(event.field(field))),
+            None => serde_json::to_string(event).expect("events are always JSON"),
+        }
+    }
+
+    /// POSTs the event, trying once more if the server fails with a 5xx,
+    /// which is often a restart or a rate limit passing.
+    #[cfg(feature = "webhook")]
+    pub fn send(&self, event: &Event) -> anyhow::Result<()> {
+        let body = self.payload(event);
+        match self.post(&body).map_err(|err| *err) {
+            Err(ureq::Error::Status(status, _)) if retried(status) => {
+                tracing::debug!("{} answered {status}, trying again", self.url);
+                self.post(&body).map_err(|err| *err)
+            }
+            result => result,
+        }
+        .with_context(|| format!("Failed to POST to {}", self.url))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "webhook"))]
+    pub fn send(&self, _event: &Event) -> anyhow::Result<()> {
+        anyhow::bail!("ffxiv-timers was built without the webhook feature")
+    }
+
+    /// The error is boxed, as ureq's is large enough to slow every return.
+    #[cfg(feature = "webhook")]
+    fn post(&self, body: &str) -> Result<ureq::Response, Box<ureq::Error>> {
+        let mut request = ureq::post(&self.url).timeout(TIMEOUT);
+        if !self
+            .headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        {
+            request = request.set("Content-Type", "application/json");
+        }
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        request.send_string(body).map_err(Box::new)
+    }
+}
+
+/// Whether a response with this status is worth a second try.
+#[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+fn retried(status: u16) -> bool {
+    (500..600).contains(&status)
+}
+
+/// `text` escaped to go between a JSON string's quotes.
+fn json_string(text: &str) -> String {
+    let quoted = serde_json::to_string(text).expect("strings are always JSON");
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(payload: Option<&str>) -> anyhow::Result<Webhook> {
+        Webhook::new(&WebhookConfig {
+            url: "https://ntfy.sh/timers".to_string(),
+            payload: payload.map(str::to_string),
+            ..WebhookConfig::default()
+        })
+    }
+
+    #[test]
+    fn payloads_fill_in_the_event_as_json() {
+        let event = Event {
+            kind: "sub_returned",
+            key: "sub_returned/MOGS/Nautilus/1".to_string(),
+            title: "Submarine returned".to_string(),
+            body: "Nautilus of «MOGS» is \"back\"".to_string(),
+        };
+        let slack = webhook(Some(r#"{{"text": "*{title}*\n{body}"}}"#)).unwrap();
+        assert_eq!(
+            slack.payload(&event),
+            r#"{"text": "*Submarine returned*\nNautilus of «MOGS» is \"back\""}"#
+        );
+        let plain = webhook(None).unwrap().payload(&event);
+        let plain: serde_json::Value = serde_json::from_str(&plain).unwrap();
+        assert_eq!(plain["kind"], "sub_returned");
+    }
+
+    #[test]
+    fn payloads_are_checked_up_front() {
+        assert!(webhook(Some("{{\"text\": \"{time}\"}}")).is_err());
+        assert!(webhook(Some("{title}")).is_err());
+        assert!(retried(502) && !retried(404) && !retried(200));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::{
+    config,
+    gamedata::{self, World},
+    paths, profiles,
+};
+
+const WORLD_TABLE_FILE: &str = "worlds.csv";
+
+/// Names from `worlds.csv`, for worlds added or renamed since the game data
+/// was taken. Set once at startup.
+static OVERRIDES: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+#[derive(Deserialize)]
+struct WorldRow {
+    id: u32,
+    name: String,
+}
+
+/// Reads an `id,name` world table from `path`, or from `worlds.csv` in the
+/// config directory if it exists, to take precedence over the game data's
+/// names and those in `config.toml`.
+pub fn load_overrides(path: Option<&Path>) -> anyhow::Result<()> {
+    if OVERRIDES.get().is_some() {
+        return Ok(());
+    }
+    let mut overrides = configured();
+    let path = match path {
+        Some(path) => Some(path.to_path_buf()),
+        None => default_path().filter(|path| path.exists()),
+    };
+    if let Some(path) = path {
+        debug!("Reading world names from {}", path.display());
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("Failed to open world table {path:?}"))?;
+        overrides
+            .extend(parse(file).with_context(|| format!("Failed to read world table {path:?}"))?);
+    }
+    let _ = OVERRIDES.set(overrides);
+    Ok(())
+}
+
+/// The `[worlds]` table from `config.toml`.
+fn configured() -> HashMap<u32, String> {
+    config::get()
+        .worlds
+        .iter()
+        .filter_map(|(id, name)| match id.parse() {
+            Ok(id) => Some((id, name.clone())),
+            Err(_) => {
+                warn!("Ignoring world `{name}` with invalid id `{id}` in config.toml");
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse(reader: impl Read) -> anyhow::Result<HashMap<u32, String>> {
+    let mut overrides = HashMap::new();
+    for row in csv::Reader::from_reader(reader).deserialize() {
+        let row: WorldRow = row?;
+        overrides.insert(row.id, row.name);
+    }
+    Ok(overrides)
+}
+
+/// The world's name, or "World#n" for ids no table knows. Names set
+/// for the profile being read come first.
+pub fn name(id: u32) -> String {
+    let profile = profiles::current().and_then(|profile| profile.worlds.get(&id.to_string()));
+    match profile {
+        Some(name) => name.clone(),
+        None => lookup(OVERRIDES.get(), id),
+    }
+}
+
+fn lookup(overrides: Option<&HashMap<u32, String>>, id: u32) -> String {
+    if let Some(name) = overrides.and_then(|overrides| overrides.get(&id)) {
+        return name.clone();
+    }
+    match world(id) {
+        Some(world) => world.name.clone(),
+        None => format!("World#{id}"),
+    }
+}
+
+fn world(id: u32) -> Option<&'static World> {
+    gamedata::worlds()
+        .rows()
+        .iter()
+        .find(|world| world.id == id)
+}
+
+/// The data center a world in the game data belongs to.
+pub fn data_center(id: u32) -> Option<&'static str> {
+    world(id).map(|world| world.data_center.as_str())
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(paths::config_dir()?.join(WORLD_TABLE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_builtin_worlds() {
+        assert_eq!(lookup(None, 72), "Tonberry");
+        assert_eq!(lookup(None, 91), "Balmung");
+        assert_eq!(lookup(None, 411), "Golem");
+    }
+
+    #[test]
+    fn unknown_worlds_show_their_id() {
+        assert_eq!(lookup(None, 0), "World#0");
+        assert_eq!(lookup(None, 9999), "World#9999");
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides = parse("id,name\n72,Tonberry (JP)\n9999,Testworld\n".as_bytes()).unwrap();
+        assert_eq!(lookup(Some(&overrides), 72), "Tonberry (JP)");
+        assert_eq!(lookup(Some(&overrides), 9999), "Testworld");
+        assert_eq!(lookup(Some(&overrides), 91), "Balmung");
+    }
+
+    #[test]
+    fn worlds_know_their_data_center() {
+        assert_eq!(data_center(72), Some("Elemental"));
+        assert_eq!(data_center(91), Some("Crystal"));
+        assert_eq!(data_center(9999), None);
+    }
+
+    #[test]
+    fn world_ids_are_unique() {
+        let worlds = gamedata::worlds().rows();
+        let mut ids = worlds.iter().map(|world| world.id).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), worlds.len());
+    }
+}
@@ -0,0 +1,287 @@
+//! A fake home directory for running the tools end to end. The plugins'
+//! files are written where the launcher keeps them, and the tools are
+//! pointed at the home with [`HOME_ENV`], so they find everything the way
+//! they would on a real install. Each fixture method adds one plugin's data;
+//! anything else can be dropped in with [`FakeHome::file`]. The tools' clock
+//! is stopped at the home's [`FakeHome::now`], so fixtures timed from it
+//! give the same countdowns on every run.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use ffxiv_timers_core::{
+    clock::FAKE_NOW_ENV,
+    paths::{self, CONFIG_FILE_ENV, HOME_ENV, PLUGIN_CONFIGS_ENV},
+};
+
+/// A config.toml setting no tool knows, which every run warns about.
+const UNKNOWN_SETTING: &str = "not_a_setting";
+
+/// What the tools' log lines are tagged with, on stderr.
+const LOG_LEVELS: [&str; 5] = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+/// A temporary home directory, removed when dropped.
+pub struct FakeHome {
+    home: PathBuf,
+    now: DateTime<Utc>,
+}
+
+impl FakeHome {
+    /// An empty home named after `test`, which must be unique among the
+    /// tests running at once.
+    pub fn new(test: &str) -> FakeHome {
+        let name = format!("ffxiv-timers-{test}-{}", std::process::id());
+        let home = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        let now = Utc::now().duration_trunc(Duration::seconds(1)).unwrap();
+        FakeHome { home, now }
+    }
+
+    /// Like [`FakeHome::new`], with a config.toml that's always warned
+    /// about, to check where the warning is printed with
+    /// [`Run::warned_status`].
+    pub fn warned(test: &str) -> FakeHome {
+        let home = FakeHome::new(test);
+        home.config(&format!("{UNKNOWN_SETTING} = true\n"));
+        home
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.home
+    }
+
+    /// The time the tools run at, to the second.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    /// Where the launcher keeps the plugins' files in this home.
+    pub fn plugin_configs(&self) -> PathBuf {
+        paths::default_plugin_configs(&self.home)
+    }
+
+    /// Writes `config.toml` where the tools look for it.
+    pub fn config(&self, contents: &str) -> &FakeHome {
+        let dir = self.home.join(".config/ffxiv-timers");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.toml"), contents).unwrap();
+        self
+    }
+
+    /// Writes a file in the plugin config directory, replacing any there.
+    pub fn file(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> &FakeHome {
+        let path = self.plugin_configs().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        self
+    }
+
+    /// Marks the file at `path` in the plugin config directory as last
+    /// written `ago` before [`FakeHome::now`].
+    pub fn written(&self, path: impl AsRef<Path>, ago: Duration) -> &FakeHome {
+        let modified = (self.now - ago).into();
+        fs::File::options()
+            .write(true)
+            .open(self.plugin_configs().join(path))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        self
+    }
+
+    /// Accountant's record of an Almond in `plot` of ward 1 in Mist, on
+    /// Cactuar, planted and last tended the given time ago.
+    pub fn crop(&self, plot: u32, planted: Duration, tended: Duration) -> &FakeHome {
+        let ago = |duration: Duration| (self.now - duration).to_rfc3339();
+        let data = format!(
+            r#"{{"Item1": {{"Zone": 339, "ServerId": 79, "Ward": 1, "Plot": {plot}}},
+                "Item2": [{{"PlantTime": "{}", "LastTending": "{}", "PlantId": 4842,
+                            "AccuratePlantTime": true}}]}}"#,
+            ago(planted),
+            ago(tended)
+        );
+        self.file(format!("Accountant/crops_plot/{plot}.json"), data)
+    }
+
+    /// Accountant's entry for a crop on the Island Sanctuary's cropland,
+    /// planted and watered the given time ago, with a seed the crop table
+    /// doesn't know.
+    pub fn island_crop(&self, planted: Duration, tended: Duration) -> &FakeHome {
+        let ago = |duration: Duration| (self.now - duration).to_rfc3339();
+        let data = format!(
+            r#"{{"Item1": {{"Zone": 1055, "ServerId": 79, "Ward": 0, "Plot": 0}},
+                "Item2": [{{"PlantTime": "{}", "LastTending": "{}", "PlantId": 37000,
+                            "AccuratePlantTime": true}}]}}"#,
+            ago(planted),
+            ago(tended)
+        );
+        self.file("Accountant/crops_plot/island.json", data)
+    }
+
+    /// Accountant's tasks for the character with `id` on Cactuar, whose next
+    /// map can be gathered `next_map` from now, or ago when negative.
+    pub fn map_allowance(&self, id: u64, name: &str, next_map: Duration) -> &FakeHome {
+        let next_map = (self.now + next_map).to_rfc3339();
+        let data = format!(
+            r#"{{"Item1": {{"Name": "{name}", "ServerId": 79}},
+                "Item2": {{"Map": "{next_map}"}}}}"#
+        );
+        self.file(format!("Accountant/tasks/{id}.json"), data)
+    }
+
+    /// A submarine in SubmarineTracker's database, belonging to the free
+    /// company `tag` of `character` on Tonberry, back `returns` from now.
+    pub fn submarine(
+        &self,
+        character: &str,
+        tag: &str,
+        name: &str,
+        returns: Duration,
+    ) -> &FakeHome {
+        let path = self
+            .plugin_configs()
+            .join("SubmarineTracker/submarine-sqlite.db");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let db = rusqlite::Connection::open(&path).unwrap();
+        db.execute_batch(
+            "CREATE TABLE IF NOT EXISTS freecompany (FreeCompanyId BLOB, CharacterName TEXT,
+                 World TEXT, FreeCompanyTag TEXT);
+             CREATE TABLE IF NOT EXISTS submarine (SubmarineId INTEGER, FreeCompanyId BLOB,
+                 Name TEXT, Return INTEGER);",
+        )
+        .unwrap();
+        // The tag stands in for the id, so each one is a single company
+        let fc_id = tag.as_bytes();
+        db.execute(
+            "INSERT INTO freecompany SELECT ?1, ?2, 'Tonberry', ?3
+             WHERE NOT EXISTS (SELECT 1 FROM freecompany WHERE FreeCompanyId = ?1)",
+            rusqlite::params![fc_id, character, tag],
+        )
+        .unwrap();
+        db.execute(
+            "INSERT INTO submarine
+             SELECT COUNT(*) + 1, ?1, ?2, ?3 FROM submarine",
+            rusqlite::params![fc_id, name, (self.now + returns).timestamp()],
+        )
+        .unwrap();
+        self
+    }
+
+    /// A second row for the company `tag` in SubmarineTracker's database,
+    /// under `character`, as the plugin leaves after its character is
+    /// renamed.
+    pub fn renamed_fc(&self, tag: &str, character: &str) -> &FakeHome {
+        let path = self
+            .plugin_configs()
+            .join("SubmarineTracker/submarine-sqlite.db");
+        let db = rusqlite::Connection::open(path).unwrap();
+        db.execute(
+            "INSERT INTO freecompany VALUES (?1, ?2, 'Tonberry', ?3)",
+            rusqlite::params![tag.as_bytes(), character, tag],
+        )
+        .unwrap();
+        self
+    }
+
+    /// InventoryTools' files, with `characters` on Tonberry by id and name,
+    /// and the `items` they hold as character id, item id and quantity. Both
+    /// files are replaced.
+    pub fn inventory(&self, characters: &[(u64, &str)], items: &[(u64, u32, u32)]) -> &FakeHome {
+        let saved = characters
+            .iter()
+            .map(|(id, name)| format!(r#""{id}": {{"Name": "{name}", "WorldId": 72}}"#))
+            .collect::<Vec<_>>();
+        let meta = format!(r#"{{"SavedCharacters": {{{}}}}}"#, saved.join(", "));
+        let rows = items
+            .iter()
+            .enumerate()
+            .map(|(slot, &(character, item, quantity))| {
+                // Bag, slot, item and quantity lead; the owner is the 23rd
+                // of the gearsets layout's 26 columns, the one the plugin
+                // writes today
+                let mut row = [0; 26];
+                row[1] = slot as u64;
+                row[2] = item.into();
+                row[3] = quantity.into();
+                row[22] = character;
+                row.iter().map(u64::to_string).collect::<Vec<_>>().join(",")
+            })
+            .collect::<Vec<_>>();
+        self.file("InventoryTools.json", meta)
+            .file("InventoryTools/inventories.csv", rows.join("\n"))
+    }
+
+    /// `program` run in this home at [`FakeHome::now`], with colors off and
+    /// nothing from the real environment overriding where files are found
+    /// or what's logged.
+    pub fn command(&self, program: impl AsRef<std::ffi::OsStr>) -> Command {
+        let mut command = Command::new(program);
+        command
+            .env(HOME_ENV, &self.home)
+            .env(FAKE_NOW_ENV, self.now.to_rfc3339())
+            .env("NO_COLOR", "1")
+            .env_remove(PLUGIN_CONFIGS_ENV)
+            .env_remove(CONFIG_FILE_ENV)
+            .env_remove("CLICOLOR_FORCE")
+            .env_remove("RUST_LOG");
+        command
+    }
+
+    /// Runs `program` with `args`, as from [`FakeHome::command`].
+    pub fn run(&self, program: impl AsRef<std::ffi::OsStr>, args: &[&str]) -> Run {
+        let output = self.command(program).args(args).output().unwrap();
+        Run {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            status: output.status.code().unwrap(),
+        }
+    }
+}
+
+impl Drop for FakeHome {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+/// What a tool printed and exited with.
+#[derive(Debug)]
+pub struct Run {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+}
+
+impl Run {
+    /// Checks stdout holds nothing but the report: no log lines at any
+    /// level, and no mention of a [`FakeHome::warned`] config.
+    pub fn assert_report_only(&self) {
+        let logged = self
+            .stdout
+            .split_whitespace()
+            .find(|word| LOG_LEVELS.contains(word));
+        assert!(
+            logged.is_none() && !self.stdout.contains(UNKNOWN_SETTING),
+            "stdout: {}",
+            self.stdout
+        );
+    }
+
+    /// The exit status, having checked the warning about a
+    /// [`FakeHome::warned`] config went to stderr, leaving stdout to the
+    /// report.
+    pub fn warned_status(&self) -> i32 {
+        assert!(
+            self.stderr.contains(UNKNOWN_SETTING),
+            "stderr: {}",
+            self.stderr
+        );
+        self.assert_report_only();
+        self.status
+    }
+}
@@ -0,0 +1,18 @@
+//! Records the git commit being built, for `ffxiv-timers version`, when
+//! built from a checkout with git available.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=FFXIV_TIMERS_GIT_HASH={}", hash.trim());
+    }
+}
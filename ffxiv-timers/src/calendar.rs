@@ -0,0 +1,160 @@
+use clap::ValueEnum;
+use ffxiv_timers_core::{
+    clock,
+    ics::{self, Event},
+    json::CropStatus,
+    time::format_local,
+};
+
+use crate::dashboard::Section;
+
+/// The reports with timers to put on a calendar.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    Crops,
+    Maps,
+    Subs,
+}
+
+impl Source {
+    pub const ALL: [Source; 3] = [Source::Crops, Source::Maps, Source::Subs];
+
+    fn section(self) -> Section {
+        match self {
+            Source::Crops => Section::Crops,
+            Source::Maps => Section::Maps,
+            Source::Subs => Section::Subs,
+        }
+    }
+}
+
+/// A UID that stays the same for one crop, character or submarine.
+fn uid(parts: &[&str]) -> String {
+    let id = parts
+        .join("-")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_");
+    format!("{id}@ffxiv-timers")
+}
+
+/// Prints one calendar with every upcoming timer from `sources`, or from
+/// every source whose files exist when none are given. With `project`,
+/// each character's allowances after the next are added too, that many in
+/// all.
+pub fn run(sources: &[Source], project: Option<usize>) -> anyhow::Result<()> {
+    let sources = match sources {
+        [] => &Source::ALL,
+        _ => sources,
+    };
+    let mut events = vec![];
+    for &source in sources {
+        if !source.section().available() {
+            tracing::warn!(
+                "Skipping {}: its files don't exist",
+                source.section().name()
+            );
+            continue;
+        }
+        match source {
+            Source::Crops => {
+                for crop in crop_timers::reports()? {
+                    let Some(start) = crop.stage_time else {
+                        continue;
+                    };
+                    let stage = match crop.status {
+                        CropStatus::Good => "ready to harvest",
+                        CropStatus::Okay => "wilts",
+                        CropStatus::Wilt => "withers",
+                        CropStatus::Done | CropStatus::Dead => continue,
+                    };
+                    events.push(Event {
+                        uid: uid(&["crop", &crop.plant_id.to_string()]),
+                        start,
+                        category: "Crops",
+                        summary: format!("{} {stage}", crop.name),
+                        description: format!(
+                            "{} ({} plots) {stage} at {}",
+                            crop.name,
+                            crop.patch_count,
+                            format_local(start)
+                        ),
+                    });
+                }
+            }
+            Source::Maps => {
+                for map in map_allowances::reports()? {
+                    let coming = map_allowances::projection(
+                        map.next_map,
+                        clock::now(),
+                        project.unwrap_or(0),
+                    );
+                    for (index, &start) in coming.iter().enumerate().skip(1) {
+                        events.push(Event {
+                            uid: uid(&["map", &map.character, &map.world, &index.to_string()]),
+                            start,
+                            category: "Maps",
+                            summary: format!("{} can gather a map", map.character),
+                            description: format!(
+                                "{} ({}) can gather another treasure map from {}",
+                                map.character,
+                                map.world,
+                                format_local(start)
+                            ),
+                        });
+                    }
+                    events.push(Event {
+                        uid: uid(&["map", &map.character, &map.world]),
+                        start: map.next_map,
+                        category: "Maps",
+                        summary: format!("{} can gather a map", map.character),
+                        description: format!(
+                            "{} ({}) can gather a treasure map from {}",
+                            map.character,
+                            map.world,
+                            format_local(map.next_map)
+                        ),
+                    });
+                }
+            }
+            Source::Subs => {
+                for fc in sub_returns::reports()? {
+                    for sub in &fc.submarines {
+                        let Some(start) = sub.return_time else {
+                            continue;
+                        };
+                        events.push(Event {
+                            uid: uid(&["sub", &fc.tag, &sub.name]),
+                            start,
+                            category: "Submarines",
+                            summary: format!("{} returns", sub.name),
+                            description: format!(
+                                "{} of «{}» ({}, {}) returns at {}",
+                                sub.name,
+                                fc.tag,
+                                fc.character,
+                                fc.world,
+                                format_local(start)
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    print!("{}", ics::calendar(&events, clock::now()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uids_are_stable_and_spaceless() {
+        assert_eq!(
+            uid(&["map", "Alice Doe", "Tonberry"]),
+            "map-Alice_Doe-Tonberry@ffxiv-timers"
+        );
+    }
+}
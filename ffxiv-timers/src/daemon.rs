@@ -0,0 +1,382 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    process::Command,
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use ffxiv_timers_core::{
+    clock,
+    config::{self, NotifyConfig},
+    json::{CropReport, CropStatus, FreeCompanyReport, HeldItem, MapReport, Tool},
+    read_cache, shutdown, state,
+    time::{format_duration, format_local, Past, Style},
+    urgency::{self, Urgency},
+    webhook::{Event, Webhook},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::dashboard::Section;
+
+const STATE_FILE: &str = "notify-state.json";
+
+/// Bumped when [`State`] changes in a way older files can't be read as.
+const STATE_SCHEMA: u32 = 1;
+
+/// The events already sent for occurrences that are still going on.
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    fired: BTreeSet<String>,
+}
+
+/// What each source reported this round, or `None` when it couldn't be read.
+#[derive(Default)]
+struct Sources {
+    crops: Option<Vec<CropReport>>,
+    maps: Option<Vec<MapReport>>,
+    subs: Option<Vec<FreeCompanyReport>>,
+    /// Watchlist item ids and what's held of them
+    items: Option<(Vec<u32>, Vec<HeldItem>)>,
+}
+
+/// Checks every source each interval, or once, sending each event to the
+/// configured channels the first time its occurrence is seen. Stops between
+/// checks on SIGTERM or SIGINT.
+pub fn run(once: bool, interval: Option<u64>) -> anyhow::Result<()> {
+    read_cache::keep_in_memory();
+    let config = &config::get().notify;
+    if config.crop_wilting.threshold.is_some() {
+        tracing::warn!(
+            "Ignoring `threshold` in [notify.crop_wilting]; set `soon` in [urgency.crops] instead"
+        );
+    }
+    let webhook = webhook(config)?;
+    let interval = Duration::from_secs(interval.unwrap_or(config.interval).max(1));
+    let path = state::path(STATE_FILE)?;
+    let mut state: State = state::load(&path, STATE_SCHEMA)?;
+    shutdown::listen();
+
+    loop {
+        let sources = read_sources();
+        let events = events(config, &sources, clock::now());
+        let mut fired = BTreeSet::new();
+        for event in &events {
+            if state.fired.contains(&event.key) || dispatch(config, webhook.as_ref(), event) {
+                fired.insert(event.key.clone());
+            }
+        }
+        // Sources that couldn't be read keep what was sent for them, so they
+        // don't repeat it when they come back
+        let checked = checked_kinds(&sources);
+        fired.extend(
+            state
+                .fired
+                .iter()
+                .filter(|key| !checked.iter().any(|kind| key.starts_with(kind)))
+                .cloned(),
+        );
+        state.fired = fired;
+        state::save(&path, STATE_SCHEMA, &state)?;
+
+        if once || shutdown::wait(interval) {
+            return Ok(());
+        }
+    }
+}
+
+/// `--test-webhook`: sends a sample event to the configured webhook, so
+/// it can be checked without waiting for a timer.
+pub fn test_webhook() -> anyhow::Result<()> {
+    let Some(webhook) = webhook(&config::get().notify)? else {
+        bail!("No webhook is set; add `webhook` to [notify] in config.toml");
+    };
+    webhook.send(&Event::sample())?;
+    println!("Sent a test event to {}", webhook.url);
+    Ok(())
+}
+
+/// The configured webhook, checked.
+fn webhook(config: &NotifyConfig) -> anyhow::Result<Option<Webhook>> {
+    if config.webhook.is_some() && !cfg!(feature = "webhook") {
+        bail!("`webhook` in [notify] needs ffxiv-timers built with the webhook feature");
+    }
+    config.webhook.as_ref().map(Webhook::new).transpose()
+}
+
+fn read_sources() -> Sources {
+    Sources {
+        crops: Section::Crops.read_or_skip(crop_timers::reports),
+        maps: Section::Maps.read_or_skip(map_allowances::reports),
+        subs: Section::Subs.read_or_skip(sub_returns::reports),
+        items: Section::Inventory.read_or_skip(|| {
+            let ids = inventory_tracker::watchlist_ids(config::get());
+            let held = inventory_tracker::holdings(&ids)?;
+            Ok((ids, held))
+        }),
+    }
+}
+
+/// The event kinds whose sources were read this round.
+fn checked_kinds(sources: &Sources) -> Vec<&'static str> {
+    [
+        ("crop_wilting", sources.crops.is_some()),
+        ("map_ready", sources.maps.is_some()),
+        ("sub_returned", sources.subs.is_some()),
+        ("item_low", sources.items.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(kind, checked)| checked.then_some(kind))
+    .collect()
+}
+
+/// Every enabled event going on at `now`.
+fn events(config: &NotifyConfig, sources: &Sources, now: DateTime<Utc>) -> Vec<Event> {
+    let mut events = vec![];
+    let when = |time: DateTime<Utc>| {
+        format!(
+            "{} ({})",
+            format_duration(time - now, Style::Humanized, Past::Ago),
+            format_local(time)
+        )
+    };
+
+    if let (true, Some(crops)) = (config.crop_wilting.enabled, &sources.crops) {
+        let thresholds = urgency::thresholds(Tool::Crops);
+        for crop in crops {
+            let Some(next_stage) = crop.stage_time else {
+                continue;
+            };
+            let urgency = crop_timers::urgency(crop.status, crop.stage_time, &thresholds, now);
+            let body = match crop.status {
+                CropStatus::Okay if urgency >= Urgency::Soon => {
+                    format!("{} wilts {}", crop.name, when(next_stage))
+                }
+                CropStatus::Wilt => {
+                    format!("{} is wilting and dies {}", crop.name, when(next_stage))
+                }
+                _ => continue,
+            };
+            events.push(Event {
+                kind: "crop_wilting",
+                key: format!("crop_wilting/{}/{}", crop.plant_id, next_stage.timestamp()),
+                title: "Crops need tending".to_string(),
+                body,
+            });
+        }
+    }
+
+    if let (true, Some(maps)) = (config.map_ready.enabled, &sources.maps) {
+        let thresholds = urgency::thresholds(Tool::Maps);
+        let ready = |map: &&MapReport| thresholds.level(map.next_map, now) >= Urgency::Due;
+        for map in maps.iter().filter(ready) {
+            events.push(Event {
+                kind: "map_ready",
+                key: format!(
+                    "map_ready/{}/{}/{}",
+                    map.character,
+                    map.world,
+                    map.next_map.timestamp()
+                ),
+                title: "Treasure map ready".to_string(),
+                body: format!("{} ({}) can gather a map", map.character, map.world),
+            });
+        }
+    }
+
+    if let (true, Some(fcs)) = (config.sub_returned.enabled, &sources.subs) {
+        let thresholds = urgency::thresholds(Tool::Subs);
+        let back = |&time: &DateTime<Utc>| thresholds.level(time, now) >= Urgency::Due;
+        for fc in fcs {
+            for sub in &fc.submarines {
+                let Some(time) = sub.return_time.filter(back) else {
+                    continue;
+                };
+                events.push(Event {
+                    kind: "sub_returned",
+                    key: format!("sub_returned/{}/{}/{}", fc.tag, sub.name, time.timestamp()),
+                    title: "Submarine returned".to_string(),
+                    body: format!("{} of «{}» is back", sub.name, fc.tag),
+                });
+            }
+        }
+    }
+
+    let item_low = (config.item_low.enabled, config.item_low.threshold);
+    if let (true, Some(threshold), Some((ids, held))) = (item_low.0, item_low.1, &sources.items) {
+        let mut totals = ids
+            .iter()
+            .map(|&id| (id, (format!("item {id}"), 0)))
+            .collect::<BTreeMap<_, _>>();
+        for item in held {
+            if let Some((name, total)) = totals.get_mut(&item.item_id) {
+                name.clone_from(&item.item_name);
+                *total += item.quantity;
+            }
+        }
+        for (id, (name, total)) in totals {
+            if total < threshold {
+                events.push(Event {
+                    kind: "item_low",
+                    key: format!("item_low/{id}"),
+                    title: "Running low".to_string(),
+                    body: format!("Only {total} {name} left across every character"),
+                });
+            }
+        }
+    }
+
+    events
+}
+
+/// Sends the event through every configured channel, reporting whether any
+/// of them took it.
+fn dispatch(config: &NotifyConfig, webhook: Option<&Webhook>, event: &Event) -> bool {
+    let desktop = config
+        .desktop
+        .unwrap_or(config.webhook.is_none() && config.command.is_empty());
+    let mut results = vec![];
+    if desktop {
+        results.push(("desktop", desktop_notification(event)));
+    }
+    if let Some(webhook) = webhook {
+        results.push(("webhook", webhook.send(event)));
+    }
+    if !config.command.is_empty() {
+        results.push(("command", command(&config.command, event)));
+    }
+    let mut sent = false;
+    for (channel, result) in results {
+        match result {
+            Ok(()) => sent = true,
+            Err(err) => tracing::warn!("Failed to send `{}` by {channel}: {err:#}", event.key),
+        }
+    }
+    sent
+}
+
+fn desktop_notification(event: &Event) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            quote(&event.body),
+            quote(&event.title)
+        ));
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "ffxiv-timers", &event.title, &event.body]);
+        command
+    } else {
+        bail!("Desktop notifications aren't supported here; set `command` in [notify] instead");
+    };
+    let status = command.status().context("Failed to run the notifier")?;
+    if !status.success() {
+        bail!("The notifier exited with {status}");
+    }
+    Ok(())
+}
+
+fn command(program: &[String], event: &Event) -> anyhow::Result<()> {
+    let status = Command::new(&program[0])
+        .args(&program[1..])
+        .env("FFXIV_TIMERS_EVENT", event.kind)
+        .env("FFXIV_TIMERS_EVENT_KEY", &event.key)
+        .env("FFXIV_TIMERS_EVENT_TITLE", &event.title)
+        .env("FFXIV_TIMERS_EVENT_BODY", &event.body)
+        .status()
+        .with_context(|| format!("Failed to run {:?}", program[0]))?;
+    if !status.success() {
+        bail!("{:?} exited with {status}", program[0]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use ffxiv_timers_core::json::SubmarineReport;
+
+    use super::*;
+
+    #[test]
+    fn finds_each_kind_of_event() {
+        let now = Utc::now();
+        let config = NotifyConfig {
+            item_low: config::EventConfig {
+                enabled: true,
+                threshold: Some(10),
+            },
+            ..NotifyConfig::default()
+        };
+        let crop = |plant_id, status, minutes| CropReport {
+            plant_id,
+            name: "Almond".to_string(),
+            patch_count: 1,
+            status,
+            stage_time: Some(now + Duration::minutes(minutes)),
+            estimated: false,
+            patches: vec![],
+        };
+        let sources = Sources {
+            crops: Some(vec![
+                crop(1, CropStatus::Okay, 30),
+                crop(2, CropStatus::Okay, 300),
+                crop(3, CropStatus::Good, 5),
+            ]),
+            maps: Some(vec![MapReport {
+                character: "Alice Doe".to_string(),
+                world: "Tonberry".to_string(),
+                next_map: now - Duration::hours(1),
+                tier: None,
+                updated: None,
+                profile: None,
+            }]),
+            subs: Some(vec![FreeCompanyReport {
+                character: "Alice Doe".to_string(),
+                tag: "MOGS".to_string(),
+                world: "Tonberry".to_string(),
+                submarines: vec![
+                    SubmarineReport {
+                        name: "Nautilus".to_string(),
+                        return_time: Some(now - Duration::minutes(1)),
+                        label: None,
+                    },
+                    SubmarineReport {
+                        name: "Argo".to_string(),
+                        return_time: Some(now + Duration::minutes(1)),
+                        label: None,
+                    },
+                ],
+                profile: None,
+            }]),
+            items: Some((vec![21072, 5111], vec![])),
+        };
+        let kinds = events(&config, &sources, now)
+            .into_iter()
+            .map(|event| event.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                "crop_wilting",
+                "map_ready",
+                "sub_returned",
+                "item_low",
+                "item_low"
+            ]
+        );
+    }
+
+    #[test]
+    fn unread_sources_keep_their_sent_events() {
+        let sources = Sources {
+            maps: Some(vec![]),
+            ..Sources::default()
+        };
+        assert_eq!(checked_kinds(&sources), ["map_ready"]);
+        assert!(checked_kinds(&Sources::default()).is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+use std::{fs::read_to_string, io::Write, path::PathBuf};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use ffxiv_timers_core::{
+    check::Severity,
+    json,
+    options::Options,
+    paths, profiles,
+    source::{self, Failure, SourceError, SourceResult},
+    theme,
+};
+use serde::Deserialize;
+use termcolor::WriteColor;
+
+const CONFIG_FILE: &str = "ffxiv-timers.json";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    Crops,
+    Maps,
+    Subs,
+    Inventory,
+}
+
+impl Section {
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Crops => "crops",
+            Section::Maps => "maps",
+            Section::Subs => "subs",
+            Section::Inventory => "inventory",
+        }
+    }
+
+    /// The files the section reads, in every profile.
+    pub fn data_paths(self) -> anyhow::Result<Vec<PathBuf>> {
+        profiles::each(|_| self.profile_paths())
+    }
+
+    /// The files the section reads in one profile, which all have to exist
+    /// for it to run.
+    fn profile_paths(self) -> anyhow::Result<Vec<PathBuf>> {
+        Ok(match self {
+            Section::Crops => vec![crop_timers::data_path()?],
+            Section::Maps => vec![map_allowances::data_path()?],
+            Section::Subs => vec![sub_returns::data_path()?],
+            Section::Inventory => inventory_tracker::data_paths()?.to_vec(),
+        })
+    }
+
+    /// Whether every file the section reads exists, in at least one
+    /// profile.
+    pub fn available(self) -> bool {
+        let found = profiles::each(|_| {
+            let paths = self.profile_paths()?;
+            Ok(vec![paths.iter().all(|path| path.exists())])
+        });
+        found.is_ok_and(|found| found.contains(&true))
+    }
+
+    /// The section's report from `report`, or why it couldn't be read,
+    /// without reading anything when its files are missing.
+    pub fn read<T>(self, report: impl FnOnce() -> anyhow::Result<T>) -> SourceResult<T> {
+        if !self.available() {
+            return Err(self.missing());
+        }
+        Ok(report()?)
+    }
+
+    /// Like [`Section::read`], but `None` when it can't be read, logging why
+    /// unless its files are only missing.
+    pub fn read_or_skip<T>(self, report: impl FnOnce() -> anyhow::Result<T>) -> Option<T> {
+        self.read(report)
+            .inspect_err(|err| {
+                if err.failure != Failure::Missing {
+                    tracing::warn!("Skipping {}: {err}", self.name());
+                }
+            })
+            .ok()
+    }
+
+    /// The section's files aren't there.
+    fn missing(self) -> SourceError {
+        match self.data_paths() {
+            Ok(paths) => SourceError::missing(&paths),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+/// The `dashboard` settings in `ffxiv-timers.json`, in the config directory.
+#[derive(Deserialize)]
+#[serde(default)]
+struct DashboardConfig {
+    /// Reports to print, in order [default: crops, maps, subs, inventory]
+    sections: Vec<Section>,
+    /// Arguments for the inventory report [default: --ventures]
+    inventory_args: Vec<String>,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> DashboardConfig {
+        DashboardConfig {
+            sections: vec![
+                Section::Crops,
+                Section::Maps,
+                Section::Subs,
+                Section::Inventory,
+            ],
+            inventory_args: vec!["--ventures".to_string()],
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    dashboard: DashboardConfig,
+}
+
+impl Config {
+    fn load() -> anyhow::Result<Config> {
+        let Some(path) = paths::config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = read_to_string(&path).with_context(|| format!("Failed to open {path:?}"))?;
+        serde_json::from_str(&data).with_context(|| format!("Failed to parse {path:?}"))
+    }
+}
+
+/// Prints every section in turn, or only `sections` when any are given. A
+/// section that can't be read, as when its plugin hasn't written its files
+/// yet, is one dim line saying why, and doesn't stop the rest. With
+/// `--json`, the sections' envelopes are printed together as one array, and
+/// those that can't be read are only logged. Returns the most urgent
+/// section's severity, counting each failure by its kind, or an error when
+/// every section failed.
+pub fn run(options: &Options, sections: &[Section]) -> anyhow::Result<Severity> {
+    if options.format.is_some() {
+        anyhow::bail!("--format fields differ between reports, so it can't be used with `all`");
+    }
+    let config = Config::load()?.dashboard;
+    let sections = match sections {
+        [] => &config.sections,
+        _ => sections,
+    };
+
+    let mut results = vec![];
+    let mut envelopes = vec![];
+    for (index, &section) in sections.iter().enumerate() {
+        if index > 0 && !options.json {
+            writeln!(options.stdout())?;
+        }
+        let run = || match section {
+            Section::Crops => crop_timers::run(options),
+            Section::Maps => map_allowances::run(options),
+            Section::Subs => sub_returns::run(options),
+            Section::Inventory => inventory_tracker::run(config.inventory_args.clone(), options),
+        };
+        let report = || -> SourceResult<Severity> {
+            if section.available() {
+                return Ok(run()?);
+            }
+            // Reports can still be shown from the last ones kept
+            if options.fallback_cache {
+                if let Ok(severity) = run() {
+                    return Ok(severity);
+                }
+            }
+            Err(section.missing())
+        };
+        let result = match options.json {
+            true => {
+                let (collected, result) = json::collect(report);
+                envelopes.extend(collected);
+                result
+            }
+            false => report(),
+        };
+        if let Err(err) = &result {
+            match options.json {
+                true => tracing::warn!("Skipping {}: {err}", section.name()),
+                false => {
+                    let mut stdout = options.stdout();
+                    stdout.set_color(&theme::dim())?;
+                    write!(stdout, "{}: {err}", section.name())?;
+                    stdout.reset()?;
+                    writeln!(stdout)?;
+                }
+            }
+        }
+        results.push(result);
+    }
+
+    if options.json {
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &envelopes)?;
+        writeln!(stdout)?;
+    }
+    let failed = results
+        .iter()
+        .filter(|result| {
+            result
+                .as_ref()
+                .is_err_and(|err| err.failure != Failure::Missing)
+        })
+        .count();
+    if failed > 0 && failed == sections.len() {
+        anyhow::bail!("Every section failed");
+    }
+    Ok(source::severity(&results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_settings_keep_their_defaults() {
+        let config: Config =
+            serde_json::from_str(r#"{"dashboard": {"sections": ["subs", "crops"]}}"#).unwrap();
+        assert_eq!(config.dashboard.sections, [Section::Subs, Section::Crops]);
+        assert_eq!(config.dashboard.inventory_args, ["--ventures"]);
+    }
+}
@@ -0,0 +1,317 @@
+//! `doctor`: a line for each place the reports read from, saying whether
+//! it's found and usable, and what to do about it when it isn't. The plugins'
+//! files are read with the same loaders the reports use, so a source that
+//! passes here reads the same way there.
+
+use anyhow::bail;
+use chrono::{DateTime, Duration, Utc};
+use ffxiv_timers_core::{
+    check::Severity,
+    clock, config,
+    gamedata::{self, Origin},
+    options::Options,
+    paths, profiles,
+    scan::Scan,
+    theme,
+    time::{format_duration, Past, Style},
+};
+use termcolor::WriteColor;
+
+use crate::dashboard::Section;
+
+/// Files last written longer ago than this are probably from a character
+/// or plugin that's no longer played.
+const STALE_DAYS: i64 = 7;
+
+/// The result of one check.
+#[derive(Debug)]
+struct Finding {
+    severity: Severity,
+    subject: String,
+    message: String,
+    /// What to do about it, when it isn't fine
+    hint: Option<&'static str>,
+}
+
+impl Finding {
+    fn new(severity: Severity, subject: impl Into<String>, message: impl Into<String>) -> Finding {
+        Finding {
+            severity,
+            subject: subject.into(),
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn hint(mut self, hint: &'static str) -> Finding {
+        if self.severity != Severity::Ok {
+            self.hint = Some(hint);
+        }
+        self
+    }
+}
+
+impl Section {
+    /// What the section's loader makes of one profile's files.
+    fn scan(self) -> anyhow::Result<Scan> {
+        match self {
+            Section::Crops => crop_timers::scan(),
+            Section::Maps => map_allowances::scan(),
+            Section::Subs => sub_returns::scan(),
+            Section::Inventory => inventory_tracker::scan(),
+        }
+    }
+
+    /// What the section's rows are, in `doctor`'s counts.
+    fn rows(self) -> &'static str {
+        match self {
+            Section::Crops => "plots",
+            Section::Maps => "characters",
+            Section::Subs => "submarines",
+            Section::Inventory => "items",
+        }
+    }
+
+    /// How to get the plugin to write the section's files.
+    fn remedy(self) -> &'static str {
+        match self {
+            Section::Crops => "Accountant writes a plot's file when you visit it in game",
+            Section::Maps => "Accountant writes a character's file once you log in with them",
+            Section::Subs => "SubmarineTracker writes its database once you visit your workshop",
+            Section::Inventory => {
+                "InventoryTools writes these with its CSV export on, once you log in"
+            }
+        }
+    }
+}
+
+/// Prints a line per check, returning the worst found. Unless `--check` is
+/// given, any failed check is an error, so the exit status says whether
+/// everything can be read. `config_error` is why config.toml couldn't be
+/// loaded, if it couldn't, which is itself a check.
+pub fn run(options: &Options, config_error: Option<anyhow::Error>) -> anyhow::Result<Severity> {
+    let mut findings = vec![config_finding(config_error)];
+    if let Some(home) = paths::home_env() {
+        let severity = match home.is_dir() {
+            true => Severity::Ok,
+            false => Severity::Critical,
+        };
+        findings.push(
+            Finding::new(
+                severity,
+                "Home",
+                format!("{}, from {}", home.display(), paths::HOME_ENV),
+            )
+            .hint("Create the directory, or unset the variable to use your own home"),
+        );
+    }
+    findings.extend(gamedata_findings());
+    let profiles = profiles::each(|profile| Ok(vec![check_profile(profile)]));
+    match profiles {
+        Ok(profiles) => findings.extend(profiles.into_iter().flatten()),
+        Err(err) => findings.push(
+            Finding::new(Severity::Critical, "Profiles", format!("{err:#}"))
+                .hint("Pass a --profile labelled in config.toml's [[profiles]]"),
+        ),
+    }
+
+    let mut stdout = options.stdout();
+    for finding in &findings {
+        print(&mut stdout, finding)?;
+    }
+    let severity = findings
+        .iter()
+        .map(|finding| finding.severity)
+        .max()
+        .unwrap_or_default();
+    let failed = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Critical)
+        .count();
+    if failed > 0 && !options.check {
+        bail!("{failed} of {} checks failed", findings.len());
+    }
+    Ok(severity)
+}
+
+fn config_finding(error: Option<anyhow::Error>) -> Finding {
+    let path = config::path();
+    let shown = path
+        .as_ref()
+        .map_or("none".to_string(), |path| path.display().to_string());
+    match (error, path) {
+        (Some(err), _) => Finding::new(Severity::Critical, "config.toml", format!("{err:#}"))
+            .hint("Fix the setting named, or move the file aside to use the defaults"),
+        (None, Some(path)) if path.exists() => {
+            Finding::new(Severity::Ok, "config.toml", format!("{shown}, read"))
+        }
+        (None, _) => Finding::new(
+            Severity::Ok,
+            "config.toml",
+            format!("{shown}, not found, so using the defaults"),
+        ),
+    }
+}
+
+/// The game data's patch, and each table read from the override directory
+/// instead.
+fn gamedata_findings() -> Vec<Finding> {
+    let mut findings = vec![Finding::new(
+        Severity::Ok,
+        "Game data",
+        format!("snapshot from {}", gamedata::SNAPSHOT_PATCH),
+    )];
+    for table in gamedata::summary() {
+        let subject = format!("Game data {}", table.name);
+        if let Some(ignored) = table.ignored {
+            findings.push(
+                Finding::new(
+                    Severity::Warning,
+                    subject,
+                    format!("{ignored}, so using the snapshot"),
+                )
+                .hint("Fix the file, or remove it to use the built-in table"),
+            );
+        } else if let Origin::Override(path) = table.origin {
+            let message = format!("{} from {}", table.patch, path.display());
+            findings.push(Finding::new(Severity::Ok, subject, message));
+        }
+    }
+    findings
+}
+
+/// The checks for the profile being read: where its plugin configs are,
+/// then each report's files.
+fn check_profile(profile: Option<&str>) -> Vec<Finding> {
+    let profile = profile
+        .map(str::to_string)
+        .or_else(|| profiles::current().and_then(|profile| profile.label.clone()));
+    let subject = |what: &str| match &profile {
+        Some(profile) => format!("{what} [{profile}]"),
+        None => what.to_string(),
+    };
+
+    let mut findings = vec![match paths::plugin_configs_origin() {
+        Ok((dir, origin)) => {
+            let severity = match dir.is_dir() {
+                true => Severity::Ok,
+                false => Severity::Critical,
+            };
+            Finding::new(
+                severity,
+                subject("Plugin configs"),
+                format!("{}, from {origin}", dir.display()),
+            )
+        }
+        Err(err) => Finding::new(
+            Severity::Critical,
+            subject("Plugin configs"),
+            format!("{err:#}"),
+        ),
+    }
+    .hint("Pass --data-root or set FFXIV_TIMERS_PLUGIN_CONFIGS to the launcher's pluginConfigs")];
+
+    let now = clock::now();
+    for section in [
+        Section::Crops,
+        Section::Maps,
+        Section::Subs,
+        Section::Inventory,
+    ] {
+        let (severity, message) = judge(section, section.scan(), now);
+        findings
+            .push(Finding::new(severity, subject(section.name()), message).hint(section.remedy()));
+    }
+    findings
+}
+
+/// How a section's files fared, and what to say about them.
+fn judge(section: Section, scan: anyhow::Result<Scan>, now: DateTime<Utc>) -> (Severity, String) {
+    let scan = match scan {
+        Ok(scan) => scan,
+        Err(err) => return (Severity::Critical, format!("{err:#}")),
+    };
+    let found = format!("{} {} in {} files", scan.rows, section.rows(), scan.files);
+    let age = scan
+        .modified
+        .map(|modified| now - DateTime::<Utc>::from(modified));
+    let written = age.map_or(String::new(), |age| {
+        format!(
+            ", last written {}",
+            format_duration(-age, Style::Humanized, Past::Ago)
+        )
+    });
+    if let Some(first) = scan.failed.first() {
+        let severity = match scan.files {
+            0 => Severity::Critical,
+            _ => Severity::Warning,
+        };
+        let skipped = scan.failed.len();
+        return (
+            severity,
+            format!("{found}, skipping {skipped}, e.g. {first}"),
+        );
+    }
+    if scan.rows == 0 {
+        return (Severity::Warning, format!("no {} found", section.rows()));
+    }
+    if age.is_some_and(|age| age > Duration::days(STALE_DAYS)) {
+        return (Severity::Warning, format!("{found}{written}"));
+    }
+    (Severity::Ok, format!("{found}{written}"))
+}
+
+fn print(stdout: &mut dyn WriteColor, finding: &Finding) -> anyhow::Result<()> {
+    let (label, color) = match finding.severity {
+        Severity::Ok => ("[ ok ]", theme::good()),
+        Severity::Warning => ("[warn]", theme::warn()),
+        Severity::Critical => ("[FAIL]", theme::error()),
+    };
+    stdout.set_color(&color)?;
+    write!(stdout, "{label}")?;
+    stdout.reset()?;
+    writeln!(stdout, " {}: {}", finding.subject, finding.message)?;
+    if let Some(hint) = finding.hint {
+        stdout.set_color(&theme::dim())?;
+        writeln!(stdout, "       {hint}")?;
+        stdout.reset()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn sources_are_judged_by_what_was_read() {
+        let now = SystemTime::now();
+        let judged_at = DateTime::<Utc>::from(now);
+        let scan = |rows, files, failed: &[&str], age: u64| Scan {
+            files,
+            rows,
+            failed: failed.iter().map(|reason| reason.to_string()).collect(),
+            modified: Some(now - Duration::from_secs(age)),
+            ..Scan::default()
+        };
+        let judged = |scan| judge(Section::Crops, Ok(scan), judged_at);
+
+        let (severity, message) = judged(scan(3, 3, &[], 60 * 60));
+        assert_eq!(severity, Severity::Ok);
+        assert!(message.starts_with("3 plots in 3 files, last written"));
+        assert_eq!(judged(scan(3, 3, &[], 8 * 86400)).0, Severity::Warning);
+        assert_eq!(judged(scan(0, 1, &[], 60)).0, Severity::Warning);
+        assert_eq!(
+            judged(scan(2, 2, &["3.json: EOF"], 60)).0,
+            Severity::Warning
+        );
+        assert_eq!(
+            judged(scan(0, 0, &["3.json: EOF"], 60)).0,
+            Severity::Critical
+        );
+        let missing = judge(Section::Crops, Err(anyhow::anyhow!("missing")), judged_at);
+        assert_eq!(missing, (Severity::Critical, "missing".to_string()));
+    }
+}
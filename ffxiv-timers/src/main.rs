@@ -0,0 +1,517 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use ffxiv_timers_core::{
+    check::{self, Severity},
+    clock, config,
+    i18n::{self, Lang},
+    logging,
+    options::{ColorWhen, Options},
+    pager, paths, profiles, read_cache,
+    theme::{self, Preset},
+    urgency,
+};
+
+mod calendar;
+mod daemon;
+mod dashboard;
+mod doctor;
+mod metrics;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "serve")]
+mod serve;
+mod status;
+#[cfg(feature = "tui")]
+mod tui;
+mod version;
+
+/// The end of `--help`, for what the flags don't cover.
+const AFTER_HELP: &str = "\
+Environment:
+    FFXIV_TIMERS_PLUGIN_CONFIGS
+            The plugins' config directory, used when --data-root isn't given
+    FFXIV_TIMERS_CONFIG
+            The settings file, used when --config isn't given
+    FFXIV_TIMERS_HOME
+            A home directory to find everything in instead of the user's: the
+            launcher's plugin configs, and the config, cache and state kept
+            as on Linux, e.g. `.config/ffxiv-timers`
+    NO_COLOR
+            Never color reports when set to anything, unless --color says to
+    CLICOLOR_FORCE
+            Color reports even when not printing to a terminal when set to
+            anything but 0, unless --color says otherwise
+    RUST_LOG
+            Which events to log, e.g. `debug`, in place of --verbose and --quiet
+    PAGER
+            Shows reports too long for the terminal, unless --no-pager is given
+            [default: less -RFX]
+    FFXIV_FAKE_NOW
+            An RFC 3339 time to run at instead of now, stopping every
+            countdown there, e.g. to reproduce a timer shown at that moment
+
+Exit status:
+    0   Success
+    1   Any error, including a plugin file that couldn't be read
+    2   The command line couldn't be parsed; `inventory` also exits with 2
+        when the InventoryTools files are missing
+
+With --check, the report decides instead:
+    0   Nothing needs attention
+    1   A crop is ready, a map can be gathered, a submarine is back, or
+        `inventory --needs` or `--slots` finds something short or too full;
+        or, for `all`, a report's files are damaged
+    2   A crop is wilting or has withered, or a map or submarine has waited
+        past config.toml's [urgency] `overdue`; or, for `all`, a report's
+        files are laid out in a way this version can't read
+    3   Any error
+
+Only the report is printed to stdout; warnings and errors go to stderr.";
+
+/// Timers and inventory reports from FFXIV's Dalamud plugins.
+#[derive(Parser)]
+#[command(name = "ffxiv-timers", version, after_long_help = AFTER_HELP)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// When to color the report [default: auto, or config.toml's `color`]
+    #[arg(long, global = true, value_enum)]
+    color: Option<Color>,
+    /// Colors for a dark or light terminal background, with any set in
+    /// config.toml's [theme] table on top [default: dark, or the preset set
+    /// there]
+    #[arg(long, global = true, value_enum)]
+    theme: Option<Theme>,
+    /// The language for report labels and times [default: en, or
+    /// config.toml's `lang`]
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Language>,
+    /// Print JSON for other programs instead of the colored report
+    #[arg(long, global = true)]
+    json: bool,
+    /// Log debug events, like each file looked at; RUST_LOG overrides this
+    #[arg(long, short, global = true)]
+    verbose: bool,
+    /// Log only errors, not warnings
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Keep the crop, map and submarine reports and inventory queries read,
+    /// and show the last ones kept, marked as stale, when the plugins' files
+    /// can't be read; they're too old after config.toml's `fallback_max_age`
+    /// seconds [default: 3600]
+    #[arg(long, global = true)]
+    fallback_cache: bool,
+    /// Exit with how urgent the report is: 1 when something is ready, 2
+    /// when a crop is wilting or withered, and 3 on errors
+    #[arg(long, global = true)]
+    check: bool,
+    /// Never show a report longer than the terminal through $PAGER, as
+    /// config.toml's `pager = false` also turns off
+    #[arg(long, global = true)]
+    no_pager: bool,
+    /// Read every plugin file again, even those unchanged since the last run
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// Print one line per row, filling in `{field}` placeholders; each
+    /// report's fields are listed when an unknown one is used
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    format: Option<String>,
+    /// Printed before the --format lines, which may use `{count}`
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    format_header: Option<String>,
+    /// Printed after the --format lines, which may use `{count}`
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    format_footer: Option<String>,
+    /// Read settings from PATH instead of config.toml in the config
+    /// directory, and tables like items.csv from beside it
+    #[arg(long, global = true, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    config: Option<PathBuf>,
+    /// Read the plugins' files from DIR instead of looking for the
+    /// launcher's pluginConfigs directory
+    #[arg(long, global = true, value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    data_root: Option<PathBuf>,
+    /// Read only the config.toml profile with this label, instead of
+    /// merging every profile's reports
+    #[arg(long, global = true, value_name = "LABEL")]
+    profile: Option<String>,
+    /// Minutes before a timer is due to show it as soon, for every report
+    /// [default: config.toml's [urgency], or 60 for crops and 30 otherwise]
+    #[arg(long, global = true, value_name = "MINUTES")]
+    soon: Option<u32>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Garden crop timers, from Accountant
+    Crops {
+        /// Keep the timers counting down full screen, reloading them as
+        /// the plugin rewrites its files and every SECONDS [default: 30],
+        /// until Ctrl-C
+        #[arg(long, value_name = "SECONDS", num_args = 0..=1)]
+        watch: Option<Option<u64>>,
+        /// Show the timers as Accountant has them even when its timestamps
+        /// are ahead of the system clock
+        #[arg(long)]
+        trust_data: bool,
+        /// Show when the next visit would be needed if every crop were
+        /// tended now, and which would be ready to harvest before then
+        #[arg(long)]
+        if_tended: bool,
+        /// Also list crops on the Island Sanctuary, under their own header
+        #[arg(long)]
+        include_island: bool,
+        /// List each crop's plots under it, grouped by house, with each
+        /// bed's own status and time left
+        #[arg(long)]
+        detail: bool,
+    },
+    /// Treasure map allowances, from Accountant
+    Maps {
+        /// List each character's next N allowances, 18 hours apart
+        #[arg(long, value_name = "N")]
+        project: Option<usize>,
+        /// Show a character twice after a world transfer, from both worlds'
+        /// files, instead of dropping the old world's
+        #[arg(long)]
+        no_merge: bool,
+    },
+    /// Submarine voyages, from SubmarineTracker
+    Subs {
+        /// Keep the return times counting down full screen, reloading them
+        /// as the plugin rewrites its database, until Ctrl-C
+        #[arg(long)]
+        watch: bool,
+        /// Have --check warn about free companies with submarine slots
+        /// left unregistered
+        #[arg(long)]
+        strict_slots: bool,
+    },
+    /// Inventory reports, from InventoryTools; see `inventory --help`
+    #[command(disable_help_flag = true)]
+    Inventory {
+        /// Passed on as-is, taking the same flags as inventory-tracker
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Every report in one go, skipping those whose plugin files are missing
+    All {
+        /// Reports to print, in order, instead of the configured ones
+        #[arg(value_enum)]
+        sections: Vec<dashboard::Section>,
+    },
+    /// One line with the most urgent timers, for a waybar or i3blocks module
+    Status {
+        /// What the bar expects
+        #[arg(long, value_enum, default_value_t)]
+        format: status::Format,
+    },
+    /// Write Prometheus metrics from every report to PATH, or stdout
+    ExportMetrics {
+        /// A `.prom` file in node_exporter's textfile directory
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        path: Option<PathBuf>,
+    },
+    /// Serve each report's JSON over HTTP, re-reading files as they change
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8399")]
+        listen: String,
+    },
+    /// Publish each timer as a Home Assistant sensor to the broker set in
+    /// config.toml
+    #[cfg(feature = "mqtt")]
+    PublishMqtt {
+        /// Keep publishing every SECS seconds instead of once
+        #[arg(long = "loop", value_name = "SECS")]
+        every: Option<u64>,
+    },
+    /// Send notifications as timers come due, using the [notify] settings
+    /// in config.toml
+    NotifyDaemon {
+        /// Check once and exit, e.g. from cron
+        #[arg(long)]
+        once: bool,
+        /// Seconds between checks, instead of the configured interval
+        #[arg(long, value_name = "SECS")]
+        interval: Option<u64>,
+        /// Send a sample event to the configured webhook and exit
+        #[arg(long, conflicts_with_all = ["once", "interval"])]
+        test_webhook: bool,
+    },
+    /// Print an iCalendar file with every upcoming timer
+    Ics {
+        /// Reports to include, e.g. `crops,subs` [default: all]
+        #[arg(long, value_enum, value_delimiter = ',')]
+        sources: Vec<calendar::Source>,
+        /// Also add each character's next N map allowances, 18 hours apart
+        #[arg(long, value_name = "N")]
+        project: Option<usize>,
+    },
+    /// Every report in tabs, with live countdowns
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Seconds between re-reading the plugins' files
+        #[arg(long, value_name = "SECS", default_value_t = 60)]
+        refresh: u64,
+    },
+    /// Check that config.toml and each report's files can be read, with a
+    /// hint for each that can't; exits with 1 when any check fails
+    Doctor,
+    /// Print where config.toml is read from
+    ConfigPath,
+    /// Print the version, the plugin file layouts it reads and where it
+    /// looks for files; as JSON with --json
+    Version,
+    /// Print a completion script for SHELL, e.g. `ffxiv-timers completions
+    /// bash > ~/.local/share/bash-completion/completions/ffxiv-timers`
+    #[command(hide = true)]
+    Completions {
+        /// The shell to complete in
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Color {
+    /// Only when printing to a terminal, unless NO_COLOR or CLICOLOR_FORCE
+    /// is set
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    fn when(self) -> ColorWhen {
+        match self {
+            Color::Auto => ColorWhen::Auto,
+            Color::Always => ColorWhen::Always,
+            Color::Never => ColorWhen::Never,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn preset(self) -> Preset {
+        match self {
+            Theme::Dark => Preset::Dark,
+            Theme::Light => Preset::Light,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Language {
+    En,
+    De,
+    Fr,
+    Ja,
+}
+
+impl Language {
+    fn lang(self) -> Lang {
+        match self {
+            Language::En => Lang::En,
+            Language::De => Lang::De,
+            Language::Fr => Lang::Fr,
+            Language::Ja => Lang::Ja,
+        }
+    }
+}
+
+fn main() -> anyhow::Result<ExitCode> {
+    let cli = Cli::parse();
+    if let Some(path) = cli.config {
+        paths::set_config_file(path);
+    }
+    if let Some(theme) = cli.theme {
+        theme::set_preset(theme.preset());
+    }
+    if let Some(lang) = cli.lang {
+        i18n::set_lang(lang.lang());
+    }
+    if let Some(dir) = cli.data_root {
+        paths::set_plugin_configs_dir(dir);
+    }
+    if let Some(label) = cli.profile {
+        profiles::select(label);
+    }
+    if let Some(minutes) = cli.soon {
+        urgency::set_soon(minutes);
+    }
+    if cli.no_pager {
+        pager::disable();
+    }
+    if cli.no_cache {
+        read_cache::disable();
+    }
+    // `inventory` passes its flags on untouched, so look for these among them
+    let passed = |flags: &[&str]| match &cli.command {
+        Command::Inventory { args } => args.iter().any(|arg| flags.contains(&arg.as_str())),
+        _ => false,
+    };
+    logging::init(
+        cli.verbose || passed(&["-v", "--verbose"]),
+        cli.quiet || passed(&["-q", "--quiet"]),
+    );
+    let check = cli.check || passed(&["--check"]);
+    let options = Options {
+        color: cli.color.map(Color::when),
+        json: cli.json,
+        verbose: cli.verbose,
+        quiet: cli.quiet,
+        fallback_cache: cli.fallback_cache,
+        check,
+        format: cli.format,
+        format_header: cli.format_header,
+        format_footer: cli.format_footer,
+        watch: false,
+        watch_interval: None,
+        trust_data: false,
+        strict_slots: false,
+        if_tended: false,
+        include_island: false,
+        detail: false,
+        project: None,
+        no_merge: false,
+    };
+
+    let result = clock::init().and_then(|_| match cli.command {
+        // A config.toml that can't be read is one of the things it checks
+        Command::Doctor => doctor::run(&options, config::load().err()),
+        // Says where config.toml is even when it can't be read
+        Command::Version => version::run(&options).map(|()| Severity::Ok),
+        command => config::load().and_then(|_| run(command, &options)),
+    });
+    check::finish(check, result)
+}
+
+/// Runs a command, returning how urgent its report is.
+fn run(command: Command, options: &Options) -> anyhow::Result<Severity> {
+    match command {
+        Command::Crops {
+            watch,
+            trust_data,
+            if_tended,
+            include_island,
+            detail,
+        } => {
+            return crop_timers::run(&Options {
+                watch: watch.is_some(),
+                watch_interval: watch.flatten(),
+                trust_data,
+                if_tended,
+                include_island,
+                detail,
+                ..options.clone()
+            })
+        }
+        Command::Maps { project, no_merge } => {
+            return map_allowances::run(&Options {
+                project,
+                no_merge,
+                ..options.clone()
+            })
+        }
+        Command::Subs {
+            watch,
+            strict_slots,
+        } => {
+            return sub_returns::run(&Options {
+                watch,
+                strict_slots,
+                ..options.clone()
+            })
+        }
+        Command::Inventory { args } => return inventory_tracker::run(args, options),
+        // The sections are paged as one report
+        Command::All { sections } => return pager::together(|| dashboard::run(options, &sections)),
+        Command::Status { format } => status::run(format, options.fallback_cache)?,
+        Command::ExportMetrics { path } => metrics::run(path.as_deref())?,
+        #[cfg(feature = "serve")]
+        Command::Serve { listen } => serve::run(&listen)?,
+        #[cfg(feature = "mqtt")]
+        Command::PublishMqtt { every } => mqtt::run(every.map(std::time::Duration::from_secs))?,
+        Command::NotifyDaemon {
+            test_webhook: true, ..
+        } => daemon::test_webhook()?,
+        Command::NotifyDaemon { once, interval, .. } => daemon::run(once, interval)?,
+        Command::Ics { sources, project } => calendar::run(&sources, project)?,
+        #[cfg(feature = "tui")]
+        Command::Tui { refresh } => tui::run(std::time::Duration::from_secs(refresh))?,
+        Command::Doctor => return doctor::run(options, None),
+        Command::Version => version::run(options)?,
+        Command::ConfigPath => match config::path() {
+            Some(path) => println!("{}", path.display()),
+            None => anyhow::bail!("No config directory could be found for this user"),
+        },
+        Command::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
+    }
+    // The rest have no report to judge
+    Ok(Severity::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use ffxiv_timers_core::{
+        clock::FAKE_NOW_ENV,
+        paths::{CONFIG_FILE_ENV, HOME_ENV, PLUGIN_CONFIGS_ENV},
+    };
+
+    use super::*;
+
+    #[test]
+    fn command_line_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn every_flag_and_command_is_documented() {
+        fn check(command: &clap::Command) {
+            for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+                assert!(
+                    arg.get_help().is_some(),
+                    "`{} {}` has no help",
+                    command.get_name(),
+                    arg.get_id()
+                );
+            }
+            for sub in command.get_subcommands() {
+                assert!(
+                    sub.get_about().is_some(),
+                    "`{}` has no help",
+                    sub.get_name()
+                );
+                check(sub);
+            }
+        }
+        check(&Cli::command());
+    }
+
+    #[test]
+    fn help_lists_the_environment() {
+        for var in [
+            PLUGIN_CONFIGS_ENV,
+            CONFIG_FILE_ENV,
+            HOME_ENV,
+            "NO_COLOR",
+            "CLICOLOR_FORCE",
+            "RUST_LOG",
+            "PAGER",
+            FAKE_NOW_ENV,
+        ] {
+            assert!(AFTER_HELP.contains(var), "{var} isn't in --help");
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    fmt::{Display, Write as _},
+    io::Write,
+    path::Path,
+};
+
+use ffxiv_timers_core::{clock, json::CropStatus, state};
+
+use crate::dashboard::Section;
+
+const STATUSES: [CropStatus; 5] = [
+    CropStatus::Good,
+    CropStatus::Okay,
+    CropStatus::Wilt,
+    CropStatus::Done,
+    CropStatus::Dead,
+];
+
+/// Prometheus' text exposition format, built one metric family at a time.
+#[derive(Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    /// Starts a family; its samples have to follow before the next one.
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(label, value)| format!("{label}=\"{}\"", escape(value)))
+                .collect::<Vec<_>>();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {value}");
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+/// Writes metrics from every source whose files exist to `path`, or stdout
+/// without one, for node_exporter's textfile collector. A source that fails
+/// is reported as down rather than stopping the rest.
+pub fn run(path: Option<&Path>) -> anyhow::Result<()> {
+    let now = clock::now();
+    let mut metrics = Exposition::default();
+    let mut up = vec![];
+
+    if let Some(result) = Section::Crops.available().then(crop_timers::reports) {
+        up.push((Section::Crops, result.is_ok()));
+        if let Ok(reports) = result.inspect_err(|err| note(Section::Crops, err)) {
+            metrics.family(
+                "ffxiv_crops",
+                "gauge",
+                "Garden plots by the status of their crop's most urgent plot.",
+            );
+            for status in STATUSES {
+                let plots = reports
+                    .iter()
+                    .filter(|report| report.status == status)
+                    .map(|report| report.patch_count)
+                    .sum::<usize>();
+                metrics.sample("ffxiv_crops", &[("status", status.name())], plots);
+            }
+            metrics.family(
+                "ffxiv_crop_plots",
+                "gauge",
+                "Garden plots growing each crop, by its most urgent plot's status.",
+            );
+            for report in &reports {
+                let labels = [("crop", &*report.name), ("status", report.status.name())];
+                metrics.sample("ffxiv_crop_plots", &labels, report.patch_count);
+            }
+            metrics.family(
+                "ffxiv_crop_next_stage_timestamp_seconds",
+                "gauge",
+                "When each crop's status next changes, as a Unix time.",
+            );
+            for report in &reports {
+                if let Some(time) = report.stage_time {
+                    let labels = [("crop", &*report.name)];
+                    metrics.sample(
+                        "ffxiv_crop_next_stage_timestamp_seconds",
+                        &labels,
+                        time.timestamp(),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(result) = Section::Maps.available().then(map_allowances::reports) {
+        up.push((Section::Maps, result.is_ok()));
+        if let Ok(reports) = result.inspect_err(|err| note(Section::Maps, err)) {
+            let ready = reports.iter().filter(|map| map.next_map <= now).count();
+            metrics.family(
+                "ffxiv_maps_ready",
+                "gauge",
+                "Characters who can gather a treasure map now.",
+            );
+            metrics.sample("ffxiv_maps_ready", &[], ready);
+            metrics.family(
+                "ffxiv_map_next_ready_timestamp_seconds",
+                "gauge",
+                "When each character can next gather a treasure map, as a Unix time.",
+            );
+            for map in &reports {
+                let labels = [("character", &*map.character), ("world", &*map.world)];
+                metrics.sample(
+                    "ffxiv_map_next_ready_timestamp_seconds",
+                    &labels,
+                    map.next_map.timestamp(),
+                );
+            }
+        }
+    }
+
+    if let Some(result) = Section::Subs.available().then(sub_returns::reports) {
+        up.push((Section::Subs, result.is_ok()));
+        if let Ok(reports) = result.inspect_err(|err| note(Section::Subs, err)) {
+            metrics.family(
+                "ffxiv_submarine_return_timestamp_seconds",
+                "gauge",
+                "When each submarine returns from its voyage, as a Unix time.",
+            );
+            for fc in &reports {
+                for sub in &fc.submarines {
+                    let Some(time) = sub.return_time else {
+                        continue;
+                    };
+                    let labels = [
+                        ("free_company", &*fc.tag),
+                        ("character", &*fc.character),
+                        ("world", &*fc.world),
+                        ("submarine", &*sub.name),
+                    ];
+                    metrics.sample(
+                        "ffxiv_submarine_return_timestamp_seconds",
+                        &labels,
+                        time.timestamp(),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(result) = Section::Inventory
+        .available()
+        .then(inventory_tracker::watchlist)
+    {
+        up.push((Section::Inventory, result.is_ok()));
+        if let Ok(items) = result.inspect_err(|err| note(Section::Inventory, err)) {
+            metrics.family(
+                "ffxiv_item_quantity",
+                "gauge",
+                "How much of each watchlist item every character and retainer holds.",
+            );
+            for item in &items {
+                let item_id = item.item_id.to_string();
+                let labels = [
+                    ("item_id", &*item_id),
+                    ("item", &*item.item_name),
+                    ("character", &*item.character),
+                    ("world", &*item.world),
+                    ("retainer", if item.is_retainer { "true" } else { "false" }),
+                    ("hq", if item.hq { "true" } else { "false" }),
+                ];
+                metrics.sample("ffxiv_item_quantity", &labels, item.quantity);
+            }
+        }
+    }
+
+    metrics.family(
+        "ffxiv_source_up",
+        "gauge",
+        "Whether each source whose plugin files exist could be read.",
+    );
+    for (section, ok) in up {
+        metrics.sample(
+            "ffxiv_source_up",
+            &[("source", section.name())],
+            u8::from(ok),
+        );
+    }
+
+    match path {
+        // The temporary file doesn't end in `.prom`, so it's never collected
+        Some(path) => state::write_atomically(path, metrics.text.as_bytes()),
+        None => Ok(std::io::stdout()
+            .lock()
+            .write_all(metrics.text.as_bytes())?),
+    }
+}
+
+fn note(section: Section, err: &anyhow::Error) {
+    tracing::warn!("Failed to read {}: {err:#}", section.name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_escape_their_labels() {
+        let mut metrics = Exposition::default();
+        metrics.family("ffxiv_test", "gauge", "A test.");
+        metrics.sample("ffxiv_test", &[], 1);
+        metrics.sample("ffxiv_test", &[("a", "x"), ("b", "\"q\"\\\n")], 2);
+        assert_eq!(
+            metrics.text,
+            "# HELP ffxiv_test A test.\n\
+             # TYPE ffxiv_test gauge\n\
+             ffxiv_test 1\n\
+             ffxiv_test{a=\"x\",b=\"\\\"q\\\"\\\\\\n\"} 2\n"
+        );
+    }
+}
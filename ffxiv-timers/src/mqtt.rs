@@ -0,0 +1,280 @@
+use std::{collections::BTreeMap, sync::mpsc, thread, time::Duration};
+
+use anyhow::Context;
+use ffxiv_timers_core::{
+    clock,
+    config::{self, MqttConfig},
+};
+use rumqttc::{Client, Event, LastWill, MqttOptions, Outgoing, Packet, QoS};
+use serde_json::{json, Value};
+
+use crate::dashboard::Section;
+
+/// How long to wait for the broker to acknowledge a round of messages.
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One Home Assistant sensor, read from the shared state topic.
+struct Sensor {
+    object_id: String,
+    name: String,
+    device_class: Option<&'static str>,
+    /// Where the sensor's value sits in the state, e.g. `ventures.alice`
+    key: String,
+    value: Value,
+}
+
+/// A message waiting to be published.
+#[derive(Debug, PartialEq)]
+struct Message {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+/// Publishes a sensor for each timer from every source whose files exist,
+/// once, or every `interval` until killed.
+pub fn run(interval: Option<Duration>) -> anyhow::Result<()> {
+    let Some(mqtt) = &config::get().mqtt else {
+        anyhow::bail!(
+            "No MQTT broker is configured; add an [mqtt] table with at least `host` to {}",
+            config::path().map_or("config.toml".into(), |path| path.display().to_string())
+        );
+    };
+    let broker = format!("{}:{}", mqtt.host, mqtt.port);
+    let availability = format!("{}/availability", mqtt.topic_prefix);
+
+    let mut options = MqttOptions::new(&mqtt.client_id, &mqtt.host, mqtt.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    options.set_last_will(LastWill::new(
+        &availability,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = &mqtt.username {
+        options.set_credentials(username, mqtt.password.as_deref().unwrap_or_default());
+    }
+    let (client, mut connection) = Client::new(options, 16);
+
+    // The connection only makes progress while it's being polled
+    let (acks, acked) = mpsc::channel();
+    let events = thread::spawn(move || {
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::PubAck(_))) => {
+                    let _ = acks.send(Ok(()));
+                }
+                Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    let _ = acks.send(Err(err));
+                    break;
+                }
+            }
+        }
+    });
+
+    loop {
+        let mut messages = messages(mqtt, &sensors());
+        messages.push(Message {
+            topic: availability.clone(),
+            payload: "online".to_string(),
+            retain: true,
+        });
+        for message in &messages {
+            client
+                .publish(
+                    &message.topic,
+                    QoS::AtLeastOnce,
+                    message.retain,
+                    message.payload.as_bytes(),
+                )
+                .with_context(|| format!("Failed to publish to the MQTT broker at {broker}"))?;
+        }
+        for _ in &messages {
+            match acked.recv_timeout(ACK_TIMEOUT) {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => {
+                    anyhow::bail!("Failed to reach the MQTT broker at {broker}: {err}")
+                }
+                Err(_) => anyhow::bail!("The MQTT broker at {broker} stopped responding"),
+            }
+        }
+        let Some(interval) = interval else { break };
+        thread::sleep(interval);
+    }
+
+    client
+        .disconnect()
+        .with_context(|| format!("Failed to disconnect from the MQTT broker at {broker}"))?;
+    let _ = events.join();
+    Ok(())
+}
+
+/// The sensors for every source that can be read.
+fn sensors() -> Vec<Sensor> {
+    let mut sensors = vec![];
+    if let Some(crops) = Section::Crops.read_or_skip(crop_timers::reports) {
+        let deadline = crops.iter().filter_map(|crop| crop.stage_time).min();
+        sensors.push(Sensor {
+            object_id: "crop_deadline".to_string(),
+            name: "Next crop deadline".to_string(),
+            device_class: Some("timestamp"),
+            key: "crop_deadline".to_string(),
+            value: json!(deadline),
+        });
+    }
+    if let Some(maps) = Section::Maps.read_or_skip(map_allowances::reports) {
+        let now = clock::now();
+        let ready = maps.iter().filter(|map| map.next_map <= now).count();
+        sensors.push(Sensor {
+            object_id: "maps_ready".to_string(),
+            name: "Maps ready".to_string(),
+            device_class: None,
+            key: "maps_ready".to_string(),
+            value: json!(ready),
+        });
+    }
+    if let Some(fcs) = Section::Subs.read_or_skip(sub_returns::reports) {
+        let next = fcs
+            .iter()
+            .flat_map(|fc| &fc.submarines)
+            .filter_map(|sub| sub.return_time)
+            .min();
+        sensors.push(Sensor {
+            object_id: "sub_return".to_string(),
+            name: "Next submarine return".to_string(),
+            device_class: Some("timestamp"),
+            key: "sub_return".to_string(),
+            value: json!(next),
+        });
+    }
+    let ventures = || inventory_tracker::holdings(&[inventory_tracker::VENTURE]);
+    if let Some(items) = Section::Inventory.read_or_skip(ventures) {
+        let mut totals = BTreeMap::<String, u32>::new();
+        for item in items.iter().filter(|item| !item.is_retainer) {
+            *totals
+                .entry(format!("{} ({})", item.character, item.world))
+                .or_default() += item.quantity;
+        }
+        for (character, total) in totals {
+            let id = slug(&character);
+            sensors.push(Sensor {
+                object_id: format!("ventures_{id}"),
+                name: format!("Ventures {character}"),
+                device_class: None,
+                key: format!("ventures.{id}"),
+                value: json!(total),
+            });
+        }
+    }
+    sensors
+}
+
+fn slug(name: &str) -> String {
+    let name = name.to_lowercase();
+    let parts = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    parts.join("_")
+}
+
+/// The retained discovery config for each sensor and the state they're all
+/// read from.
+fn messages(mqtt: &MqttConfig, sensors: &[Sensor]) -> Vec<Message> {
+    let state_topic = format!("{}/state", mqtt.topic_prefix);
+    let node = slug(&mqtt.client_id);
+    let mut state = json!({});
+    let mut messages = vec![];
+    for sensor in sensors {
+        let mut config = json!({
+            "name": sensor.name,
+            "unique_id": format!("{node}_{}", sensor.object_id),
+            "state_topic": state_topic,
+            "value_template": format!("{{{{ value_json.{} }}}}", sensor.key),
+            "availability_topic": format!("{}/availability", mqtt.topic_prefix),
+            "device": {"identifiers": [node], "name": "FFXIV Timers"},
+        });
+        if let Some(class) = sensor.device_class {
+            config["device_class"] = json!(class);
+        }
+        messages.push(Message {
+            topic: format!(
+                "{}/sensor/{node}/{}/config",
+                mqtt.discovery_prefix, sensor.object_id
+            ),
+            payload: config.to_string(),
+            retain: true,
+        });
+
+        let mut slot = &mut state;
+        for part in sensor.key.split('.') {
+            slot = slot
+                .as_object_mut()
+                .expect("state keys only nest objects")
+                .entry(part)
+                .or_insert_with(|| json!({}));
+        }
+        *slot = sensor.value.clone();
+    }
+    messages.push(Message {
+        topic: state_topic,
+        payload: state.to_string(),
+        retain: false,
+    });
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sensors_share_one_state_topic() {
+        let mqtt = MqttConfig {
+            host: "broker.lan".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            client_id: "ffxiv-timers".to_string(),
+            topic_prefix: "ffxiv".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        };
+        let sensors = [
+            Sensor {
+                object_id: "maps_ready".to_string(),
+                name: "Maps ready".to_string(),
+                device_class: None,
+                key: "maps_ready".to_string(),
+                value: json!(3),
+            },
+            Sensor {
+                object_id: "ventures_alice_doe_tonberry".to_string(),
+                name: "Ventures Alice Doe (Tonberry)".to_string(),
+                device_class: None,
+                key: "ventures.alice_doe_tonberry".to_string(),
+                value: json!(120),
+            },
+        ];
+        let messages = messages(&mqtt, &sensors);
+        assert_eq!(
+            messages[1].topic,
+            "homeassistant/sensor/ffxiv_timers/ventures_alice_doe_tonberry/config"
+        );
+        let config: Value = serde_json::from_str(&messages[1].payload).unwrap();
+        assert_eq!(
+            config["value_template"],
+            "{{ value_json.ventures.alice_doe_tonberry }}"
+        );
+        assert_eq!(config["availability_topic"], "ffxiv/availability");
+        assert_eq!(
+            messages[2],
+            Message {
+                topic: "ffxiv/state".to_string(),
+                payload: r#"{"maps_ready":3,"ventures":{"alice_doe_tonberry":120}}"#.to_string(),
+                retain: false,
+            }
+        );
+    }
+}
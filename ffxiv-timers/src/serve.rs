@@ -0,0 +1,151 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use anyhow::Context;
+use ffxiv_timers_core::{
+    json::{Envelope, InventoryReport, Tool},
+    read_cache,
+    source::{Failure, SourceError, SourceResult},
+    watch,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::dashboard::Section;
+
+const SECTIONS: [Section; 4] = [
+    Section::Crops,
+    Section::Maps,
+    Section::Subs,
+    Section::Inventory,
+];
+
+/// A section's last report, kept until any file it was read from changes.
+struct Cached {
+    stamp: Vec<Option<SystemTime>>,
+    data: serde_json::Value,
+}
+
+#[derive(Default)]
+struct Cache {
+    reports: HashMap<&'static str, Cached>,
+}
+
+impl Cache {
+    /// The section's report in its envelope, re-read if its files changed.
+    fn envelope(&mut self, section: Section) -> SourceResult<serde_json::Value> {
+        let paths = section.read(|| section.data_paths())?;
+        let stamp = paths
+            .iter()
+            .flat_map(|path| watch::stamp(path))
+            .collect::<Vec<_>>();
+        let fresh = self
+            .reports
+            .get(section.name())
+            .is_some_and(|cached| cached.stamp == stamp);
+        if fresh {
+            tracing::debug!("Serving {} from the cache", section.name());
+        } else {
+            let data = report(section)?;
+            self.reports.insert(section.name(), Cached { stamp, data });
+        }
+        let data = &self.reports[section.name()].data;
+        let envelope = serde_json::to_value(Envelope::new(tool(section), data));
+        Ok(envelope.map_err(anyhow::Error::from)?)
+    }
+}
+
+fn tool(section: Section) -> Tool {
+    match section {
+        Section::Crops => Tool::Crops,
+        Section::Maps => Tool::Maps,
+        Section::Subs => Tool::Subs,
+        Section::Inventory => Tool::Inventory,
+    }
+}
+
+fn report(section: Section) -> anyhow::Result<serde_json::Value> {
+    Ok(match section {
+        Section::Crops => serde_json::to_value(crop_timers::reports()?)?,
+        Section::Maps => serde_json::to_value(map_allowances::reports()?)?,
+        Section::Subs => serde_json::to_value(sub_returns::reports()?)?,
+        Section::Inventory => serde_json::to_value(InventoryReport {
+            kind: "query".to_string(),
+            items: inventory_tracker::watchlist()?,
+        })?,
+    })
+}
+
+/// Serves each report's `--json` output at `/crops`, `/maps`, `/subs` and
+/// `/inventory`, and all of them at `/all`, until killed. A report that
+/// can't be read is a 503 at its own path, and in `/all` an object with the
+/// tool, the kind of failure and the error instead of its envelope; those
+/// whose files are missing are left out.
+pub fn run(listen: &str) -> anyhow::Result<()> {
+    let server = Server::http(listen)
+        .map_err(|err| anyhow::anyhow!(err))
+        .with_context(|| format!("Failed to listen on {listen}"))?;
+    tracing::info!("Serving reports on http://{listen}/");
+    read_cache::keep_in_memory();
+    let mut cache = Cache::default();
+    for request in server.incoming_requests() {
+        let path = request_path(&request).to_string();
+        let (status, body) = respond(&mut cache, &request);
+        let json =
+            Header::from_bytes("Content-Type", "application/json").expect("the header is valid");
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(json);
+        if let Err(err) = request.respond(response) {
+            tracing::warn!("Failed to respond to {path}: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn request_path(request: &Request) -> &str {
+    let url = request.url();
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+fn respond(cache: &mut Cache, request: &Request) -> (u16, String) {
+    if *request.method() != Method::Get {
+        return error(405, "Only GET is supported");
+    }
+    let path = request_path(request).trim_end_matches('/');
+    if path == "/all" {
+        let envelopes = SECTIONS
+            .into_iter()
+            .filter_map(|section| match cache.envelope(section) {
+                Ok(envelope) => Some(envelope),
+                Err(err) if err.failure == Failure::Missing => None,
+                Err(err) => {
+                    tracing::warn!("Failed to read {}: {err}", section.name());
+                    Some(failed(section, &err))
+                }
+            })
+            .collect::<Vec<_>>();
+        return (200, serde_json::Value::from(envelopes).to_string());
+    }
+    let Some(section) = SECTIONS
+        .into_iter()
+        .find(|section| path.strip_prefix('/') == Some(section.name()))
+    else {
+        return error(404, "Try /crops, /maps, /subs, /inventory or /all");
+    };
+    match cache.envelope(section) {
+        Ok(envelope) => (200, envelope.to_string()),
+        Err(err) => (503, failed(section, &err).to_string()),
+    }
+}
+
+/// What's served for a report that couldn't be read.
+fn failed(section: Section, err: &SourceError) -> serde_json::Value {
+    serde_json::json!({
+        "tool": section.name(),
+        "failure": err.failure,
+        "error": format!("Failed to read {}: {:#}", section.name(), err.error),
+    })
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
@@ -0,0 +1,362 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use ffxiv_timers_core::{
+    clock, fallback,
+    i18n::{tr, trf},
+    json::{CropReport, CropStatus, FreeCompanyReport, MapReport, Tool},
+    time::{format_duration, Past, Style},
+    urgency::{self, Urgency},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::dashboard::Section;
+
+/// How the status line is printed, for whichever bar runs it.
+#[derive(Copy, Clone, Default, ValueEnum)]
+pub enum Format {
+    /// One JSON object with text, class and tooltip
+    #[default]
+    Waybar,
+    /// Full text, short text and color on separate lines
+    I3blocks,
+}
+
+/// The i3blocks color for a level, from the Dracula palette bars tend to
+/// use.
+fn color(urgency: Urgency) -> Option<&'static str> {
+    match urgency {
+        Urgency::Ok => None,
+        Urgency::Soon => Some("#F1FA8C"),
+        Urgency::Due => Some("#FFB86C"),
+        Urgency::Overdue => Some("#FF5555"),
+    }
+}
+
+/// What one data source adds to the status line.
+struct Summary {
+    /// Left out of the line when there's nothing to count down to
+    text: Option<String>,
+    urgency: Urgency,
+    /// The source's part of the tooltip
+    lines: Vec<String>,
+}
+
+/// The waybar custom module protocol.
+#[derive(Serialize)]
+struct Waybar {
+    text: String,
+    class: &'static str,
+    tooltip: String,
+}
+
+/// Prints the most urgent timer from each source whose files exist, for a
+/// status bar module. With `fallback_cache`, a source whose files can't be
+/// read is shown from the last reports kept, if there are any.
+pub fn run(format: Format, fallback_cache: bool) -> anyhow::Result<()> {
+    let now = clock::now();
+    let mut summaries = vec![];
+    for section in [Section::Crops, Section::Subs, Section::Maps] {
+        let available = section.available();
+        if !available && !fallback_cache {
+            continue;
+        }
+        let summary = match section {
+            Section::Crops => summarize(
+                Tool::Crops,
+                fallback_cache,
+                crop_timers::reports,
+                |reports| crops(reports, now),
+            ),
+            Section::Subs => summarize(
+                Tool::Subs,
+                fallback_cache,
+                sub_returns::reports,
+                |reports| subs(reports, now),
+            ),
+            Section::Maps => summarize(
+                Tool::Maps,
+                fallback_cache,
+                map_allowances::reports,
+                |reports| maps(reports, now),
+            ),
+            Section::Inventory => unreachable!("inventory has no timers"),
+        };
+        // Missing files with nothing kept from before are skipped as usual
+        if summary.is_err() && !available {
+            continue;
+        }
+        summaries.push(summary.unwrap_or_else(|err| Summary {
+            text: None,
+            urgency: Urgency::Ok,
+            lines: vec![format!("Failed to read {}: {err:#}", section.name())],
+        }));
+    }
+
+    let text = summaries
+        .iter()
+        .filter_map(|summary| summary.text.as_deref())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let urgency = summaries
+        .iter()
+        .map(|summary| summary.urgency)
+        .max()
+        .unwrap_or_default();
+    match format {
+        Format::Waybar => {
+            let status = Waybar {
+                text,
+                class: urgency.class(),
+                tooltip: summaries
+                    .iter()
+                    .flat_map(|summary| &summary.lines)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            println!("{}", serde_json::to_string(&status)?);
+        }
+        Format::I3blocks => {
+            println!("{text}");
+            println!("{text}");
+            if let Some(color) = color(urgency) {
+                println!("{color}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A source's summary, with a note after its text and tooltip heading if
+/// it's from the `--fallback-cache`.
+fn summarize<T: Serialize + DeserializeOwned>(
+    tool: Tool,
+    fallback_cache: bool,
+    read: impl FnOnce() -> anyhow::Result<T>,
+    summary: impl FnOnce(&T) -> Summary,
+) -> anyhow::Result<Summary> {
+    let (reports, cached_at) = fallback::load(tool, fallback_cache, read)?;
+    let mut summary = summary(&reports);
+    if cached_at.is_some() {
+        summary.text = summary.text.map(|text| fallback::mark(&text, cached_at));
+        if let Some(heading) = summary.lines.first_mut() {
+            *heading = fallback::mark(heading, cached_at);
+        }
+    }
+    Ok(summary)
+}
+
+/// Hours and minutes until `time`, e.g. `00:41`, with a day count in front
+/// once it's a day or more, e.g. `5d 13:12`.
+fn countdown(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (time - now).num_minutes().max(0);
+    let clock = format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60);
+    match minutes / 1440 {
+        0 => clock,
+        days => format!("{} {clock}", trf("time.days", &[("n", &days.to_string())])),
+    }
+}
+
+fn remaining(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    format_duration(time - now, Style::Compact, Past::Label(tr("status.ready")))
+}
+
+fn crops(reports: &[CropReport], now: DateTime<Utc>) -> Summary {
+    let count = |status| {
+        reports
+            .iter()
+            .filter(|report| report.status == status)
+            .count()
+    };
+    let (dead, done) = (count(CropStatus::Dead), count(CropStatus::Done));
+    let text = if dead > 0 {
+        Some(format!("Crops {dead} dead"))
+    } else if done > 0 {
+        Some(format!("Crops {done} ready"))
+    } else {
+        let next = reports.iter().filter_map(|report| report.stage_time).min();
+        next.map(|time| format!("Crop {}", countdown(time, now)))
+    };
+    let thresholds = urgency::thresholds(Tool::Crops);
+    let urgency = reports
+        .iter()
+        .map(|report| crop_timers::urgency(report.status, report.stage_time, &thresholds, now))
+        .max()
+        .unwrap_or_default();
+    let mut lines = vec!["Crops".to_string()];
+    lines.extend(reports.iter().map(|report| {
+        let status = report.status.name();
+        match report.stage_time {
+            Some(time) => format!(
+                "    {} ({}): {status}, {}",
+                report.name,
+                report.patch_count,
+                remaining(time, now)
+            ),
+            None => format!("    {} ({}): {status}", report.name, report.patch_count),
+        }
+    }));
+    Summary {
+        text,
+        urgency,
+        lines,
+    }
+}
+
+fn subs(reports: &[FreeCompanyReport], now: DateTime<Utc>) -> Summary {
+    let returns = reports
+        .iter()
+        .flat_map(|fc| &fc.submarines)
+        .filter_map(|sub| sub.return_time)
+        .collect::<Vec<_>>();
+    let ready = returns.iter().filter(|&&time| time <= now).count();
+    let text = match ready {
+        0 => returns
+            .iter()
+            .min()
+            .map(|&time| format!("Sub {}", countdown(time, now))),
+        _ => Some(format!("Subs {ready}")),
+    };
+    let mut lines = vec![];
+    for fc in reports {
+        lines.push(format!(
+            "{} «{}» ({})",
+            tr("subs.title"),
+            fc.tag,
+            fc.character
+        ));
+        lines.extend(fc.submarines.iter().map(|sub| match sub.return_time {
+            Some(time) => format!("    {}: {}", sub.name, remaining(time, now)),
+            None => format!("    {}: {}", sub.name, tr("status.unassigned")),
+        }));
+    }
+    let thresholds = urgency::thresholds(Tool::Subs);
+    Summary {
+        text,
+        urgency: returns
+            .iter()
+            .map(|&time| thresholds.level(time, now))
+            .max()
+            .unwrap_or_default(),
+        lines,
+    }
+}
+
+fn maps(reports: &[MapReport], now: DateTime<Utc>) -> Summary {
+    let ready = reports.iter().filter(|map| map.next_map <= now).count();
+    let text = match ready {
+        0 => reports
+            .iter()
+            .map(|map| map.next_map)
+            .min()
+            .map(|time| format!("Map {}", countdown(time, now))),
+        _ => Some(format!("Maps {ready}")),
+    };
+    let mut lines = vec!["Maps".to_string()];
+    lines.extend(reports.iter().map(|map| {
+        format!(
+            "    {} ({}): {}",
+            map.character,
+            map.world,
+            remaining(map.next_map, now)
+        )
+    }));
+    let thresholds = urgency::thresholds(Tool::Maps);
+    Summary {
+        text,
+        urgency: reports
+            .iter()
+            .map(|map| thresholds.level(map.next_map, now))
+            .max()
+            .unwrap_or_default(),
+        lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use ffxiv_timers_core::json::SubmarineReport;
+
+    use super::*;
+
+    #[test]
+    fn counts_ready_timers_or_counts_down_to_the_next() {
+        let now = Utc::now();
+        let sub = |minutes| SubmarineReport {
+            name: "Nautilus".to_string(),
+            return_time: Some(now + Duration::minutes(minutes)),
+            label: None,
+        };
+        let fc = |submarines| FreeCompanyReport {
+            character: "Alice Doe".to_string(),
+            tag: "MOGS".to_string(),
+            world: "Tonberry".to_string(),
+            submarines,
+            profile: None,
+        };
+
+        let waiting = subs(&[fc(vec![sub(41), sub(90)])], now);
+        assert_eq!(waiting.text.as_deref(), Some("Sub 00:41"));
+        assert_eq!(waiting.urgency, Urgency::Ok);
+        let nearly = subs(&[fc(vec![sub(20)])], now);
+        assert_eq!(nearly.urgency, Urgency::Soon);
+
+        let back = subs(&[fc(vec![sub(-5), sub(-1), sub(90)])], now);
+        assert_eq!(back.text.as_deref(), Some("Subs 2"));
+        assert_eq!(back.urgency, Urgency::Due);
+        assert_eq!(back.lines[1], "    Nautilus: ready");
+
+        assert_eq!(subs(&[], now).text, None);
+    }
+
+    #[test]
+    fn dead_crops_outrank_everything() {
+        let now = Utc::now();
+        let crop = |status, stage_time| CropReport {
+            plant_id: 4842,
+            name: "Almond".to_string(),
+            patch_count: 1,
+            status,
+            stage_time,
+            estimated: false,
+            patches: vec![],
+        };
+        let summary = crops(
+            &[
+                crop(CropStatus::Good, Some(now + Duration::hours(30))),
+                crop(CropStatus::Dead, None),
+            ],
+            now,
+        );
+        assert_eq!(summary.text.as_deref(), Some("Crops 1 dead"));
+        assert_eq!(summary.lines[2], "    Almond (1): dead");
+        assert_eq!(summary.urgency, Urgency::Overdue);
+        assert_eq!(
+            crops(
+                &[crop(CropStatus::Good, Some(now + Duration::hours(30)))],
+                now
+            )
+            .text,
+            Some("Crop 1d 06:00".to_string())
+        );
+    }
+
+    #[test]
+    fn countdowns_count_days_past_a_day() {
+        let now = Utc::now();
+        let countdown = |duration| countdown(now + duration, now);
+        assert_eq!(countdown(Duration::zero()), "00:00");
+        assert_eq!(countdown(Duration::seconds(59)), "00:00");
+        assert_eq!(countdown(Duration::minutes(-5)), "00:00");
+        assert_eq!(
+            countdown(Duration::hours(23) + Duration::minutes(59)),
+            "23:59"
+        );
+        assert_eq!(countdown(Duration::days(1)), "1d 00:00");
+        assert_eq!(
+            countdown(Duration::hours(133) + Duration::minutes(12)),
+            "5d 13:12"
+        );
+    }
+}
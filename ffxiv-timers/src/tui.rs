@@ -0,0 +1,609 @@
+use std::{
+    collections::HashSet,
+    io::{self, Stdout},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ffxiv_timers_core::{
+    clock,
+    i18n::tr,
+    json::{
+        CropReport, CropStatus, FreeCompanyReport, HeldItem, MapReport, PlotReport,
+        SubmarineReport, Tool,
+    },
+    profiles,
+    theme::{self, Role},
+    time::{format_duration, format_local, Past, Style as TimeStyle},
+    urgency::{self, Urgency},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Tabs},
+    Frame, Terminal,
+};
+use termcolor::Color as TermColor;
+
+use crate::dashboard::Section;
+
+const TABS: [&str; 5] = ["Overview", "Crops", "Maps", "Subs", "Inventory"];
+
+/// One source's reports as of the last read.
+enum Loaded<T> {
+    /// The plugin hasn't written its files
+    Missing,
+    Failed(String),
+    Ready(T),
+}
+
+fn load<T>(section: Section, report: impl FnOnce() -> anyhow::Result<T>) -> Loaded<T> {
+    if !section.available() {
+        return Loaded::Missing;
+    }
+    match report() {
+        Ok(reports) => Loaded::Ready(reports),
+        Err(err) => Loaded::Failed(format!("{err:#}")),
+    }
+}
+
+struct Data {
+    crops: Loaded<Vec<CropReport>>,
+    maps: Loaded<Vec<MapReport>>,
+    subs: Loaded<Vec<FreeCompanyReport>>,
+    items: Loaded<Vec<HeldItem>>,
+    read_at: Instant,
+}
+
+impl Data {
+    fn read() -> Data {
+        Data {
+            crops: load(Section::Crops, crop_timers::reports),
+            maps: load(Section::Maps, map_allowances::reports),
+            subs: load(Section::Subs, sub_returns::reports),
+            items: load(Section::Inventory, inventory_tracker::watchlist),
+            read_at: Instant::now(),
+        }
+    }
+}
+
+/// One line of a tab. Group headings can be opened to show what's in them.
+struct Row {
+    line: Line<'static>,
+    group: Option<String>,
+}
+
+impl Row {
+    fn plain(line: Line<'static>) -> Row {
+        Row { line, group: None }
+    }
+}
+
+/// A role's color from the theme, as the terminal library names it.
+fn color(role: Role) -> Color {
+    match theme::color(role) {
+        TermColor::Black => Color::Black,
+        TermColor::Blue => Color::Blue,
+        TermColor::Green => Color::Green,
+        TermColor::Red => Color::Red,
+        TermColor::Cyan => Color::Cyan,
+        TermColor::Magenta => Color::Magenta,
+        TermColor::Yellow => Color::Yellow,
+        TermColor::White => Color::White,
+        TermColor::Ansi256(index) => Color::Indexed(index),
+        TermColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+        _ => Color::Reset,
+    }
+}
+
+fn text(content: impl Into<String>, role: Role) -> Span<'static> {
+    Span::styled(content.into(), Style::new().fg(color(role)))
+}
+
+/// A submarine's label from `[sub_labels]`, dim after the rest of its line.
+fn label(sub: &SubmarineReport) -> Span<'static> {
+    let label = sub
+        .label
+        .as_deref()
+        .map_or(String::new(), |label| format!(" {label}"));
+    text(label, Role::Dim)
+}
+
+fn heading(content: impl Into<String>) -> Span<'static> {
+    Span::styled(
+        content.into(),
+        Style::new()
+            .fg(color(Role::Header))
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// A crop's or plot's level, as the crops report judges it.
+fn crop_urgency(
+    status: CropStatus,
+    next_stage: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Urgency {
+    crop_timers::urgency(status, next_stage, &urgency::thresholds(Tool::Crops), now)
+}
+
+fn countdown(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    format!(
+        "{} ({})",
+        format_duration(
+            time - now,
+            TimeStyle::Compact,
+            Past::Label(tr("status.ready"))
+        ),
+        format_local(time)
+    )
+}
+
+struct App {
+    data: Data,
+    tab: usize,
+    /// The selected row on each tab
+    selected: [usize; TABS.len()],
+    /// Groups opened or closed from how they start out
+    toggled: HashSet<String>,
+}
+
+impl App {
+    fn is_open(&self, group: &str, by_default: bool) -> bool {
+        by_default != self.toggled.contains(group)
+    }
+
+    /// A row for each thing on the current tab, at `now`.
+    fn rows(&self, now: DateTime<Utc>) -> Vec<Row> {
+        match self.tab {
+            0 => self.overview(now),
+            1 => self.crops(now),
+            2 => self.maps(now),
+            3 => self.subs(now),
+            _ => self.items(),
+        }
+    }
+
+    /// Rows explaining why a source has nothing to show, if it doesn't.
+    fn unavailable<'a, T>(
+        &self,
+        section: Section,
+        loaded: &'a Loaded<T>,
+    ) -> Result<&'a T, Vec<Row>> {
+        match loaded {
+            Loaded::Ready(reports) => Ok(reports),
+            Loaded::Missing => Err(vec![Row::plain(Line::from(text(
+                format!(
+                    "No {} data yet; its plugin hasn't written any files",
+                    section.name()
+                ),
+                Role::Dim,
+            )))]),
+            Loaded::Failed(err) => Err(vec![Row::plain(Line::from(text(
+                format!("Failed to read {}: {err}", section.name()),
+                Role::Error,
+            )))]),
+        }
+    }
+
+    /// Everything needing attention, most urgent first, then what's next.
+    fn overview(&self, now: DateTime<Utc>) -> Vec<Row> {
+        // (urgency, when, line), where a higher urgency sorts first
+        let mut entries: Vec<(Urgency, Option<DateTime<Utc>>, Line<'static>)> = vec![];
+        if let Loaded::Ready(crops) = &self.data.crops {
+            for crop in crops {
+                let urgency = crop_urgency(crop.status, crop.stage_time, now);
+                let when = crop
+                    .stage_time
+                    .map(|time| countdown(time, now))
+                    .unwrap_or_default();
+                entries.push((
+                    urgency,
+                    crop.stage_time,
+                    Line::from(vec![
+                        heading("Crops  "),
+                        text(
+                            format!(
+                                "{} ({}) {} {when}",
+                                crop.name,
+                                crop.patch_count,
+                                crop.status.name()
+                            ),
+                            urgency.role(),
+                        ),
+                    ]),
+                ));
+            }
+        }
+        if let Loaded::Ready(fcs) = &self.data.subs {
+            let thresholds = urgency::thresholds(Tool::Subs);
+            for fc in fcs {
+                for sub in &fc.submarines {
+                    let Some(time) = sub.return_time else {
+                        continue;
+                    };
+                    let urgency = thresholds.level(time, now);
+                    entries.push((
+                        urgency,
+                        Some(time),
+                        Line::from(vec![
+                            heading("Subs   "),
+                            text(
+                                format!("{} «{}» {}", sub.name, fc.tag, countdown(time, now)),
+                                urgency.role(),
+                            ),
+                            label(sub),
+                        ]),
+                    ));
+                }
+            }
+        }
+        if let Loaded::Ready(maps) = &self.data.maps {
+            let thresholds = urgency::thresholds(Tool::Maps);
+            for map in maps {
+                let urgency = thresholds.level(map.next_map, now);
+                entries.push((
+                    urgency,
+                    Some(map.next_map),
+                    Line::from(vec![
+                        heading("Maps   "),
+                        text(
+                            format!(
+                                "{} ({}) {}",
+                                map.character,
+                                map.world,
+                                countdown(map.next_map, now)
+                            ),
+                            urgency.role(),
+                        ),
+                    ]),
+                ));
+            }
+        }
+        entries.sort_by_key(|(urgency, when, _)| (std::cmp::Reverse(*urgency), *when));
+        if entries.is_empty() {
+            return vec![Row::plain(Line::from(text("Nothing to show", Role::Dim)))];
+        }
+        entries
+            .into_iter()
+            .map(|(_, _, line)| Row::plain(line))
+            .collect()
+    }
+
+    fn crops(&self, now: DateTime<Utc>) -> Vec<Row> {
+        let crops = match self.unavailable(Section::Crops, &self.data.crops) {
+            Ok(crops) => crops,
+            Err(rows) => return rows,
+        };
+        let mut rows = vec![];
+        for crop in crops {
+            let group = format!("crop/{}", crop.plant_id);
+            let open = self.is_open(&group, false);
+            let when = crop
+                .stage_time
+                .map(|time| format!(" - {}", countdown(time, now)))
+                .unwrap_or_default();
+            rows.push(Row {
+                line: Line::from(vec![
+                    heading(if open { "▾ " } else { "▸ " }),
+                    text(
+                        format!(
+                            "{} ({}) {}{when}",
+                            crop.name,
+                            crop.patch_count,
+                            crop.status.name()
+                        ),
+                        crop_urgency(crop.status, crop.stage_time, now).role(),
+                    ),
+                ]),
+                group: Some(group),
+            });
+            if !open {
+                continue;
+            }
+            for plot in &crop.patches {
+                let when = plot
+                    .stage_time
+                    .map(|time| format!(" - {}", countdown(time, now)))
+                    .unwrap_or_default();
+                // A house shared between accounts shows up once per profile
+                let same_house = |other: &&PlotReport| {
+                    (&other.world, &other.district, other.ward, other.plot)
+                        == (&plot.world, &plot.district, plot.ward, plot.plot)
+                };
+                let ambiguous = crop.patches.iter().filter(same_house).count() > 1;
+                let house = format!(
+                    "{} {} ward {} plot {}",
+                    plot.world, plot.district, plot.ward, plot.plot
+                );
+                rows.push(Row::plain(Line::from(text(
+                    format!(
+                        "      {}: {}{when}",
+                        profiles::tag(&house, plot.profile.as_deref(), ambiguous),
+                        plot.status.name()
+                    ),
+                    crop_urgency(plot.status, plot.stage_time, now).role(),
+                ))));
+            }
+        }
+        rows
+    }
+
+    fn maps(&self, now: DateTime<Utc>) -> Vec<Row> {
+        let maps = match self.unavailable(Section::Maps, &self.data.maps) {
+            Ok(maps) => maps,
+            Err(rows) => return rows,
+        };
+        let thresholds = urgency::thresholds(Tool::Maps);
+        maps.iter()
+            .map(|map| {
+                let color = thresholds.level(map.next_map, now).role();
+                Row::plain(Line::from(text(
+                    format!(
+                        "{} ({}) - {}",
+                        map.character,
+                        map.world,
+                        countdown(map.next_map, now)
+                    ),
+                    color,
+                )))
+            })
+            .collect()
+    }
+
+    fn subs(&self, now: DateTime<Utc>) -> Vec<Row> {
+        let fcs = match self.unavailable(Section::Subs, &self.data.subs) {
+            Ok(fcs) => fcs,
+            Err(rows) => return rows,
+        };
+        let thresholds = urgency::thresholds(Tool::Subs);
+        let mut rows = vec![];
+        for fc in fcs {
+            let group = format!("fc/{}/{}", fc.tag, fc.character);
+            let open = self.is_open(&group, true);
+            let back = fc
+                .submarines
+                .iter()
+                .filter(|sub| sub.return_time.is_some_and(|time| time <= now))
+                .count();
+            rows.push(Row {
+                line: Line::from(vec![heading(format!(
+                    "{}«{}» {} ({}) | {} back of {}",
+                    if open { "▾ " } else { "▸ " },
+                    fc.tag,
+                    fc.character,
+                    fc.world,
+                    back,
+                    fc.submarines.len()
+                ))]),
+                group: Some(group),
+            });
+            if !open {
+                continue;
+            }
+            for sub in &fc.submarines {
+                let line = match sub.return_time {
+                    None => text(
+                        format!("      {} - {}", sub.name, tr("subs.unassigned")),
+                        Role::Urgent,
+                    ),
+                    Some(time) if time <= now => text(
+                        format!("      {} - {}", sub.name, tr("subs.complete")),
+                        thresholds.level(time, now).role(),
+                    ),
+                    Some(time) => text(
+                        format!("      {} - {}", sub.name, countdown(time, now)),
+                        thresholds.level(time, now).role(),
+                    ),
+                };
+                rows.push(Row::plain(Line::from(vec![line, label(sub)])));
+            }
+        }
+        rows
+    }
+
+    fn items(&self) -> Vec<Row> {
+        let held = match self.unavailable(Section::Inventory, &self.data.items) {
+            Ok(held) => held,
+            Err(rows) => return rows,
+        };
+        let mut rows = vec![];
+        let mut ids = held.iter().map(|item| item.item_id).collect::<Vec<_>>();
+        ids.dedup();
+        for id in ids {
+            let holders = held
+                .iter()
+                .filter(|item| item.item_id == id)
+                .collect::<Vec<_>>();
+            let total = holders
+                .iter()
+                .map(|item| u64::from(item.quantity))
+                .sum::<u64>();
+            let group = format!("item/{id}");
+            let open = self.is_open(&group, false);
+            rows.push(Row {
+                line: Line::from(vec![
+                    heading(if open { "▾ " } else { "▸ " }),
+                    text(format!("{}: {total}", holders[0].item_name), Role::Good),
+                ]),
+                group: Some(group),
+            });
+            if !open {
+                continue;
+            }
+            for item in holders {
+                let holder = match item.is_retainer {
+                    true => format!("retainer {}", item.character),
+                    false => format!("{} ({})", item.character, item.world),
+                };
+                let hq = if item.hq { " HQ" } else { "" };
+                rows.push(Row::plain(Line::from(text(
+                    format!("      {holder}: {}{hq}", item.quantity),
+                    Role::Good,
+                ))));
+            }
+        }
+        rows
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs, body, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.size());
+
+        frame.render_widget(
+            Tabs::new(TABS.to_vec())
+                .select(self.tab)
+                .block(Block::default().borders(Borders::ALL).title("ffxiv-timers"))
+                .highlight_style(
+                    Style::new()
+                        .fg(color(Role::Warn))
+                        .add_modifier(Modifier::BOLD),
+                ),
+            tabs,
+        );
+
+        let rows = self.rows(clock::now());
+        let selected = &mut self.selected[self.tab];
+        *selected = (*selected).min(rows.len().saturating_sub(1));
+        let height = usize::from(body.height.saturating_sub(2)).max(1);
+        let scroll = selected.saturating_sub(height - 1);
+        let lines = rows
+            .into_iter()
+            .enumerate()
+            .map(|(index, row)| match index == *selected {
+                true => row
+                    .line
+                    .patch_style(Style::new().add_modifier(Modifier::REVERSED)),
+                false => row.line,
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL))
+                .scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0)),
+            body,
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(text(
+                format!(
+                    "tab/←→ switch  ↑↓ move  enter open  r reload  q quit  read {}s ago",
+                    self.data.read_at.elapsed().as_secs()
+                ),
+                Role::Dim,
+            ))),
+            help,
+        );
+    }
+
+    /// Handles a key, reporting whether it asked to quit.
+    fn key(&mut self, code: KeyCode) -> bool {
+        let selected = &mut self.selected[self.tab];
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.tab = (self.tab + 1) % TABS.len()
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.tab = (self.tab + TABS.len() - 1) % TABS.len()
+            }
+            KeyCode::Char(digit @ '1'..='5') => self.tab = digit as usize - '1' as usize,
+            KeyCode::Down | KeyCode::Char('j') => *selected += 1,
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::PageDown => *selected += 10,
+            KeyCode::PageUp => *selected = selected.saturating_sub(10),
+            KeyCode::Home => *selected = 0,
+            KeyCode::End => *selected = usize::MAX,
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let selected = *selected;
+                let group = self
+                    .rows(clock::now())
+                    .into_iter()
+                    .nth(selected)
+                    .and_then(|row| row.group);
+                if let Some(group) = group {
+                    if !self.toggled.remove(&group) {
+                        self.toggled.insert(group);
+                    }
+                }
+            }
+            KeyCode::Char('r') => self.data = Data::read(),
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Puts the terminal back however the TUI ends, including by panicking.
+struct Restore;
+
+impl Restore {
+    fn enter() -> io::Result<Restore> {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok(Restore)
+    }
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+}
+
+/// Shows every report in tabs until `q`, re-reading the plugins' files every
+/// `refresh` or on `r`.
+pub fn run(refresh: Duration) -> anyhow::Result<()> {
+    let _restore = Restore::enter()?;
+    let mut terminal: Terminal<CrosstermBackend<Stdout>> =
+        Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let mut app = App {
+        data: Data::read(),
+        tab: 0,
+        selected: [0; TABS.len()],
+        toggled: HashSet::new(),
+    };
+    terminal.clear()?;
+
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        // Wake on the next second so countdowns tick over together
+        let tick = 1000 - u64::from(clock::now().timestamp_subsec_millis().min(999));
+        if event::poll(Duration::from_millis(tick))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && app.key(key.code) {
+                    return Ok(());
+                }
+                if key.code == KeyCode::Char('r') {
+                    // Reading may have warned on stderr over the screen
+                    terminal.clear()?;
+                }
+            }
+        }
+        if app.data.read_at.elapsed() >= refresh {
+            app.data = Data::read();
+            terminal.clear()?;
+        }
+    }
+}
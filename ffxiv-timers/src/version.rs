@@ -0,0 +1,136 @@
+//! `version`: which build this is, what it can read of each plugin's files,
+//! and where it looks for everything, for scripts and bug reports.
+
+use std::{io::Write, path::PathBuf};
+
+use ffxiv_timers_core::{config, gamedata, options::Options, paths, source::Support, theme};
+use serde::Serialize;
+use termcolor::WriteColor;
+
+#[derive(Serialize)]
+struct Version {
+    version: &'static str,
+    /// The commit built from, when it was built from a git checkout
+    git_hash: Option<&'static str>,
+    /// The patch the built-in game data was taken from
+    gamedata_patch: &'static str,
+    sources: Vec<Source>,
+    paths: Paths,
+}
+
+#[derive(Serialize)]
+struct Source {
+    name: &'static str,
+    #[serde(flatten)]
+    support: Support,
+}
+
+#[derive(Serialize)]
+struct Paths {
+    config: Option<PathBuf>,
+    state: Option<PathBuf>,
+    cache: Option<PathBuf>,
+    plugin_configs: Option<PathBuf>,
+}
+
+fn version() -> Version {
+    Version {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: option_env!("FFXIV_TIMERS_GIT_HASH"),
+        gamedata_patch: gamedata::SNAPSHOT_PATCH,
+        sources: vec![
+            Source {
+                name: "crops",
+                support: crop_timers::support(),
+            },
+            Source {
+                name: "maps",
+                support: map_allowances::support(),
+            },
+            Source {
+                name: "subs",
+                support: sub_returns::support(),
+            },
+            Source {
+                name: "inventory",
+                support: inventory_tracker::support(),
+            },
+        ],
+        paths: Paths {
+            config: config::path(),
+            state: paths::state_dir(),
+            cache: paths::cache_dir(),
+            plugin_configs: paths::plugin_configs_dir().ok(),
+        },
+    }
+}
+
+/// Prints the version, as JSON with `--json`.
+pub fn run(options: &Options) -> anyhow::Result<()> {
+    let version = version();
+    let mut stdout = options.stdout();
+    if options.json {
+        serde_json::to_writer_pretty(&mut stdout, &version)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    stdout.set_color(&theme::header())?;
+    write!(stdout, "ffxiv-timers {}", version.version)?;
+    if let Some(hash) = version.git_hash {
+        write!(stdout, " ({hash})")?;
+    }
+    writeln!(stdout)?;
+    stdout.reset()?;
+    writeln!(stdout, "Game data from patch {}", version.gamedata_patch)?;
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Reads")?;
+    for source in &version.sources {
+        stdout.set_color(&theme::good())?;
+        writeln!(stdout, "    {}: {}", source.name, source.support.files)?;
+        stdout.set_color(&theme::dim())?;
+        for layout in &source.support.layouts {
+            writeln!(stdout, "        {layout}")?;
+        }
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Paths")?;
+    stdout.reset()?;
+    let paths = &version.paths;
+    for (name, path) in [
+        ("config", &paths.config),
+        ("state", &paths.state),
+        ("cache", &paths.cache),
+        ("plugin configs", &paths.plugin_configs),
+    ] {
+        let path = path.as_ref().map_or("(none found)".to_string(), |path| {
+            path.display().to_string()
+        });
+        writeln!(stdout, "    {name:<14} {path}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_source_says_what_it_reads() {
+        let version = serde_json::to_value(version()).unwrap();
+        assert_eq!(version["gamedata_patch"], gamedata::SNAPSHOT_PATCH);
+        let sources = version["sources"].as_array().unwrap();
+        assert_eq!(sources.len(), 4);
+        for source in sources {
+            assert!(source["files"]
+                .as_str()
+                .is_some_and(|files| !files.is_empty()));
+            assert!(!source["layouts"].as_array().unwrap().is_empty());
+        }
+        let crops = sources[0]["layouts"][0].as_str().unwrap();
+        assert!(crops.contains("Item2: [{"), "{crops}");
+        assert!(crops.contains("PlantId"), "{crops}");
+    }
+}
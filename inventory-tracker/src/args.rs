@@ -0,0 +1,659 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+use ffxiv_timers_core::{
+    i18n,
+    options::{ColorWhen, Options},
+    pager, profiles, read_cache,
+    template::RowFormat,
+    theme,
+};
+
+use crate::{
+    export::FORMAT_FIELDS,
+    filter::CharacterFilter,
+    items::Category,
+    sort::{Sort, SortKey},
+};
+
+const USAGE: &str = "\
+Usage: inventory-tracker [OPTIONS] [ITEM]...
+
+Reports on the inventory data exported by the InventoryTools plugin.
+With no options, lists how many of each ITEM, given by id or name, every
+character is holding (the watchlist from config.toml, or ventures, if no
+items are given).
+
+Options:
+    --combine-quality       Count HQ and NQ stacks of an item together
+    --locations             List who holds each ITEM, most first, with a bar for their
+                            share of the total
+    --min-qty <N>           Sum up holders with fewer than N into one row of
+                            --locations [default: 0]
+    --search <TEXT>         List items whose name contains TEXT; repeat to narrow
+    --limit <N>             Maximum number of items --search lists [default: 25]
+    --repl                  Load the inventory once and answer `find`, `count` and
+                            `char` commands typed on stdin
+    --needs <PATH>          Check stock against a CSV of `item,quantity,quality` lines,
+                            where item is an id or name and quality is blank or `hq`;
+                            with --check, exits with 1 if anything is short
+    --ventures              Show each character's ventures and roughly how many days
+                            their retainers will take to use them up
+    --slots                 Show used and free slots for each inventory
+    --armoury               Show used and free slots in each armoury chest section
+    --listings              List what each retainer has up on the market board
+    --orphans               List rows left behind by characters and retainers that
+                            InventoryTools no longer has saved
+    --exclude-orphans       Leave those rows out of every other report
+    --saddlebag             List saddlebag contents, and who has the premium saddlebag
+    --top [N]               List the N items using the most slots in each inventory
+                            [default: 20]
+    --top-quantity [N]      Like --top, but ordered by quantity held
+    --spiritbond            List equipped and armoury gear ready for materia extraction
+    --spiritbond-at <PERCENT>
+                            Spiritbond --spiritbond lists gear from [default: 100]
+    --repair                List equipped and armoury gear in need of repair
+    --repair-below <PERCENT>
+                            Condition --repair lists gear under [default: 50]
+    --materia               Summarise loose and slotted materia
+    --currency              Show each character's gil, seals, tomestones and scrips
+    --value                 Estimate what each character's items would sell for, using
+                            Universalis prices cached for a few hours
+    --wealth                Show each character's gil plus the estimated value of their
+                            tradable items, with their share of the account's total
+    --min <GIL>             Sum characters worth less than GIL into one --wealth row
+    --dyes                  Summarise which dyes are applied across your gear
+    --glamours              List items glamoured to look like another item
+    --dupes                 List gear held more than once across characters and retainers
+    --reducible             List collectables that can be aetherially reduced
+    --desynth [ILVL]        List gear that can be desynthesized, only below ILVL if
+                            given [default: desynth_below from the config file]
+    --consolidate           Suggest partial stacks that could be merged to free slots
+    --fc-chest              List free company chest contents tab by tab
+    --gearsets <ITEM>       Show which gearsets use an item, given its id or name
+    --gearset-name <NAME>   List every item tagged with a gearset
+    --snapshot <PATH>       Write the whole inventory to PATH as JSON
+    --diff <OLD> <NEW>      Show items gained and lost between two snapshots
+    --include-moves         Make --diff report items moved between containers
+    --log-items <IDS>       Log the total held of each comma-separated item id on this
+                            run, alongside any ids listed in the config file
+    --history <ITEM> [DAYS] Show logged totals of an item, given its id or name, over
+                            the last DAYS days [default: 30]
+    --burn-rate <ITEM> [DAYS]
+                            Fit a trend to an item's logged totals over the last DAYS
+                            days and project when it runs out, against what retainers
+                            and submarine voyages account for [default: 30]
+    --full-warn <PERCENT>   Fullness at which --slots and --armoury highlight an
+                            inventory [default: 90]
+    --watch                 Re-run the report whenever the plugin updates its files
+    --check                 Exit with 1 when --needs finds something short or --slots
+                            an inventory past --full-warn, and with 3 on errors
+    --fallback-cache        Keep the rows each plain query reads, and show the last
+                            ones kept, marked stale, when the plugin's files can't be
+                            read; too old after config.toml's fallback_max_age seconds
+    --csv                   Print the rows behind a plain query, --search, --currency,
+                            --listings, --saddlebag, --fc-chest or --gearsets as CSV
+                            with the columns character, world, container, item_id,
+                            item_name, hq, quantity
+    --json                  Print the same reports as JSON, with quantities totalled
+                            per item, holder and quality
+    --format <TEMPLATE>     Print the same totals one line each, filling in {item},
+                            {item_id}, {character}, {world}, {quantity}, {hq} and
+                            {retainer}; write {{ and }} for literal braces
+    --format-header <TEMPLATE>
+                            Line printed before the --format lines, which may use
+                            {count}
+    --format-footer <TEMPLATE>
+                            Line printed after them, likewise
+    --no-pager              Never show a report longer than the terminal through $PAGER
+                            [default: less -RFX], as the config file's `pager =
+                            false` also turns off
+    --no-cache              Read every plugin file again, even those unchanged since
+                            the last run
+    --color <WHEN>          Color the report: auto, always or never [default: auto,
+                            coloring only a terminal unless NO_COLOR or
+                            CLICOLOR_FORCE is set]
+    --theme <PRESET>        Colors for a dark or light terminal background, with any
+                            set in the config file's [theme] table on top [default:
+                            dark, or the preset set there]
+    --lang <LANG>           Language for report labels and times: en, de, fr or ja
+                            [default: en, or the config file's `lang`]
+    --sort <KEY>            Order the rows of a plain query, --search, --fc-chest,
+                            --saddlebag and --csv, --json or --format output by
+                            quantity, name, character or container [default: as
+                            InventoryTools lists them]
+    --desc                  Sort from the highest or last first
+    --include-glamour       Count the glamour dresser and armoire in queries, --search,
+                            --repl, --needs and --value, tagging them `(dresser)`
+    --character <NAME>      Only report on characters or retainers whose name contains
+                            NAME, or with the raw id NAME; may be repeated
+    --world <NAME|ID>       Only report on characters from a world; may be repeated
+    --category <NAME>       Only report on items in a category: weapons, armor,
+                            accessories, materials, consumables, crystals, currency,
+                            materia, furnishings or miscellany
+    --item-table <PATH>     CSV of item ids and names [default: items.csv in the
+                            ffxiv-timers config directory]
+    --world-table <PATH>    CSV of world ids and names overriding the built-in list
+                            [default: worlds.csv in the ffxiv-timers config directory]
+    --config <PATH>         Shared settings file, with the other settings and tables
+                            read from beside it [default: config.toml in the
+                            ffxiv-timers config directory]
+    --profile <LABEL>       Only read the config file's profile with this label, instead
+                            of reporting on each profile in turn
+    -v, --verbose           Log each file read, when the InventoryTools files were last
+                            written, and how many rows belong to characters it hasn't
+                            saved a name for
+    -q, --quiet             Only log errors, not warnings; RUST_LOG overrides both
+    -h, --help              Print this help
+
+Exit status is 1 on errors, and 2 when the InventoryTools files are missing.
+With --check it's 0 or 1 for the report as above, and 3 on any error. Only
+the report is printed to stdout; warnings and errors go to stderr.
+";
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Query,
+    Search,
+    Repl,
+    Needs,
+    Ventures,
+    Slots,
+    Armoury,
+    Listings,
+    Orphans,
+    Saddlebag,
+    Top,
+    Spiritbond,
+    Repair,
+    Materia,
+    Currency,
+    Value,
+    Wealth,
+    Dyes,
+    Glamours,
+    FcChest,
+    Dupes,
+    Reducible,
+    Desynth,
+    Consolidate,
+    Gearsets,
+    GearsetName,
+    Snapshot,
+    Diff,
+    History,
+    Burn,
+}
+
+/// How a report is printed.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Output {
+    Text,
+    Csv,
+    Json,
+    Format,
+}
+
+impl Mode {
+    /// The report's name in `--json` output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Query => "query",
+            Mode::Search => "search",
+            Mode::Repl => "repl",
+            Mode::Needs => "needs",
+            Mode::Ventures => "ventures",
+            Mode::Slots => "slots",
+            Mode::Armoury => "armoury",
+            Mode::Listings => "listings",
+            Mode::Orphans => "orphans",
+            Mode::Saddlebag => "saddlebag",
+            Mode::Top => "top",
+            Mode::Spiritbond => "spiritbond",
+            Mode::Repair => "repair",
+            Mode::Materia => "materia",
+            Mode::Currency => "currency",
+            Mode::Value => "value",
+            Mode::Wealth => "wealth",
+            Mode::Dyes => "dyes",
+            Mode::Glamours => "glamours",
+            Mode::FcChest => "fc-chest",
+            Mode::Dupes => "dupes",
+            Mode::Reducible => "reducible",
+            Mode::Desynth => "desynth",
+            Mode::Consolidate => "consolidate",
+            Mode::Gearsets => "gearsets",
+            Mode::GearsetName => "gearset-name",
+            Mode::Snapshot => "snapshot",
+            Mode::Diff => "diff",
+            Mode::History => "history",
+            Mode::Burn => "burn-rate",
+        }
+    }
+
+    /// Whether the report is about having items to hand, so the glamour
+    /// dresser and armoire are left out without `--include-glamour`.
+    pub fn hides_glamour(self) -> bool {
+        matches!(
+            self,
+            Mode::Query | Mode::Search | Mode::Repl | Mode::Needs | Mode::Value | Mode::Wealth
+        )
+    }
+
+    /// Whether the report's rows map onto the `--csv`, `--json` and
+    /// `--format` items.
+    fn lists_items(self) -> bool {
+        matches!(
+            self,
+            Mode::Query
+                | Mode::Search
+                | Mode::Currency
+                | Mode::Listings
+                | Mode::Saddlebag
+                | Mode::FcChest
+                | Mode::Gearsets
+        )
+    }
+}
+
+pub struct Args {
+    pub mode: Mode,
+    /// The flag that selected `mode`, for error messages
+    mode_flag: String,
+    /// The flag that selected `output`, likewise
+    output_flag: String,
+    pub items: Vec<u32>,
+    /// Items given by name, looked up once the item table is loaded
+    pub item_names: Vec<String>,
+    pub combine_quality: bool,
+    pub locations: bool,
+    pub min_qty: u32,
+    /// Worth below which `--wealth` sums characters into one row
+    pub min_gil: u64,
+    pub search: Vec<String>,
+    pub limit: usize,
+    pub needs: PathBuf,
+    pub full_warn: f64,
+    pub top: usize,
+    pub top_by_quantity: bool,
+    pub spiritbond_at: f64,
+    pub repair_below: f64,
+    pub desynth_below: Option<u32>,
+    pub gearset_item: String,
+    pub gearset_name: String,
+    pub snapshot: PathBuf,
+    pub diff: (PathBuf, PathBuf),
+    pub include_moves: bool,
+    pub log_items: Vec<u32>,
+    pub history_item: String,
+    pub history_days: u32,
+    pub item_table: Option<PathBuf>,
+    pub world_table: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub characters: CharacterFilter,
+    pub category: Option<Category>,
+    pub exclude_orphans: bool,
+    pub include_glamour: bool,
+    pub sort: Option<Sort>,
+    pub output: Output,
+    /// The templates for [`Output::Format`]
+    pub format: Option<RowFormat>,
+    pub color: Option<ColorWhen>,
+    pub watch: bool,
+    pub check: bool,
+    /// Keep each plain query's rows, to show when the files can't be read
+    pub fallback_cache: bool,
+    pub verbose: bool,
+    pub quiet: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            mode: Mode::Query,
+            mode_flag: String::new(),
+            output_flag: String::new(),
+            items: vec![],
+            item_names: vec![],
+            combine_quality: false,
+            locations: false,
+            min_qty: 0,
+            min_gil: 0,
+            search: vec![],
+            limit: 25,
+            needs: PathBuf::new(),
+            full_warn: 90.0,
+            top: 20,
+            top_by_quantity: false,
+            spiritbond_at: 100.0,
+            repair_below: 50.0,
+            desynth_below: None,
+            gearset_item: String::new(),
+            gearset_name: String::new(),
+            snapshot: PathBuf::new(),
+            diff: Default::default(),
+            include_moves: false,
+            log_items: vec![],
+            history_item: String::new(),
+            history_days: 30,
+            item_table: None,
+            world_table: None,
+            config: None,
+            characters: CharacterFilter::default(),
+            category: None,
+            exclude_orphans: false,
+            include_glamour: false,
+            sort: None,
+            output: Output::Text,
+            format: None,
+            color: None,
+            watch: false,
+            check: false,
+            fallback_cache: false,
+            verbose: false,
+            quiet: false,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the arguments after the program name. `--json` given to the
+    /// `ffxiv-timers` binary counts as if it had been given here.
+    pub fn parse_from(
+        args: impl IntoIterator<Item = String>,
+        options: &Options,
+    ) -> anyhow::Result<Args> {
+        let mut parsed = Args {
+            verbose: options.verbose,
+            quiet: options.quiet,
+            check: options.check,
+            fallback_cache: options.fallback_cache,
+            ..Args::default()
+        };
+        if options.json {
+            parsed.set_output(Output::Json, "--json")?;
+        }
+        // Only the templates are wanted, checked once every flag is in
+        let mut format = Options {
+            format: options.format.clone(),
+            format_header: options.format_header.clone(),
+            format_footer: options.format_footer.clone(),
+            ..Options::default()
+        };
+        if format.format.is_some() {
+            parsed.set_output(Output::Format, "--format")?;
+        }
+        let mut desc = false;
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            // Allow `--flag=value` as well as `--flag value`
+            let (flag, mut inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .take()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow!("{flag} requires a value"))
+            };
+
+            match &*flag {
+                "--combine-quality" => parsed.combine_quality = true,
+                "--locations" => parsed.locations = true,
+                "--min-qty" => parsed.min_qty = parse_value(&flag, value()?)?,
+                "--search" => {
+                    parsed.set_mode(Mode::Search, &flag)?;
+                    parsed.search.push(value()?);
+                }
+                "--limit" => parsed.limit = parse_value(&flag, value()?)?,
+                "--repl" => parsed.set_mode(Mode::Repl, &flag)?,
+                "--needs" => {
+                    parsed.set_mode(Mode::Needs, &flag)?;
+                    parsed.needs = value()?.into();
+                }
+                "--ventures" => parsed.set_mode(Mode::Ventures, &flag)?,
+                "--slots" => parsed.set_mode(Mode::Slots, &flag)?,
+                "--armoury" => parsed.set_mode(Mode::Armoury, &flag)?,
+                "--listings" => parsed.set_mode(Mode::Listings, &flag)?,
+                "--orphans" => parsed.set_mode(Mode::Orphans, &flag)?,
+                "--exclude-orphans" => parsed.exclude_orphans = true,
+                "--saddlebag" => parsed.set_mode(Mode::Saddlebag, &flag)?,
+                "--top" | "--top-quantity" => {
+                    parsed.set_mode(Mode::Top, &flag)?;
+                    parsed.top_by_quantity |= flag == "--top-quantity";
+                    // The count is optional, so only a number is taken as one
+                    let count = match inline_value.take() {
+                        Some(count) => Some(count),
+                        None => args.next_if(|arg| arg.parse::<usize>().is_ok()),
+                    };
+                    if let Some(count) = count {
+                        parsed.top = parse_value(&flag, count)?;
+                    }
+                }
+                "--spiritbond" => parsed.set_mode(Mode::Spiritbond, &flag)?,
+                "--spiritbond-at" => parsed.spiritbond_at = parse_value(&flag, value()?)?,
+                "--repair" => parsed.set_mode(Mode::Repair, &flag)?,
+                "--repair-below" => parsed.repair_below = parse_value(&flag, value()?)?,
+                "--materia" => parsed.set_mode(Mode::Materia, &flag)?,
+                "--currency" => parsed.set_mode(Mode::Currency, &flag)?,
+                "--value" => parsed.set_mode(Mode::Value, &flag)?,
+                "--wealth" => parsed.set_mode(Mode::Wealth, &flag)?,
+                "--min" => parsed.min_gil = parse_value(&flag, value()?)?,
+                "--dyes" => parsed.set_mode(Mode::Dyes, &flag)?,
+                "--glamours" => parsed.set_mode(Mode::Glamours, &flag)?,
+                "--fc-chest" => parsed.set_mode(Mode::FcChest, &flag)?,
+                "--dupes" => parsed.set_mode(Mode::Dupes, &flag)?,
+                "--reducible" => parsed.set_mode(Mode::Reducible, &flag)?,
+                "--desynth" => {
+                    parsed.set_mode(Mode::Desynth, &flag)?;
+                    // The item level is optional, so only a number is taken as one
+                    let ilvl = match inline_value.take() {
+                        Some(ilvl) => Some(ilvl),
+                        None => args.next_if(|arg| arg.parse::<u32>().is_ok()),
+                    };
+                    if let Some(ilvl) = ilvl {
+                        parsed.desynth_below = Some(parse_value(&flag, ilvl)?);
+                    }
+                }
+                "--consolidate" => parsed.set_mode(Mode::Consolidate, &flag)?,
+                "--gearsets" => {
+                    parsed.set_mode(Mode::Gearsets, &flag)?;
+                    parsed.gearset_item = value()?;
+                }
+                "--gearset-name" => {
+                    parsed.set_mode(Mode::GearsetName, &flag)?;
+                    parsed.gearset_name = value()?;
+                }
+                "--snapshot" => {
+                    parsed.set_mode(Mode::Snapshot, &flag)?;
+                    parsed.snapshot = value()?.into();
+                }
+                "--diff" => {
+                    parsed.set_mode(Mode::Diff, &flag)?;
+                    parsed.diff = (value()?.into(), value()?.into());
+                }
+                "--include-moves" => parsed.include_moves = true,
+                "--log-items" => {
+                    for id in value()?.split(',') {
+                        parsed
+                            .log_items
+                            .push(parse_value(&flag, id.trim().to_string())?);
+                    }
+                }
+                "--history" => {
+                    parsed.set_mode(Mode::History, &flag)?;
+                    parsed.history_item = value()?;
+                    // The window is optional, so only a number is taken as one
+                    if let Some(days) = args.next_if(|arg| arg.parse::<u32>().is_ok()) {
+                        parsed.history_days = parse_value(&flag, days)?;
+                    }
+                }
+                "--burn-rate" => {
+                    parsed.set_mode(Mode::Burn, &flag)?;
+                    parsed.history_item = value()?;
+                    if let Some(days) = args.next_if(|arg| arg.parse::<u32>().is_ok()) {
+                        parsed.history_days = parse_value(&flag, days)?;
+                    }
+                }
+                "--character" => parsed.characters.characters.push(value()?),
+                "--world" => parsed.characters.worlds.push(value()?),
+                "--category" => {
+                    let name = value()?;
+                    let Some(category) = Category::parse(&name) else {
+                        let known = Category::names().collect::<Vec<_>>().join(", ");
+                        bail!("unknown category `{name}`; expected one of {known}");
+                    };
+                    parsed.category = Some(category);
+                }
+                "--include-glamour" => parsed.include_glamour = true,
+                "--sort" => {
+                    let name = value()?;
+                    let Some(key) = SortKey::parse(&name) else {
+                        let known = SortKey::names().collect::<Vec<_>>().join(", ");
+                        bail!("unknown sort key `{name}`; expected one of {known}");
+                    };
+                    // --desc may come either side of it
+                    parsed.sort = Some(Sort { key, desc: false });
+                }
+                "--desc" => desc = true,
+                "--csv" => parsed.set_output(Output::Csv, &flag)?,
+                "--json" => parsed.set_output(Output::Json, &flag)?,
+                "--format" => {
+                    parsed.set_output(Output::Format, &flag)?;
+                    format.format = Some(value()?);
+                }
+                "--format-header" => format.format_header = Some(value()?),
+                "--format-footer" => format.format_footer = Some(value()?),
+                "--watch" => parsed.watch = true,
+                "--check" => parsed.check = true,
+                "--fallback-cache" => parsed.fallback_cache = true,
+                "--no-pager" => pager::disable(),
+                "--no-cache" => read_cache::disable(),
+                "--color" => parsed.color = Some(value()?.parse()?),
+                "--theme" => theme::set_preset(value()?.parse()?),
+                "--lang" => i18n::set_lang(value()?.parse()?),
+                "--item-table" => parsed.item_table = Some(value()?.into()),
+                "--world-table" => parsed.world_table = Some(value()?.into()),
+                "--config" => parsed.config = Some(value()?.into()),
+                "--profile" => profiles::select(value()?),
+                "--full-warn" => parsed.full_warn = parse_value(&flag, value()?)?,
+                "-v" | "--verbose" => parsed.verbose = true,
+                "-q" | "--quiet" => parsed.quiet = true,
+                "-h" | "--help" => {
+                    print!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ if !flag.starts_with('-') => match flag.parse() {
+                    Ok(id) => parsed.items.push(id),
+                    Err(_) => parsed.item_names.push(flag),
+                },
+                _ => bail!("unrecognized argument `{flag}`\n\n{USAGE}"),
+            }
+        }
+        if parsed.watch
+            && matches!(
+                parsed.mode,
+                Mode::Diff | Mode::Repl | Mode::History | Mode::Burn
+            )
+        {
+            bail!("--watch can't be used with {}", parsed.mode_flag);
+        }
+        if parsed.verbose && parsed.quiet {
+            bail!("--verbose can't be combined with --quiet");
+        }
+        match &mut parsed.sort {
+            Some(sort) => sort.desc = desc,
+            None if desc => bail!("--desc needs --sort"),
+            None => {}
+        }
+        let sorted = matches!(
+            parsed.mode,
+            Mode::Query | Mode::Search | Mode::FcChest | Mode::Saddlebag
+        );
+        if parsed.sort.is_some() && parsed.output == Output::Text && !sorted {
+            bail!("--sort can't be used with {}", parsed.mode_flag);
+        }
+        if parsed.locations && parsed.mode != Mode::Query {
+            bail!("--locations can't be combined with {}", parsed.mode_flag);
+        }
+        if parsed.locations && parsed.output != Output::Text {
+            bail!("--locations can't be combined with {}", parsed.output_flag);
+        }
+        if parsed.min_qty > 0 && !parsed.locations {
+            bail!("--min-qty only applies to --locations");
+        }
+        if parsed.min_gil > 0 && parsed.mode != Mode::Wealth {
+            bail!("--min only applies to --wealth");
+        }
+        if parsed.exclude_orphans && parsed.mode == Mode::Orphans {
+            bail!("--exclude-orphans can't be used with --orphans");
+        }
+        parsed.format = format.row_format(FORMAT_FIELDS)?;
+        if parsed.output != Output::Text && !parsed.mode.lists_items() {
+            bail!(
+                "{} can't be used with {}; it only applies to reports that list \
+                 individual items",
+                parsed.output_flag,
+                parsed.mode_flag
+            );
+        }
+        Ok(parsed)
+    }
+
+    fn set_mode(&mut self, mode: Mode, flag: &str) -> anyhow::Result<()> {
+        if self.mode != Mode::Query && self.mode != mode {
+            bail!("{flag} can't be combined with another report mode");
+        }
+        self.mode = mode;
+        self.mode_flag = flag.to_string();
+        Ok(())
+    }
+
+    fn set_output(&mut self, output: Output, flag: &str) -> anyhow::Result<()> {
+        if self.output != Output::Text && self.output != output {
+            bail!("{flag} can't be combined with {}", self.output_flag);
+        }
+        self.output = output;
+        self.output_flag = flag.to_string();
+        Ok(())
+    }
+}
+
+fn parse_value<T>(flag: &str, value: String) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .with_context(|| format!("invalid value `{value}` for {flag}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Args> {
+        Args::parse_from(args.iter().map(|arg| arg.to_string()), &Options::default())
+    }
+
+    #[test]
+    fn every_documented_flag_is_accepted() {
+        let flags = USAGE
+            .lines()
+            .filter_map(|line| line.trim_start().split([' ', ',']).next())
+            .filter(|flag| flag.starts_with("--") && *flag != "--help");
+        for flag in flags {
+            // Other errors, like a missing value, still mean it was recognized
+            if let Err(err) = parse(&[flag]) {
+                assert!(
+                    !err.to_string().starts_with("unrecognized argument"),
+                    "{flag} is documented but not accepted"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, ContainerGroup},
+    inventory::InventoryItem,
+    MetaConfig,
+};
+
+/// Used and free slots in every armoury chest section, per character.
+/// Sections at or above `full_warn` percent are highlighted, and full ones
+/// shown in red since they stop new gear from being picked up.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    items: &[InventoryItem],
+    full_warn: f64,
+) -> anyhow::Result<()> {
+    // character id -> container id -> used slots
+    let mut used: BTreeMap<u64, BTreeMap<u32, u32>> = BTreeMap::new();
+    for item in items {
+        let is_armoury = containers::lookup(item.container)
+            .is_some_and(|container| container.group == ContainerGroup::Armoury);
+        if !is_armoury {
+            continue;
+        }
+        *used
+            .entry(item.character_id)
+            .or_default()
+            .entry(item.container)
+            .or_default() += u32::from(item.item_id != 0);
+    }
+
+    let sections = containers::all()
+        .iter()
+        .filter(|container| container.group == ContainerGroup::Armoury)
+        .collect::<Vec<_>>();
+    let max_label_len = sections
+        .iter()
+        .map(|container| container.section.unwrap_or_default().len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Armoury Chest")?;
+    for (character, used) in used {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        for container in &sections {
+            let used = used.get(&container.id).copied().unwrap_or(0);
+            let percent = f64::from(used) * 100.0 / f64::from(container.capacity);
+            let color = if used >= container.capacity {
+                theme::error()
+            } else if percent >= full_warn {
+                theme::warn()
+            } else {
+                theme::good()
+            };
+            stdout.set_color(&color)?;
+            writeln!(
+                stdout,
+                "        {label:<max_label_len$} {used:>4}/{capacity} ({free} free)",
+                label = container.section.unwrap_or_default(),
+                capacity = container.capacity,
+                free = container.capacity.saturating_sub(used),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, container_name, ContainerGroup},
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    MetaConfig,
+};
+
+/// Quantity of one item held by a character and their retainers, and where.
+#[derive(Default)]
+struct Candidate {
+    quantity: u32,
+    locations: BTreeSet<String>,
+}
+
+/// Whether items in the container can be broken down without first moving
+/// them somewhere. Equipped gear and the glamour dresser are left alone.
+fn is_loose(id: u32) -> bool {
+    containers::lookup(id).is_some_and(|container| {
+        matches!(
+            container.group,
+            ContainerGroup::Bags
+                | ContainerGroup::Armoury
+                | ContainerGroup::Saddlebag
+                | ContainerGroup::PremiumSaddlebag
+                | ContainerGroup::RetainerBags
+        )
+    })
+}
+
+/// Collectables in bags and retainers that can be aetherially reduced.
+pub fn reducible_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let candidates = items
+        .iter()
+        .filter(|item| item.quality() == Quality::Collectable && table.is_reducible(item.item_id));
+    report(stdout, conf, table, candidates, "Reducible Collectables")
+}
+
+/// Desynthesizable gear in bags, the armoury and retainers, optionally only
+/// under an item level.
+pub fn desynth_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    below: Option<u32>,
+) -> anyhow::Result<()> {
+    let candidates = items.iter().filter(|item| {
+        let under = match (below, table.item_level(item.item_id)) {
+            (Some(below), Some(ilvl)) => ilvl < below,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        under && table.can_desynth(item.item_id)
+    });
+    let title = match below {
+        Some(below) => format!("Desynthesis Candidates below ilvl {below}"),
+        None => "Desynthesis Candidates".to_string(),
+    };
+    report(stdout, conf, table, candidates, &title)
+}
+
+/// Lists candidates per character, most plentiful first.
+fn report<'a>(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    candidates: impl Iterator<Item = &'a InventoryItem>,
+    title: &str,
+) -> anyhow::Result<()> {
+    // owner id -> item id -> candidate
+    let mut by_owner: BTreeMap<u64, BTreeMap<u32, Candidate>> = BTreeMap::new();
+    for item in candidates {
+        if !is_loose(item.container) {
+            continue;
+        }
+        let owner = conf.owner_of(item.character_id);
+        let candidate = by_owner
+            .entry(owner)
+            .or_default()
+            .entry(item.item_id)
+            .or_default();
+        candidate.quantity += item.quantity;
+        candidate
+            .locations
+            .insert(match item.character_id == owner {
+                true => container_name(item.container),
+                false => format!(
+                    "{} ({})",
+                    conf.holder_name(item.character_id),
+                    container_name(item.container)
+                ),
+            });
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "{title}")?;
+    if by_owner.is_empty() {
+        stdout.set_color(&theme::good())?;
+        writeln!(stdout, "    Nothing to break down")?;
+        return Ok(());
+    }
+
+    let max_name_len = by_owner
+        .values()
+        .flat_map(|candidates| candidates.keys())
+        .map(|&id| table.name(id).len())
+        .max()
+        .unwrap_or(0);
+
+    for (owner, candidates) in by_owner {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(owner))?;
+        stdout.set_color(&theme::good())?;
+        let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, candidate)| std::cmp::Reverse(candidate.quantity));
+        for (item_id, candidate) in candidates {
+            let locations = candidate.locations.into_iter().collect::<Vec<_>>();
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} {quantity:>5}  {locations}",
+                name = table.name(item_id),
+                quantity = candidate.quantity,
+                locations = locations.join(", "),
+            )?;
+        }
+    }
+
+    Ok(())
+}
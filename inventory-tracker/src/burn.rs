@@ -0,0 +1,205 @@
+use chrono::{DateTime, Duration, Utc};
+use ffxiv_timers_core::{clock, json::FreeCompanyReport, theme};
+use termcolor::WriteColor;
+
+use crate::{history::Record, items::ItemTable};
+
+/// How much faster than expected an item can be used before it's flagged,
+/// allowing for the odd venture spent by hand.
+const TOLERANCE: f64 = 1.1;
+
+/// What else was going on over the window, to compare the trend with.
+#[derive(Default)]
+pub struct Expected {
+    /// Retainers, and how many of the item each uses a day, when the item
+    /// is one retainers use
+    pub retainers: Option<(u32, f64)>,
+    /// Submarine voyages that came back in the window, when the database
+    /// could be read
+    pub voyages: Option<usize>,
+}
+
+/// How many of `fcs`' submarines came back between `since` and `now`.
+/// SubmarineTracker keeps only each submarine's latest voyage, so this is
+/// at most one per submarine.
+pub fn voyages(fcs: &[FreeCompanyReport], since: DateTime<Utc>, now: DateTime<Utc>) -> usize {
+    fcs.iter()
+        .flat_map(|fc| &fc.submarines)
+        .filter_map(|sub| sub.return_time)
+        .filter(|&time| since <= time && time <= now)
+        .count()
+}
+
+/// The slope of the straight line best fitting `points`, in units a day,
+/// or `None` without two points logged at different times.
+fn per_day(points: &[(DateTime<Utc>, u64)]) -> Option<f64> {
+    let first = points.first()?.0;
+    let xs = points
+        .iter()
+        .map(|&(at, _)| (at - first).num_seconds() as f64 / 86400.0)
+        .collect::<Vec<_>>();
+    let count = points.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / count;
+    let mean_y = points.iter().map(|&(_, total)| total as f64).sum::<f64>() / count;
+    let (mut sxx, mut sxy) = (0.0, 0.0);
+    for (x, &(_, total)) in xs.iter().zip(points) {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (total as f64 - mean_y);
+    }
+    (sxx > 0.0).then(|| sxy / sxx)
+}
+
+/// When `left` runs out, using `per_day` from `at`.
+fn runs_out(left: u64, per_day: f64, at: DateTime<Utc>) -> DateTime<Utc> {
+    at + Duration::seconds((left as f64 / per_day * 86400.0) as i64)
+}
+
+/// How fast an item's logged total has gone down over the last `days`
+/// days, when it would run out at that rate, and what the retainers and
+/// submarines would account for.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    table: &ItemTable,
+    records: &[Record],
+    item_id: u32,
+    days: u32,
+    expected: &Expected,
+) -> anyhow::Result<()> {
+    let since = clock::now() - Duration::days(days.into());
+    let points = records
+        .iter()
+        .filter(|record| record.at >= since)
+        .filter_map(|record| Some((record.at, *record.totals.get(&item_id)?)))
+        .collect::<Vec<_>>();
+
+    stdout.set_color(&theme::header())?;
+    writeln!(
+        stdout,
+        "{} - burn rate over the last {days} days",
+        table.name(item_id)
+    )?;
+    stdout.set_color(&theme::good())?;
+    let (Some(&(first_at, _)), Some(&(last_at, latest))) = (points.first(), points.last()) else {
+        writeln!(
+            stdout,
+            "    Nothing logged; track it with --log-items {item_id}"
+        )?;
+        return Ok(());
+    };
+    let day = |at: DateTime<Utc>| at.format("%Y-%m-%d").to_string();
+    let Some(slope) = per_day(&points) else {
+        writeln!(
+            stdout,
+            "    Only {latest} logged on {}; a trend needs totals from at least two runs",
+            day(last_at)
+        )?;
+        return Ok(());
+    };
+    writeln!(
+        stdout,
+        "    {} totals logged, {} to {}",
+        points.len(),
+        day(first_at),
+        day(last_at)
+    )?;
+
+    let used = -slope;
+    if used <= 0.0 {
+        writeln!(
+            stdout,
+            "    Not being used up: {latest} held, going up {:.1} a day",
+            slope
+        )?;
+    } else if latest == 0 {
+        stdout.set_color(&theme::error())?;
+        writeln!(stdout, "    Using {used:.1} a day, and none are left")?;
+    } else {
+        writeln!(
+            stdout,
+            "    Using {used:.1} a day; the {latest} left run out around {}",
+            day(runs_out(latest, used, last_at))
+        )?;
+    }
+
+    stdout.set_color(&theme::dim())?;
+    if let Some((retainers, each)) = expected.retainers {
+        let expected_use = f64::from(retainers) * each;
+        writeln!(
+            stdout,
+            "    Retainers would use {expected_use:.1} a day ({retainers} at {each} a day each)"
+        )?;
+        if used > expected_use * TOLERANCE {
+            stdout.set_color(&theme::warn())?;
+            writeln!(
+                stdout,
+                "    That's {:.1} a day more than the retainers account for",
+                used - expected_use
+            )?;
+            stdout.set_color(&theme::dim())?;
+        }
+    }
+    if let Some(voyages) = expected.voyages {
+        let plural = if voyages == 1 { "" } else { "s" };
+        write!(
+            stdout,
+            "    Submarines: {voyages} voyage{plural} came back in this window"
+        )?;
+        if voyages > 0 && used > 0.0 {
+            let per_voyage =
+                used * (last_at - first_at).num_seconds() as f64 / 86400.0 / voyages as f64;
+            write!(stdout, ", or {per_voyage:.1} used for each")?;
+        }
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ffxiv_timers_core::json::SubmarineReport;
+
+    use super::*;
+
+    #[test]
+    fn the_trend_needs_two_times() {
+        let now = Utc::now();
+        assert_eq!(per_day(&[]), None);
+        assert_eq!(per_day(&[(now, 50)]), None);
+        assert_eq!(per_day(&[(now, 50), (now, 40)]), None);
+
+        let day = Duration::days(1);
+        let steady = [(now, 100), (now + day, 90), (now + day * 3, 70)];
+        assert!((per_day(&steady).unwrap() + 10.0).abs() < 1e-9);
+        // A refill between runs pulls the line up rather than breaking it
+        let refilled = [(now, 100), (now + day, 80), (now + day * 2, 160)];
+        assert!(per_day(&refilled).unwrap() > 0.0);
+        assert_eq!(runs_out(70, 10.0, now), now + day * 7);
+    }
+
+    #[test]
+    fn only_voyages_back_within_the_window_count() {
+        let now = Utc::now();
+        let sub = |hours: i64| SubmarineReport {
+            name: "Nautilus".to_string(),
+            return_time: Some(now + Duration::hours(hours)),
+            label: None,
+        };
+        let fc = FreeCompanyReport {
+            character: "Alice Doe".to_string(),
+            tag: "MOGS".to_string(),
+            world: "Tonberry".to_string(),
+            submarines: vec![
+                sub(-30),
+                sub(-2),
+                sub(5),
+                SubmarineReport {
+                    return_time: None,
+                    ..sub(0)
+                },
+            ],
+            profile: None,
+        };
+        assert_eq!(voyages(&[fc], now - Duration::days(1), now), 1);
+        assert_eq!(voyages(&[], now - Duration::days(1), now), 0);
+    }
+}
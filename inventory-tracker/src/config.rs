@@ -0,0 +1,57 @@
+use std::fs::read_to_string;
+
+use anyhow::Context;
+use ffxiv_timers_core::paths;
+use serde::Deserialize;
+
+const CONFIG_FILE: &str = "inventory-tracker.json";
+
+/// Optional user settings, read from `inventory-tracker.json` in the
+/// ffxiv-timers config directory.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Currencies to report in addition to the built-in set
+    pub currencies: Vec<CurrencyConfig>,
+    /// World name or id to price items on for `--value`, instead of each
+    /// character's own world
+    pub price_world: Option<String>,
+    /// How many hours fetched market prices are reused for [default: 6]
+    pub price_cache_hours: Option<f64>,
+    /// Ventures each retainer uses per day for `--ventures` [default: 12]
+    pub ventures_per_retainer_day: Option<f64>,
+    /// Retainers every character is assumed to have for `--ventures`,
+    /// instead of counting those InventoryTools has seen
+    pub retainer_count: Option<u32>,
+    /// Item level `--desynth` lists gear below when none is given
+    pub desynth_below: Option<u32>,
+    /// Days apart the inventory CSV and meta config can be modified before
+    /// their names are suspected to be stale [default: 7]
+    pub sync_warn_days: Option<f64>,
+    /// Items whose totals are logged on every run, as with `--log-items`
+    pub log_items: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct CurrencyConfig {
+    pub id: u32,
+    /// Overrides the item table name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Weekly acquisition cap, for the current patch's capped tomestone
+    #[serde(default)]
+    pub weekly_cap: Option<u32>,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Config> {
+        let Some(path) = paths::config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = read_to_string(&path).with_context(|| format!("Failed to open {path:?}"))?;
+        serde_json::from_str(&data).with_context(|| format!("Failed to parse {path:?}"))
+    }
+}
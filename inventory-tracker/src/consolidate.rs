@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, ContainerGroup},
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    slots::counts_toward_slots,
+    MetaConfig,
+};
+
+struct Partial {
+    stacks: u32,
+    quantity: u32,
+    holders: Vec<u64>,
+}
+
+/// Items split over several partial stacks that one character could merge,
+/// whether in their own bags or across their retainers.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut partials: BTreeMap<(u64, u32, Quality), Partial> = BTreeMap::new();
+    for item in items {
+        let Some(container) = containers::lookup(item.container) else {
+            continue;
+        };
+        // The glamour dresser holds one item per slot regardless
+        if !counts_toward_slots(container.group) || container.group == ContainerGroup::GlamourChest
+        {
+            continue;
+        }
+        if item.item_id == 0 || item.quantity >= table.stack_size(item.item_id) {
+            continue;
+        }
+        let partial = partials
+            .entry((
+                conf.owner_of(item.character_id),
+                item.item_id,
+                item.quality(),
+            ))
+            .or_insert_with(|| Partial {
+                stacks: 0,
+                quantity: 0,
+                holders: vec![],
+            });
+        partial.stacks += 1;
+        partial.quantity += item.quantity;
+        if !partial.holders.contains(&item.character_id) {
+            partial.holders.push(item.character_id);
+        }
+    }
+
+    let mut suggestions = partials
+        .into_iter()
+        .filter_map(|((owner, item_id, quality), partial)| {
+            let stack_size = table.stack_size(item_id);
+            let needed = partial.quantity.div_ceil(stack_size);
+            let saved = partial.stacks - needed;
+            (saved > 0).then_some((saved, owner, item_id, quality, partial))
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort_by(|a, b| b.0.cmp(&a.0).then((a.1, a.2, a.3).cmp(&(b.1, b.2, b.3))));
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Stacks to Consolidate")?;
+    stdout.set_color(&theme::good())?;
+    if suggestions.is_empty() {
+        writeln!(stdout, "    Nothing to merge")?;
+        return Ok(());
+    }
+
+    for (saved, _, item_id, quality, partial) in suggestions {
+        let quality = match quality {
+            Quality::Normal => "",
+            Quality::High => " HQ",
+            Quality::Collectable => " (collectable)",
+        };
+        let holders = partial
+            .holders
+            .iter()
+            .map(|&holder| conf.holder_name(holder))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let slots = if saved == 1 { "slot" } else { "slots" };
+        writeln!(
+            stdout,
+            "    {name}{quality}: {stacks} stacks of {quantity}/{stack_size} across {holders} - consolidate to save {saved} {slots}",
+            name = table.name(item_id),
+            stacks = partial.stacks,
+            quantity = partial.quantity,
+            stack_size = table.stack_size(item_id),
+        )?;
+    }
+
+    Ok(())
+}
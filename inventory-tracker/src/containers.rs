@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+
+use ffxiv_timers_core::gamedata;
+use serde::{
+    de::{value::StrDeserializer, IntoDeserializer},
+    Deserialize,
+};
+use tracing::warn;
+
+/// Which logical inventory a container page belongs to. Pages of the same
+/// group are counted together when reporting slot usage.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerGroup {
+    Bags,
+    Equipped,
+    Currency,
+    Crystals,
+    Armoury,
+    Saddlebag,
+    PremiumSaddlebag,
+    RetainerBags,
+    RetainerEquipped,
+    RetainerGil,
+    RetainerCrystals,
+    RetainerMarket,
+    FreeCompanyChest,
+    FreeCompanyGil,
+    FreeCompanyCrystals,
+    GlamourChest,
+    Armoire,
+}
+
+impl ContainerGroup {
+    pub fn label(self) -> &'static str {
+        match self {
+            ContainerGroup::Bags => "Bags",
+            ContainerGroup::Equipped => "Equipped",
+            ContainerGroup::Currency => "Currency",
+            ContainerGroup::Crystals => "Crystals",
+            ContainerGroup::Armoury => "Armoury Chest",
+            ContainerGroup::Saddlebag => "Saddlebag",
+            ContainerGroup::PremiumSaddlebag => "Premium Saddlebag",
+            ContainerGroup::RetainerBags => "Retainer",
+            ContainerGroup::RetainerEquipped => "Retainer Equipped",
+            ContainerGroup::RetainerGil => "Retainer Gil",
+            ContainerGroup::RetainerCrystals => "Retainer Crystals",
+            ContainerGroup::RetainerMarket => "Retainer Market",
+            ContainerGroup::FreeCompanyChest => "FC Chest",
+            ContainerGroup::FreeCompanyGil => "FC Gil",
+            ContainerGroup::FreeCompanyCrystals => "FC Crystals",
+            ContainerGroup::GlamourChest => "Glamour Dresser",
+            ContainerGroup::Armoire => "Armoire",
+        }
+    }
+}
+
+pub struct Container {
+    pub id: u32,
+    pub group: ContainerGroup,
+    pub capacity: u32,
+    /// Names pages that hold one kind of item, like the armoury chest's
+    pub section: Option<&'static str>,
+}
+
+/// Every container page in the game data, in its order.
+pub fn all() -> &'static [Container] {
+    static CONTAINERS: OnceLock<Vec<Container>> = OnceLock::new();
+    CONTAINERS.get_or_init(|| {
+        gamedata::containers()
+            .rows()
+            .iter()
+            .filter_map(|row| {
+                let name: StrDeserializer<serde::de::value::Error> =
+                    row.group.as_str().into_deserializer();
+                let Ok(group) = ContainerGroup::deserialize(name) else {
+                    warn!(
+                        "Skipping container {} in unknown group `{}`",
+                        row.id, row.group
+                    );
+                    return None;
+                };
+                Some(Container {
+                    id: row.id,
+                    group,
+                    capacity: row.capacity,
+                    section: row.section.as_deref(),
+                })
+            })
+            .collect()
+    })
+}
+
+pub fn lookup(id: u32) -> Option<&'static Container> {
+    all().iter().find(|container| container.id == id)
+}
+
+/// Whether the container holds a retainer's market board listings.
+pub fn is_listing(id: u32) -> bool {
+    lookup(id).is_some_and(|container| container.group == ContainerGroup::RetainerMarket)
+}
+
+/// Whether the container is the glamour dresser or armoire. Their items are
+/// only there for appearance, so item searches and totals skip them unless
+/// asked not to.
+pub fn is_glamour_storage(id: u32) -> bool {
+    lookup(id).is_some_and(|container| {
+        matches!(
+            container.group,
+            ContainerGroup::GlamourChest | ContainerGroup::Armoire
+        )
+    })
+}
+
+pub fn group_capacity(group: ContainerGroup) -> u32 {
+    all()
+        .iter()
+        .filter(|container| container.group == group)
+        .map(|container| container.capacity)
+        .sum()
+}
+
+/// A display name for a single container page, e.g. "Saddlebag 2" or
+/// "Armoury Chest (Body)".
+pub fn container_name(id: u32) -> String {
+    let Some(container) = lookup(id) else {
+        return format!("Container #{id}");
+    };
+    if let Some(section) = container.section {
+        return format!("{} ({section})", container.group.label());
+    }
+    let pages = all()
+        .iter()
+        .filter(|other| other.group == container.group)
+        .collect::<Vec<_>>();
+    if pages.len() == 1 {
+        return container.group.label().to_string();
+    }
+    let page = pages.iter().position(|other| other.id == id).unwrap_or(0) + 1;
+    format!("{} {page}", container.group.label())
+}
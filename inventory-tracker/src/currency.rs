@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{config::Config, inventory::InventoryItem, items::ItemTable, MetaConfig};
+
+pub struct Currency {
+    pub id: u32,
+    pub name: String,
+    pub weekly_cap: Option<u32>,
+}
+
+/// Gil's item id.
+pub const GIL: u32 = 1;
+
+/// Currencies reported by default. The capped tomestone changes every
+/// patch, so its cap is expected to come from the config file.
+const BUILTIN_CURRENCIES: &[u32] = &[
+    GIL, 20,    // Storm Seal
+    21,    // Serpent Seal
+    22,    // Flame Seal
+    25,    // Wolf Mark
+    27,    // Allied Seal
+    28,    // Allagan Tomestone of Poetics
+    29,    // MGP
+    10307, // Centurio Seal
+    26533, // Sack of Nuts
+    26807, // Bicolor Gemstone
+];
+
+/// Portion of the weekly cap at which a currency is highlighted.
+const NEAR_CAP: f64 = 0.9;
+
+pub fn currencies(config: &Config, table: &ItemTable) -> Vec<Currency> {
+    let mut currencies = BUILTIN_CURRENCIES
+        .iter()
+        .map(|&id| Currency {
+            id,
+            name: table.name(id),
+            weekly_cap: None,
+        })
+        .collect::<Vec<_>>();
+    for extra in &config.currencies {
+        let currency = Currency {
+            id: extra.id,
+            name: extra.name.clone().unwrap_or_else(|| table.name(extra.id)),
+            weekly_cap: extra.weekly_cap,
+        };
+        match currencies.iter_mut().find(|other| other.id == extra.id) {
+            Some(existing) => *existing = currency,
+            None => currencies.push(currency),
+        }
+    }
+    currencies
+}
+
+/// How much of each of `currencies` every character and retainer holds,
+/// by holder id and then index into `currencies`.
+pub fn held(
+    currencies: &[Currency],
+    items: &[InventoryItem],
+) -> BTreeMap<u64, BTreeMap<usize, u32>> {
+    let mut by_character: BTreeMap<u64, BTreeMap<usize, u32>> = BTreeMap::new();
+    for item in items {
+        if let Some(index) = currencies.iter().position(|c| c.id == item.item_id) {
+            *by_character
+                .entry(item.character_id)
+                .or_default()
+                .entry(index)
+                .or_default() += item.quantity;
+        }
+    }
+    by_character
+}
+
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    currencies: &[Currency],
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let by_character = held(currencies, items);
+
+    let max_name_len = currencies
+        .iter()
+        .map(|currency| currency.name.len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Currencies")?;
+    for (character, amounts) in by_character {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        for (index, amount) in amounts {
+            let currency = &currencies[index];
+            let (color, display) = match currency.weekly_cap {
+                Some(cap) => {
+                    let near = f64::from(amount) >= f64::from(cap) * NEAR_CAP;
+                    let color = if near { theme::warn() } else { theme::good() };
+                    (color, format!("{amount}/{cap}"))
+                }
+                None => (theme::good(), amount.to_string()),
+            };
+            stdout.set_color(&color)?;
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} {display:>12}",
+                name = currency.name
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+use anyhow::{bail, Context};
+use ffxiv_timers_core::theme;
+use serde::Deserialize;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::container_name,
+    items::ItemTable,
+    snapshot::{self, Snapshot},
+};
+
+/// What a change is counted against. Container is only tracked when moves
+/// between containers should show up as changes.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    character: u64,
+    container: Option<u32>,
+    item_id: u32,
+    hq: bool,
+    collectable: bool,
+}
+
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    table: &ItemTable,
+    old_path: &Path,
+    new_path: &Path,
+    include_moves: bool,
+) -> anyhow::Result<()> {
+    let old = load(old_path)?;
+    let new = load(new_path)?;
+
+    let mut changes: BTreeMap<Key, i64> = BTreeMap::new();
+    for (snapshot, sign) in [(&old, -1), (&new, 1)] {
+        for character in &snapshot.characters {
+            for container in &character.containers {
+                for item in &container.items {
+                    let key = Key {
+                        character: character.id,
+                        container: include_moves.then_some(container.id),
+                        item_id: item.item_id,
+                        hq: item.hq,
+                        collectable: item.collectable,
+                    };
+                    *changes.entry(key).or_default() += sign * i64::from(item.quantity);
+                }
+            }
+        }
+    }
+
+    let mut changes = changes
+        .into_iter()
+        .filter(|&(_, change)| change != 0)
+        .collect::<Vec<_>>();
+    changes.sort_by(|(a_key, a), (b_key, b)| b.abs().cmp(&a.abs()).then_with(|| a_key.cmp(b_key)));
+
+    stdout.set_color(&theme::header())?;
+    writeln!(
+        stdout,
+        "Inventory changes from {} to {}",
+        old.generated_at.format("%Y-%m-%d %H:%M"),
+        new.generated_at.format("%Y-%m-%d %H:%M")
+    )?;
+    if changes.is_empty() {
+        writeln!(stdout, "    No changes")?;
+    }
+    for (key, change) in changes {
+        let color = if change > 0 {
+            theme::ready()
+        } else {
+            theme::error()
+        };
+        stdout.set_color(&color)?;
+
+        let mut name = table.name(key.item_id);
+        if key.hq {
+            name += " (HQ)";
+        } else if key.collectable {
+            name += " (collectable)";
+        }
+        let holder = holder_name(&new, key.character)
+            .or_else(|| holder_name(&old, key.character))
+            .unwrap_or_else(|| format!("Character#{:X}", key.character));
+        let container = key
+            .container
+            .map(|id| format!(" ({})", container_name(id)))
+            .unwrap_or_default();
+        writeln!(stdout, "    {change:+} {name} on {holder}{container}")?;
+    }
+
+    Ok(())
+}
+
+fn load(path: &Path) -> anyhow::Result<Snapshot> {
+    #[derive(Deserialize)]
+    struct SchemaProbe {
+        schema: u32,
+    }
+
+    let data = read_to_string(path).with_context(|| format!("Failed to open {path:?}"))?;
+    let probe: SchemaProbe = serde_json::from_str(&data)
+        .with_context(|| format!("{path:?} is not an inventory snapshot"))?;
+    if probe.schema != snapshot::SCHEMA_VERSION {
+        bail!(
+            "{path:?} uses snapshot schema {}, but this version of inventory-tracker \
+             reads schema {}; re-export it with --snapshot",
+            probe.schema,
+            snapshot::SCHEMA_VERSION
+        );
+    }
+    serde_json::from_str(&data).with_context(|| format!("Failed to read snapshot {path:?}"))
+}
+
+fn holder_name(snapshot: &Snapshot, id: u64) -> Option<String> {
+    let character = snapshot.characters.iter().find(|chr| chr.id == id)?;
+    let name = character.name.clone()?;
+    Some(match character.owner_id {
+        Some(_) => format!("retainer {name}"),
+        None => name,
+    })
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, HashSet};
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::container_name,
+    gear::is_worn_gear,
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    MetaConfig,
+};
+
+/// Gear held more than once across the account. An item counts as gear when
+/// the item table gives it an item level, or when any copy of it is equipped
+/// or sitting in an armoury chest.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let worn = items
+        .iter()
+        .filter(|item| is_worn_gear(item.container))
+        .map(|item| item.item_id)
+        .collect::<HashSet<_>>();
+    let is_gear = |id: u32| table.item_level(id).is_some() || worn.contains(&id);
+
+    let mut copies: BTreeMap<(u32, bool), Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        if item.item_id != 0 && is_gear(item.item_id) {
+            copies
+                .entry((item.item_id, item.quality() == Quality::High))
+                .or_default()
+                .push(item);
+        }
+    }
+
+    let mut dupes = copies
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .collect::<Vec<_>>();
+    dupes.sort_by(|(a_key, a), (b_key, b)| b.len().cmp(&a.len()).then(a_key.cmp(b_key)));
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Duplicate Gear")?;
+    if dupes.is_empty() {
+        stdout.set_color(&theme::good())?;
+        writeln!(stdout, "    No gear is held more than once")?;
+        return Ok(());
+    }
+
+    let max_holder_len = dupes
+        .iter()
+        .flat_map(|(_, copies)| copies)
+        .map(|item| conf.display_name(item.character_id).len())
+        .max()
+        .unwrap_or(0);
+
+    for ((item_id, hq), copies) in dupes {
+        let quality = if hq { " HQ" } else { "" };
+        stdout.set_color(&theme::header())?;
+        writeln!(
+            stdout,
+            "    {name}{quality} x{count}",
+            name = table.name(item_id),
+            count = copies.len(),
+        )?;
+        stdout.set_color(&theme::good())?;
+        for item in copies {
+            writeln!(
+                stdout,
+                "        {holder:<max_holder_len$} - {container}",
+                holder = conf.display_name(item.character_id),
+                container = container_name(item.container),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use ffxiv_timers_core::json::{self, HeldItem, InventoryReport, Tool};
+use serde::Serialize;
+
+use crate::{
+    args::{Args, Output},
+    containers::container_name,
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    sort::{Row as SortRow, Sort},
+    MetaConfig,
+};
+
+/// One row of `--csv` output. The column set is part of the CLI's interface,
+/// so new columns should only ever be appended.
+#[derive(Serialize)]
+struct Row {
+    character: String,
+    world: String,
+    container: String,
+    item_id: u32,
+    item_name: String,
+    hq: bool,
+    quantity: u32,
+}
+
+/// The fields `--format` can use, one row per item, holder and quality.
+pub const FORMAT_FIELDS: &[&str] = &[
+    "item",
+    "item_id",
+    "character",
+    "world",
+    "quantity",
+    "hq",
+    "retainer",
+];
+
+/// Writes the rows a report would be built from to stdout in the format
+/// `args` asks for, in `--sort` order if given.
+pub fn write<'a>(
+    args: &Args,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+) -> anyhow::Result<()> {
+    write_cached(args, conf, table, items, None)
+}
+
+/// Like [`write`], noting in JSON when the rows were read if they came from
+/// the `--fallback-cache`.
+pub fn write_cached<'a>(
+    args: &Args,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+    cached_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    match args.output {
+        Output::Csv => write_csv(conf, table, items, args.sort),
+        Output::Json => write_json(args.mode.name(), conf, table, items, args.sort, cached_at),
+        Output::Format => {
+            let format = args.format.as_ref().expect("--format sets the templates");
+            let held = sorted_held_items(conf, table, items, args.sort);
+            format.write(std::io::stdout().lock(), &held, |item, field| match field {
+                "item" => item.item_name.clone(),
+                "item_id" => item.item_id.to_string(),
+                "character" => item.character.clone(),
+                "world" => item.world.clone(),
+                "quantity" => item.quantity.to_string(),
+                "hq" => item.hq.to_string(),
+                "retainer" => item.is_retainer.to_string(),
+                _ => unreachable!("checked by Template::parse"),
+            })?;
+            Ok(())
+        }
+        Output::Text => unreachable!("text output is written by each report"),
+    }
+}
+
+fn write_csv<'a>(
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+    sort: Option<Sort>,
+) -> anyhow::Result<()> {
+    let mut items = items
+        .into_iter()
+        .filter(|item| item.item_id != 0)
+        .collect::<Vec<_>>();
+    if let Some(sort) = sort {
+        items = sort.sort(items, |item| SortRow {
+            container: container_name(item.container),
+            ..SortRow::held(conf, table, item.item_id, item.character_id, item.quantity)
+        });
+    }
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    for item in items {
+        writer.serialize(Row {
+            character: conf.name_of(item.character_id),
+            world: conf.world_of(item.character_id),
+            container: container_name(item.container),
+            item_id: item.item_id,
+            item_name: table.name(item.item_id),
+            hq: item.quality() == Quality::High,
+            quantity: item.quantity,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_json<'a>(
+    kind: &str,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+    sort: Option<Sort>,
+    cached_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    let report = InventoryReport {
+        kind: kind.to_string(),
+        items: sorted_held_items(conf, table, items, sort),
+    };
+    json::emit_cached(Tool::Inventory, report, cached_at)
+}
+
+/// Totals the rows by item, holder and quality.
+pub fn held_items<'a>(
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+) -> Vec<HeldItem> {
+    sorted_held_items(conf, table, items, None)
+}
+
+/// Like [`held_items`], in `sort` order if given rather than by item and
+/// holder id.
+fn sorted_held_items<'a>(
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: impl IntoIterator<Item = &'a InventoryItem>,
+    sort: Option<Sort>,
+) -> Vec<HeldItem> {
+    // (item id, holder, hq) -> quantity
+    let mut totals: BTreeMap<(u32, u64, bool), u32> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 {
+            continue;
+        }
+        let hq = item.quality() == Quality::High;
+        *totals
+            .entry((item.item_id, item.character_id, hq))
+            .or_default() += item.quantity;
+    }
+
+    let mut totals = totals.into_iter().collect::<Vec<_>>();
+    if let Some(sort) = sort {
+        totals = sort.sort(totals, |&((item_id, holder, _), quantity)| {
+            SortRow::held(conf, table, item_id, holder, quantity)
+        });
+    }
+    totals
+        .into_iter()
+        .map(|((item_id, holder, hq), quantity)| HeldItem {
+            item_id,
+            item_name: table.name(item_id),
+            character: conf.name_of(holder),
+            world: conf.world_of(holder),
+            is_retainer: conf.owner_of(holder) != holder,
+            quantity,
+            hq,
+            profile: None,
+        })
+        .collect()
+}
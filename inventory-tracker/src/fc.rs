@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, container_name, ContainerGroup},
+    inventory::InventoryItem,
+    items::ItemTable,
+    sort::{Row, Sort},
+    MetaConfig,
+};
+
+pub fn is_fc_container(id: u32) -> bool {
+    containers::lookup(id).is_some_and(|container| {
+        matches!(
+            container.group,
+            ContainerGroup::FreeCompanyChest
+                | ContainerGroup::FreeCompanyGil
+                | ContainerGroup::FreeCompanyCrystals
+        )
+    })
+}
+
+/// Free company chest contents, tab by tab, under the character the plugin
+/// stored them for. Each tab is in slot order unless `sort` says otherwise.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    sort: Option<Sort>,
+) -> anyhow::Result<()> {
+    // owner id -> container id -> items, in slot order
+    let mut chests: BTreeMap<u64, BTreeMap<u32, Vec<&InventoryItem>>> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 || !is_fc_container(item.container) {
+            continue;
+        }
+        chests
+            .entry(conf.owner_of(item.character_id))
+            .or_default()
+            .entry(item.container)
+            .or_default()
+            .push(item);
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Free Company Chest")?;
+    if chests.is_empty() {
+        stdout.set_color(&theme::warn())?;
+        writeln!(stdout, "    No free company chest contents recorded")?;
+        return Ok(());
+    }
+
+    let max_name_len = chests
+        .values()
+        .flat_map(|tabs| tabs.values())
+        .flatten()
+        .map(|item| table.name(item.item_id).len())
+        .max()
+        .unwrap_or(0);
+
+    for (owner, tabs) in chests {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(owner))?;
+        for (container, mut items) in tabs {
+            items.sort_by_key(|item| item.slot);
+            if let Some(sort) = sort {
+                items = sort.sort(items, |item| Row {
+                    container: container_name(container),
+                    ..Row::held(conf, table, item.item_id, item.character_id, item.quantity)
+                });
+            }
+            stdout.set_color(&theme::header())?;
+            writeln!(stdout, "        {}", container_name(container))?;
+            stdout.set_color(&theme::good())?;
+            for item in items {
+                writeln!(
+                    stdout,
+                    "            {name:<max_name_len$} - {quantity}",
+                    name = table.name(item.item_id),
+                    quantity = item.quantity,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
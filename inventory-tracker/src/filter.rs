@@ -0,0 +1,71 @@
+use ffxiv_timers_core::worlds;
+
+use crate::MetaConfig;
+
+/// Restricts reports to some characters. A character pattern matches a
+/// substring of a character's or retainer's name (a character's retainers
+/// come along with it), or the raw id of characters missing from the meta
+/// config. World patterns match a world name or id.
+#[derive(Default)]
+pub struct CharacterFilter {
+    pub characters: Vec<String>,
+    pub worlds: Vec<String>,
+}
+
+impl CharacterFilter {
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty() && self.worlds.is_empty()
+    }
+
+    pub fn keeps(&self, conf: &MetaConfig, id: u64) -> bool {
+        self.matches_character(conf, id) && self.matches_world(conf, id)
+    }
+
+    fn matches_character(&self, conf: &MetaConfig, id: u64) -> bool {
+        if self.characters.is_empty() {
+            return true;
+        }
+        let owner = conf.owner_of(id);
+        self.characters.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            [id, owner].into_iter().any(|id| match conf.character(id) {
+                Some(chr) => chr.name.to_lowercase().contains(&pattern),
+                None => pattern == id.to_string() || pattern == format!("{id:x}"),
+            })
+        })
+    }
+
+    fn matches_world(&self, conf: &MetaConfig, id: u64) -> bool {
+        if self.worlds.is_empty() {
+            return true;
+        }
+        let Some(chr) = conf.character(id) else {
+            return false;
+        };
+        self.worlds.iter().any(|pattern| {
+            pattern.eq_ignore_ascii_case(&worlds::name(chr.world_id))
+                || *pattern == chr.world_id.to_string()
+        })
+    }
+
+    /// Called when a filtered report came up empty: if the filter doesn't
+    /// match any character in the meta config either, says so and lists the
+    /// names it could have matched.
+    pub fn explain_empty(&self, conf: &MetaConfig) {
+        let known_match = conf.saved_characters.keys().any(|&id| self.keeps(conf, id));
+        if self.is_empty() || known_match {
+            return;
+        }
+
+        let mut names = conf
+            .saved_characters
+            .values()
+            .map(|chr| format!("{} ({})", chr.name, worlds::name(chr.world_id)))
+            .collect::<Vec<_>>();
+        names.sort();
+        tracing::warn!(
+            "No characters match the given filters. Known characters:\n    {}",
+            names.join("\n    ")
+        );
+    }
+}
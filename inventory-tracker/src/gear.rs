@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, container_name, ContainerGroup},
+    inventory::InventoryItem,
+    items::ItemTable,
+    MetaConfig,
+};
+
+/// Spiritbond is stored in hundredths of a percent.
+const SPIRITBOND_SCALE: f64 = 10000.0;
+/// Condition is stored with 30000 meaning 100%.
+const CONDITION_SCALE: f64 = 30000.0;
+
+pub fn spiritbond_percent(item: &InventoryItem) -> f64 {
+    f64::from(item.spiritbond) * 100.0 / SPIRITBOND_SCALE
+}
+
+pub fn condition_percent(item: &InventoryItem) -> f64 {
+    f64::from(item.condition) * 100.0 / CONDITION_SCALE
+}
+
+/// Gear that's ready for materia extraction.
+pub fn spiritbond_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    threshold: f64,
+) -> anyhow::Result<()> {
+    report(
+        stdout,
+        conf,
+        table,
+        items,
+        &format!("Spiritbond at or above {threshold}%"),
+        |item| Some(spiritbond_percent(item)).filter(|&percent| percent >= threshold),
+    )
+}
+
+/// Gear that should be repaired soon.
+pub fn repair_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    threshold: f64,
+) -> anyhow::Result<()> {
+    report(
+        stdout,
+        conf,
+        table,
+        items,
+        &format!("Condition below {threshold}%"),
+        |item| Some(condition_percent(item)).filter(|&percent| percent < threshold),
+    )
+}
+
+fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    title: &str,
+    percent: impl Fn(&InventoryItem) -> Option<f64>,
+) -> anyhow::Result<()> {
+    let mut by_character: BTreeMap<u64, Vec<(String, u32, f64)>> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 || !is_worn_gear(item.container) {
+            continue;
+        }
+        if let Some(percent) = percent(item) {
+            by_character.entry(item.character_id).or_default().push((
+                table.name(item.item_id),
+                item.container,
+                percent,
+            ));
+        }
+    }
+
+    let max_name_len = by_character
+        .values()
+        .flatten()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "{title}")?;
+    for (character, gear) in by_character {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        stdout.set_color(&theme::good())?;
+        for (name, container, percent) in gear {
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} - {percent:>6.2}% ({})",
+                container_name(container)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn is_worn_gear(container: u32) -> bool {
+    matches!(
+        containers::lookup(container).map(|container| container.group),
+        Some(ContainerGroup::Equipped | ContainerGroup::Armoury)
+    )
+}
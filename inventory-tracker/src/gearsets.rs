@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+use tracing::warn;
+
+use crate::{
+    containers::container_name, inventory::InventoryItem, items::ItemTable, search::Terms,
+    MetaConfig,
+};
+
+/// Matches an item given either its numeric id or part of its name.
+pub struct ItemSelector {
+    id: Option<u32>,
+    terms: Terms,
+}
+
+impl ItemSelector {
+    pub fn new(query: &str) -> ItemSelector {
+        ItemSelector {
+            id: query.trim().parse().ok(),
+            terms: Terms::new(&[query.to_string()]),
+        }
+    }
+
+    pub fn matches(&self, table: &ItemTable, item_id: u32) -> bool {
+        match self.id {
+            Some(id) => id == item_id,
+            None => item_id != 0 && self.terms.matches(&table.name(item_id)),
+        }
+    }
+}
+
+/// For every instance of the selected item, which gearsets reference it.
+pub fn item_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut by_item: BTreeMap<String, Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        by_item
+            .entry(table.name(item.item_id))
+            .or_default()
+            .push(item);
+    }
+
+    if by_item.is_empty() {
+        warn!("No matching items");
+        return Ok(());
+    }
+
+    let max_name_len = items
+        .iter()
+        .map(|item| conf.display_name(item.character_id).len())
+        .max()
+        .unwrap_or(0);
+
+    for (name, instances) in by_item {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "{name}")?;
+        for item in instances {
+            let gearsets = item.gearsets();
+            let gearsets = if gearsets.is_empty() {
+                stdout.set_color(&theme::warn())?;
+                "not in any gearset".to_string()
+            } else {
+                stdout.set_color(&theme::good())?;
+                gearsets.join(", ")
+            };
+            writeln!(
+                stdout,
+                "    {holder:<max_name_len$} - {container}: {gearsets}",
+                holder = conf.display_name(item.character_id),
+                container = container_name(item.container),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Every item tagged with the named gearset, per character.
+pub fn gearset_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    gearset: &str,
+) -> anyhow::Result<()> {
+    let mut by_character: BTreeMap<u64, Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        let in_set = item
+            .gearsets()
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(gearset));
+        if in_set {
+            by_character
+                .entry(item.character_id)
+                .or_default()
+                .push(item);
+        }
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Gearset {gearset}")?;
+    if by_character.is_empty() {
+        stdout.set_color(&theme::warn())?;
+        writeln!(stdout, "    No items are tagged with this gearset")?;
+        return Ok(());
+    }
+
+    let max_name_len = by_character
+        .values()
+        .flatten()
+        .map(|item| table.name(item.item_id).len())
+        .max()
+        .unwrap_or(0);
+
+    for (character, items) in by_character {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        stdout.set_color(&theme::good())?;
+        for item in items {
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} - {container}",
+                name = table.name(item.item_id),
+                container = container_name(item.container),
+            )?;
+        }
+    }
+
+    Ok(())
+}
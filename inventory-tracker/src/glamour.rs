@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{containers::container_name, inventory::InventoryItem, items::ItemTable, MetaConfig};
+
+/// Rows of the game's Stain sheet, which the `stain` column refers to.
+const DYES: &[&str] = &[
+    "Snow White",
+    "Ash Grey",
+    "Goobbue Grey",
+    "Slate Grey",
+    "Charcoal Grey",
+    "Soot Black",
+    "Rose Pink",
+    "Lilac Purple",
+    "Rolanberry Red",
+    "Dalamud Red",
+    "Rust Red",
+    "Wine Red",
+    "Coral Pink",
+    "Blood Red",
+    "Salmon Pink",
+    "Sunset Orange",
+    "Mesa Red",
+    "Bark Brown",
+    "Chocolate Brown",
+    "Russet Brown",
+    "Kobold Brown",
+    "Cork Brown",
+    "Qiqirn Brown",
+    "Opo-opo Brown",
+    "Aldgoat Brown",
+    "Pumpkin Orange",
+    "Acorn Brown",
+    "Orchard Brown",
+    "Chestnut Brown",
+    "Gobbiebag Brown",
+    "Shale Brown",
+    "Mole Brown",
+    "Loam Brown",
+    "Bone White",
+    "Ul Brown",
+    "Desert Yellow",
+    "Honey Yellow",
+    "Millioncorn Yellow",
+    "Coeurl Yellow",
+    "Cream Yellow",
+    "Halatali Yellow",
+    "Raisin Brown",
+    "Mud Green",
+    "Sylph Green",
+    "Lime Green",
+    "Moss Green",
+    "Meadow Green",
+    "Olive Green",
+    "Marsh Green",
+    "Thicket Green",
+    "Chocobo Green",
+    "Nophica Green",
+    "Deepwood Green",
+    "Celeste Green",
+    "Turquoise Green",
+    "Morbol Green",
+    "Ice Blue",
+    "Sky Blue",
+    "Seafog Blue",
+    "Peacock Blue",
+    "Rhotano Blue",
+    "Corpse Blue",
+    "Ceruleum Blue",
+    "Woad Blue",
+    "Ink Blue",
+    "Raptor Blue",
+    "Othard Blue",
+    "Storm Blue",
+    "Void Blue",
+    "Royal Blue",
+    "Midnight Blue",
+    "Shadow Blue",
+    "Abyssal Blue",
+    "Lavender Purple",
+    "Gloom Purple",
+    "Currant Purple",
+    "Iris Purple",
+    "Grape Purple",
+    "Lotus Pink",
+    "Colibri Pink",
+    "Plum Purple",
+    "Regal Purple",
+];
+
+/// The general-purpose dyes start at 101 in the Stain sheet.
+const SPECIAL_DYES_START: u32 = 101;
+const SPECIAL_DYES: &[&str] = &[
+    "Pure White",
+    "Jet Black",
+    "Pastel Pink",
+    "Dark Red",
+    "Dark Brown",
+    "Pastel Green",
+    "Dark Green",
+    "Pastel Blue",
+    "Dark Blue",
+    "Pastel Purple",
+    "Dark Purple",
+    "Metallic Silver",
+    "Metallic Gold",
+    "Metallic Red",
+    "Metallic Orange",
+    "Metallic Yellow",
+    "Metallic Green",
+    "Metallic Sky Blue",
+    "Metallic Blue",
+    "Metallic Purple",
+];
+
+pub fn dye_name(stain: u32) -> String {
+    let name = if stain < SPECIAL_DYES_START {
+        DYES.get((stain as usize).wrapping_sub(1))
+    } else {
+        SPECIAL_DYES.get((stain - SPECIAL_DYES_START) as usize)
+    };
+    match name {
+        Some(name) => name.to_string(),
+        None => format!("Dye #{stain}"),
+    }
+}
+
+/// How many pieces of gear are dyed each colour, per character.
+pub fn dye_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut by_dye: BTreeMap<u32, BTreeMap<u64, u32>> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 || item.stain == 0 {
+            continue;
+        }
+        let owner = conf.owner_of(item.character_id);
+        *by_dye
+            .entry(item.stain)
+            .or_default()
+            .entry(owner)
+            .or_default() += 1;
+    }
+
+    let mut dyes = by_dye
+        .into_iter()
+        .map(|(stain, holders)| (holders.values().sum::<u32>(), stain, holders))
+        .collect::<Vec<_>>();
+    dyes.sort_by(|(a_total, a_stain, _), (b_total, b_stain, _)| {
+        b_total.cmp(a_total).then(a_stain.cmp(b_stain))
+    });
+
+    let max_name_len = dyes
+        .iter()
+        .map(|&(_, stain, _)| dye_name(stain).len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Dyes")?;
+    stdout.set_color(&theme::good())?;
+    for (total, stain, holders) in dyes {
+        let holders = holders
+            .iter()
+            .map(|(&holder, count)| format!("{} {count}", conf.display_name(holder)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            stdout,
+            "    {name:<max_name_len$} {total:>4}  ({holders})",
+            name = dye_name(stain)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Items wearing the appearance of another item.
+pub fn glamour_report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut by_character: BTreeMap<u64, Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        if item.item_id != 0 && item.glamour_id != 0 {
+            by_character
+                .entry(item.character_id)
+                .or_default()
+                .push(item);
+        }
+    }
+
+    let max_name_len = by_character
+        .values()
+        .flatten()
+        .map(|item| table.name(item.item_id).len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Glamours")?;
+    for (character, items) in by_character {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        stdout.set_color(&theme::good())?;
+        for item in items {
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} -> {glamour} ({container})",
+                name = table.name(item.item_id),
+                glamour = table.name(item.glamour_id),
+                container = container_name(item.container),
+            )?;
+        }
+    }
+
+    Ok(())
+}
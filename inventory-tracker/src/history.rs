@@ -0,0 +1,165 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use ffxiv_timers_core::{clock, state, theme};
+use serde::{Deserialize, Serialize};
+use termcolor::WriteColor;
+
+use crate::{inventory::InventoryItem, items::ItemTable};
+
+const HISTORY_FILE: &str = "inventory-history.jsonl";
+
+/// Bars used for the sparkline, from lowest to highest.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One line of the history log: how many of each tracked item were held
+/// across every character at the time of a run.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub at: DateTime<Utc>,
+    pub totals: BTreeMap<u32, u64>,
+}
+
+pub fn path() -> anyhow::Result<PathBuf> {
+    state::path(HISTORY_FILE)
+}
+
+pub fn record(tracked: &[u32], items: &[InventoryItem]) -> Record {
+    let mut totals = tracked
+        .iter()
+        .map(|&id| (id, 0))
+        .collect::<BTreeMap<_, _>>();
+    for item in items {
+        if let Some(total) = totals.get_mut(&item.item_id) {
+            *total += u64::from(item.quantity);
+        }
+    }
+    Record {
+        at: clock::now(),
+        totals,
+    }
+}
+
+/// Adds a record to the end of the log. The whole log is rewritten
+/// atomically, so a crash part way through leaves the previous history
+/// intact.
+pub fn append(path: &Path, record: &Record) -> anyhow::Result<()> {
+    let mut data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+    };
+    if data.last().is_some_and(|&last| last != b'\n') {
+        data.push(b'\n');
+    }
+    serde_json::to_writer(&mut data, record)?;
+    data.push(b'\n');
+    state::write_atomically(path, &data)
+}
+
+/// Reads every record in the log, skipping lines that don't parse.
+pub fn load(path: &Path) -> anyhow::Result<Vec<Record>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {path:?}")),
+    };
+    let mut records = vec![];
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read {path:?}"))?;
+        if let Ok(record) = serde_json::from_str(&line) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// One bar per value, scaled between the smallest and largest values.
+pub fn sparkline(values: &[u64]) -> String {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = (max - min).max(1);
+    values
+        .iter()
+        .map(|&value| SPARKS[((value - min) * (SPARKS.len() as u64 - 1) / range) as usize])
+        .collect()
+}
+
+/// The logged totals of an item over the last `days` days.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    table: &ItemTable,
+    records: &[Record],
+    item_id: u32,
+    days: u32,
+) -> anyhow::Result<()> {
+    let since = clock::now() - Duration::days(days.into());
+    let points = records
+        .iter()
+        .filter(|record| record.at >= since)
+        .filter_map(|record| Some((record.at, *record.totals.get(&item_id)?)))
+        .collect::<Vec<_>>();
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "{} - last {days} days", table.name(item_id))?;
+    stdout.set_color(&theme::good())?;
+    let Some(&(_, latest)) = points.last() else {
+        writeln!(
+            stdout,
+            "    Nothing logged; track it with --log-items {item_id}"
+        )?;
+        return Ok(());
+    };
+
+    for (at, total) in &points {
+        writeln!(stdout, "    {}  {total}", at.format("%Y-%m-%d %H:%M"))?;
+    }
+    let values = points.iter().map(|&(_, total)| total).collect::<Vec<_>>();
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "    {}", sparkline(&values))?;
+    writeln!(
+        stdout,
+        "    min {}, max {}, latest {latest}",
+        values.iter().min().unwrap_or(&0),
+        values.iter().max().unwrap_or(&0),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_spans_min_to_max() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[5, 5]), "▁▁");
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+    }
+
+    #[test]
+    fn append_keeps_earlier_records() {
+        let dir = std::env::temp_dir().join(format!("inventory-history-{}", std::process::id()));
+        let path = dir.join(HISTORY_FILE);
+        let _ = fs::remove_file(&path);
+        for total in [10, 20] {
+            let record = Record {
+                at: Utc::now(),
+                totals: BTreeMap::from([(21072, total)]),
+            };
+            append(&path, &record).unwrap();
+        }
+        let records = load(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].totals[&21072], 20);
+        assert!(!path.with_extension("jsonl.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use anyhow::Context;
+use csv::ByteRecord;
+use ffxiv_timers_core::source::Incompatible;
+use serde::{Deserialize, Serialize};
+
+/// InventoryTools writes its CSV without a header row, so the column names
+/// are supplied here. The plugin has added columns over time; the layout is
+/// picked by the number of fields in the first row.
+struct Layout {
+    name: &'static str,
+    columns: &'static [&'static str],
+}
+
+const LAYOUTS: &[Layout] = &[
+    // Before gearset membership was exported
+    Layout {
+        name: "legacy",
+        columns: &[
+            "container",
+            "slot",
+            "item_id",
+            "quantity",
+            "spiritbond",
+            "condition",
+            "flags",
+            "materia1",
+            "materia2",
+            "materia3",
+            "materia4",
+            "materia5",
+            "materia_grade1",
+            "materia_grade2",
+            "materia_grade3",
+            "materia_grade4",
+            "materia_grade5",
+            "stain",
+            "glamour_id",
+            "unk1",
+            "unk2",
+            "unk3",
+            "character_id",
+            "unk4",
+        ],
+    },
+    Layout {
+        name: "gearsets",
+        columns: &[
+            "container",
+            "slot",
+            "item_id",
+            "quantity",
+            "spiritbond",
+            "condition",
+            "flags",
+            "materia1",
+            "materia2",
+            "materia3",
+            "materia4",
+            "materia5",
+            "materia_grade1",
+            "materia_grade2",
+            "materia_grade3",
+            "materia_grade4",
+            "materia_grade5",
+            "stain",
+            "glamour_id",
+            "unk1",
+            "unk2",
+            "unk3",
+            "character_id",
+            "unk4",
+            "gearset_ids",
+            "gearset_names",
+        ],
+    },
+];
+
+// Bits of the `flags` column, mirroring InventoryTools' `ItemFlags`:
+//   0x1 high quality
+//   0x2 company crest applied
+//   0x4 relic
+//   0x8 collectable
+const FLAG_HQ: u32 = 0x1;
+const FLAG_COLLECTABLE: u32 = 0x8;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InventoryItem {
+    pub container: u32,
+    pub slot: u32,
+    pub item_id: u32,
+    pub quantity: u32,
+    pub spiritbond: u32,
+    pub condition: u32,
+    pub flags: u32,
+    materia1: u32,
+    materia2: u32,
+    materia3: u32,
+    materia4: u32,
+    materia5: u32,
+    materia_grade1: u32,
+    materia_grade2: u32,
+    materia_grade3: u32,
+    materia_grade4: u32,
+    materia_grade5: u32,
+    pub stain: u32,
+    /// The item whose appearance is projected onto this one, or 0
+    pub glamour_id: u32,
+    pub character_id: u64,
+    /// Comma-separated; missing from layouts older than the gearset columns
+    #[serde(default)]
+    gearset_ids: String,
+    #[serde(default)]
+    gearset_names: String,
+}
+
+/// Items of different quality never stack together in game, so they're kept
+/// apart in reports too.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Quality {
+    Normal,
+    High,
+    Collectable,
+}
+
+impl InventoryItem {
+    pub fn quality(&self) -> Quality {
+        if self.flags & FLAG_COLLECTABLE != 0 {
+            Quality::Collectable
+        } else if self.flags & FLAG_HQ != 0 {
+            Quality::High
+        } else {
+            Quality::Normal
+        }
+    }
+
+    /// The (materia, grade) pairs melded into the item, skipping empty slots.
+    pub fn materia(&self) -> impl Iterator<Item = (u32, u32)> {
+        [
+            (self.materia1, self.materia_grade1),
+            (self.materia2, self.materia_grade2),
+            (self.materia3, self.materia_grade3),
+            (self.materia4, self.materia_grade4),
+            (self.materia5, self.materia_grade5),
+        ]
+        .into_iter()
+        .filter(|&(materia, _)| materia != 0)
+    }
+
+    /// The names of the gearsets using this item. The id and name columns
+    /// are parallel lists; a set with no name falls back to its number.
+    pub fn gearsets(&self) -> Vec<String> {
+        if self.gearset_ids.trim().is_empty() {
+            return vec![];
+        }
+        let names = self
+            .gearset_names
+            .split(',')
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        self.gearset_ids
+            .split(',')
+            .map(str::trim)
+            .enumerate()
+            .map(|(index, id)| match names.get(index) {
+                Some(name) if !name.is_empty() => name.to_string(),
+                _ => format!("Gearset {id}"),
+            })
+            .collect()
+    }
+}
+
+/// Streams rows out of inventories.csv without reading the whole file into
+/// memory. Rows whose item id is rejected by the filter are skipped before
+/// the rest of the record is deserialized.
+pub struct Items<R, F> {
+    reader: csv::Reader<R>,
+    headers: ByteRecord,
+    item_id_column: usize,
+    record: ByteRecord,
+    /// The first row is read up front to pick the layout
+    pending: bool,
+    filter: F,
+}
+
+pub fn open<F>(path: &Path, filter: F) -> anyhow::Result<Items<BufReader<File>, F>>
+where
+    F: FnMut(u32) -> bool,
+{
+    let file = File::open(path).with_context(|| format!("Failed to open {path:?}"))?;
+    from_reader(BufReader::new(file), filter)
+}
+
+/// Like [`open`], for CSV data from anywhere else.
+pub fn from_reader<R, F>(reader: R, filter: F) -> anyhow::Result<Items<R, F>>
+where
+    R: Read,
+    F: FnMut(u32) -> bool,
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(reader);
+
+    let mut record = ByteRecord::new();
+    let pending = reader.read_byte_record(&mut record)?;
+    let columns = match pending {
+        true => select_layout(record.len())?.columns,
+        false => LAYOUTS[LAYOUTS.len() - 1].columns,
+    };
+    let item_id_column = columns
+        .iter()
+        .position(|&column| column == "item_id")
+        .expect("every layout has an item_id column");
+
+    Ok(Items {
+        reader,
+        headers: ByteRecord::from(columns.to_vec()),
+        item_id_column,
+        record,
+        pending,
+        filter,
+    })
+}
+
+/// Each layout by name and how many columns it has, oldest first.
+pub fn layouts() -> impl Iterator<Item = (&'static str, usize)> {
+    LAYOUTS
+        .iter()
+        .map(|layout| (layout.name, layout.columns.len()))
+}
+
+fn select_layout(column_count: usize) -> anyhow::Result<&'static Layout> {
+    LAYOUTS
+        .iter()
+        .find(|layout| layout.columns.len() == column_count)
+        .ok_or_else(|| {
+            let known = LAYOUTS
+                .iter()
+                .map(|layout| format!("{} ({} columns)", layout.name, layout.columns.len()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Incompatible(format!(
+                "inventories.csv has {column_count} columns, which doesn't match any known \
+                 InventoryTools layout: {known}"
+            ))
+            .into()
+        })
+}
+
+impl<R, F> Items<R, F>
+where
+    R: Read,
+    F: FnMut(u32) -> bool,
+{
+    /// Whether the file has any rows at all. Only meaningful before the
+    /// first call to `next`.
+    pub fn has_rows(&self) -> bool {
+        self.pending
+    }
+
+    fn read_next(&mut self) -> anyhow::Result<Option<InventoryItem>> {
+        while std::mem::take(&mut self.pending) || self.reader.read_byte_record(&mut self.record)? {
+            let item_id = self
+                .record
+                .get(self.item_id_column)
+                .and_then(|field| std::str::from_utf8(field).ok())
+                .and_then(|field| field.trim().parse::<u32>().ok());
+            if let Some(item_id) = item_id {
+                if !(self.filter)(item_id) {
+                    continue;
+                }
+            }
+
+            let item = self.record.deserialize(Some(&self.headers))?;
+            return Ok(Some(item));
+        }
+        Ok(None)
+    }
+}
+
+impl<R, F> Iterator for Items<R, F>
+where
+    R: Read,
+    F: FnMut(u32) -> bool,
+{
+    type Item = anyhow::Result<InventoryItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// A row in the current layout, for tests that need inventory data.
+#[cfg(test)]
+pub fn test_row(container: u32, slot: u32, item_id: u32, quantity: u32, character: u64) -> String {
+    let mut fields = vec![0u64; LAYOUTS[LAYOUTS.len() - 1].columns.len()];
+    fields[0] = container.into();
+    fields[1] = slot.into();
+    fields[2] = item_id.into();
+    fields[3] = quantity.into();
+    fields[22] = character;
+    fields
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses rows made with [`test_row`].
+#[cfg(test)]
+pub fn test_items(rows: &[String]) -> Vec<InventoryItem> {
+    from_reader(rows.join("\n").as_bytes(), |_| true)
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{BufWriter, Write},
+    };
+
+    use ffxiv_timers_core::source::Failure;
+
+    use super::*;
+
+    fn read(csv: &str) -> Vec<InventoryItem> {
+        from_reader(csv.as_bytes(), |_| true)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn legacy_rows_have_no_gearsets() {
+        let items = read(include_str!("../tests/fixtures/legacy.csv"));
+        let fields = items
+            .iter()
+            .map(|item| (item.container, item.slot, item.item_id, item.quantity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [(0, 0, 21072, 300), (0, 1, 5111, 12), (1000, 3, 36180, 1)]
+        );
+        assert_eq!(items[1].quality(), Quality::High);
+        let gear = &items[2];
+        assert_eq!((gear.spiritbond, gear.condition), (8000, 30000));
+        assert_eq!(gear.materia().collect::<Vec<_>>(), [(41, 10), (42, 10)]);
+        assert_eq!(gear.stain, 17);
+        assert_eq!(gear.character_id, 18014398509481984);
+        assert!(gear.gearsets().is_empty());
+    }
+
+    #[test]
+    fn gearset_rows_name_their_sets() {
+        let items = read(include_str!("../tests/fixtures/gearsets.csv"));
+        let fields = items
+            .iter()
+            .map(|item| (item.container, item.slot, item.item_id, item.quantity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [(0, 0, 21072, 45), (1000, 3, 36180, 1), (3200, 0, 5111, 99)]
+        );
+        let gear = &items[1];
+        assert_eq!(gear.glamour_id, 36181);
+        assert_eq!(gear.character_id, 18014398509481985);
+        assert_eq!(gear.gearsets(), ["Paladin", "Gearset 4"]);
+        assert!(items[0].gearsets().is_empty());
+        assert_eq!(items[2].quality(), Quality::Collectable);
+    }
+
+    #[test]
+    fn unknown_layouts_say_how_many_columns_they_saw() {
+        let Err(err) = from_reader(
+            include_str!("../tests/fixtures/unknown.csv").as_bytes(),
+            |_| true,
+        ) else {
+            panic!("a 25-column file was accepted");
+        };
+        assert_eq!(Failure::of(&err), Failure::Incompatible);
+        let message = err.to_string();
+        assert!(message.contains("has 25 columns"), "{message}");
+        assert!(message.contains("legacy (24 columns)"), "{message}");
+        assert!(message.contains("gearsets (26 columns)"), "{message}");
+    }
+
+    /// A large generated file is read row by row through `open`, as the
+    /// real inventories.csv is, rather than from a string in memory.
+    #[test]
+    fn large_files_are_streamed_from_disk() {
+        let path =
+            std::env::temp_dir().join(format!("inventory-stream-{}.csv", std::process::id()));
+        let mut file = BufWriter::new(File::create(&path).unwrap());
+        for slot in 0..200_000 {
+            let item_id = if slot % 4 == 0 { 21072 } else { 0 };
+            writeln!(
+                file,
+                "{}",
+                test_row(slot / 1000, slot % 1000, item_id, slot, 100)
+            )
+            .unwrap();
+        }
+        file.into_inner().unwrap().sync_all().unwrap();
+
+        let mut rows = 0;
+        let mut quantity = 0u64;
+        for item in open(&path, |item_id| item_id != 0).unwrap() {
+            let item = item.unwrap();
+            assert_eq!(item.item_id, 21072);
+            assert_eq!(item.container * 1000 + item.slot, item.quantity);
+            rows += 1;
+            quantity += u64::from(item.quantity);
+        }
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rows, 50_000);
+        // Every fourth slot number, 0 + 4 + ... + 199_996
+        assert_eq!(quantity, 4 * (49_999 * 50_000 / 2));
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use ffxiv_timers_core::{gamedata, paths};
+use serde::Deserialize;
+
+pub const GIL: u32 = 1;
+pub const VENTURE: u32 = 21072;
+
+const ITEM_TABLE_FILE: &str = "items.csv";
+
+#[derive(Deserialize)]
+struct ItemRow {
+    id: u32,
+    name: String,
+    /// Only present for equipment
+    #[serde(default)]
+    ilvl: Option<u32>,
+    #[serde(default)]
+    stack_size: Option<u32>,
+    #[serde(default)]
+    category: Option<String>,
+    /// Collectables that can be aetherially reduced
+    #[serde(default)]
+    reducible: Option<String>,
+    /// Whether the item can be desynthesized, for tables that distinguish
+    #[serde(default)]
+    desynth: Option<String>,
+}
+
+/// Reads a yes/no column, treating anything but an explicit yes as no.
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        &*value.trim().to_lowercase(),
+        "1" | "y" | "yes" | "true" | "x"
+    )
+}
+
+/// Broad item categories for `--category`, coarser than the game's own UI
+/// categories.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Category {
+    Weapons,
+    Armor,
+    Accessories,
+    Materials,
+    Consumables,
+    Crystals,
+    Currency,
+    Materia,
+    Furnishings,
+    Miscellany,
+}
+
+/// Accepted spellings of each category, compared after normalizing.
+const CATEGORY_NAMES: &[(&str, Category)] = &[
+    ("weapons", Category::Weapons),
+    ("weapon", Category::Weapons),
+    ("tools", Category::Weapons),
+    ("armor", Category::Armor),
+    ("armour", Category::Armor),
+    ("accessories", Category::Accessories),
+    ("accessory", Category::Accessories),
+    ("materials", Category::Materials),
+    ("material", Category::Materials),
+    ("consumables", Category::Consumables),
+    ("consumable", Category::Consumables),
+    ("crystals", Category::Crystals),
+    ("crystal", Category::Crystals),
+    ("currency", Category::Currency),
+    ("currencies", Category::Currency),
+    ("materia", Category::Materia),
+    ("furnishings", Category::Furnishings),
+    ("furnishing", Category::Furnishings),
+    ("housing", Category::Furnishings),
+    ("miscellany", Category::Miscellany),
+    ("misc", Category::Miscellany),
+];
+
+impl Category {
+    /// Matches names case-insensitively, ignoring hyphens, underscores and
+    /// spaces, so `Off-Hand` and `offhand` are the same.
+    pub fn parse(name: &str) -> Option<Category> {
+        let name = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_lowercase();
+        CATEGORY_NAMES
+            .iter()
+            .find(|&&(alias, _)| alias == name)
+            .map(|&(_, category)| category)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        // The first spelling of each category is its canonical name
+        CATEGORY_NAMES
+            .iter()
+            .enumerate()
+            .filter(|&(index, &(_, category))| {
+                index == 0 || CATEGORY_NAMES[index - 1].1 != category
+            })
+            .map(|(_, &(name, _))| name)
+    }
+}
+
+/// Stack size assumed for items the table doesn't give one for. Most
+/// materials and consumables stack to 999.
+const DEFAULT_STACK_SIZE: u32 = 999;
+
+/// Item names, read from an `id,name` CSV (e.g. exported from the game's
+/// Item sheet) layered over the handful in the game data. Optional `ilvl`,
+/// `stack_size` and `category` columns mark which items are equipment, how
+/// many fit in a slot, and what kind of item they are; `reducible` and
+/// `desynth` flag what can be broken down.
+pub struct ItemTable {
+    names: HashMap<u32, String>,
+    levels: HashMap<u32, u32>,
+    stack_sizes: HashMap<u32, u32>,
+    categories: HashMap<u32, Category>,
+    reducible: HashSet<u32>,
+    desynth: HashMap<u32, bool>,
+}
+
+impl ItemTable {
+    /// Just the names in the game data.
+    pub fn builtin() -> ItemTable {
+        ItemTable {
+            names: gamedata::items()
+                .rows()
+                .iter()
+                .map(|item| (item.id, item.name.clone()))
+                .collect(),
+            levels: HashMap::new(),
+            stack_sizes: HashMap::new(),
+            categories: HashMap::new(),
+            reducible: HashSet::new(),
+            desynth: HashMap::new(),
+        }
+    }
+
+    /// Loads the table from `path`, or from `items.csv` in the config
+    /// directory when no path is given. Only an explicitly requested table
+    /// is required to exist.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<ItemTable> {
+        let mut table = ItemTable::builtin();
+
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(table),
+            },
+        };
+        let mut reader = csv::Reader::from_path(&path)
+            .with_context(|| format!("Failed to open item table {path:?}"))?;
+        for row in reader.deserialize() {
+            let row: ItemRow =
+                row.with_context(|| format!("Failed to read item table {path:?}"))?;
+            table.names.insert(row.id, row.name);
+            if let Some(ilvl) = row.ilvl {
+                table.levels.insert(row.id, ilvl);
+            }
+            if let Some(stack_size) = row.stack_size {
+                table.stack_sizes.insert(row.id, stack_size);
+            }
+            if let Some(category) = row.category.filter(|category| !category.is_empty()) {
+                let Some(parsed) = Category::parse(&category) else {
+                    bail!(
+                        "Unknown category `{category}` for item {} in {path:?}",
+                        row.id
+                    );
+                };
+                table.categories.insert(row.id, parsed);
+            }
+            if row.reducible.as_deref().is_some_and(parse_flag) {
+                table.reducible.insert(row.id);
+            }
+            if let Some(desynth) = row.desynth.filter(|desynth| !desynth.trim().is_empty()) {
+                table.desynth.insert(row.id, parse_flag(&desynth));
+            }
+        }
+
+        Ok(table)
+    }
+
+    pub fn name(&self, id: u32) -> String {
+        match self.names.get(&id) {
+            Some(name) => name.clone(),
+            None => format!("Item #{id}"),
+        }
+    }
+
+    /// Looks up an item id by its exact name, ignoring case.
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.names
+            .iter()
+            .find(|(_, other)| other.eq_ignore_ascii_case(name))
+            .map(|(&id, _)| id)
+    }
+
+    pub fn item_level(&self, id: u32) -> Option<u32> {
+        self.levels.get(&id).copied()
+    }
+
+    /// The item's category from the table. Without one, only gil and the
+    /// elemental shards, crystals and clusters (ids 2-19) are recognized.
+    pub fn category(&self, id: u32) -> Option<Category> {
+        match self.categories.get(&id) {
+            Some(&category) => Some(category),
+            None if id == GIL => Some(Category::Currency),
+            None if (2..=19).contains(&id) => Some(Category::Crystals),
+            None => None,
+        }
+    }
+
+    /// Whether collectable copies of the item can be aetherially reduced.
+    pub fn is_reducible(&self, id: u32) -> bool {
+        self.reducible.contains(&id)
+    }
+
+    /// Whether the item can be desynthesized. Without a `desynth` column,
+    /// all equipment is assumed to be.
+    pub fn can_desynth(&self, id: u32) -> bool {
+        match self.desynth.get(&id) {
+            Some(&desynth) => desynth,
+            None => self.levels.contains_key(&id),
+        }
+    }
+
+    /// How many of an item fit in one slot. Without a `stack_size` column,
+    /// equipment is assumed not to stack and everything else to stack to 999.
+    pub fn stack_size(&self, id: u32) -> u32 {
+        match self.stack_sizes.get(&id) {
+            Some(&size) => size,
+            None if self.levels.contains_key(&id) => 1,
+            None => DEFAULT_STACK_SIZE,
+        }
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(paths::config_dir()?.join(ITEM_TABLE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_parse_loosely() {
+        assert_eq!(Category::parse("Consumables"), Some(Category::Consumables));
+        assert_eq!(Category::parse("armour"), Some(Category::Armor));
+        assert_eq!(Category::parse("MISC"), Some(Category::Miscellany));
+        assert_eq!(Category::parse("furnish-ings"), Some(Category::Furnishings));
+        assert_eq!(Category::parse("food"), None);
+    }
+
+    #[test]
+    fn canonical_category_names_are_listed_once() {
+        let names = Category::names().collect::<Vec<_>>();
+        assert_eq!(names.len(), 10);
+        assert_eq!(names[0], "weapons");
+        assert!(names.iter().all(|name| Category::parse(name).is_some()));
+    }
+
+    #[test]
+    fn flags_need_an_explicit_yes() {
+        assert!(parse_flag("1"));
+        assert!(parse_flag(" Yes "));
+        assert!(parse_flag("TRUE"));
+        assert!(!parse_flag(""));
+        assert!(!parse_flag("0"));
+        assert!(!parse_flag("no"));
+    }
+}
@@ -0,0 +1,905 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs::read_to_string,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use ffxiv_timers_core::{
+    check::Severity,
+    clock, fallback,
+    json::{HeldItem, Tool},
+    logging,
+    options::{self, Options, Stdout},
+    paths, profiles,
+    scan::Scan,
+    source::{MissingInput, Support},
+    theme, watch, worlds,
+};
+use serde::{Deserialize, Serialize};
+use termcolor::WriteColor;
+use tracing::{debug, warn};
+
+use args::{Args, Mode, Output};
+use config::Config;
+use filter::CharacterFilter;
+use inventory::InventoryItem;
+pub use items::VENTURE;
+use items::{Category, ItemTable};
+
+mod args;
+mod armoury;
+mod breakdown;
+mod burn;
+mod config;
+mod consolidate;
+mod containers;
+mod currency;
+mod diff;
+mod dupes;
+mod export;
+mod fc;
+mod filter;
+mod gear;
+mod gearsets;
+mod glamour;
+mod history;
+mod inventory;
+mod items;
+mod listings;
+mod locations;
+mod materia;
+mod needs;
+mod orphans;
+mod query;
+mod repl;
+mod saddlebag;
+mod search;
+mod slots;
+mod snapshot;
+mod sort;
+mod top;
+mod value;
+mod ventures;
+mod wealth;
+
+/// InventoryTools' files, inside the plugin config directory.
+const INVENTORY_FILE: [&str; 2] = ["InventoryTools", "inventories.csv"];
+const INVENTORY_META_FILE: [&str; 1] = ["InventoryTools.json"];
+
+/// The InventoryTools files every report but `--diff`, `--history` and
+/// `--burn-rate` reads.
+pub fn data_paths() -> anyhow::Result<[PathBuf; 2]> {
+    Ok([
+        paths::plugin_path(INVENTORY_META_FILE)?,
+        paths::plugin_path(INVENTORY_FILE)?,
+    ])
+}
+
+/// Days apart the plugin's files can be written before names are suspect.
+const DEFAULT_SYNC_WARN_DAYS: f64 = 7.0;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetaConfig {
+    #[serde(deserialize_with = "character_ids")]
+    saved_characters: HashMap<u64, SavedCharacter>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SavedCharacter {
+    name: String,
+    world_id: u32,
+    /// The character a retainer belongs to, or 0 for player characters
+    #[serde(default)]
+    owner_id: u64,
+}
+
+/// The plugin keys saved characters by their id as a string. They're parsed
+/// once up front so lookups for every inventory row don't allocate.
+fn character_ids<'de, D>(deserializer: D) -> Result<HashMap<u64, SavedCharacter>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let characters = HashMap::<String, SavedCharacter>::deserialize(deserializer)?;
+    Ok(characters
+        .into_iter()
+        .filter_map(|(id, chr)| match id.parse() {
+            Ok(id) => Some((id, chr)),
+            Err(_) => {
+                warn!(
+                    "Ignoring saved character `{}` with invalid id `{id}` in InventoryTools.json",
+                    chr.name
+                );
+                None
+            }
+        })
+        .collect())
+}
+
+/// The name to show for a character, after any alias from config.toml.
+fn alias(name: &str) -> String {
+    ffxiv_timers_core::config::get().alias(name).to_string()
+}
+
+impl MetaConfig {
+    fn character(&self, id: u64) -> Option<&SavedCharacter> {
+        self.saved_characters.get(&id)
+    }
+
+    /// The player character holding the given inventory, resolving retainers
+    /// to their owner.
+    fn owner_of(&self, id: u64) -> u64 {
+        match self.character(id) {
+            Some(chr) if chr.owner_id != 0 => chr.owner_id,
+            _ => id,
+        }
+    }
+
+    /// The character's name, or its hex id when the meta config doesn't know
+    /// it, which is how the plugin's own UI shows it.
+    fn name_of(&self, id: u64) -> String {
+        match self.character(id) {
+            Some(chr) => alias(&chr.name),
+            None => format!("Character#{id:X}"),
+        }
+    }
+
+    /// A short name for whoever holds an inventory, e.g. "retainer Bob".
+    fn holder_name(&self, id: u64) -> String {
+        match self.character(id) {
+            Some(chr) if chr.owner_id != 0 => format!("retainer {}", alias(&chr.name)),
+            Some(chr) => alias(&chr.name),
+            None => self.name_of(id),
+        }
+    }
+
+    fn world_of(&self, id: u64) -> String {
+        self.character(id)
+            .map(|chr| worlds::name(chr.world_id))
+            .unwrap_or_default()
+    }
+
+    fn display_name(&self, id: u64) -> String {
+        match self.character(id) {
+            Some(chr) => format!("{} ({})", alias(&chr.name), worlds::name(chr.world_id)),
+            None => self.name_of(id),
+        }
+    }
+}
+
+/// Runs the report selected by `args`, the command line after the program
+/// name, and returns how urgent it is: a warning when `--needs` finds
+/// something short or `--slots` an inventory past `--full-warn`.
+pub fn run(args: impl IntoIterator<Item = String>, options: &Options) -> anyhow::Result<Severity> {
+    let mut args = Args::parse_from(args, options)?;
+    logging::init(args.verbose, args.quiet);
+    if let Some(path) = args.config.take() {
+        paths::set_config_file(path);
+    }
+    let shared = ffxiv_timers_core::config::load()?;
+    if args.items.is_empty() && args.item_names.is_empty() {
+        args.items = watchlist_ids(shared);
+    }
+
+    // CSV, JSON and --format output are meant for other programs, so they're never colored
+    let color_choice = match args.output {
+        Output::Text => options::color_choice(args.color.or(options.color)),
+        Output::Csv | Output::Json | Output::Format => termcolor::ColorChoice::Never,
+    };
+    // Watching and the REPL keep writing, so there's never a finished report to page
+    let mut stdout = match args.output == Output::Text && !args.watch && args.mode != Mode::Repl {
+        true => Stdout::paged(color_choice),
+        false => Stdout::new(color_choice),
+    };
+    stdout.set_color(&theme::header())?;
+
+    let config = Config::load()?;
+    let table = ItemTable::load(args.item_table.as_deref())?;
+    worlds::load_overrides(args.world_table.as_deref())?;
+    for name in &args.item_names {
+        match table.find(name) {
+            Some(id) => args.items.push(id),
+            None => bail!("Unknown item `{name}`"),
+        }
+    }
+
+    // Diffs only look at previously exported snapshots
+    if args.mode == Mode::Diff {
+        let (old, new) = &args.diff;
+        diff::report(&mut stdout, &table, old, new, args.include_moves)?;
+        return Ok(Severity::Ok);
+    }
+    // History and burn rates only read the log written by earlier runs
+    if matches!(args.mode, Mode::History | Mode::Burn) {
+        let item_id = match args.history_item.parse() {
+            Ok(id) => id,
+            Err(_) => match table.find(&args.history_item) {
+                Some(id) => id,
+                None => anyhow::bail!("Unknown item `{}`", args.history_item),
+            },
+        };
+        let records = history::load(&history::path()?)?;
+        if args.mode == Mode::History {
+            history::report(&mut stdout, &table, &records, item_id, args.history_days)?;
+        } else {
+            let expected = expected_use(&args, &config, item_id);
+            burn::report(
+                &mut stdout,
+                &table,
+                &records,
+                item_id,
+                args.history_days,
+                &expected,
+            )?;
+        }
+        return Ok(Severity::Ok);
+    }
+
+    // Each profile is reported on in turn, under its name
+    let severities = profiles::each(|profile| {
+        let [conf_path, inv_path] = data_paths()?;
+        let inputs = [
+            ("InventoryTools.json", &*conf_path),
+            ("inventories.csv", &*inv_path),
+        ];
+        // Under --fallback-cache a query can fall back from missing files
+        let cached = args.fallback_cache;
+        if !cached {
+            check_inputs(&inputs)?;
+        }
+        let Some(profile) = profile else {
+            check_sync(&config, &inv_path, &conf_path);
+            if args.watch {
+                let paths = [inv_path.clone(), conf_path.clone()];
+                watch::run(&mut stdout, &paths, None, None, |stdout, _| {
+                    report(stdout, &args, &config, &table, &conf_path, &inv_path).map(|_| ())
+                })?;
+                return Ok(vec![]);
+            }
+            let severity = report(&mut stdout, &args, &config, &table, &conf_path, &inv_path)?;
+            return Ok(vec![severity]);
+        };
+        if args.watch {
+            bail!("--watch needs --profile to pick one profile to watch");
+        }
+        check_sync(&config, &inv_path, &conf_path);
+        if args.output == Output::Text {
+            stdout.set_color(&theme::header())?;
+            writeln!(stdout, "[{profile}]")?;
+        }
+        let severity = report(&mut stdout, &args, &config, &table, &conf_path, &inv_path)?;
+        Ok(vec![severity])
+    })?;
+    Ok(severities.into_iter().max().unwrap_or_default())
+}
+
+/// What the retainers and submarines would have used of an item over the
+/// `--burn-rate` window, from whichever of InventoryTools and
+/// SubmarineTracker can be read.
+fn expected_use(args: &Args, config: &Config, item_id: u32) -> burn::Expected {
+    let mut expected = burn::Expected::default();
+    if item_id == items::VENTURE {
+        let per_retainer = config
+            .ventures_per_retainer_day
+            .unwrap_or(ventures::DEFAULT_VENTURES_PER_DAY);
+        let conf = data_paths().and_then(|[conf_path, _]| read_meta(&conf_path));
+        match conf {
+            Ok(conf) => {
+                let characters = &args.characters;
+                let retainers = match config.retainer_count {
+                    Some(each) => {
+                        let owners = conf
+                            .saved_characters
+                            .iter()
+                            .filter(|(&id, chr)| chr.owner_id == 0 && characters.keeps(&conf, id))
+                            .count();
+                        owners as u32 * each
+                    }
+                    None => conf
+                        .saved_characters
+                        .values()
+                        .filter(|chr| chr.owner_id != 0 && characters.keeps(&conf, chr.owner_id))
+                        .count() as u32,
+                };
+                expected.retainers = Some((retainers, per_retainer));
+            }
+            Err(e) => debug!("Leaving retainers out of the burn rate: {e:#}"),
+        }
+    }
+    match sub_returns::reports() {
+        Ok(fcs) => {
+            let now = clock::now();
+            let since = now - chrono::Duration::days(args.history_days.into());
+            expected.voyages = Some(burn::voyages(&fcs, since, now));
+        }
+        Err(e) => debug!("Leaving submarines out of the burn rate: {e:#}"),
+    }
+    expected
+}
+
+/// The items reported when none are given: the watchlist from config.toml,
+/// or ventures without one.
+pub fn watchlist_ids(shared: &ffxiv_timers_core::config::Config) -> Vec<u32> {
+    match shared.watchlist.is_empty() {
+        true => vec![items::VENTURE],
+        false => shared.watchlist.clone(),
+    }
+}
+
+/// How much of each watchlist item every character and retainer holds, for
+/// reports built outside this tool.
+pub fn watchlist() -> anyhow::Result<Vec<HeldItem>> {
+    holdings(&watchlist_ids(ffxiv_timers_core::config::load()?))
+}
+
+/// How much of each of `watched` every character and retainer holds.
+pub fn holdings(watched: &[u32]) -> anyhow::Result<Vec<HeldItem>> {
+    let table = ItemTable::load(None)?;
+    worlds::load_overrides(None)?;
+    profiles::each(|profile| {
+        let held = profile_holdings(watched, &table)?;
+        Ok(held
+            .into_iter()
+            .map(|item| HeldItem {
+                profile: profile.map(str::to_string),
+                ..item
+            })
+            .collect())
+    })
+}
+
+fn profile_holdings(watched: &[u32], table: &ItemTable) -> anyhow::Result<Vec<HeldItem>> {
+    let [conf_path, inv_path] = data_paths()?;
+    let conf = read_meta(&conf_path)?;
+    let source = Source {
+        path: &inv_path,
+        conf: &conf,
+        characters: &CharacterFilter::default(),
+        category: None,
+        table,
+        exclude_orphans: false,
+        exclude_glamour: false,
+    };
+    let items = source.load(|item_id| watched.contains(&item_id))?;
+    Ok(export::held_items(&conf, table, &items))
+}
+
+fn read_meta(path: &Path) -> anyhow::Result<MetaConfig> {
+    let data = read_to_string(path).with_context(|| format!("Failed to open {path:?}"))?;
+    serde_json::from_str(&data).with_context(|| format!("Failed to parse {path:?}"))
+}
+
+/// The layouts of inventories.csv the reader knows.
+pub fn support() -> Support {
+    Support {
+        files: INVENTORY_FILE.join("/"),
+        layouts: inventory::layouts()
+            .map(|(name, columns)| format!("{name} ({columns} columns)"))
+            .collect(),
+    }
+}
+
+/// What InventoryTools' files in the profile being read hold, for `doctor`.
+/// Every row of inventories.csv is read, in whichever of the plugin's
+/// layouts it matches.
+pub fn scan() -> anyhow::Result<Scan> {
+    let [conf_path, inv_path] = data_paths()?;
+    for (name, path) in [
+        ("InventoryTools.json", &conf_path),
+        ("inventories.csv", &inv_path),
+    ] {
+        if !path.exists() {
+            bail!("{name} is missing; expected it at {}", path.display());
+        }
+    }
+    read_meta(&conf_path)?;
+    let mut scan = Scan::default();
+    scan.read(&conf_path);
+    scan.read(&inv_path);
+    for (index, row) in inventory::open(&inv_path, |_| true)?.enumerate() {
+        match row {
+            Ok(_) => scan.rows += 1,
+            Err(err) => scan
+                .failed
+                .push(format!("inventories.csv row {}: {err:#}", index + 1)),
+        }
+    }
+    Ok(scan)
+}
+
+/// Fails with [`MissingInput`] naming any of `files` that don't exist,
+/// rather than on a bare "file not found" once they're opened.
+fn check_inputs(files: &[(&str, &Path)]) -> anyhow::Result<()> {
+    let missing = files
+        .iter()
+        .filter(|(_, path)| !path.exists())
+        .map(|(name, path)| format!("{name} is missing; expected it at {}", path.display()))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(MissingInput(missing.join(", and ")).into())
+}
+
+/// The plugin writes the inventory CSV and its meta config separately, so a
+/// renamed character or retainer can keep its old name for as long as the
+/// meta config goes unsaved. Notes when the two were written far apart.
+fn check_sync(config: &Config, inv_path: &Path, conf_path: &Path) {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(chrono::DateTime::<chrono::Utc>::from)
+    };
+    let (Some(inv_time), Some(conf_time)) = (modified(inv_path), modified(conf_path)) else {
+        return;
+    };
+    debug!(
+        "inventories.csv last written {}",
+        inv_time.format("%Y-%m-%d %H:%M UTC")
+    );
+    debug!(
+        "InventoryTools.json last written {}",
+        conf_time.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    let warn_days = config.sync_warn_days.unwrap_or(DEFAULT_SYNC_WARN_DAYS);
+    let apart_days = (inv_time - conf_time).num_seconds().abs() as f64 / 86400.0;
+    if apart_days > warn_days {
+        let newer = match inv_time > conf_time {
+            true => "inventories.csv",
+            false => "InventoryTools.json",
+        };
+        warn!(
+            "{newer} is {apart_days:.0} days newer than the other InventoryTools file, so names \
+             may be out of date; open the plugin in game to refresh its config"
+        );
+    }
+}
+
+/// Runs the selected report against the current inventory files, returning
+/// how urgent it is.
+fn report(
+    stdout: &mut dyn WriteColor,
+    args: &Args,
+    config: &Config,
+    table: &ItemTable,
+    conf_path: &Path,
+    inv_path: &Path,
+) -> anyhow::Result<Severity> {
+    // A plain query can be answered from the last rows kept
+    if args.mode == Mode::Query {
+        let (read, cached_at) = fallback::load(Tool::Inventory, args.fallback_cache, || {
+            let conf = read_meta(conf_path)?;
+            let source = Source::new(args, &conf, table, inv_path);
+            log_items(args, config, &source)?;
+            let items = source.load(|item_id| args.items.contains(&item_id))?;
+            Ok(Queried { conf, items })
+        })?;
+        let (conf, items) = (&read.conf, &read.items);
+        if args.output != Output::Text {
+            return export::write_cached(args, conf, table, items, cached_at)
+                .map(|()| Severity::Ok);
+        }
+        // The report has no title of its own to note it's stale after
+        if cached_at.is_some() {
+            stdout.set_color(&theme::dim())?;
+            writeln!(stdout, "{}", fallback::mark("inventories.csv", cached_at))?;
+        }
+        if args.locations {
+            locations::report(stdout, conf, table, items, &args.items, args.min_qty)?;
+            return Ok(Severity::Ok);
+        }
+        query::report(
+            stdout,
+            conf,
+            table,
+            items,
+            &args.items,
+            args.combine_quality,
+            args.sort,
+        )?;
+        return Ok(Severity::Ok);
+    }
+
+    let conf = read_meta(conf_path)?;
+    let source = Source::new(args, &conf, table, inv_path);
+    log_items(args, config, &source)?;
+
+    match args.mode {
+        Mode::Search => {
+            // Names are matched once per distinct item id rather than per row
+            let terms = search::Terms::new(&args.search);
+            let mut matched = HashMap::new();
+            let items = source.load(|item_id| match matched.entry(item_id) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    *entry.insert(item_id != 0 && terms.matches(&table.name(item_id)))
+                }
+            })?;
+            if args.output != Output::Text {
+                return export::write(args, &conf, table, &items).map(|()| Severity::Ok);
+            }
+            search::report(stdout, &conf, table, &items, args.limit, args.sort)?;
+        }
+        Mode::Needs => {
+            let needs = needs::load(&args.needs, table)?;
+            let items = source.load(|item_id| needs.iter().any(|need| need.item_id == item_id))?;
+            if !needs::report(stdout, &conf, table, &items, &needs)? {
+                return Ok(Severity::Warning);
+            }
+        }
+        Mode::Repl => {
+            let index = repl::Index::build(source.load(|item_id| item_id != 0)?, table);
+            repl::run(
+                stdout,
+                &conf,
+                table,
+                &index,
+                args.limit,
+                args.combine_quality,
+            )?;
+        }
+        Mode::Ventures => {
+            let items = source.load(|item_id| item_id == items::VENTURE)?;
+            ventures::report(stdout, &conf, config, &args.characters, &items)?;
+        }
+        Mode::Slots => {
+            let items = source.load(|_| true)?;
+            if slots::report(stdout, &conf, &items, args.full_warn)? {
+                return Ok(Severity::Warning);
+            }
+        }
+        Mode::Top => {
+            let items = source.load(|item_id| item_id != 0)?;
+            top::report(stdout, &conf, table, &items, args.top, args.top_by_quantity)?;
+        }
+        Mode::Armoury => {
+            let items = source.load(|_| true)?;
+            armoury::report(stdout, &conf, &items, args.full_warn)?;
+        }
+        Mode::Listings => {
+            let items = source.load(|item_id| item_id != 0)?;
+            if args.output != Output::Text {
+                let items = items
+                    .iter()
+                    .filter(|item| containers::is_listing(item.container));
+                return export::write(args, &conf, table, items).map(|()| Severity::Ok);
+            }
+            listings::report(stdout, &conf, table, &items)?;
+        }
+        Mode::Orphans => {
+            let items = source.load(|_| true)?;
+            orphans::report(stdout, &conf, table, &items)?;
+        }
+        Mode::Saddlebag => {
+            let items = source.load(|_| true)?;
+            if args.output != Output::Text {
+                let items = items
+                    .iter()
+                    .filter(|item| saddlebag::is_saddlebag_container(item.container));
+                return export::write(args, &conf, table, items).map(|()| Severity::Ok);
+            }
+            saddlebag::report(stdout, &conf, table, &items, args.sort)?;
+        }
+        Mode::Spiritbond => {
+            let items = source.load(|item_id| item_id != 0)?;
+            gear::spiritbond_report(stdout, &conf, table, &items, args.spiritbond_at)?;
+        }
+        Mode::Repair => {
+            let items = source.load(|item_id| item_id != 0)?;
+            gear::repair_report(stdout, &conf, table, &items, args.repair_below)?;
+        }
+        Mode::Materia => {
+            let items = source.load(|item_id| item_id != 0)?;
+            materia::report(stdout, &conf, table, &items)?;
+        }
+        Mode::Currency => {
+            let currencies = currency::currencies(config, table);
+            let items =
+                source.load(|item_id| currencies.iter().any(|currency| currency.id == item_id))?;
+            if args.output != Output::Text {
+                return export::write(args, &conf, table, &items).map(|()| Severity::Ok);
+            }
+            currency::report(stdout, &conf, &currencies, &items)?;
+        }
+        Mode::Value => {
+            let items = source.load(|item_id| item_id != 0)?;
+            value::report(stdout, &conf, config, &items)?;
+        }
+        Mode::Wealth => {
+            let items = source.load(|item_id| item_id != 0)?;
+            wealth::report(stdout, &conf, config, table, &items, args.min_gil)?;
+        }
+        Mode::Dyes => {
+            let items = source.load(|item_id| item_id != 0)?;
+            glamour::dye_report(stdout, &conf, &items)?;
+        }
+        Mode::Glamours => {
+            let items = source.load(|item_id| item_id != 0)?;
+            glamour::glamour_report(stdout, &conf, table, &items)?;
+        }
+        Mode::FcChest => {
+            let items = source.load(|item_id| item_id != 0)?;
+            if args.output != Output::Text {
+                let items = items
+                    .iter()
+                    .filter(|item| fc::is_fc_container(item.container));
+                return export::write(args, &conf, table, items).map(|()| Severity::Ok);
+            }
+            fc::report(stdout, &conf, table, &items, args.sort)?;
+        }
+        Mode::Reducible => {
+            let items = source.load(|item_id| table.is_reducible(item_id))?;
+            breakdown::reducible_report(stdout, &conf, table, &items)?;
+        }
+        Mode::Desynth => {
+            let items = source.load(|item_id| table.can_desynth(item_id))?;
+            let below = args.desynth_below.or(config.desynth_below);
+            breakdown::desynth_report(stdout, &conf, table, &items, below)?;
+        }
+        Mode::Dupes => {
+            let items = source.load(|item_id| item_id != 0)?;
+            dupes::report(stdout, &conf, table, &items)?;
+        }
+        Mode::Consolidate => {
+            let items = source.load(|item_id| item_id != 0)?;
+            consolidate::report(stdout, &conf, table, &items)?;
+        }
+        Mode::Gearsets => {
+            let selector = gearsets::ItemSelector::new(&args.gearset_item);
+            let items = source.load(|item_id| selector.matches(table, item_id))?;
+            if args.output != Output::Text {
+                return export::write(args, &conf, table, &items).map(|()| Severity::Ok);
+            }
+            gearsets::item_report(stdout, &conf, table, &items)?;
+        }
+        Mode::GearsetName => {
+            let items = source.load(|item_id| item_id != 0)?;
+            gearsets::gearset_report(stdout, &conf, table, &items, &args.gearset_name)?;
+        }
+        Mode::Snapshot => {
+            let items = source.load(|item_id| item_id != 0)?;
+            snapshot::write(&args.snapshot, &snapshot::build(&conf, &items))?;
+        }
+        Mode::Query | Mode::Diff | Mode::History | Mode::Burn => unreachable!(),
+    }
+
+    Ok(Severity::Ok)
+}
+
+/// What a plain query read, kept by `--fallback-cache` to show again when
+/// the plugin's files can't be read. Only the queried items' rows are kept.
+#[derive(Serialize, Deserialize)]
+struct Queried {
+    conf: MetaConfig,
+    items: Vec<InventoryItem>,
+}
+
+/// Adds the totals of the items to log, from `--log-items` and config.toml,
+/// to the history.
+fn log_items(args: &Args, config: &Config, source: &Source) -> anyhow::Result<()> {
+    let logged = args
+        .log_items
+        .iter()
+        .chain(&config.log_items)
+        .copied()
+        .collect::<Vec<_>>();
+    if !logged.is_empty() {
+        let items = source.load(|item_id| logged.contains(&item_id))?;
+        history::append(&history::path()?, &history::record(&logged, &items))?;
+    }
+    Ok(())
+}
+
+/// Where report rows come from, and which characters' rows are wanted.
+struct Source<'a> {
+    path: &'a Path,
+    conf: &'a MetaConfig,
+    characters: &'a CharacterFilter,
+    category: Option<Category>,
+    table: &'a ItemTable,
+    /// Drops rows belonging to characters missing from the meta config
+    exclude_orphans: bool,
+    /// Drops rows in the glamour dresser and armoire
+    exclude_glamour: bool,
+}
+
+impl<'a> Source<'a> {
+    /// The rows `args` asks for, from the inventory at `path`.
+    fn new(args: &'a Args, conf: &'a MetaConfig, table: &'a ItemTable, path: &'a Path) -> Self {
+        Source {
+            path,
+            conf,
+            characters: &args.characters,
+            category: args.category,
+            table,
+            exclude_orphans: args.exclude_orphans,
+            exclude_glamour: args.mode.hides_glamour() && !args.include_glamour,
+        }
+    }
+
+    /// Only the rows a report actually looks at are kept in memory.
+    fn load(&self, mut filter: impl FnMut(u32) -> bool) -> anyhow::Result<Vec<InventoryItem>> {
+        let rows = inventory::open(self.path, |item_id| {
+            let in_category = match self.category {
+                Some(category) => self.table.category(item_id) == Some(category),
+                None => true,
+            };
+            in_category && filter(item_id)
+        })?;
+        if !rows.has_rows() {
+            warn!(
+                "No inventory rows in {}; InventoryTools hasn't exported any characters yet",
+                self.path.display()
+            );
+            return Ok(vec![]);
+        }
+        let items = rows
+            .filter(|item| match item {
+                Ok(item) => {
+                    self.characters.keeps(self.conf, item.character_id)
+                        && !(self.exclude_orphans
+                            && orphans::is_orphan(self.conf, item.character_id))
+                        && !(self.exclude_glamour && containers::is_glamour_storage(item.container))
+                }
+                Err(_) => true,
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let unknown = items
+            .iter()
+            .filter(|item| orphans::is_orphan(self.conf, item.character_id))
+            .count();
+        if unknown > 0 {
+            debug!(
+                "{unknown} rows belong to characters missing from InventoryTools.json \
+                 and are shown by id; see --orphans"
+            );
+        }
+        if items.is_empty() {
+            self.characters.explain_empty(self.conf);
+        }
+        Ok(items)
+    }
+}
+
+/// Alice (Tonberry) with her retainer Bob, Carol (Balmung), and Dan on a
+/// world missing from the world table.
+#[cfg(test)]
+fn test_conf() -> MetaConfig {
+    serde_json::from_str(
+        r#"{"SavedCharacters": {
+            "100": {"Name": "Alice Doe", "WorldId": 72},
+            "200": {"Name": "Bob", "WorldId": 72, "OwnerId": 100},
+            "300": {"Name": "Carol Roe", "WorldId": 91},
+            "400": {"Name": "Dan", "WorldId": 9999}
+        }}"#,
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::inventory::{test_items, test_row};
+
+    #[test]
+    fn known_characters_are_named() {
+        let conf = test_conf();
+        assert_eq!(conf.display_name(100), "Alice Doe (Tonberry)");
+        assert_eq!(conf.display_name(300), "Carol Roe (Balmung)");
+        assert_eq!(conf.name_of(200), "Bob");
+        assert_eq!(conf.holder_name(200), "retainer Bob");
+        assert_eq!(conf.owner_of(200), 100);
+    }
+
+    #[test]
+    fn unknown_worlds_are_named_by_id() {
+        let conf = test_conf();
+        assert_eq!(conf.display_name(400), "Dan (World#9999)");
+        assert_eq!(conf.world_of(400), "World#9999");
+    }
+
+    #[test]
+    fn unknown_characters_fall_back_to_their_id() {
+        let conf = test_conf();
+        let items = test_items(&[
+            test_row(0, 0, items::VENTURE, 300, 100),
+            test_row(0, 0, items::VENTURE, 5, 0x1004F00AB12CD),
+        ]);
+        let unknown = items[1].character_id;
+        assert!(conf.character(unknown).is_none());
+        assert_eq!(conf.display_name(unknown), "Character#1004F00AB12CD");
+        assert_eq!(conf.name_of(unknown), "Character#1004F00AB12CD");
+        assert_eq!(conf.holder_name(unknown), "Character#1004F00AB12CD");
+        assert_eq!(conf.owner_of(unknown), unknown);
+        assert_eq!(conf.world_of(unknown), "");
+    }
+
+    /// Every other slot empty, the rest spread evenly over Alice, her
+    /// retainer Bob, Carol and a character missing from the meta config.
+    fn large_inventory() -> String {
+        (0..100_000u32)
+            .map(|slot| {
+                let item_id = if slot % 2 == 0 { 0 } else { items::VENTURE };
+                let character = [100, 200, 300, 999][(slot / 2) as usize % 4];
+                test_row(0, slot, item_id, 1, character)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn load_large(rows: &str, filter: fn(u32) -> bool) -> Vec<InventoryItem> {
+        inventory::from_reader(rows.as_bytes(), filter)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn empty_slots_are_skipped_and_owners_resolve_by_id() {
+        let conf = test_conf();
+        let rows = large_inventory();
+        assert_eq!(load_large(&rows, |_| true).len(), 100_000);
+        let items = load_large(&rows, |item_id| item_id != 0);
+        assert_eq!(items.len(), 50_000);
+        assert!(items.iter().all(|item| item.item_id == items::VENTURE));
+
+        let mut owners = BTreeMap::new();
+        for item in &items {
+            *owners.entry(conf.owner_of(item.character_id)).or_insert(0) += 1;
+        }
+        // Bob's rows go to Alice; the unknown character keeps its own id
+        assert_eq!(
+            owners,
+            BTreeMap::from([(100, 25_000), (300, 12_500), (999, 12_500)])
+        );
+    }
+
+    /// Not a rigorous benchmark, but enough to catch the per-row lookups
+    /// going back to allocating, or empty slots being deserialized again.
+    /// Timings vary too much between machines to run by default; use
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn large_inventories_load_quickly() {
+        use std::time::Instant;
+
+        let conf = test_conf();
+        let rows = large_inventory();
+        let timed = |filter: fn(u32) -> bool| {
+            let start = Instant::now();
+            let items = load_large(&rows, filter);
+            (items, start.elapsed())
+        };
+        let (all, all_time) = timed(|_| true);
+        let (_, skipped_time) = timed(|item_id| item_id != 0);
+        assert!(skipped_time < all_time);
+
+        let by_string = conf
+            .saved_characters
+            .iter()
+            .map(|(id, chr)| (id.to_string(), chr.owner_id))
+            .collect::<HashMap<_, _>>();
+        let start = Instant::now();
+        let string_owners = all
+            .iter()
+            .filter_map(|item| by_string.get(&item.character_id.to_string()))
+            .filter(|&&owner| owner != 0)
+            .count();
+        let string_time = start.elapsed();
+        let start = Instant::now();
+        let owners = all
+            .iter()
+            .filter(|item| conf.owner_of(item.character_id) != item.character_id)
+            .count();
+        let int_time = start.elapsed();
+        assert_eq!(owners, string_owners);
+        assert!(int_time < string_time);
+    }
+}
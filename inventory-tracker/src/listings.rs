@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{containers, inventory::InventoryItem, items::ItemTable, query::Holding, MetaConfig};
+
+/// Items each retainer has up on the market board, grouped by owner.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    // owner id -> retainer id -> item id -> quantity listed
+    type Listings = BTreeMap<u64, BTreeMap<u32, Holding>>;
+    let mut by_owner: BTreeMap<u64, Listings> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 || !containers::is_listing(item.container) {
+            continue;
+        }
+        by_owner
+            .entry(conf.owner_of(item.character_id))
+            .or_default()
+            .entry(item.character_id)
+            .or_default()
+            .entry(item.item_id)
+            .or_default()
+            .add(item.quality(), item.quantity, false);
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Market Listings")?;
+    if by_owner.is_empty() {
+        stdout.set_color(&theme::good())?;
+        writeln!(stdout, "    Nothing is listed")?;
+        return Ok(());
+    }
+
+    let max_name_len = by_owner
+        .values()
+        .flat_map(|retainers| retainers.values())
+        .flat_map(|listed| listed.keys())
+        .map(|&id| table.name(id).len())
+        .max()
+        .unwrap_or(0);
+
+    for (owner, retainers) in by_owner {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(owner))?;
+        for (retainer, listed) in retainers {
+            stdout.set_color(&theme::header())?;
+            writeln!(stdout, "        {}", conf.holder_name(retainer))?;
+            stdout.set_color(&theme::good())?;
+            for (item_id, holding) in listed {
+                writeln!(
+                    stdout,
+                    "            {name:<max_name_len$} - {quantity}",
+                    name = table.name(item_id),
+                    quantity = holding.display(),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
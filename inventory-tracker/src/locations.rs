@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::{
+    layout::{self, Align, Column, Shrink},
+    theme,
+};
+use termcolor::WriteColor;
+
+use crate::{inventory::InventoryItem, items::ItemTable, MetaConfig};
+
+/// How many characters a holder with the whole stock gets as a bar.
+const BAR_WIDTH: usize = 20;
+
+/// Where each requested item is held, most first, with a bar for each
+/// holder's share of the total. Holders with less than `min_qty` are summed
+/// into one row after the rest.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    item_ids: &[u32],
+    min_qty: u32,
+) -> anyhow::Result<()> {
+    // item id -> holder id -> quantity, of every quality
+    let mut held: BTreeMap<u32, BTreeMap<u64, u32>> = BTreeMap::new();
+    for item in items {
+        if item_ids.contains(&item.item_id) {
+            *held
+                .entry(item.item_id)
+                .or_default()
+                .entry(item.character_id)
+                .or_default() += item.quantity;
+        }
+    }
+
+    let reports = held
+        .into_iter()
+        .map(|(item_id, holders)| (item_id, rows(conf, holders, min_qty)))
+        .collect::<Vec<_>>();
+    let quantity_len = reports
+        .iter()
+        .flat_map(|(_, rows)| rows)
+        .map(|(_, quantity, _)| quantity.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let figures =
+        |quantity: u32, share: f64| format!("{quantity:>quantity_len$} {:>3.0}%", share * 100.0);
+    // Names give way to the terminal's width before the bars, which go
+    // before the quantities
+    let widths = layout::allocate(
+        4,
+        &[
+            Column::new(0, Shrink::Truncate { min: 12 }).measure(
+                reports
+                    .iter()
+                    .flat_map(|(_, rows)| rows.iter().map(|(name, _, _)| name.as_str())),
+            ),
+            Column::new(1, Shrink::Never).measure(
+                reports
+                    .iter()
+                    .flat_map(|(_, rows)| rows)
+                    .map(|&(_, quantity, share)| figures(quantity, share)),
+            ),
+            Column::new(1, Shrink::Drop).measure(["█".repeat(BAR_WIDTH)]),
+        ],
+        layout::available(),
+    );
+
+    for (item_id, rows) in reports {
+        let total = rows.iter().map(|&(_, quantity, _)| quantity).sum::<u32>();
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "{} - {total}", table.name(item_id))?;
+        for (name, quantity, share) in rows {
+            stdout.set_color(&theme::good())?;
+            write!(
+                stdout,
+                "    {} {}",
+                layout::fit(&name, widths[0].unwrap_or(0), Align::Left),
+                figures(quantity, share),
+            )?;
+            if widths[2].is_some() {
+                stdout.set_color(&theme::dim())?;
+                write!(
+                    stdout,
+                    " {}",
+                    "█".repeat((share * BAR_WIDTH as f64).round() as usize)
+                )?;
+            }
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Each holder's name, quantity and share of the total, most first, ending
+/// with those below `min_qty` together.
+fn rows(conf: &MetaConfig, holders: BTreeMap<u64, u32>, min_qty: u32) -> Vec<(String, u32, f64)> {
+    let total = holders.values().sum::<u32>().max(1);
+    let (mut kept, others): (Vec<_>, Vec<_>) = holders
+        .into_iter()
+        .partition(|&(_, quantity)| quantity >= min_qty);
+    kept.sort_by(|(a_id, a), (b_id, b)| b.cmp(a).then(a_id.cmp(b_id)));
+    let mut rows = kept
+        .into_iter()
+        .map(|(holder, quantity)| (holder_label(conf, holder), quantity))
+        .collect::<Vec<_>>();
+    match others.len() {
+        0 => {}
+        // One holder alone is as easy to name as to count
+        1 => rows.push((holder_label(conf, others[0].0), others[0].1)),
+        count => rows.push((
+            format!("{count} others"),
+            others.iter().map(|&(_, quantity)| quantity).sum(),
+        )),
+    }
+    rows.into_iter()
+        .map(|(name, quantity)| (name, quantity, f64::from(quantity) / f64::from(total)))
+        .collect()
+}
+
+/// A character with their world, or a retainer with whose it is, which is
+/// where you'd have to go to collect from it.
+fn holder_label(conf: &MetaConfig, holder: u64) -> String {
+    let owner = conf.owner_of(holder);
+    match owner == holder {
+        true => conf.display_name(holder),
+        false => format!(
+            "{} ({})",
+            conf.holder_name(holder),
+            conf.display_name(owner)
+        ),
+    }
+}
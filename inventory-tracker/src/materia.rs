@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{inventory::InventoryItem, items::ItemTable, MetaConfig};
+
+/// Rows of the game's Materia sheet, which is what the materia1-5 columns
+/// refer to.
+const MATERIA_TYPES: &[(u32, &str)] = &[
+    (1, "Strength"),
+    (2, "Vitality"),
+    (3, "Dexterity"),
+    (4, "Intelligence"),
+    (5, "Mind"),
+    (6, "Piety"),
+    (7, "Heavens' Eye"),
+    (8, "Savage Aim"),
+    (9, "Savage Might"),
+    (10, "Battledance"),
+    (11, "Quickarm"),
+    (12, "Quicktongue"),
+    (13, "Craftsman's Competence"),
+    (14, "Craftsman's Cunning"),
+    (15, "Craftsman's Command"),
+    (16, "Gatherer's Guerdon"),
+    (17, "Gatherer's Guile"),
+    (18, "Gatherer's Grasp"),
+];
+
+const GRADES: &[&str] = &[
+    "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII",
+];
+
+/// A materia kind, e.g. ("Savage Aim", 10) for Savage Aim Materia X.
+type Kind = (String, usize);
+
+#[derive(Default)]
+struct Counts {
+    total: u32,
+    by_character: BTreeMap<u64, u32>,
+}
+
+impl Counts {
+    fn add(&mut self, character: u64, quantity: u32) {
+        self.total += quantity;
+        *self.by_character.entry(character).or_default() += quantity;
+    }
+}
+
+/// Decodes a materia column and its grade column. Grades are stored
+/// zero-based, so grade 0 is Materia I.
+fn slotted_kind(materia: u32, grade: u32) -> Kind {
+    let name = MATERIA_TYPES
+        .iter()
+        .find(|&&(id, _)| id == materia)
+        .map(|&(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("Type #{materia}"));
+    (name, grade as usize + 1)
+}
+
+/// Loose materia are recognised by their item names, which always read
+/// "<type> Materia <grade>".
+fn loose_kind(name: &str) -> Option<Kind> {
+    let (kind, grade) = name.rsplit_once(" Materia ")?;
+    let grade = GRADES.iter().position(|&numeral| numeral == grade)? + 1;
+    Some((kind.to_string(), grade))
+}
+
+fn kind_name((kind, grade): &Kind) -> String {
+    match GRADES.get(grade - 1) {
+        Some(numeral) => format!("{kind} Materia {numeral}"),
+        None => format!("{kind} Materia (grade {grade})"),
+    }
+}
+
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut loose: BTreeMap<Kind, Counts> = BTreeMap::new();
+    let mut slotted: BTreeMap<Kind, Counts> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 {
+            continue;
+        }
+        let owner = conf.owner_of(item.character_id);
+        if let Some(kind) = loose_kind(&table.name(item.item_id)) {
+            loose.entry(kind).or_default().add(owner, item.quantity);
+        }
+        for (materia, grade) in item.materia() {
+            slotted
+                .entry(slotted_kind(materia, grade))
+                .or_default()
+                .add(owner, 1);
+        }
+    }
+
+    let max_name_len = loose
+        .keys()
+        .chain(slotted.keys())
+        .map(|kind| kind_name(kind).len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Materia")?;
+    for (title, counts) in [("Loose", &loose), ("Slotted", &slotted)] {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {title}")?;
+        stdout.set_color(&theme::good())?;
+        for (kind, counts) in counts {
+            let holders = counts
+                .by_character
+                .iter()
+                .map(|(&character, count)| format!("{} {count}", conf.display_name(character)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                stdout,
+                "        {name:<max_name_len$} {total:>5}  ({holders})",
+                name = kind_name(kind),
+                total = counts.total,
+            )?;
+        }
+    }
+
+    let loose_total: u32 = loose.values().map(|counts| counts.total).sum();
+    let slotted_total: u32 = slotted.values().map(|counts| counts.total).sum();
+    stdout.set_color(&theme::header())?;
+    writeln!(
+        stdout,
+        "    Total: {loose_total} loose, {slotted_total} slotted"
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, Context};
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, container_name},
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    MetaConfig,
+};
+
+pub struct Need {
+    pub item_id: u32,
+    pub quantity: u32,
+    pub hq_only: bool,
+}
+
+impl Need {
+    fn counts(&self, item: &InventoryItem) -> bool {
+        // Listed stock has to be pulled from the market board before use
+        if containers::is_listing(item.container) {
+            return false;
+        }
+        match item.quality() {
+            Quality::High => true,
+            Quality::Normal => !self.hq_only,
+            // Collectables can't be used as crafting materials
+            Quality::Collectable => false,
+        }
+    }
+}
+
+/// Reads a CSV with `item,quantity,quality` columns. The item is an id or
+/// exact item name, and the quality column is optional; `hq` only counts
+/// high quality stacks.
+pub fn load(path: &Path, table: &ItemTable) -> anyhow::Result<Vec<Need>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("Failed to open needs file {path:?}"))?;
+    let mut needs = vec![];
+    for row in reader.records() {
+        let row = row.with_context(|| format!("Failed to read needs file {path:?}"))?;
+        let item = row.get(0).unwrap_or_default();
+        let item_id = match item.parse() {
+            Ok(id) => id,
+            Err(_) => match table.find(item) {
+                Some(id) => id,
+                None => bail!("Unknown item `{item}` in {path:?}"),
+            },
+        };
+        let quantity = row.get(1).unwrap_or_default();
+        let quantity = quantity
+            .parse()
+            .with_context(|| format!("Invalid quantity `{quantity}` for {item} in {path:?}"))?;
+        let hq_only = match &*row.get(2).unwrap_or_default().to_lowercase() {
+            "" | "any" | "nq" => false,
+            "hq" => true,
+            other => bail!("Unknown quality `{other}` for {item} in {path:?}"),
+        };
+        needs.push(Need {
+            item_id,
+            quantity,
+            hq_only,
+        });
+    }
+    Ok(needs)
+}
+
+/// Prints have/need for every line of the needs file, with where the stock
+/// is. Returns whether everything is on hand.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    needs: &[Need],
+) -> anyhow::Result<bool> {
+    let max_name_len = needs
+        .iter()
+        .map(|need| table.name(need.item_id).len() + if need.hq_only { 3 } else { 0 })
+        .max()
+        .unwrap_or(0);
+
+    let mut satisfied = true;
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Crafting Needs")?;
+    for need in needs {
+        let mut stock: BTreeMap<(u64, u32), u32> = BTreeMap::new();
+        for item in items {
+            if item.item_id == need.item_id && need.counts(item) {
+                *stock
+                    .entry((item.character_id, item.container))
+                    .or_default() += item.quantity;
+            }
+        }
+        let have = stock.values().sum::<u32>();
+        let short = need.quantity.saturating_sub(have);
+        satisfied &= short == 0;
+
+        let name = match need.hq_only {
+            true => format!("{} HQ", table.name(need.item_id)),
+            false => table.name(need.item_id),
+        };
+        stdout.set_color(&if short == 0 {
+            theme::ready()
+        } else {
+            theme::error()
+        })?;
+        write!(
+            stdout,
+            "    {name:<max_name_len$} {have:>6}/{need}",
+            need = need.quantity
+        )?;
+        if short > 0 {
+            write!(stdout, " ({short} missing)")?;
+        }
+        writeln!(stdout)?;
+
+        stdout.set_color(&theme::good())?;
+        for ((holder, container), quantity) in stock {
+            writeln!(
+                stdout,
+                "        {quantity:>6} - {holder}, {container}",
+                holder = conf.holder_name(holder),
+                container = container_name(container),
+            )?;
+        }
+    }
+
+    Ok(satisfied)
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{containers::container_name, inventory::InventoryItem, items::ItemTable, MetaConfig};
+
+/// How many items are shown for each orphaned character.
+const SHOWN_ITEMS: usize = 5;
+
+/// Whether a character's rows are left over from someone the meta config no
+/// longer knows about, usually a dismissed retainer.
+pub fn is_orphan(conf: &MetaConfig, id: u64) -> bool {
+    conf.character(id).is_none()
+}
+
+/// Character ids present in the inventory but missing from the meta config,
+/// with their row counts and a few of their items. Items are ordered newest
+/// first by id, since newer game content gets higher ids.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let mut by_character: BTreeMap<u64, Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        if is_orphan(conf, item.character_id) {
+            by_character
+                .entry(item.character_id)
+                .or_default()
+                .push(item);
+        }
+    }
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Orphaned Inventories")?;
+    if by_character.is_empty() {
+        stdout.set_color(&theme::good())?;
+        writeln!(stdout, "    Every row belongs to a saved character")?;
+        return Ok(());
+    }
+
+    for (id, rows) in by_character {
+        let row_count = rows.len();
+        let mut held = rows
+            .into_iter()
+            .filter(|item| item.item_id != 0)
+            .collect::<Vec<_>>();
+        stdout.set_color(&theme::warn())?;
+        writeln!(
+            stdout,
+            "    {} - {} rows, {} items",
+            conf.name_of(id),
+            row_count,
+            held.len()
+        )?;
+        held.sort_by_key(|item| std::cmp::Reverse(item.item_id));
+        stdout.set_color(&theme::good())?;
+        for item in held.iter().take(SHOWN_ITEMS) {
+            writeln!(
+                stdout,
+                "        {} x{} ({})",
+                table.name(item.item_id),
+                item.quantity,
+                container_name(item.container)
+            )?;
+        }
+        if held.len() > SHOWN_ITEMS {
+            writeln!(stdout, "        ...and {} more", held.len() - SHOWN_ITEMS)?;
+        }
+    }
+
+    Ok(())
+}
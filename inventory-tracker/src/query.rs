@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::{
+    layout::{self, Align, Column, Shrink},
+    theme,
+};
+use termcolor::WriteColor;
+
+use crate::{
+    containers,
+    inventory::{InventoryItem, Quality},
+    items::ItemTable,
+    sort::{self, Row, Sort},
+    MetaConfig,
+};
+
+/// Quantities of one item held by one character, split by quality.
+#[derive(Default)]
+pub struct Holding {
+    normal: u32,
+    high: u32,
+    collectable: u32,
+    /// How much of the above is up for sale on the market board
+    listed: u32,
+    /// How much of the above is in the glamour dresser or armoire
+    dresser: u32,
+}
+
+impl Holding {
+    pub fn add(&mut self, quality: Quality, quantity: u32, combine_quality: bool) {
+        match quality {
+            Quality::High if !combine_quality => self.high += quantity,
+            Quality::Normal | Quality::High => self.normal += quantity,
+            Quality::Collectable => self.collectable += quantity,
+        }
+    }
+
+    pub fn add_listed(&mut self, quantity: u32) {
+        self.listed += quantity;
+    }
+
+    pub fn add_dresser(&mut self, quantity: u32) {
+        self.dresser += quantity;
+    }
+
+    /// Adds a row, noting whether it's listed or kept for glamour.
+    pub fn add_item(&mut self, item: &InventoryItem, combine_quality: bool) {
+        self.add(item.quality(), item.quantity, combine_quality);
+        if containers::is_listing(item.container) {
+            self.add_listed(item.quantity);
+        }
+        if containers::is_glamour_storage(item.container) {
+            self.add_dresser(item.quantity);
+        }
+    }
+
+    /// How many are held, of every quality.
+    pub fn total(&self) -> u32 {
+        self.normal + self.high + self.collectable
+    }
+
+    pub fn display(&self) -> String {
+        let mut parts = vec![];
+        if self.high > 0 {
+            parts.push(format!("{} HQ", self.high));
+        }
+        if self.normal > 0 {
+            if self.high > 0 {
+                parts.push(format!("{} NQ", self.normal));
+            } else {
+                parts.push(self.normal.to_string());
+            }
+        }
+        if self.collectable > 0 {
+            parts.push(format!("{} collectable", self.collectable));
+        }
+        let mut held = parts.join(", ");
+        if self.listed > 0 {
+            held += &format!(" ({} listed)", self.listed);
+        }
+        match self.dresser {
+            0 => {}
+            dresser if dresser == self.total() => held += " (dresser)",
+            dresser => held += &format!(" ({dresser} in dresser)"),
+        }
+        held
+    }
+}
+
+/// Lists how many of each requested item every character is holding, by
+/// item and holder id unless `sort` says otherwise.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    item_ids: &[u32],
+    combine_quality: bool,
+    sort: Option<Sort>,
+) -> anyhow::Result<()> {
+    let mut holdings: BTreeMap<u32, BTreeMap<u64, Holding>> = BTreeMap::new();
+    for item in items {
+        if !item_ids.contains(&item.item_id) {
+            continue;
+        }
+        let holding = holdings
+            .entry(item.item_id)
+            .or_default()
+            .entry(item.character_id)
+            .or_default();
+        holding.add_item(item, combine_quality);
+    }
+    let holdings: Vec<(u32, Vec<(u64, Holding)>)> = match sort {
+        None => holdings
+            .into_iter()
+            .map(|(item_id, holders)| (item_id, holders.into_iter().collect()))
+            .collect(),
+        Some(sort) => {
+            let rows = holdings
+                .into_iter()
+                .flat_map(|(item_id, holders)| {
+                    holders
+                        .into_iter()
+                        .map(move |(holder, holding)| (item_id, holder, holding))
+                })
+                .collect();
+            let rows = sort.sort(rows, |&(item_id, holder, ref holding)| {
+                Row::held(conf, table, item_id, holder, holding.total())
+            });
+            sort::group(rows, |&(item_id, _, _)| item_id)
+                .into_iter()
+                .map(|(item_id, rows)| {
+                    let holders = rows
+                        .into_iter()
+                        .map(|(_, holder, holding)| (holder, holding))
+                        .collect();
+                    (item_id, holders)
+                })
+                .collect()
+        }
+    };
+
+    // Names give way to the terminal's width, never the quantities
+    let widths = layout::allocate(
+        4,
+        &[
+            Column::new(0, Shrink::Truncate { min: 12 }).measure(
+                holdings
+                    .iter()
+                    .flat_map(|(_, holders)| holders)
+                    .map(|&(holder, _)| conf.display_name(holder)),
+            ),
+            Column::new(3, Shrink::Never).measure(
+                holdings
+                    .iter()
+                    .flat_map(|(_, holders)| holders)
+                    .map(|(_, holding)| holding.display()),
+            ),
+        ],
+        layout::available(),
+    );
+
+    for (item_id, holders) in holdings {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "{}", table.name(item_id))?;
+        stdout.set_color(&theme::good())?;
+        for (holder, holding) in holders {
+            writeln!(
+                stdout,
+                "    {name} - {quantity}",
+                name = layout::fit(
+                    &conf.display_name(holder),
+                    widths[0].unwrap_or(0),
+                    Align::Left
+                ),
+                quantity = holding.display(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::{collections::HashMap, io::BufRead};
+
+use anyhow::{bail, Context};
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+use tracing::warn;
+
+use crate::{
+    filter::CharacterFilter,
+    inventory::InventoryItem,
+    items::ItemTable,
+    query,
+    search::{self, Terms},
+    MetaConfig,
+};
+
+const HELP: &str = "\
+Commands:
+    find <TEXT>     List items whose name contains TEXT
+    count <ID>      Show how many of an item every character holds
+    char <NAME>     List everything held by characters or retainers matching NAME
+    help            Print this help
+    quit            Leave the REPL
+";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Find(String),
+    Count(u32),
+    Char(String),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parses one line of input. Blank lines aren't a command.
+    pub fn parse(line: &str) -> anyhow::Result<Option<Command>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let (word, rest) = match line.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (line, ""),
+        };
+        let argument = |name: &str| {
+            if rest.is_empty() {
+                bail!("`{word}` needs {name}");
+            }
+            Ok(rest.to_string())
+        };
+        let command = match &*word.to_lowercase() {
+            "find" => Command::Find(argument("some text to search for")?),
+            "count" => {
+                let id = argument("an item id")?;
+                Command::Count(
+                    id.parse()
+                        .with_context(|| format!("`{id}` isn't an item id"))?,
+                )
+            }
+            "char" => Command::Char(argument("a character name")?),
+            "help" | "?" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            other => bail!("Unknown command `{other}`, try `help`"),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Every inventory row, indexed so commands don't have to rescan the file.
+pub struct Index {
+    items: Vec<InventoryItem>,
+    by_id: HashMap<u32, Vec<usize>>,
+    /// Lowercased item names, for substring searches
+    names: Vec<(String, u32)>,
+}
+
+impl Index {
+    pub fn build(items: Vec<InventoryItem>, table: &ItemTable) -> Index {
+        let mut by_id: HashMap<u32, Vec<usize>> = HashMap::new();
+        for (index, item) in items.iter().enumerate() {
+            if item.item_id != 0 {
+                by_id.entry(item.item_id).or_default().push(index);
+            }
+        }
+        let mut names = by_id
+            .keys()
+            .map(|&id| (table.name(id).to_lowercase(), id))
+            .collect::<Vec<_>>();
+        names.sort();
+        Index {
+            items,
+            by_id,
+            names,
+        }
+    }
+
+    pub fn with_id(&self, id: u32) -> Vec<InventoryItem> {
+        self.by_id
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|&index| self.items[index].clone())
+            .collect()
+    }
+
+    pub fn matching_name(&self, text: &str) -> Vec<InventoryItem> {
+        let terms = Terms::new(&[text.to_string()]);
+        self.names
+            .iter()
+            .filter(|(name, _)| terms.matches(name))
+            .flat_map(|&(_, id)| self.with_id(id))
+            .collect()
+    }
+
+    pub fn held_by(&self, conf: &MetaConfig, name: &str) -> Vec<InventoryItem> {
+        let filter = CharacterFilter {
+            characters: vec![name.to_string()],
+            worlds: vec![],
+        };
+        self.items
+            .iter()
+            .filter(|item| item.item_id != 0 && filter.keeps(conf, item.character_id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Reads commands from stdin until `quit` or end of input.
+pub fn run(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    index: &Index,
+    limit: usize,
+    combine_quality: bool,
+) -> anyhow::Result<()> {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        stdout.set_color(&theme::header())?;
+        write!(stdout, "> ")?;
+        stdout.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(stdout)?;
+            return Ok(());
+        };
+
+        let command = match Command::parse(&line?) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!("{err:#}");
+                continue;
+            }
+        };
+        match command {
+            Command::Find(text) => {
+                let items = index.matching_name(&text);
+                search::report(stdout, conf, table, &items, limit, None)?;
+            }
+            Command::Count(id) => {
+                let items = index.with_id(id);
+                query::report(stdout, conf, table, &items, &[id], combine_quality, None)?;
+            }
+            Command::Char(name) => {
+                let items = index.held_by(conf, &name);
+                search::report(stdout, conf, table, &items, limit, None)?;
+            }
+            Command::Help => {
+                stdout.set_color(&theme::good())?;
+                write!(stdout, "{HELP}")?;
+            }
+            Command::Quit => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inventory::{self, test_row},
+        items::VENTURE,
+    };
+
+    fn index() -> (Index, MetaConfig) {
+        let items = inventory::test_items(&[
+            test_row(0, 0, VENTURE, 300, 100),
+            test_row(0, 1, 0, 0, 100),
+            test_row(0, 2, 20, 5, 100),
+            test_row(10000, 0, VENTURE, 50, 200),
+            test_row(0, 0, 21, 7, 300),
+        ]);
+        let conf = crate::test_conf();
+        (Index::build(items, &ItemTable::builtin()), conf)
+    }
+
+    #[test]
+    fn index_groups_rows_by_item() {
+        let (index, _) = index();
+        let ventures = index.with_id(VENTURE);
+        assert_eq!(ventures.len(), 2);
+        assert_eq!(ventures.iter().map(|item| item.quantity).sum::<u32>(), 350);
+        assert!(index.with_id(0).is_empty());
+        assert!(index.with_id(12345).is_empty());
+    }
+
+    #[test]
+    fn index_searches_names_case_insensitively() {
+        let (index, _) = index();
+        let seals = index.matching_name("SEAL");
+        let mut ids = seals.iter().map(|item| item.item_id).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![20, 21]);
+        assert_eq!(index.matching_name("storm seal").len(), 1);
+        assert!(index.matching_name("gil").is_empty());
+    }
+
+    #[test]
+    fn index_finds_items_held_by_a_character_and_their_retainers() {
+        let (index, conf) = index();
+        assert_eq!(index.held_by(&conf, "alice").len(), 3);
+        assert_eq!(index.held_by(&conf, "bob").len(), 1);
+        assert!(index.held_by(&conf, "nobody").is_empty());
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            Command::parse("find  ash log ").unwrap(),
+            Some(Command::Find("ash log".to_string()))
+        );
+        assert_eq!(
+            Command::parse("count 21072").unwrap(),
+            Some(Command::Count(21072))
+        );
+        assert_eq!(
+            Command::parse("CHAR Alice").unwrap(),
+            Some(Command::Char("Alice".to_string()))
+        );
+        assert_eq!(Command::parse("quit").unwrap(), Some(Command::Quit));
+        assert_eq!(Command::parse("exit").unwrap(), Some(Command::Quit));
+        assert_eq!(Command::parse("?").unwrap(), Some(Command::Help));
+        assert_eq!(Command::parse("   ").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        assert!(Command::parse("find").is_err());
+        assert!(Command::parse("count venture").is_err());
+        assert!(Command::parse("dance").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, container_name, ContainerGroup},
+    inventory::InventoryItem,
+    items::ItemTable,
+    sort::{Row, Sort},
+    MetaConfig,
+};
+
+#[derive(Default)]
+struct Saddlebags<'a> {
+    /// Container id -> items, for both regular and premium pages
+    pages: BTreeMap<u32, Vec<&'a InventoryItem>>,
+    /// The plugin only exports premium pages for characters with the
+    /// companion subscription, even when they're empty
+    premium: bool,
+}
+
+pub fn is_saddlebag_container(id: u32) -> bool {
+    containers::lookup(id).is_some_and(|container| {
+        matches!(
+            container.group,
+            ContainerGroup::Saddlebag | ContainerGroup::PremiumSaddlebag
+        )
+    })
+}
+
+/// Chocobo saddlebag contents per character, noting who has the premium
+/// saddlebag. Each page is in slot order unless `sort` says otherwise.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    sort: Option<Sort>,
+) -> anyhow::Result<()> {
+    let mut by_character: BTreeMap<u64, Saddlebags> = BTreeMap::new();
+    for item in items {
+        // Retainers don't have saddlebags, but every player character should
+        // show up even without any saddlebag rows
+        if conf.owner_of(item.character_id) != item.character_id {
+            continue;
+        }
+        let saddlebags = by_character.entry(item.character_id).or_default();
+        let group = containers::lookup(item.container).map(|container| container.group);
+        match group {
+            Some(ContainerGroup::Saddlebag) => {}
+            Some(ContainerGroup::PremiumSaddlebag) => saddlebags.premium = true,
+            _ => continue,
+        }
+        let page = saddlebags.pages.entry(item.container).or_default();
+        if item.item_id != 0 {
+            page.push(item);
+        }
+    }
+
+    let max_name_len = by_character
+        .values()
+        .flat_map(|saddlebags| saddlebags.pages.values())
+        .flatten()
+        .map(|item| table.name(item.item_id).len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Saddlebags")?;
+    for (character, saddlebags) in by_character {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(character))?;
+        if saddlebags.pages.is_empty() {
+            stdout.set_color(&theme::warn())?;
+            writeln!(stdout, "        No saddlebag recorded")?;
+            continue;
+        }
+        for (container, mut items) in saddlebags.pages {
+            items.sort_by_key(|item| item.slot);
+            if let Some(sort) = sort {
+                items = sort.sort(items, |item| Row {
+                    container: container_name(container),
+                    ..Row::held(conf, table, item.item_id, item.character_id, item.quantity)
+                });
+            }
+            stdout.set_color(&theme::header())?;
+            writeln!(stdout, "        {}", container_name(container))?;
+            stdout.set_color(&theme::good())?;
+            if items.is_empty() {
+                writeln!(stdout, "            (empty)")?;
+            }
+            for item in items {
+                writeln!(
+                    stdout,
+                    "            {name:<max_name_len$} - {quantity}",
+                    name = table.name(item.item_id),
+                    quantity = item.quantity,
+                )?;
+            }
+        }
+        if !saddlebags.premium {
+            stdout.set_color(&theme::warn())?;
+            writeln!(stdout, "        No premium saddlebag")?;
+        }
+    }
+
+    Ok(())
+}
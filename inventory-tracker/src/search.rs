@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::{
+    layout::{self, Align, Column, Shrink},
+    theme,
+};
+use termcolor::WriteColor;
+use tracing::warn;
+
+use crate::{
+    containers::container_name,
+    inventory::InventoryItem,
+    items::ItemTable,
+    query::Holding,
+    sort::{self, Row, Sort},
+    MetaConfig,
+};
+
+/// Case-insensitive search terms; an item matches when its name contains
+/// every one of them.
+pub struct Terms(Vec<String>);
+
+impl Terms {
+    pub fn new(queries: &[String]) -> Terms {
+        Terms(
+            queries
+                .iter()
+                .flat_map(|query| query.split_whitespace())
+                .map(str::to_lowercase)
+                .collect(),
+        )
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.0.iter().all(|term| name.contains(&**term))
+    }
+}
+
+/// Prints every matching item grouped by item, then by holder and container,
+/// in name order unless `sort` says otherwise. `items` is expected to contain
+/// only rows that already matched.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    limit: usize,
+    sort: Option<Sort>,
+) -> anyhow::Result<()> {
+    let found = match sort {
+        None => by_name(conf, table, items),
+        Some(sort) => sorted(conf, table, items, sort),
+    };
+
+    if found.is_empty() {
+        warn!("No matching items");
+        return Ok(());
+    }
+
+    // Holders give way to the terminal's width, never where the items are
+    let shown = || {
+        found
+            .iter()
+            .take(limit)
+            .flat_map(|(_, locations)| locations)
+    };
+    let widths = layout::allocate(
+        4,
+        &[
+            Column::new(0, Shrink::Truncate { min: 12 })
+                .measure(shown().map(|((holder, _), _)| holder)),
+            Column::new(3, Shrink::Never).measure(shown().map(|((_, container), holding)| {
+                format!("{}: {}", container_name(*container), holding.display())
+            })),
+        ],
+        layout::available(),
+    );
+
+    let total = found.len();
+    for (name, locations) in found.into_iter().take(limit) {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "{name}")?;
+        stdout.set_color(&theme::good())?;
+        for ((holder, container), holding) in locations {
+            writeln!(
+                stdout,
+                "    {holder} - {container}: {quantity}",
+                holder = layout::fit(&holder, widths[0].unwrap_or(0), Align::Left),
+                container = container_name(container),
+                quantity = holding.display(),
+            )?;
+        }
+    }
+    if total > limit {
+        warn!(
+            "... and {} more matching items (raise --limit to see them)",
+            total - limit
+        );
+    }
+
+    Ok(())
+}
+
+/// Where each item is, keyed by its name and then by holder and container.
+type Found = Vec<(String, Vec<((String, u32), Holding)>)>;
+
+/// Items by name, and their holders by name, as they're shown.
+fn by_name(conf: &MetaConfig, table: &ItemTable, items: &[InventoryItem]) -> Found {
+    let mut found: BTreeMap<String, BTreeMap<(String, u32), Holding>> = BTreeMap::new();
+    for item in items {
+        found
+            .entry(table.name(item.item_id))
+            .or_default()
+            .entry((conf.display_name(item.character_id), item.container))
+            .or_default()
+            .add_item(item, false);
+    }
+    found
+        .into_iter()
+        .map(|(name, locations)| (name, locations.into_iter().collect()))
+        .collect()
+}
+
+/// Each item's holdings in `sort` order, with each item where its first
+/// holding falls.
+fn sorted(conf: &MetaConfig, table: &ItemTable, items: &[InventoryItem], sort: Sort) -> Found {
+    let mut found: BTreeMap<(u32, u64, u32), Holding> = BTreeMap::new();
+    for item in items {
+        found
+            .entry((item.item_id, item.character_id, item.container))
+            .or_default()
+            .add_item(item, false);
+    }
+    let rows = sort.sort(
+        found.into_iter().collect(),
+        |&((item_id, holder, container), ref holding)| Row {
+            container: container_name(container),
+            ..Row::held(conf, table, item_id, holder, holding.total())
+        },
+    );
+    sort::group(rows, |&((item_id, _, _), _)| item_id)
+        .into_iter()
+        .map(|(item_id, rows)| {
+            let locations = rows
+                .into_iter()
+                .map(|((_, holder, container), holding)| {
+                    ((conf.display_name(holder), container), holding)
+                })
+                .collect();
+            (table.name(item_id), locations)
+        })
+        .collect()
+}
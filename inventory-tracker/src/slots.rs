@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, ContainerGroup},
+    inventory::InventoryItem,
+    MetaConfig,
+};
+
+#[derive(Default)]
+struct SlotUsage {
+    used: u32,
+    capacity: u32,
+}
+
+impl SlotUsage {
+    fn percent(&self) -> f64 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            f64::from(self.used) * 100.0 / f64::from(self.capacity)
+        }
+    }
+}
+
+/// Prints how many slots each holder's inventories use. Returns whether any
+/// is at least `full_warn` percent full.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    items: &[InventoryItem],
+    full_warn: f64,
+) -> anyhow::Result<bool> {
+    // owner id -> (group, holder id) -> usage. Retainer rows are stored under
+    // the retainer's own id, so they stay separate from the owner's bags.
+    let mut usage: BTreeMap<u64, BTreeMap<(ContainerGroup, u64), SlotUsage>> = BTreeMap::new();
+    for item in items {
+        let Some(container) = containers::lookup(item.container) else {
+            continue;
+        };
+        if !counts_toward_slots(container.group) {
+            continue;
+        }
+        let owner = conf.owner_of(item.character_id);
+        let entry = usage
+            .entry(owner)
+            .or_default()
+            .entry((container.group, item.character_id))
+            .or_insert_with(|| SlotUsage {
+                used: 0,
+                capacity: containers::group_capacity(container.group),
+            });
+        if item.item_id != 0 {
+            entry.used += 1;
+        }
+    }
+
+    let max_label_len = usage
+        .values()
+        .flat_map(|groups| groups.keys())
+        .map(|&(group, holder)| group_label(conf, group, holder).len())
+        .max()
+        .unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Inventory Slots")?;
+    let mut any_overfull = false;
+    for (owner, groups) in usage {
+        let overfull = groups.values().any(|usage| usage.percent() >= full_warn);
+        any_overfull |= overfull;
+        stdout.set_color(&if overfull {
+            theme::warn()
+        } else {
+            theme::header()
+        })?;
+        writeln!(stdout, "    {}", conf.display_name(owner))?;
+
+        for ((group, holder), usage) in groups {
+            stdout.set_color(&if usage.percent() >= full_warn {
+                theme::warn()
+            } else {
+                theme::good()
+            })?;
+            let label = group_label(conf, group, holder);
+            writeln!(
+                stdout,
+                "        {label:<max_label_len$} {used:>4}/{capacity} ({free} free)",
+                used = usage.used,
+                capacity = usage.capacity,
+                free = usage.capacity.saturating_sub(usage.used),
+            )?;
+        }
+    }
+
+    Ok(any_overfull)
+}
+
+pub fn counts_toward_slots(group: ContainerGroup) -> bool {
+    matches!(
+        group,
+        ContainerGroup::Bags
+            | ContainerGroup::Saddlebag
+            | ContainerGroup::PremiumSaddlebag
+            | ContainerGroup::RetainerBags
+            | ContainerGroup::GlamourChest
+    )
+}
+
+pub fn group_label(conf: &MetaConfig, group: ContainerGroup, holder: u64) -> String {
+    match group {
+        ContainerGroup::RetainerBags => format!("{} {}", group.label(), conf.name_of(holder)),
+        _ => group.label().to_string(),
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use ffxiv_timers_core::{clock, state, worlds};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    containers::container_name,
+    inventory::{InventoryItem, Quality},
+    MetaConfig,
+};
+
+/// Bumped whenever the snapshot format changes in a way older readers can't
+/// handle.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema: u32,
+    pub generated_at: DateTime<Utc>,
+    pub characters: Vec<Character>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Character {
+    pub id: u64,
+    pub name: Option<String>,
+    pub world: Option<String>,
+    /// Set for retainers, pointing at the character that owns them
+    pub owner_id: Option<u64>,
+    pub containers: Vec<Container>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Container {
+    pub id: u32,
+    pub name: String,
+    pub items: Vec<Item>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Item {
+    pub slot: u32,
+    pub item_id: u32,
+    pub quantity: u32,
+    pub hq: bool,
+    pub collectable: bool,
+    pub spiritbond: u32,
+    pub condition: u32,
+    pub materia: Vec<Materia>,
+    pub stain: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Materia {
+    pub id: u32,
+    pub grade: u32,
+}
+
+pub fn build(conf: &MetaConfig, items: &[InventoryItem]) -> Snapshot {
+    let mut by_character: BTreeMap<u64, BTreeMap<u32, Vec<Item>>> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 {
+            continue;
+        }
+        by_character
+            .entry(item.character_id)
+            .or_default()
+            .entry(item.container)
+            .or_default()
+            .push(Item {
+                slot: item.slot,
+                item_id: item.item_id,
+                quantity: item.quantity,
+                hq: item.quality() == Quality::High,
+                collectable: item.quality() == Quality::Collectable,
+                spiritbond: item.spiritbond,
+                condition: item.condition,
+                materia: item
+                    .materia()
+                    .map(|(id, grade)| Materia { id, grade })
+                    .collect(),
+                stain: item.stain,
+            });
+    }
+
+    let characters = by_character
+        .into_iter()
+        .map(|(id, containers)| {
+            let savedchar = conf.character(id);
+            Character {
+                id,
+                name: savedchar.map(|chr| chr.name.clone()),
+                world: savedchar.map(|chr| worlds::name(chr.world_id)),
+                owner_id: savedchar
+                    .map(|chr| chr.owner_id)
+                    .filter(|&owner_id| owner_id != 0),
+                containers: containers
+                    .into_iter()
+                    .map(|(id, items)| Container {
+                        id,
+                        name: container_name(id),
+                        items,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Snapshot {
+        schema: SCHEMA_VERSION,
+        generated_at: clock::now(),
+        characters,
+    }
+}
+
+pub fn write(path: &Path, snapshot: &Snapshot) -> anyhow::Result<()> {
+    state::write_atomically_with(path, |file| {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, snapshot)?;
+        Ok(writer.flush()?)
+    })
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+
+use crate::{items::ItemTable, MetaConfig};
+
+/// What `--sort` orders rows by.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Quantity,
+    Name,
+    Character,
+    Container,
+}
+
+impl SortKey {
+    const ALL: [(&'static str, SortKey); 4] = [
+        ("quantity", SortKey::Quantity),
+        ("name", SortKey::Name),
+        ("character", SortKey::Character),
+        ("container", SortKey::Container),
+    ];
+
+    pub fn parse(name: &str) -> Option<SortKey> {
+        SortKey::ALL
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|&(_, key)| key)
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        SortKey::ALL.iter().map(|&(name, _)| name)
+    }
+}
+
+/// The order `--sort` and `--desc` ask for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub desc: bool,
+}
+
+/// What a row of any listing is sorted by, with names as they're shown.
+#[derive(Clone, Debug, Default)]
+pub struct Row {
+    pub item_name: String,
+    pub item_id: u32,
+    /// The player character it's held by, or whose retainer holds it
+    pub owner: String,
+    /// The character or retainer itself
+    pub holder: String,
+    pub container: String,
+    pub quantity: u32,
+}
+
+impl Row {
+    /// `quantity` of an item held by `holder`, named as reports show them,
+    /// in no container in particular.
+    pub fn held(
+        conf: &MetaConfig,
+        table: &ItemTable,
+        item_id: u32,
+        holder: u64,
+        quantity: u32,
+    ) -> Row {
+        Row {
+            item_name: table.name(item_id),
+            item_id,
+            owner: conf.display_name(conf.owner_of(holder)),
+            holder: conf.display_name(holder),
+            container: String::new(),
+            quantity,
+        }
+    }
+
+    /// Every field, most significant first, for breaking ties.
+    #[allow(clippy::type_complexity)]
+    fn fields(&self) -> (&str, u32, &str, &str, &str, u32) {
+        (
+            &self.item_name,
+            self.item_id,
+            &self.owner,
+            &self.holder,
+            &self.container,
+            self.quantity,
+        )
+    }
+}
+
+impl Sort {
+    /// Orders two rows by the key, then by every other field, so rows only
+    /// compare equal when nothing tells them apart and the order is the
+    /// same from one run to the next. Retainers sort with their owner.
+    pub fn compare(&self, a: &Row, b: &Row) -> Ordering {
+        let order = match self.key {
+            SortKey::Quantity => a.quantity.cmp(&b.quantity),
+            SortKey::Name => a.item_name.cmp(&b.item_name),
+            SortKey::Character => (&a.owner, &a.holder).cmp(&(&b.owner, &b.holder)),
+            SortKey::Container => a.container.cmp(&b.container),
+        };
+        let order = match self.desc {
+            true => order.reverse(),
+            false => order,
+        };
+        order.then_with(|| a.fields().cmp(&b.fields()))
+    }
+
+    /// Sorts `rows`, each seen through `row`, which is called once per row.
+    pub fn sort<T>(&self, rows: Vec<T>, row: impl Fn(&T) -> Row) -> Vec<T> {
+        let mut keyed = rows
+            .into_iter()
+            .map(|item| (row(&item), item))
+            .collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| self.compare(a, b));
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// Splits sorted rows into groups sharing a `key`, each group placed where
+/// its first row fell and keeping its rows in order.
+pub fn group<T, K: PartialEq>(rows: Vec<T>, key: impl Fn(&T) -> K) -> Vec<(K, Vec<T>)> {
+    let mut groups: Vec<(K, Vec<T>)> = vec![];
+    for row in rows {
+        let key = key(&row);
+        match groups.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, group)) => group.push(row),
+            None => groups.push((key, vec![row])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(item_name: &str, holder: &str, quantity: u32) -> Row {
+        Row {
+            item_name: item_name.to_string(),
+            owner: holder.to_string(),
+            holder: holder.to_string(),
+            quantity,
+            ..Row::default()
+        }
+    }
+
+    fn sorted(sort: Sort, rows: Vec<Row>) -> Vec<(String, String, u32)> {
+        sort.sort(rows, Row::clone)
+            .into_iter()
+            .map(|row| (row.item_name, row.holder, row.quantity))
+            .collect()
+    }
+
+    fn expect(rows: &[(&str, &str, u32)]) -> Vec<(String, String, u32)> {
+        rows.iter()
+            .map(|&(name, holder, quantity)| (name.to_string(), holder.to_string(), quantity))
+            .collect()
+    }
+
+    #[test]
+    fn ties_break_on_every_other_field() {
+        let rows = vec![
+            row("Venture", "Bob", 10),
+            row("Cotton", "Alice", 10),
+            row("Venture", "Alice", 10),
+            row("Cotton", "Alice", 3),
+        ];
+        let by_quantity = Sort {
+            key: SortKey::Quantity,
+            desc: true,
+        };
+        assert_eq!(
+            sorted(by_quantity, rows.clone()),
+            expect(&[
+                ("Cotton", "Alice", 10),
+                ("Venture", "Alice", 10),
+                ("Venture", "Bob", 10),
+                ("Cotton", "Alice", 3),
+            ])
+        );
+        let by_character = Sort {
+            key: SortKey::Character,
+            desc: false,
+        };
+        assert_eq!(
+            sorted(by_character, rows),
+            expect(&[
+                ("Cotton", "Alice", 3),
+                ("Cotton", "Alice", 10),
+                ("Venture", "Alice", 10),
+                ("Venture", "Bob", 10),
+            ])
+        );
+    }
+
+    #[test]
+    fn retainers_sort_under_their_owner() {
+        let retainer = Row {
+            owner: "Bob".to_string(),
+            holder: "retainer Amy".to_string(),
+            ..row("Venture", "", 1)
+        };
+        let rows = vec![row("Venture", "Cid", 1), retainer, row("Venture", "Bob", 1)];
+        let sort = Sort {
+            key: SortKey::Character,
+            desc: false,
+        };
+        let holders = sorted(sort, rows)
+            .into_iter()
+            .map(|(_, holder, _)| holder)
+            .collect::<Vec<_>>();
+        assert_eq!(holders, ["Bob", "retainer Amy", "Cid"]);
+    }
+
+    #[test]
+    fn groups_stay_where_their_first_row_fell() {
+        let groups = group(vec![(2, 'a'), (1, 'b'), (2, 'c')], |&(key, _)| key);
+        assert_eq!(groups, [(2, vec![(2, 'a'), (2, 'c')]), (1, vec![(1, 'b')])]);
+    }
+}
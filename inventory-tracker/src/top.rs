@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    containers::{self, ContainerGroup},
+    inventory::InventoryItem,
+    items::ItemTable,
+    slots::{counts_toward_slots, group_label},
+    MetaConfig,
+};
+
+#[derive(Default)]
+struct Usage {
+    stacks: u32,
+    quantity: u32,
+}
+
+/// The items taking up the most room in each inventory, by number of stacks
+/// or, with `by_quantity`, by how many are held.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    count: usize,
+    by_quantity: bool,
+) -> anyhow::Result<()> {
+    // owner id -> (group, holder id) -> item id -> usage
+    type Groups = BTreeMap<(ContainerGroup, u64), BTreeMap<u32, Usage>>;
+    let mut usage: BTreeMap<u64, Groups> = BTreeMap::new();
+    for item in items {
+        let Some(container) = containers::lookup(item.container) else {
+            continue;
+        };
+        if item.item_id == 0 || !counts_toward_slots(container.group) {
+            continue;
+        }
+        let entry = usage
+            .entry(conf.owner_of(item.character_id))
+            .or_default()
+            .entry((container.group, item.character_id))
+            .or_default()
+            .entry(item.item_id)
+            .or_default();
+        entry.stacks += 1;
+        entry.quantity += item.quantity;
+    }
+
+    stdout.set_color(&theme::header())?;
+    if by_quantity {
+        writeln!(stdout, "Largest Quantities")?;
+    } else {
+        writeln!(stdout, "Most Slots Used")?;
+    }
+    for (owner, groups) in usage {
+        stdout.set_color(&theme::header())?;
+        writeln!(stdout, "    {}", conf.display_name(owner))?;
+        for ((group, holder), held) in groups {
+            let mut held = held.into_iter().collect::<Vec<_>>();
+            held.sort_by(|(a_id, a), (b_id, b)| {
+                let order = if by_quantity {
+                    (b.quantity, b.stacks).cmp(&(a.quantity, a.stacks))
+                } else {
+                    (b.stacks, b.quantity).cmp(&(a.stacks, a.quantity))
+                };
+                order.then(a_id.cmp(b_id))
+            });
+            held.truncate(count);
+
+            let max_name_len = held
+                .iter()
+                .map(|&(id, _)| table.name(id).len())
+                .max()
+                .unwrap_or(0);
+
+            stdout.set_color(&theme::header())?;
+            writeln!(stdout, "        {}", group_label(conf, group, holder))?;
+            stdout.set_color(&theme::good())?;
+            for (id, usage) in held {
+                let stacks = match usage.stacks {
+                    1 => "1 slot".to_string(),
+                    n => format!("{n} slots"),
+                };
+                writeln!(
+                    stdout,
+                    "            {name:<max_name_len$} - {stacks:>8}, {quantity} total",
+                    name = table.name(id),
+                    quantity = usage.quantity,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use ffxiv_timers_core::{clock, paths, state, theme};
+use serde::{Deserialize, Serialize};
+use termcolor::WriteColor;
+use tracing::{debug, warn};
+
+use crate::{
+    config::Config,
+    inventory::{InventoryItem, Quality},
+    MetaConfig,
+};
+
+/// How long fetched prices are trusted unless the config says otherwise.
+const DEFAULT_CACHE_HOURS: f64 = 6.0;
+const CACHE_FILE: &str = "prices.json";
+
+#[cfg(feature = "value")]
+const UNIVERSALIS_URL: &str = "https://universalis.app/api/v2";
+/// Universalis answers at most 100 items per request.
+const BATCH_SIZE: usize = 100;
+
+/// The cheapest listing of an item on one world. Items with neither price
+/// are untradable or have nothing listed.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Price {
+    nq: Option<u32>,
+    hq: Option<u32>,
+    fetched_at: DateTime<Utc>,
+}
+
+impl Price {
+    fn unit_price(&self, quality: Quality) -> Option<u32> {
+        match quality {
+            Quality::High => self.hq.or(self.nq),
+            _ => self.nq.or(self.hq),
+        }
+    }
+}
+
+/// Bumped when [`PriceCache`] changes in a way older files can't be read as.
+const CACHE_SCHEMA: u32 = 1;
+
+/// Prices by world and item, kept in the cache directory between runs.
+#[derive(Serialize, Deserialize, Default)]
+struct PriceCache {
+    worlds: HashMap<String, HashMap<u32, Price>>,
+}
+
+impl PriceCache {
+    fn load(path: &Path) -> PriceCache {
+        debug!("Reading cached prices from {}", path.display());
+        state::load(path, CACHE_SCHEMA).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable price cache: {err:#}");
+            PriceCache::default()
+        })
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        state::save(path, CACHE_SCHEMA, self)
+    }
+}
+
+/// What one character's items would sell for.
+#[derive(Default)]
+pub struct Estimate {
+    pub gil: u64,
+    /// Stacks priced from a cached price older than the cache is trusted
+    pub stale: u32,
+    /// Stacks with no price at all, fetched or cached
+    pub unpriced: u32,
+}
+
+/// Estimated market value of everything each character and their retainers
+/// hold, priced on the character's home world (or the configured world).
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    config: &Config,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let estimates = estimate(conf, config, items);
+
+    let max_name_len = estimates
+        .keys()
+        .map(|&character| conf.display_name(character).len())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Estimated Market Value")?;
+    let mut total = 0;
+    for (character, estimate) in estimates {
+        total += estimate.gil;
+        stdout.set_color(&theme::good())?;
+        write!(
+            stdout,
+            "    {name:<max_name_len$} {gil:>14} gil",
+            name = conf.display_name(character),
+            gil = estimate.gil,
+        )?;
+        stdout.set_color(&theme::warn())?;
+        if estimate.stale > 0 {
+            write!(
+                stdout,
+                " ({} stacks priced from stale data)",
+                estimate.stale
+            )?;
+        }
+        if estimate.unpriced > 0 {
+            write!(stdout, " ({} stacks without a price)", estimate.unpriced)?;
+        }
+        writeln!(stdout)?;
+    }
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "    {:<max_name_len$} {total:>14} gil", "Total")?;
+
+    Ok(())
+}
+
+/// What each character's items, and their retainers', would sell for, by
+/// the character's id. Prices older than the cache is trusted for are
+/// fetched again, and where that fails the cached price is used anyway.
+pub fn estimate(
+    conf: &MetaConfig,
+    config: &Config,
+    items: &[InventoryItem],
+) -> BTreeMap<u64, Estimate> {
+    let cache_hours = config.price_cache_hours.unwrap_or(DEFAULT_CACHE_HOURS);
+    let max_age = chrono::Duration::minutes((cache_hours * 60.0) as i64);
+    let now = clock::now();
+
+    let world_of = |character: u64| match &config.price_world {
+        Some(world) => Some(world.clone()),
+        None => conf
+            .character(conf.owner_of(character))
+            .map(|chr| chr.world_id.to_string()),
+    };
+
+    let cache_path = paths::cache_dir().map(|dir| dir.join(CACHE_FILE));
+    let mut cache = cache_path
+        .as_deref()
+        .map(PriceCache::load)
+        .unwrap_or_default();
+
+    let mut wanted: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 {
+            continue;
+        }
+        let Some(world) = world_of(item.character_id) else {
+            continue;
+        };
+        let cached = cache
+            .worlds
+            .get(&world)
+            .and_then(|prices| prices.get(&item.item_id));
+        if cached.is_none_or(|price| now - price.fetched_at > max_age) {
+            wanted.entry(world).or_default().insert(item.item_id);
+        }
+    }
+
+    debug!(
+        "{} prices to fetch from Universalis, the rest are cached",
+        wanted.values().map(BTreeSet::len).sum::<usize>()
+    );
+
+    // The first failure is reported and ends fetching for this run; the
+    // report then makes do with whatever is cached
+    'fetch: for (world, ids) in wanted {
+        let ids = ids.into_iter().collect::<Vec<_>>();
+        for batch in ids.chunks(BATCH_SIZE) {
+            match fetch(&world, batch) {
+                Ok(prices) => cache
+                    .worlds
+                    .entry(world.clone())
+                    .or_default()
+                    .extend(prices),
+                Err(err) => {
+                    warn!("Couldn't fetch prices from Universalis: {err:#}");
+                    break 'fetch;
+                }
+            }
+        }
+    }
+    if let Some(path) = &cache_path {
+        if let Err(err) = cache.save(path) {
+            warn!("Couldn't save the price cache: {err:#}");
+        }
+    }
+
+    let mut estimates: BTreeMap<u64, Estimate> = BTreeMap::new();
+    for item in items {
+        if item.item_id == 0 {
+            continue;
+        }
+        let estimate = estimates
+            .entry(conf.owner_of(item.character_id))
+            .or_default();
+        let price = world_of(item.character_id)
+            .and_then(|world| cache.worlds.get(&world)?.get(&item.item_id).copied());
+        let Some(price) = price else {
+            estimate.unpriced += 1;
+            continue;
+        };
+        // Untradable items are left out rather than counted as unpriced
+        let Some(unit_price) = price.unit_price(item.quality()) else {
+            continue;
+        };
+        estimate.gil += u64::from(unit_price) * u64::from(item.quantity);
+        if now - price.fetched_at > max_age {
+            estimate.stale += 1;
+        }
+    }
+    estimates
+}
+
+#[cfg(feature = "value")]
+fn fetch(world: &str, ids: &[u32]) -> anyhow::Result<HashMap<u32, Price>> {
+    use serde_json::Value;
+
+    let id_list = ids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let url = format!("{UNIVERSALIS_URL}/{world}/{id_list}?listings=0&entries=0");
+    let body = ureq::get(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .call()?
+        .into_string()?;
+    let response: Value = serde_json::from_str(&body)?;
+
+    // A single item is answered with the item itself instead of a map
+    let listed = match response.get("items").and_then(Value::as_object) {
+        Some(items) => items.values().collect::<Vec<_>>(),
+        None => vec![&response],
+    };
+    let price = |item: &Value, field: &str| {
+        item.get(field)
+            .and_then(Value::as_u64)
+            .filter(|&price| price > 0)
+            .map(|price| price as u32)
+    };
+
+    let fetched_at = clock::now();
+    let mut prices = HashMap::new();
+    for item in listed {
+        let Some(id) = item.get("itemID").and_then(Value::as_u64) else {
+            continue;
+        };
+        prices.insert(
+            id as u32,
+            Price {
+                nq: price(item, "minPriceNQ"),
+                hq: price(item, "minPriceHQ"),
+                fetched_at,
+            },
+        );
+    }
+    // Anything Universalis didn't answer for can't be sold on the board
+    for &id in ids {
+        prices.entry(id).or_insert(Price {
+            nq: None,
+            hq: None,
+            fetched_at,
+        });
+    }
+    Ok(prices)
+}
+
+#[cfg(not(feature = "value"))]
+fn fetch(_world: &str, _ids: &[u32]) -> anyhow::Result<HashMap<u32, Price>> {
+    anyhow::bail!("built without the `value` feature, so only cached prices are available")
+}
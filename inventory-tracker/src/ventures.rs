@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{config::Config, filter::CharacterFilter, inventory::InventoryItem, items, MetaConfig};
+
+/// Ventures a retainer gets through in a day sending out hour-long ventures
+/// around the clock.
+pub const DEFAULT_VENTURES_PER_DAY: f64 = 12.0;
+
+/// Days left before the ventures run out, or `None` with no retainers using
+/// them.
+pub fn days_remaining(ventures: u32, retainers: u32, per_retainer: f64) -> Option<f64> {
+    let per_day = f64::from(retainers) * per_retainer;
+    (per_day > 0.0).then(|| f64::from(ventures) / per_day)
+}
+
+/// Ventures held by each character and their retainers, with an estimate of
+/// how long they'll last.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    config: &Config,
+    characters: &CharacterFilter,
+    items: &[InventoryItem],
+) -> anyhow::Result<()> {
+    let per_retainer = config
+        .ventures_per_retainer_day
+        .unwrap_or(DEFAULT_VENTURES_PER_DAY);
+
+    // owner id -> (ventures, retainers)
+    let mut owners: BTreeMap<u64, (u32, u32)> = BTreeMap::new();
+    for (&id, chr) in &conf.saved_characters {
+        if chr.owner_id == 0 && characters.keeps(conf, id) {
+            owners.entry(id).or_default();
+        } else if chr.owner_id != 0 && characters.keeps(conf, chr.owner_id) {
+            owners.entry(chr.owner_id).or_default().1 += 1;
+        }
+    }
+    for item in items {
+        if item.item_id == items::VENTURE {
+            owners
+                .entry(conf.owner_of(item.character_id))
+                .or_default()
+                .0 += item.quantity;
+        }
+    }
+
+    let names = owners
+        .keys()
+        .map(|&owner| (owner, conf.display_name(owner)))
+        .collect::<BTreeMap<_, _>>();
+    let max_name_len = names.values().map(String::len).max().unwrap_or(0);
+
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "Ventures")?;
+    for (owner, (ventures, retainers)) in owners {
+        // The meta config only lists retainers that have been opened in game
+        let retainers = config.retainer_count.unwrap_or(retainers);
+        let days = days_remaining(ventures, retainers, per_retainer);
+        let color = match days {
+            Some(days) if days < 1.0 => theme::error(),
+            Some(days) if days < 3.0 => theme::warn(),
+            _ => theme::good(),
+        };
+        let plural = if retainers == 1 { "" } else { "s" };
+        let estimate = match days {
+            Some(days) => format!("{retainers} retainer{plural}, {days:.1} days"),
+            None => "no retainers".to_string(),
+        };
+        stdout.set_color(&color)?;
+        writeln!(
+            stdout,
+            "    {name:<max_name_len$} - {ventures:>5} ({estimate})",
+            name = names[&owner],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_remaining_scales_with_retainers() {
+        assert_eq!(days_remaining(120, 2, 12.0), Some(5.0));
+        assert_eq!(days_remaining(120, 0, 12.0), None);
+        assert_eq!(days_remaining(0, 1, 12.0), Some(0.0));
+    }
+}
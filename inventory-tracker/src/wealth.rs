@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use ffxiv_timers_core::theme;
+use termcolor::WriteColor;
+
+use crate::{
+    config::Config, currency, inventory::InventoryItem, items::ItemTable, value, MetaConfig,
+};
+
+/// Each character's gil and what their items would sell for, counting
+/// their retainers', most first with each one's share of the account.
+/// Characters worth less than `min_gil` in all are summed into one row
+/// after the rest.
+pub fn report(
+    stdout: &mut dyn WriteColor,
+    conf: &MetaConfig,
+    config: &Config,
+    table: &ItemTable,
+    items: &[InventoryItem],
+    min_gil: u64,
+) -> anyhow::Result<()> {
+    let currencies = currency::currencies(config, table);
+    let gil_index = currencies
+        .iter()
+        .position(|currency| currency.id == currency::GIL);
+    // character id -> (gil, estimated value of their items)
+    let mut wealth: BTreeMap<u64, (u64, u64)> = BTreeMap::new();
+    for (holder, amounts) in currency::held(&currencies, items) {
+        let gil = gil_index.and_then(|index| amounts.get(&index).copied());
+        wealth.entry(conf.owner_of(holder)).or_default().0 += u64::from(gil.unwrap_or(0));
+    }
+    let mut unpriced = 0;
+    for (character, estimate) in value::estimate(conf, config, items) {
+        wealth.entry(character).or_default().1 += estimate.gil;
+        unpriced += estimate.unpriced;
+    }
+
+    let total = wealth
+        .values()
+        .map(|&(gil, items)| gil + items)
+        .sum::<u64>();
+    let rows = rows(conf, wealth, min_gil);
+    let max_name_len = rows
+        .iter()
+        .map(|(name, ..)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+    let share = |gil: u64| gil as f64 / total.max(1) as f64 * 100.0;
+
+    stdout.set_color(&theme::header())?;
+    writeln!(
+        stdout,
+        "Wealth\n    {:<max_name_len$} {:>14} {:>14} {:>14} {:>5}",
+        "", "gil", "items", "total", "share"
+    )?;
+    for (name, gil, items) in rows {
+        stdout.set_color(&theme::good())?;
+        writeln!(
+            stdout,
+            "    {name:<max_name_len$} {gil:>14} {items:>14} {:>14} {:>4.0}%",
+            gil + items,
+            share(gil + items),
+        )?;
+    }
+    stdout.set_color(&theme::header())?;
+    writeln!(stdout, "    {:<max_name_len$} {:>44}", "Total", total)?;
+    stdout.set_color(&theme::dim())?;
+    write!(stdout, "Item estimates exclude untradable items")?;
+    if unpriced > 0 {
+        write!(stdout, " and {unpriced} stacks without a price")?;
+    }
+    writeln!(stdout)?;
+
+    Ok(())
+}
+
+/// Each character's name, gil and item value, richest first, ending with
+/// those worth less than `min_gil` together.
+fn rows(
+    conf: &MetaConfig,
+    wealth: BTreeMap<u64, (u64, u64)>,
+    min_gil: u64,
+) -> Vec<(String, u64, u64)> {
+    let (mut kept, others): (Vec<_>, Vec<_>) = wealth
+        .into_iter()
+        .partition(|&(_, (gil, items))| gil + items >= min_gil);
+    kept.sort_by(|(a_id, (a_gil, a_items)), (b_id, (b_gil, b_items))| {
+        (b_gil + b_items)
+            .cmp(&(a_gil + a_items))
+            .then(a_id.cmp(b_id))
+    });
+    let mut rows = kept
+        .into_iter()
+        .map(|(character, (gil, items))| (conf.display_name(character), gil, items))
+        .collect::<Vec<_>>();
+    match others.len() {
+        0 => {}
+        // One character alone is as easy to name as to count
+        1 => {
+            let (character, (gil, items)) = others[0];
+            rows.push((conf.display_name(character), gil, items));
+        }
+        count => rows.push((
+            format!("{count} others"),
+            others.iter().map(|&(_, (gil, _))| gil).sum(),
+            others.iter().map(|&(_, (_, items))| items).sum(),
+        )),
+    }
+    rows
+}
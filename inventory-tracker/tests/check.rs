@@ -0,0 +1,53 @@
+//! Runs inventory-tracker in a fake home, checking what `--check` exits with
+//! and that nothing but the report is printed to stdout.
+
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_inventory-tracker");
+
+/// A home whose config.toml is always warned about, with Alice holding 300
+/// ventures.
+fn home(test: &str) -> FakeHome {
+    let home = FakeHome::warned(&format!("inventory-tracker-check-{test}"));
+    home.inventory(&[(100, "Alice Doe")], &[(100, 21072, 300)]);
+    home
+}
+
+#[test]
+fn check_warns_when_needs_are_short() {
+    let home = home("needs");
+    let needs = home.path().join("needs.csv");
+    let needs_flag = needs.to_str().unwrap();
+    std::fs::write(&needs, "item,quantity\n21072,100\n").unwrap();
+    assert_eq!(
+        home.run(BIN, &["--check", "--needs", needs_flag])
+            .warned_status(),
+        0
+    );
+    std::fs::write(&needs, "item,quantity\n21072,500\n").unwrap();
+    assert_eq!(
+        home.run(BIN, &["--check", "--needs", needs_flag])
+            .warned_status(),
+        1
+    );
+    assert_eq!(home.run(BIN, &["--needs", needs_flag]).warned_status(), 0);
+}
+
+#[test]
+fn notes_about_the_report_go_to_stderr() {
+    let home = home("notes");
+    let run = home.run(BIN, &["--check", "--search", "no such item"]);
+    assert_eq!(run.warned_status(), 0);
+    assert!(run.stdout.is_empty());
+    assert!(run.stderr.contains("No matching items"));
+}
+
+#[test]
+fn errors_are_unknown_under_check() {
+    let home = FakeHome::warned("inventory-tracker-check-errors");
+    let run = home.run(BIN, &["--check"]);
+    assert_eq!(run.warned_status(), 3);
+    assert!(run.stdout.is_empty());
+    // Missing files usually exit with 2 instead
+    assert_eq!(home.run(BIN, &[]).warned_status(), 2);
+}
@@ -0,0 +1,147 @@
+//! Runs inventory-tracker in a fake home, checking the plain-text report.
+
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_inventory-tracker");
+
+#[test]
+fn reports_who_holds_each_item() {
+    let home = FakeHome::new("inventory-tracker-report");
+    home.inventory(
+        &[(100, "Alice Doe"), (200, "Bob Roe")],
+        &[(100, 21072, 300), (200, 21072, 45), (200, 5111, 12)],
+    );
+    let run = home.run(BIN, &["21072"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Venture\n    Alice Doe (Tonberry) - 300\n    Bob Roe (Tonberry)   - 45\n"
+    );
+}
+
+#[test]
+fn holders_can_be_sorted_by_quantity() {
+    let home = FakeHome::new("inventory-tracker-report-sort");
+    home.inventory(
+        &[(100, "Alice Doe"), (200, "Bob Roe")],
+        &[(100, 21072, 45), (200, 21072, 300)],
+    );
+    let run = home.run(BIN, &["21072", "--sort", "quantity", "--desc"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Venture\n    Bob Roe (Tonberry)   - 300\n    Alice Doe (Tonberry) - 45\n"
+    );
+}
+
+#[test]
+fn config_in_the_home_is_read() {
+    let home = FakeHome::new("inventory-tracker-report-watchlist");
+    home.inventory(&[(100, "Alice Doe")], &[(100, 5111, 12)])
+        .config("watchlist = [5111]\n");
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    // Not in the built-in item table, so named by id
+    assert_eq!(run.stdout, "Item #5111\n    Alice Doe (Tonberry) - 12\n");
+}
+
+#[test]
+fn locations_rank_holders_by_share() {
+    let home = FakeHome::new("inventory-tracker-report-locations");
+    home.inventory(
+        &[(100, "Alice Doe"), (200, "Bob Roe"), (300, "Cid Moe")],
+        &[(100, 21072, 44), (200, 21072, 150), (300, 21072, 6)],
+    );
+    let run = home.run(BIN, &["--locations", "venture"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Venture - 200\n    \
+         Bob Roe (Tonberry)   150  75% ███████████████\n    \
+         Alice Doe (Tonberry)  44  22% ████\n    \
+         Cid Moe (Tonberry)     6   3% █\n"
+    );
+    let run = home.run(BIN, &["--locations", "--min-qty", "50", "21072"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Venture - 200\n    \
+         Bob Roe (Tonberry) 150  75% ███████████████\n    \
+         2 others            50  25% █████\n"
+    );
+}
+
+#[test]
+fn wealth_adds_gil_to_what_items_would_sell_for() {
+    let home = FakeHome::new("inventory-tracker-report-wealth");
+    home.inventory(
+        &[(100, "Alice Doe"), (200, "Bob Roe"), (300, "Cid Moe")],
+        &[
+            (100, 1, 1000),
+            (100, 21072, 2),
+            (200, 1, 5000),
+            (300, 1, 10),
+        ],
+    );
+    // Gil has no price on the market board, and ventures sell for 100
+    let fetched_at = home.now().to_rfc3339();
+    let prices = format!(
+        r#"{{"schema": 1, "data": {{"worlds": {{"72": {{
+            "1": {{"nq": null, "hq": null, "fetched_at": "{fetched_at}"}},
+            "21072": {{"nq": 100, "hq": null, "fetched_at": "{fetched_at}"}}}}}}}}}}"#
+    );
+    let cache = home.path().join(".cache/ffxiv-timers");
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::write(cache.join("prices.json"), prices).unwrap();
+
+    let run = home.run(BIN, &["--wealth"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Wealth\n    \
+         \x20                               gil          items          total share\n    \
+         Bob Roe (Tonberry)             5000              0           5000   81%\n    \
+         Alice Doe (Tonberry)           1000            200           1200   19%\n    \
+         Cid Moe (Tonberry)               10              0             10    0%\n    \
+         Total                                                        6210\n\
+         Item estimates exclude untradable items\n"
+    );
+    let run = home.run(BIN, &["--wealth", "--min", "2000"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "Wealth\n    \
+         \x20                             gil          items          total share\n    \
+         Bob Roe (Tonberry)           5000              0           5000   81%\n    \
+         2 others                     1010            200           1210   19%\n    \
+         Total                                                      6210\n\
+         Item estimates exclude untradable items\n"
+    );
+}
+
+#[test]
+fn queries_fall_back_to_the_last_rows_kept() {
+    let home = FakeHome::new("inventory-tracker-report-fallback");
+    home.inventory(&[(100, "Alice Doe")], &[(100, 21072, 300)]);
+    let run = home.run(BIN, &["21072", "--fallback-cache"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert_eq!(run.stdout, "Venture\n    Alice Doe (Tonberry) - 300\n");
+
+    // As if the plugin were part way through rewriting it
+    std::fs::remove_file(home.plugin_configs().join("InventoryTools/inventories.csv")).unwrap();
+    let run = home.run(BIN, &["21072", "--fallback-cache"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let (stale, report) = run.stdout.split_once('\n').unwrap();
+    assert!(
+        stale.starts_with("inventories.csv (stale, from "),
+        "{stale}"
+    );
+    assert_eq!(report, "Venture\n    Alice Doe (Tonberry) - 300\n");
+    let run = home.run(BIN, &["21072", "--fallback-cache", "--json"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    assert!(run.stdout.contains(r#""cached_at""#), "{}", run.stdout);
+
+    // Without it, the missing file is reported as usual
+    let run = home.run(BIN, &["21072"]);
+    assert_eq!(run.status, 2, "stdout: {}", run.stdout);
+}
@@ -0,0 +1,453 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Duration, Utc};
+use ffxiv_timers_core::{
+    check::Severity,
+    clock, config,
+    de::datetime_or_default,
+    fallback,
+    i18n::{tr, trf},
+    json::{self, MapReport, Tool},
+    layout::{self, Align, Column, Shrink},
+    options::Options,
+    paths, profiles,
+    scan::{self, Scan},
+    source::{self, Support},
+    theme,
+    time::{format_duration, format_local, Past, Style},
+    urgency, worlds,
+};
+use serde::{Deserialize, Serialize};
+use termcolor::WriteColor;
+use tracing::{debug, info};
+
+/// Accountant's per-character task files, inside the plugin config directory.
+const TASKS_FOLDER: [&str; 2] = ["Accountant", "tasks"];
+
+#[derive(Default, Serialize, Deserialize)]
+struct AccountantTaskData {
+    #[serde(rename = "Item1")]
+    char_info: CharacterInfo,
+    #[serde(rename = "Item2")]
+    task_info: TaskInfo,
+    /// When the file was last written, which Accountant does on logout
+    #[serde(skip)]
+    updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CharacterInfo {
+    name: String,
+    server_id: i32,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TaskInfo {
+    #[serde(deserialize_with = "datetime_or_default")]
+    map: DateTime<Utc>,
+}
+
+/// How many hours apart a character can gather treasure maps.
+const ALLOWANCE_HOURS: i64 = 18;
+
+/// How old, in seconds, a character's file can be before the report says
+/// when it was last updated, unless `config.toml` sets `updated_max_age`.
+const DEFAULT_UPDATED_MAX_AGE: u64 = 3 * 24 * 3600;
+
+/// The fields `--format` can use, one row per character.
+pub const FORMAT_FIELDS: &[&str] = &["name", "world", "tier", "remaining", "at", "profile"];
+
+/// The directory Accountant writes task files to.
+pub fn data_path() -> anyhow::Result<PathBuf> {
+    paths::plugin_path(TASKS_FOLDER)
+}
+
+fn server_name(id: i32) -> String {
+    match u32::try_from(id) {
+        Ok(id) => worlds::name(id),
+        Err(_) => format!("World#{id}"),
+    }
+}
+
+/// Every character's allowance, leaving out those not seen for a week and
+/// the copies left behind on a world a character moved from.
+pub fn reports() -> anyhow::Result<Vec<MapReport>> {
+    reports_merging(true)
+}
+
+/// Every character's allowance, with those left behind by a world transfer
+/// kept too unless `merge`.
+fn reports_merging(merge: bool) -> anyhow::Result<Vec<MapReport>> {
+    worlds::load_overrides(None)?;
+    profiles::each(|profile| load(profile, merge).map(|(reports, _)| reports))
+}
+
+/// The task files' layout, as the loader reads it.
+pub fn support() -> Support {
+    Support {
+        files: format!("{}/*.json", TASKS_FOLDER.join("/")),
+        layouts: vec![source::shape(&AccountantTaskData::default())],
+    }
+}
+
+/// What the task files in the profile being read hold, for `doctor`.
+pub fn scan() -> anyhow::Result<Scan> {
+    load(None, true).map(|(_, scan)| scan)
+}
+
+fn load(profile: Option<&str>, merge: bool) -> anyhow::Result<(Vec<MapReport>, Scan)> {
+    let tasks_folder = data_path()?;
+
+    let mut scan = Scan::default();
+    let mut entries = scan::json_files::<AccountantTaskData>(&tasks_folder, &mut scan)
+        .with_context(|| {
+            format!(
+                "Failed to read Accountant's task data in {tasks_folder:?}; install the \
+                 Accountant plugin and log in on each character, or pass --data-root"
+            )
+        })?
+        .into_iter()
+        .map(|file| AccountantTaskData {
+            updated: file.modified.map(DateTime::from),
+            ..file.data
+        })
+        .collect::<Vec<_>>();
+    scan.rows = entries.len();
+
+    entries.retain(|entry| {
+        let now = clock::now();
+        let one_week_ago = now - Duration::weeks(1);
+        let recent = entry.task_info.map > one_week_ago;
+        if !recent {
+            debug!("Leaving out {}, not seen for a week", entry.char_info.name);
+        }
+        recent
+    });
+    if merge {
+        let left_behind = left_behind(&entries, clock::now());
+        let mut left_behind = left_behind.into_iter();
+        entries.retain(|entry| {
+            let stale = left_behind.next().unwrap_or(false);
+            if stale {
+                info!(
+                    "merged duplicate for '{}' (old world {})",
+                    entry.char_info.name,
+                    server_name(entry.char_info.server_id)
+                );
+            }
+            !stale
+        });
+    }
+    let config = config::get();
+    let reports = entries
+        .into_iter()
+        .map(|entry| MapReport {
+            character: config.alias(&entry.char_info.name).to_string(),
+            world: server_name(entry.char_info.server_id),
+            next_map: entry.task_info.map,
+            tier: config.map_tier(&entry.char_info.name).map(str::to_string),
+            updated: entry.updated,
+            profile: profile.map(str::to_string),
+        })
+        .collect();
+    Ok((reports, scan))
+}
+
+/// Which of `entries` are the files a character left on a world they moved
+/// from: those sharing a name with one on another world whose allowance is
+/// more than [`ALLOWANCE_HOURS`] later, which only a transfer explains once
+/// their own allowance has passed and nothing is left to count down. Two
+/// people with the same name on different worlds both keep gathering, so
+/// both stay.
+fn left_behind(entries: &[AccountantTaskData], now: DateTime<Utc>) -> Vec<bool> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (name, map) = (&entry.char_info.name, entry.task_info.map);
+            map <= now
+                && entries.iter().any(|other| {
+                    other.char_info.name == *name
+                        && other.char_info.server_id != entry.char_info.server_id
+                        && other.task_info.map - map > Duration::hours(ALLOWANCE_HOURS)
+                })
+        })
+        .collect()
+}
+
+/// A note like `updated 26d ago` for a character whose file was last
+/// written more than `max_age` seconds before `now`. Its allowance may
+/// have come and gone since without the report knowing.
+fn updated_note(
+    updated: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    max_age: u64,
+) -> Option<String> {
+    let age = now - updated?;
+    if age.num_seconds() <= i64::try_from(max_age).unwrap_or(i64::MAX) {
+        return None;
+    }
+    // Whole days are enough once it's that old
+    let time = match age.num_days() {
+        0 => format_duration(-age, Style::Compact, Past::Ago),
+        days => trf(
+            "time.ago",
+            &[("time", &trf("time.days", &[("n", &days.to_string())]))],
+        ),
+    };
+    Some(trf("maps.updated", &[("time", &time)]))
+}
+
+/// A character's next `count` allowances: the stored one, or now if it's
+/// already passed, then every [`ALLOWANCE_HOURS`] after. The times stay
+/// in UTC so a daylight saving change can't shift them; only showing them
+/// converts to local time.
+pub fn projection(next_map: DateTime<Utc>, now: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+    let first = next_map.max(now);
+    (0..count)
+        .map(|index| first + Duration::hours(ALLOWANCE_HOURS * index as i64))
+        .collect()
+}
+
+/// How urgent the allowances are: a warning while any character can gather
+/// a map, or critical once one has waited past `[urgency]`'s `overdue`.
+pub fn severity(reports: &[MapReport]) -> Severity {
+    let (thresholds, now) = (urgency::thresholds(Tool::Maps), clock::now());
+    reports
+        .iter()
+        .map(|report| thresholds.level(report.next_map, now).severity())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Prints when each character can next gather a treasure map, and returns
+/// how urgent that is.
+pub fn run(options: &Options) -> anyhow::Result<Severity> {
+    if options.watch {
+        bail!("--watch isn't supported by map-allowances");
+    }
+    if options.project.is_some() && (options.json || options.format.is_some()) {
+        bail!("--project can't be combined with --json or --format");
+    }
+    let format = options.row_format(FORMAT_FIELDS)?;
+    let (reports, cached_at) = fallback::load(Tool::Maps, options.fallback_cache, || {
+        reports_merging(!options.no_merge)
+    })?;
+    let severity = severity(&reports);
+    if options.json {
+        json::emit_cached(Tool::Maps, reports, cached_at)?;
+        return Ok(severity);
+    }
+    if let Some(format) = format {
+        let now = clock::now();
+        format.write(
+            std::io::stdout().lock(),
+            &reports,
+            |report, field| match field {
+                "name" => report.character.clone(),
+                "world" => report.world.clone(),
+                "tier" => report.tier.clone().unwrap_or_default(),
+                "remaining" => format_duration(
+                    report.next_map - now,
+                    Style::Compact,
+                    Past::Label(tr("status.ready")),
+                ),
+                "at" => format_local(report.next_map),
+                "profile" => report.profile.clone().unwrap_or_default(),
+                _ => unreachable!("checked by Template::parse"),
+            },
+        )?;
+        return Ok(severity);
+    }
+    if reports.is_empty() {
+        return Ok(severity);
+    }
+
+    // The same name in two profiles is told apart by the profile's
+    let names = reports
+        .iter()
+        .map(|report| {
+            let ambiguous = reports.iter().any(|other| {
+                other.character == report.character && other.profile != report.profile
+            });
+            let mut name = format!("{} ({})", report.character, report.world);
+            if let Some(tier) = &report.tier {
+                name = format!("{name} [{tier}]");
+            }
+            profiles::tag(&name, report.profile.as_deref(), ambiguous)
+        })
+        .collect::<Vec<_>>();
+    let now = clock::now();
+    let times = reports
+        .iter()
+        .map(|report| {
+            let remaining = format_duration(
+                report.next_map - now,
+                Style::Compact,
+                Past::Label(tr("status.ready")),
+            );
+            (remaining, format!("({})", format_local(report.next_map)))
+        })
+        .collect::<Vec<_>>();
+    let max_age = config::get()
+        .updated_max_age
+        .unwrap_or(DEFAULT_UPDATED_MAX_AGE);
+    let notes = reports
+        .iter()
+        .map(|report| updated_note(report.updated, now, max_age).unwrap_or_default())
+        .collect::<Vec<_>>();
+    // Names give way to the terminal's width before the times do
+    let widths = layout::allocate(
+        4,
+        &[
+            Column::new(0, Shrink::Truncate { min: 12 }).measure(&names),
+            Column::new(3, Shrink::Never).measure(times.iter().map(|(time, _)| time.as_str())),
+            Column::new(1, Shrink::Drop).measure(times.iter().map(|(_, at)| at.as_str())),
+            Column::new(1, Shrink::Drop).measure(&notes),
+        ],
+        layout::available(),
+    );
+
+    let mut stdout = options.stdout();
+    stdout.set_color(&theme::header())?;
+    writeln!(
+        &mut stdout,
+        "{}",
+        fallback::mark(tr("maps.title"), cached_at)
+    )?;
+    let thresholds = urgency::thresholds(Tool::Maps);
+    for (((report, name), (time, at)), note) in reports.iter().zip(names).zip(times).zip(notes) {
+        stdout.set_color(&theme::spec(thresholds.level(report.next_map, now).role()))?;
+        let name = layout::fit(&name, widths[0].unwrap_or(0), Align::Left);
+        match widths[2] {
+            Some(_) => write!(&mut stdout, "    {name} - {time} {at}")?,
+            None => write!(&mut stdout, "    {name} - {time}")?,
+        }
+        if widths[3].is_some() && !note.is_empty() {
+            stdout.set_color(&theme::dim())?;
+            write!(&mut stdout, " {note}")?;
+        }
+        writeln!(&mut stdout)?;
+        if let Some(count) = options.project {
+            stdout.set_color(&theme::dim())?;
+            for (index, time) in projection(report.next_map, now, count)
+                .into_iter()
+                .enumerate()
+            {
+                writeln!(&mut stdout, "        {}. {}", index + 1, format_local(time))?;
+            }
+        }
+    }
+
+    Ok(severity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projections_start_at_the_stored_time_or_now() {
+        let now = Utc::now();
+        let next_map = now + Duration::minutes(90);
+        assert_eq!(
+            projection(next_map, now, 3),
+            [
+                next_map,
+                next_map + Duration::hours(18),
+                next_map + Duration::hours(36)
+            ]
+        );
+        // Already ready, so the first is now
+        let ready = projection(now - Duration::hours(5), now, 2);
+        assert_eq!(ready, [now, now + Duration::hours(18)]);
+        assert!(projection(next_map, now, 0).is_empty());
+    }
+
+    fn entry(name: &str, server_id: i32, map: DateTime<Utc>) -> AccountantTaskData {
+        AccountantTaskData {
+            char_info: CharacterInfo {
+                name: name.to_string(),
+                server_id,
+            },
+            task_info: TaskInfo { map },
+            updated: None,
+        }
+    }
+
+    #[test]
+    fn only_files_older_than_the_threshold_are_noted() {
+        let now = Utc::now();
+        let note = |ago| updated_note(Some(now - ago), now, DEFAULT_UPDATED_MAX_AGE);
+        assert_eq!(note(Duration::days(2)), None);
+        assert_eq!(
+            note(Duration::days(26) + Duration::hours(23)).unwrap(),
+            "updated 26d ago"
+        );
+        assert_eq!(
+            updated_note(Some(now - Duration::hours(5)), now, 3600).unwrap(),
+            "updated 05:00:00 ago"
+        );
+        assert_eq!(updated_note(None, now, 0), None);
+    }
+
+    #[test]
+    fn only_entirely_stale_namesakes_are_left_behind() {
+        let now = Utc::now();
+        let hours = Duration::hours;
+        // Moved from Balmung two days ago, and has gathered since
+        let moved = [
+            entry("Alice Doe", 91, now - hours(48)),
+            entry("Alice Doe", 79, now + hours(5)),
+        ];
+        assert_eq!(left_behind(&moved, now), [true, false]);
+        // Two people with one name, the older still counting down
+        let counting = [
+            entry("Alice Doe", 91, now + hours(1)),
+            entry("Alice Doe", 79, now + hours(17) + hours(5)),
+        ];
+        assert_eq!(left_behind(&counting, now), [false, false]);
+        // Both ready, but within an allowance of each other
+        let close = [
+            entry("Alice Doe", 91, now - hours(10)),
+            entry("Alice Doe", 79, now - hours(1)),
+        ];
+        assert_eq!(left_behind(&close, now), [false, false]);
+        // The same world twice isn't a transfer, nor are other names
+        let unrelated = [
+            entry("Alice Doe", 79, now - hours(48)),
+            entry("Alice Doe", 79, now + hours(5)),
+            entry("Bob Doe", 91, now - hours(48)),
+        ];
+        assert_eq!(left_behind(&unrelated, now), [false, false, false]);
+    }
+
+    #[test]
+    fn missing_empty_or_stale_task_data_does_not_panic() {
+        // Keeps what is read out of the real state directory
+        ffxiv_timers_core::read_cache::disable();
+        let dir = std::env::temp_dir().join(format!("map-allowances-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        paths::set_plugin_configs_dir(dir.clone());
+        let options = Options {
+            json: true,
+            ..Options::default()
+        };
+
+        assert!(run(&options).is_err());
+        let folder = data_path().unwrap();
+        std::fs::create_dir_all(&folder).unwrap();
+        run(&options).unwrap();
+        // Only entries older than a week, which are all filtered out
+        std::fs::write(
+            folder.join("1.json"),
+            r#"{"Item1": {"Name": "Alice Doe", "ServerId": 79},
+                "Item2": {"Map": "2020-01-01T00:00:00Z"}}"#,
+        )
+        .unwrap();
+        run(&options).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
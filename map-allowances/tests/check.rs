@@ -0,0 +1,27 @@
+//! Runs map-allowances in a fake home, checking what `--check` exits with
+//! and that nothing but the report is printed to stdout.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_map-allowances");
+
+#[test]
+fn check_warns_while_a_map_can_be_gathered() {
+    let home = FakeHome::warned("map-allowances-check-ready");
+    home.map_allowance(1, "Alice Doe", Duration::hours(3));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 0);
+    home.map_allowance(1, "Alice Doe", Duration::hours(-3));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 1);
+    assert_eq!(home.run(BIN, &["--check", "--json"]).warned_status(), 1);
+    assert_eq!(home.run(BIN, &[]).warned_status(), 0);
+}
+
+#[test]
+fn errors_are_unknown_under_check() {
+    let home = FakeHome::warned("map-allowances-check-errors");
+    let run = home.run(BIN, &["--check"]);
+    assert_eq!(run.warned_status(), 3);
+    assert!(run.stdout.is_empty());
+    assert_eq!(home.run(BIN, &[]).warned_status(), 1);
+}
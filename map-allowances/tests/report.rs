@@ -0,0 +1,52 @@
+//! Runs map-allowances in a fake home, checking the plain-text report.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_map-allowances");
+
+#[test]
+fn reports_each_characters_next_map() {
+    let home = FakeHome::new("map-allowances-report");
+    home.map_allowance(1, "Alice Doe", Duration::hours(-1))
+        .map_allowance(2, "Bob Roe", Duration::hours(3));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Map Allowances");
+    assert!(
+        lines[1].starts_with("    Alice Doe (Cactuar) - ready ("),
+        "{}",
+        lines[1]
+    );
+    assert!(
+        lines[2].starts_with("    Bob Roe (Cactuar)   - 03:00:00 ("),
+        "{}",
+        lines[2]
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(!run.stdout.contains('\x1b'));
+}
+
+#[test]
+fn characters_not_seen_lately_say_when_they_were() {
+    let home = FakeHome::new("map-allowances-report-updated");
+    home.map_allowance(1, "Alice Doe", Duration::hours(-1))
+        .map_allowance(2, "Bob Roe", Duration::hours(3))
+        .written(
+            "Accountant/tasks/1.json",
+            Duration::days(26) + Duration::hours(5),
+        )
+        .written("Accountant/tasks/2.json", Duration::days(2));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(lines[1].ends_with(") updated 26d ago"), "{}", lines[1]);
+    assert!(lines[2].ends_with(')'), "{}", lines[2]);
+
+    // A shorter threshold notes both
+    home.config("updated_max_age = 86400");
+    let run = home.run(BIN, &[]);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(lines[2].ends_with(") updated 2d ago"), "{}", lines[2]);
+}
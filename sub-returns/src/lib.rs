@@ -0,0 +1,480 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, TimeZone, Utc};
+use ffxiv_timers_core::{
+    check::Severity,
+    clock, config, fallback, gamedata,
+    i18n::{tr, trf},
+    json::{self, FreeCompanyReport, SubmarineReport, Tool},
+    layout::{self, Align, Column, Shrink},
+    options::Options,
+    paths, profiles, read_cache,
+    scan::Scan,
+    source::{Incompatible, Support},
+    theme,
+    time::{format_duration, format_local, Past, Style},
+    urgency,
+    watch::{self, Refresh},
+};
+use termcolor::WriteColor;
+
+/// SubmarineTracker's database, inside the plugin config directory.
+const SUBTRACKER_DB: [&str; 2] = ["SubmarineTracker", "submarine-sqlite.db"];
+
+/// How often `--watch` redraws the countdowns.
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The fields `--format` can use, one row per submarine.
+pub const FORMAT_FIELDS: &[&str] = &[
+    "name",
+    "fc",
+    "character",
+    "world",
+    "status",
+    "remaining",
+    "at",
+    "label",
+    "profile",
+];
+
+/// Every free company's submarines, in the configured order.
+pub fn reports() -> anyhow::Result<Vec<FreeCompanyReport>> {
+    let mut reports = profiles::each(read)?;
+    let config = config::get();
+    reports.sort_by_key(|fc| config.fc_rank(&fc.tag));
+    // A submarine renamed in game leaves its label behind
+    for key in config.sub_labels.keys() {
+        let matched = reports.iter().any(|fc| {
+            fc.submarines
+                .iter()
+                .any(|sub| *key == format!("{}/{}", fc.tag, sub.name))
+        });
+        if !matched {
+            tracing::debug!("No submarine matches `{key}` in [sub_labels]; was it renamed?");
+        }
+    }
+    Ok(reports)
+}
+
+/// What the database in the profile being read holds, for `doctor`.
+pub fn scan() -> anyhow::Result<Scan> {
+    let (path, fcs) = load()?;
+    let mut scan = Scan::default();
+    scan.read(&path);
+    scan.rows = fcs.iter().map(|fc| fc.submarines.len()).sum();
+    Ok(scan)
+}
+
+fn load() -> anyhow::Result<(PathBuf, Vec<FreeCompany>)> {
+    let path = data_path()?;
+    // Writes sit in the write-ahead log until SQLite checkpoints them
+    let mut wal = path.clone().into_os_string();
+    wal.push("-wal");
+    let fcs = read_cache::read_through("subs", &path, &[path.clone(), wal.into()], || {
+        let db = open_db(&path)?;
+        get_submarine_info(&db)
+            .with_context(|| format!("Failed to read SubmarineTracker's database {path:?}"))
+    })?;
+    Ok((path, fcs))
+}
+
+/// The free companies in one profile's database.
+fn read(profile: Option<&str>) -> anyhow::Result<Vec<FreeCompanyReport>> {
+    let (_, fcs) = load()?;
+    let config = config::get();
+    Ok(fcs
+        .into_iter()
+        .map(|fc| FreeCompanyReport {
+            character: config.alias(&fc.character_name).to_string(),
+            submarines: fc
+                .submarines
+                .into_iter()
+                .map(|sub| SubmarineReport {
+                    label: config.sub_label(&fc.tag, &sub.name).map(str::to_string),
+                    name: sub.name,
+                    return_time: Some(sub.return_time)
+                        .filter(|&time| time != DateTime::<Utc>::default()),
+                })
+                .collect(),
+            tag: fc.tag,
+            world: fc.world,
+            profile: profile.map(str::to_string),
+        })
+        .collect())
+}
+
+/// How urgent the voyages are: a warning while any submarine is back, or
+/// critical once one has waited past `[urgency]`'s `overdue`. Unassigned
+/// submarines aren't counted, as some are left that way.
+pub fn severity(reports: &[FreeCompanyReport]) -> Severity {
+    let (thresholds, now) = (urgency::thresholds(Tool::Subs), clock::now());
+    reports
+        .iter()
+        .flat_map(|fc| &fc.submarines)
+        .filter_map(|sub| sub.return_time)
+        .map(|time| thresholds.level(time, now).severity())
+        .max()
+        .unwrap_or_default()
+}
+
+/// How many of a free company's submarine slots have nothing registered.
+pub fn unused_slots(fc: &FreeCompanyReport) -> usize {
+    gamedata::SUBMARINE_SLOTS.saturating_sub(fc.submarines.len())
+}
+
+/// Prints when each free company's submarines return from their voyages,
+/// and returns how urgent that is.
+pub fn run(options: &Options) -> anyhow::Result<Severity> {
+    if options.watch {
+        watch(options)?;
+        return Ok(Severity::Ok);
+    }
+    let format = options.row_format(FORMAT_FIELDS)?;
+    let (reports, cached_at) = fallback::load(Tool::Subs, options.fallback_cache, reports)?;
+    let mut severity = severity(&reports);
+    // Newer free companies haven't unlocked every slot, so empty ones only
+    // count when asked for
+    if options.strict_slots && reports.iter().any(|fc| unused_slots(fc) > 0) {
+        severity = severity.max(Severity::Warning);
+    }
+    if options.json {
+        json::emit_cached(Tool::Subs, reports, cached_at)?;
+        return Ok(severity);
+    }
+    if let Some(format) = format {
+        let now = clock::now();
+        let subs: Vec<_> = reports
+            .iter()
+            .flat_map(|fc| fc.submarines.iter().map(move |sub| (fc, sub)))
+            .collect();
+        format.write(
+            std::io::stdout().lock(),
+            &subs,
+            |(fc, sub), field| match field {
+                "name" => sub.name.clone(),
+                "fc" => fc.tag.clone(),
+                "character" => fc.character.clone(),
+                "world" => fc.world.clone(),
+                "status" => match sub.return_time {
+                    None => "unassigned",
+                    Some(time) if time <= now => "returned",
+                    Some(_) => "voyaging",
+                }
+                .to_string(),
+                "remaining" => sub
+                    .return_time
+                    .map(|time| format_duration(time - now, Style::Compact, Past::Zero))
+                    .unwrap_or_default(),
+                "at" => sub.return_time.map(format_local).unwrap_or_default(),
+                "label" => sub.label.clone().unwrap_or_default(),
+                "profile" => fc.profile.clone().unwrap_or_default(),
+                _ => unreachable!("checked by Template::parse"),
+            },
+        )?;
+        return Ok(severity);
+    }
+
+    print(&mut options.stdout(), &reports, cached_at)?;
+    Ok(severity)
+}
+
+/// The text report: each free company's submarines, with how long until
+/// they're back, and a note of any slots left unregistered.
+fn print(
+    stdout: &mut dyn WriteColor,
+    reports: &[FreeCompanyReport],
+    cached_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    // The same character in two profiles is told apart by the profile's
+    let characters = reports
+        .iter()
+        .map(|fc| {
+            let ambiguous = reports
+                .iter()
+                .any(|other| other.character == fc.character && other.profile != fc.profile);
+            profiles::tag(&fc.character, fc.profile.as_deref(), ambiguous)
+        })
+        .collect::<Vec<_>>();
+    let available = layout::available();
+    let thresholds = urgency::thresholds(Tool::Subs);
+    for (fc, character) in reports.iter().zip(characters) {
+        let title = fallback::mark(tr("subs.title"), cached_at);
+        let count = fc.submarines.len().to_string();
+        let details = format!("«{}» ({}) | {count}", fc.tag, fc.world);
+        // Only the character's name gives way in the header
+        let widths = layout::allocate(
+            0,
+            &[
+                Column::new(0, Shrink::Never).measure([&*title]),
+                Column::new(3, Shrink::Truncate { min: 12 }).measure([&*character]),
+                Column::new(1, Shrink::Never).measure([&*details]),
+            ],
+            available,
+        );
+        let character = layout::truncate(&character, widths[1].unwrap_or(0));
+        stdout.set_color(&theme::header())?;
+        write!(stdout, "{title} | {character} {details}")?;
+        let unused = unused_slots(fc);
+        if unused > 0 {
+            let key = match unused {
+                1 => "subs.slot_unused",
+                _ => "subs.slots_unused",
+            };
+            stdout.set_color(&theme::dim())?;
+            write!(stdout, " {}", trf(key, &[("n", &unused.to_string())]))?;
+        }
+        writeln!(stdout)?;
+
+        let now = clock::now();
+        let rows = fc
+            .submarines
+            .iter()
+            .map(|sub| match sub.return_time {
+                None => (theme::urgent(), tr("subs.unassigned").to_string(), None),
+                Some(time) if time <= now => (
+                    theme::spec(thresholds.level(time, now).role()),
+                    tr("subs.complete").to_string(),
+                    None,
+                ),
+                Some(time) => (
+                    theme::spec(thresholds.level(time, now).role()),
+                    format_duration(time - now, Style::Compact, Past::Zero),
+                    Some(format!("({})", format_local(time))),
+                ),
+            })
+            .collect::<Vec<_>>();
+        // Names give way to the terminal's width before the return times
+        // do. Labels come last, so a long one doesn't push the times out of
+        // line.
+        let widths = layout::allocate(
+            4,
+            &[
+                Column::new(0, Shrink::Truncate { min: 8 })
+                    .measure(fc.submarines.iter().map(|sub| &*sub.name)),
+                Column::new(3, Shrink::Never).measure(rows.iter().map(|(_, status, _)| &**status)),
+                Column::new(1, Shrink::Drop)
+                    .measure(rows.iter().filter_map(|(_, _, at)| at.as_deref())),
+                Column::new(1, Shrink::Drop)
+                    .measure(fc.submarines.iter().filter_map(|sub| sub.label.as_deref())),
+            ],
+            available,
+        );
+        for (sub, (color, status, at)) in fc.submarines.iter().zip(rows) {
+            stdout.set_color(&color)?;
+            // Those with nothing to wait for are centered to stand out
+            let align = match at {
+                Some(_) => Align::Left,
+                None => Align::Center,
+            };
+            let name = layout::fit(&sub.name, widths[0].unwrap_or(0), align);
+            match at.filter(|_| widths[2].is_some()) {
+                Some(at) => write!(stdout, "    {name} - {status} {at}")?,
+                None => write!(stdout, "    {name} - {status}")?,
+            }
+            if let Some(label) = sub.label.as_deref().filter(|_| widths[3].is_some()) {
+                stdout.set_color(&theme::dim())?;
+                write!(stdout, " {label}")?;
+            }
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `--watch`: the report redrawn every second, and read again whenever
+/// SubmarineTracker writes to its database.
+fn watch(options: &Options) -> anyhow::Result<()> {
+    let mut stdout = options.watch_stdout()?;
+    // SQLite's journal files are written beside the database, so the
+    // directory is watched rather than the file alone
+    let dirs = profiles::each(|_| {
+        let path = data_path()?;
+        Ok(path.parent().map(Path::to_path_buf).into_iter().collect())
+    })?;
+    let (mut fcs, mut cached_at) = (vec![], None);
+    watch::run(&mut stdout, &dirs, Some(TICK), None, |stdout, refresh| {
+        if refresh == Refresh::Reload {
+            (fcs, cached_at) = fallback::load(Tool::Subs, options.fallback_cache, reports)?;
+        }
+        print(stdout, &fcs, cached_at)
+    })
+}
+
+/// SubmarineTracker's database file.
+pub fn data_path() -> anyhow::Result<PathBuf> {
+    paths::plugin_path(SUBTRACKER_DB)
+}
+
+fn open_db(path: &Path) -> anyhow::Result<rusqlite::Connection> {
+    let db =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| {
+                format!(
+                    "Failed to open SubmarineTracker's database {path:?}; install the \
+                     SubmarineTracker plugin and open it in game, or pass --data-root"
+                )
+            })?;
+    Ok(db)
+}
+
+/// The tables and columns [`get_submarine_info`] reads. The database's
+/// `user_version` isn't looked at, so any with these columns is read.
+const COLUMNS: [(&str, &[&str]); 2] = [
+    (
+        "freecompany",
+        &["FreeCompanyId", "CharacterName", "World", "FreeCompanyTag"],
+    ),
+    (
+        "submarine",
+        &["SubmarineId", "FreeCompanyId", "Name", "Return"],
+    ),
+];
+
+/// Every submarine with its free company's row.
+const QUERY: &str = "
+    SELECT
+        freecompany.FreeCompanyId as fc_id,
+        freecompany.rowid as fc_row,
+        freecompany.CharacterName as character_name,
+        freecompany.World as world,
+        freecompany.FreeCompanyTag as tag,
+        submarine.SubmarineId as sub_id,
+        submarine.Name AS sub_name, 
+        submarine.Return AS return_time
+    FROM submarine JOIN freecompany ON submarine.FreeCompanyId = freecompany.FreeCompanyId
+    ORDER BY fc_id, sub_id, fc_row
+";
+
+/// The database's layout, as the loader reads it.
+pub fn support() -> Support {
+    let tables = COLUMNS
+        .iter()
+        .map(|(table, columns)| format!("{table} ({})", columns.join(", ")))
+        .collect::<Vec<_>>();
+    Support {
+        files: SUBTRACKER_DB.join("/"),
+        layouts: vec![format!("{}, at any user_version", tables.join(", "))],
+    }
+}
+
+fn get_submarine_info(db: &rusqlite::Connection) -> anyhow::Result<Vec<FreeCompany>> {
+    // A table or column SubmarineTracker renamed fails here
+    let mut stmt = db.prepare(QUERY).map_err(|err| {
+        Incompatible(format!("{err}; SubmarineTracker's layout may have changed"))
+    })?;
+    // A company can have more than one row, as when its character was
+    // renamed, so each submarine comes once per row. They're gathered by
+    // id, named after the row written last.
+    let mut fcs: Vec<(i64, FreeCompany)> = vec![];
+    let mut seen = HashSet::new();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let fc_id: Vec<u8> = row.get("fc_id")?;
+        let fc_row: i64 = row.get("fc_row")?;
+        let index = match fcs.iter().position(|(_, fc)| fc.id == fc_id) {
+            Some(index) => index,
+            None => {
+                fcs.push((
+                    i64::MIN,
+                    FreeCompany {
+                        id: fc_id.clone(),
+                        character_name: String::new(),
+                        world: String::new(),
+                        tag: String::new(),
+                        submarines: vec![],
+                    },
+                ));
+                fcs.len() - 1
+            }
+        };
+        let (newest, fc) = &mut fcs[index];
+        if fc_row > *newest {
+            *newest = fc_row;
+            fc.character_name = row.get("character_name")?;
+            fc.world = row.get("world")?;
+            fc.tag = row.get("tag")?;
+        }
+
+        let sub_id: i64 = row.get("sub_id")?;
+        if !seen.insert((fc_id, sub_id)) {
+            continue;
+        }
+        let name: String = row.get("sub_name")?;
+        let timestamp = row.get("return_time")?;
+        let Some(return_time) = Utc.timestamp_opt(timestamp, 0).single() else {
+            bail!(
+                "Submarine {name} has an impossible return time ({timestamp}); SubmarineTracker's \
+                 database may be damaged, so try opening the plugin in game to rewrite it"
+            );
+        };
+        fc.submarines.push(Submarine { name, return_time });
+    }
+    let mut fcs = fcs.into_iter().map(|(_, fc)| fc).collect::<Vec<_>>();
+    fcs.sort_by(|a, b| (&a.world, &a.tag, &a.id).cmp(&(&b.world, &b.tag, &b.id)));
+    Ok(fcs)
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FreeCompany {
+    pub id: Vec<u8>,
+    pub character_name: String,
+    pub world: String,
+    pub tag: String,
+    pub submarines: Vec<Submarine>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Submarine {
+    pub name: String,
+    pub return_time: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_supported_columns_are_the_ones_queried() {
+        for (table, columns) in COLUMNS {
+            for column in columns {
+                let name = format!("{table}.{column}");
+                assert!(QUERY.contains(&name), "{name} isn't queried");
+            }
+        }
+    }
+
+    #[test]
+    fn missing_or_empty_database_does_not_panic() {
+        // Keeps what is read out of the real state directory
+        read_cache::disable();
+        let dir = std::env::temp_dir().join(format!("sub-returns-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        paths::set_plugin_configs_dir(dir.clone());
+        let options = Options {
+            json: true,
+            ..Options::default()
+        };
+
+        assert!(run(&options).is_err());
+        let path = data_path().unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE freecompany (FreeCompanyId BLOB, CharacterName TEXT, World TEXT,
+                     FreeCompanyTag TEXT);
+                 CREATE TABLE submarine (SubmarineId INTEGER, FreeCompanyId BLOB, Name TEXT,
+                     Return INTEGER);",
+            )
+            .unwrap();
+        run(&options).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+//! Runs sub-returns in a fake home, checking what `--check` exits with and
+//! that nothing but the report is printed to stdout.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_sub-returns");
+
+#[test]
+fn check_warns_once_a_submarine_is_back() {
+    let home = FakeHome::warned("sub-returns-check-returned");
+    home.submarine("Alice Doe", "FISH", "Nautilus", Duration::hours(3));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 0);
+    home.submarine("Alice Doe", "FISH", "Argo", Duration::hours(-3));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 1);
+    assert_eq!(home.run(BIN, &["--check", "--json"]).warned_status(), 1);
+    assert_eq!(home.run(BIN, &[]).warned_status(), 0);
+}
+
+#[test]
+fn strict_slots_warns_about_unregistered_submarines() {
+    let home = FakeHome::warned("sub-returns-check-slots");
+    home.submarine("Alice Doe", "FISH", "Nautilus", Duration::hours(3));
+    assert_eq!(home.run(BIN, &["--check"]).warned_status(), 0);
+    assert_eq!(
+        home.run(BIN, &["--check", "--strict-slots"])
+            .warned_status(),
+        1
+    );
+    for name in ["Argo", "Calypso", "Ulysses"] {
+        home.submarine("Alice Doe", "FISH", name, Duration::hours(3));
+    }
+    assert_eq!(
+        home.run(BIN, &["--check", "--strict-slots"])
+            .warned_status(),
+        0
+    );
+}
+
+#[test]
+fn errors_are_unknown_under_check() {
+    let home = FakeHome::warned("sub-returns-check-errors");
+    let run = home.run(BIN, &["--check"]);
+    assert_eq!(run.warned_status(), 3);
+    assert!(run.stdout.is_empty());
+    assert_eq!(home.run(BIN, &[]).warned_status(), 1);
+}
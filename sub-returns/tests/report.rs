@@ -0,0 +1,99 @@
+//! Runs sub-returns in a fake home, checking the plain-text report.
+
+use chrono::Duration;
+use ffxiv_timers_testing::FakeHome;
+
+const BIN: &str = env!("CARGO_BIN_EXE_sub-returns");
+
+#[test]
+fn reports_each_free_companys_submarines() {
+    let home = FakeHome::new("sub-returns-report");
+    home.submarine("Alice Doe", "FISH", "Nautilus", Duration::hours(3))
+        .submarine("Alice Doe", "FISH", "Argo", Duration::hours(-3));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "Submarines | Alice Doe «FISH» (Tonberry) | 2 (2 slots unused)"
+    );
+    assert!(
+        lines[1].starts_with("    Nautilus - 03:00:00 ("),
+        "{}",
+        lines[1]
+    );
+    assert_eq!(lines[2], "      Argo   - Voyage complete");
+    assert_eq!(lines.len(), 3);
+    assert!(!run.stdout.contains('\x1b'));
+}
+
+#[test]
+fn a_renamed_characters_company_is_listed_once() {
+    let home = FakeHome::new("sub-returns-report-renamed");
+    home.submarine("Alice Doe", "FISH", "Nautilus", Duration::hours(3))
+        .submarine("Bob Doe", "BIRD", "Kestrel", Duration::hours(1))
+        .renamed_fc("FISH", "Alice Smith")
+        .submarine("Alice Smith", "FISH", "Argo", Duration::hours(-3));
+    let run = home.run(BIN, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "Submarines | Bob Doe «BIRD» (Tonberry) | 1 (3 slots unused)"
+    );
+    assert!(
+        lines[1].starts_with("    Kestrel - 01:00:00 ("),
+        "{}",
+        lines[1]
+    );
+    assert_eq!(
+        lines[2],
+        "Submarines | Alice Smith «FISH» (Tonberry) | 2 (2 slots unused)"
+    );
+    assert!(
+        lines[3].starts_with("    Nautilus - 03:00:00 ("),
+        "{}",
+        lines[3]
+    );
+    assert_eq!(lines[4], "      Argo   - Voyage complete");
+    assert_eq!(lines.len(), 5);
+}
+
+#[test]
+fn labels_follow_the_times_without_moving_them() {
+    let home = FakeHome::new("sub-returns-report-labels");
+    home.submarine("Alice Doe", "FISH", "Nautilus", Duration::hours(3))
+        .submarine("Alice Doe", "FISH", "Argo", Duration::hours(1))
+        .config(
+            r#"[sub_labels]
+"FISH/Argo" = "MGP farm"
+"FISH/Kestrel" = "unlocks"
+"#,
+        );
+    let run = home.run(BIN, &["--verbose"]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    let lines = run.stdout.lines().collect::<Vec<_>>();
+    assert!(
+        lines[1].starts_with("    Nautilus - 03:00:00 ("),
+        "{}",
+        lines[1]
+    );
+    assert!(lines[1].ends_with(')'), "{}", lines[1]);
+    assert!(
+        lines[2].starts_with("    Argo     - 01:00:00 ("),
+        "{}",
+        lines[2]
+    );
+    assert!(lines[2].ends_with(") MGP farm"), "{}", lines[2]);
+    assert!(
+        run.stderr.contains("No submarine matches `FISH/Kestrel`"),
+        "{}",
+        run.stderr
+    );
+
+    let run = home.run(BIN, &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(&run.stdout).unwrap();
+    let subs = &json["data"][0]["submarines"];
+    assert_eq!(subs[1]["label"], "MGP farm");
+    assert!(subs[0].get("label").is_none());
+}